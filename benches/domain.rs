@@ -0,0 +1,205 @@
+// Criterion benchmarks for the domain model.
+//
+// Run all of them with `cargo bench`. To compare against the recorded baseline:
+//   cargo bench -- --save-baseline main     (on the reference commit)
+//   cargo bench -- --baseline main          (on your change)
+// See benches/BASELINE.md for the numbers the suite is expected to produce.
+
+use std::hint::black_box;
+use std::time::Duration;
+
+use criterion::{BatchSize, BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use mlw_rust::entities::{Stake, StakeId, StakesCollection, Task};
+use mlw_rust::mlw::MLW;
+
+const SIZES: [u32; 3] = [1_000, 100_000, 1_000_000];
+
+// Every 100th stake carries the search target, every 10th hangs off project 55,
+// and the last ten hang off project 77 whatever the size.
+const SEARCH_TARGET: &str = "perf_target";
+const TARGET_PARENT: u32 = 55;
+const SMALL_PARENT: u32 = 77;
+const SMALL_PARENT_CHILDREN: u32 = 10;
+
+fn build_stakes(count: u32) -> Vec<Stake<Task>> {
+    (1..=count)
+        .map(|i| {
+            let name = if i % 100 == 0 {
+                format!("Stake {} - {} - other text", i, SEARCH_TARGET)
+            } else {
+                format!("Stake {}", i)
+            };
+            let parent_id = if i > count - SMALL_PARENT_CHILDREN {
+                Some(StakeId::new(SMALL_PARENT))
+            } else if i % 10 == 0 && i != TARGET_PARENT {
+                Some(StakeId::new(TARGET_PARENT))
+            } else {
+                Some(StakeId::new(i % 50 + 1))
+            };
+            let note = (i % 3 == 0).then(|| format!("Note for stake {}", i));
+            Stake::new(StakeId::new(i), name, parent_id, note)
+        })
+        .collect()
+}
+
+fn build_collection(count: u32) -> StakesCollection<Task> {
+    let mut collection = StakesCollection::new();
+    for stake in build_stakes(count) {
+        collection.add_stake(stake);
+    }
+    collection
+}
+
+// Roughly 1% areas, 10% projects and the rest tasks, linked top to bottom.
+fn build_mlw(count: u32) -> MLW {
+    let mut mlw = MLW::new();
+    let area_count = (count / 100).max(1);
+    let project_count = (count / 10).max(1);
+    for i in 0..area_count {
+        mlw.new_area(format!("Area {}", i), None);
+    }
+    for i in 0..project_count {
+        mlw.new_project(
+            format!("Project {}", i),
+            Some(StakeId::new(i % area_count + 1)),
+            Some(format!("Project note {}", i)),
+        );
+    }
+    for i in 0..count.saturating_sub(area_count + project_count) {
+        mlw.new_task(
+            format!("Task {}", i),
+            Some(StakeId::new(i % project_count + 1)),
+            None,
+        );
+    }
+    mlw
+}
+
+// The largest sizes take seconds per iteration, so keep their sample count low.
+fn configure(
+    group: &mut criterion::BenchmarkGroup<'_, criterion::measurement::WallTime>,
+    size: u32,
+) {
+    if size >= 100_000 {
+        group.sample_size(10);
+        group.measurement_time(Duration::from_secs(10));
+    } else {
+        group.sample_size(50);
+    }
+}
+
+fn bench_insertion(c: &mut Criterion) {
+    let mut group = c.benchmark_group("collection/add_stake");
+    for size in SIZES {
+        configure(&mut group, size);
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, &size| {
+            b.iter_batched(
+                || build_stakes(size),
+                |stakes| {
+                    let mut collection = StakesCollection::new();
+                    for stake in stakes {
+                        collection.add_stake(stake);
+                    }
+                    collection
+                },
+                BatchSize::PerIteration,
+            );
+        });
+    }
+    group.finish();
+}
+
+fn bench_queries(c: &mut Criterion) {
+    for size in SIZES {
+        let collection = build_collection(size);
+        let mut group = c.benchmark_group(format!("collection/{}", size));
+        configure(&mut group, size);
+
+        // Each `_scan` is the linear search its keyed or indexed lookup replaced.
+        group.bench_function("get_by_id", |b| {
+            let id = StakeId::new(size / 2);
+            b.iter(|| black_box(collection.get_by_id(black_box(&id))));
+        });
+        group.bench_function("get_by_id_scan", |b| {
+            let id = StakeId::new(size / 2);
+            b.iter(|| {
+                black_box(
+                    collection
+                        .iter()
+                        .find(|stake| stake.stake_id == *black_box(&id)),
+                )
+            });
+        });
+        group.bench_function("get_children", |b| {
+            let parent = StakeId::new(TARGET_PARENT);
+            b.iter(|| black_box(collection.get_children(black_box(&parent))));
+        });
+        group.bench_function("get_children_scan", |b| {
+            let parent = StakeId::new(TARGET_PARENT);
+            b.iter(|| {
+                let parent = black_box(&parent);
+                black_box(
+                    collection
+                        .iter()
+                        .filter(|stake| {
+                            stake.parent_id.as_ref() == Some(parent) && stake.is_active()
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            });
+        });
+        // The case the children index is for: a parent with a handful of children.
+        group.bench_function("get_children_few", |b| {
+            let parent = StakeId::new(SMALL_PARENT);
+            b.iter(|| black_box(collection.get_children(black_box(&parent))));
+        });
+        group.bench_function("get_children_few_scan", |b| {
+            let parent = StakeId::new(SMALL_PARENT);
+            b.iter(|| {
+                let parent = black_box(&parent);
+                black_box(
+                    collection
+                        .iter()
+                        .filter(|stake| {
+                            stake.parent_id.as_ref() == Some(parent) && stake.is_active()
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            });
+        });
+        // `search_by_name` is the linear scan over names that `search`'s index
+        // replaces; the two run the same query so their times compare directly.
+        group.bench_function("search_by_name", |b| {
+            b.iter(|| black_box(collection.search_by_name(black_box(SEARCH_TARGET))));
+        });
+        group.bench_function("search", |b| {
+            b.iter(|| black_box(collection.search(black_box(SEARCH_TARGET))));
+        });
+        group.bench_function("fuzzy_search", |b| {
+            b.iter(|| black_box(collection.fuzzy_search(black_box("perf_trget"))));
+        });
+        group.finish();
+    }
+}
+
+fn bench_serialization(c: &mut Criterion) {
+    for size in SIZES {
+        let mlw = build_mlw(size);
+        let json = serde_json::to_string(&mlw).expect("MLW should serialize");
+        let mut group = c.benchmark_group(format!("mlw/{}", size));
+        configure(&mut group, size);
+        group.throughput(Throughput::Bytes(json.len() as u64));
+
+        group.bench_function("serialize", |b| {
+            b.iter(|| serde_json::to_string(black_box(&mlw)).unwrap());
+        });
+        group.bench_function("deserialize", |b| {
+            b.iter(|| serde_json::from_str::<MLW>(black_box(&json)).unwrap());
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_insertion, bench_queries, bench_serialization);
+criterion_main!(benches);
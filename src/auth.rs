@@ -0,0 +1,445 @@
+// Who may use `mlw serve`. Accounts live next to the data file (`~/.mlw.json` ->
+// `~/.mlw.users.json`): each user holds tokens, each either read-only or read-write.
+//
+// A token reads `mlw_<id>_<secret>`. Only the SHA-256 hash of the secret is stored,
+// so the full token is shown once, when it is issued. Secrets are 256 random bits;
+// with nothing to guess, a fast hash is as good as a slow one.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use std::time::SystemTime;
+
+use chrono::{DateTime, Utc};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::serializer::{self, StorageError};
+
+const PREFIX: &str = "mlw";
+const ID_BYTES: usize = 6;
+const SECRET_BYTES: usize = 32;
+
+/// What a token lets its holder do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Access {
+    /// Only methods and routes that read.
+    Read,
+    /// Everything.
+    Write,
+}
+
+impl fmt::Display for Access {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Access::Read => "read-only",
+            Access::Write => "read-write",
+        })
+    }
+}
+
+/// Who a client proved to be, and what it may do.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Grant {
+    /// The user the token belongs to; none on a server that does not check tokens.
+    pub user: Option<String>,
+    pub access: Access,
+}
+
+impl Grant {
+    /// Everything, for servers that do not check tokens.
+    pub fn everyone() -> Self {
+        Grant {
+            user: None,
+            access: Access::Write,
+        }
+    }
+
+    /// Whether the grant covers a read or, if `write`, a write.
+    pub fn allows(&self, write: bool) -> bool {
+        !write || self.access == Access::Write
+    }
+}
+
+/// Why a request was refused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthError {
+    /// No token was given.
+    MissingToken,
+    /// The token is malformed, unknown, revoked or wrong.
+    InvalidToken,
+    /// A read-only token was used to write.
+    ReadOnlyToken,
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AuthError::MissingToken => "authentication required: send an access token",
+            AuthError::InvalidToken => "the access token is not valid",
+            AuthError::ReadOnlyToken => "the access token is read-only",
+        })
+    }
+}
+
+impl std::error::Error for AuthError {}
+
+/// A change to the accounts that could not be made.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccountError {
+    InvalidName(String),
+    UserExists(String),
+    UnknownUser(String),
+    UnknownToken(String),
+}
+
+impl fmt::Display for AccountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccountError::InvalidName(name) => write!(
+                f,
+                "'{}' is not a valid user name: use letters, digits, '.', '-' and '_'",
+                name
+            ),
+            AccountError::UserExists(name) => write!(f, "user '{}' already exists", name),
+            AccountError::UnknownUser(name) => write!(f, "no user named '{}'", name),
+            AccountError::UnknownToken(id) => write!(f, "no token with id '{}'", id),
+        }
+    }
+}
+
+impl std::error::Error for AccountError {}
+
+/// The users of one data file and their tokens.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Accounts {
+    users: IndexMap<String, User>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+struct User {
+    tokens: Vec<StoredToken>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct StoredToken {
+    id: String,
+    /// Hex SHA-256 of the secret.
+    hash: String,
+    access: Access,
+    created: DateTime<Utc>,
+}
+
+/// A token as listed, without its secret.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TokenInfo {
+    pub id: String,
+    pub user: String,
+    pub access: Access,
+    pub created: DateTime<Utc>,
+}
+
+impl Accounts {
+    /// Where the accounts for `data_file` are kept.
+    pub fn path_for(data_file: &Path) -> PathBuf {
+        data_file.with_extension("users.json")
+    }
+
+    pub fn add_user(&mut self, name: &str) -> Result<(), AccountError> {
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_alphanumeric() || matches!(c, '.' | '-' | '_'));
+        if !valid {
+            return Err(AccountError::InvalidName(name.to_string()));
+        }
+        if self.users.contains_key(name) {
+            return Err(AccountError::UserExists(name.to_string()));
+        }
+        self.users.insert(name.to_string(), User::default());
+        Ok(())
+    }
+
+    /// Removes a user and revokes all their tokens.
+    pub fn remove_user(&mut self, name: &str) -> Result<(), AccountError> {
+        self.users
+            .shift_remove(name)
+            .map(|_| ())
+            .ok_or_else(|| AccountError::UnknownUser(name.to_string()))
+    }
+
+    pub fn users(&self) -> impl Iterator<Item = &str> {
+        self.users.keys().map(String::as_str)
+    }
+
+    /// Issues a new token for `user` and returns it. This is the only time the
+    /// whole token is known.
+    pub fn issue_token(
+        &mut self,
+        user: &str,
+        access: Access,
+        now: DateTime<Utc>,
+    ) -> Result<String, AccountError> {
+        let tokens = &mut self
+            .users
+            .get_mut(user)
+            .ok_or_else(|| AccountError::UnknownUser(user.to_string()))?
+            .tokens;
+        let id = hex(&random::<ID_BYTES>());
+        let secret = hex(&random::<SECRET_BYTES>());
+        tokens.push(StoredToken {
+            id: id.clone(),
+            hash: hash(&secret),
+            access,
+            created: now,
+        });
+        Ok(format!("{}_{}_{}", PREFIX, id, secret))
+    }
+
+    pub fn revoke_token(&mut self, id: &str) -> Result<(), AccountError> {
+        for user in self.users.values_mut() {
+            if let Some(at) = user.tokens.iter().position(|token| token.id == id) {
+                user.tokens.remove(at);
+                return Ok(());
+            }
+        }
+        Err(AccountError::UnknownToken(id.to_string()))
+    }
+
+    /// Every token, by user.
+    pub fn tokens(&self) -> Vec<TokenInfo> {
+        self.users
+            .iter()
+            .flat_map(|(name, user)| {
+                user.tokens.iter().map(move |token| TokenInfo {
+                    id: token.id.clone(),
+                    user: name.clone(),
+                    access: token.access,
+                    created: token.created,
+                })
+            })
+            .collect()
+    }
+
+    /// Who `token` belongs to.
+    pub fn authenticate(&self, token: &str) -> Result<Grant, AuthError> {
+        let mut parts = token.trim().splitn(3, '_');
+        let (Some(PREFIX), Some(id), Some(secret)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(AuthError::InvalidToken);
+        };
+        let hashed = hash(secret);
+        self.users
+            .iter()
+            .find_map(|(name, user)| {
+                let stored = user.tokens.iter().find(|stored| stored.id == id)?;
+                same(stored.hash.as_bytes(), hashed.as_bytes()).then(|| Grant {
+                    user: Some(name.clone()),
+                    access: stored.access,
+                })
+            })
+            .ok_or(AuthError::InvalidToken)
+    }
+}
+
+/// A server's view of the accounts file, read again whenever it changes, so tokens
+/// issued or revoked while the server runs take effect for the next client.
+#[derive(Debug)]
+pub struct AccountStore {
+    path: Option<PathBuf>,
+    loaded: Mutex<(Option<SystemTime>, Accounts)>,
+}
+
+impl AccountStore {
+    /// The accounts at `path`; a missing file has no users.
+    pub fn open(path: PathBuf) -> Result<Self, StorageError> {
+        let accounts = serializer::load_accounts(&path)?;
+        Ok(AccountStore {
+            loaded: Mutex::new((modified(&path), accounts)),
+            path: Some(path),
+        })
+    }
+
+    /// Whether any token could be accepted.
+    pub fn has_tokens(&self) -> bool {
+        !self.current().tokens().is_empty()
+    }
+
+    pub fn authenticate(&self, token: &str) -> Result<Grant, AuthError> {
+        self.current().authenticate(token)
+    }
+
+    // The accounts as the file now has them. A file that cannot be read keeps the
+    // accounts last read, so a half-done edit does not lock everyone out.
+    fn current(&self) -> Accounts {
+        let mut loaded = self.loaded.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(path) = &self.path {
+            let stamp = modified(path);
+            if stamp != loaded.0
+                && let Ok(accounts) = serializer::load_accounts(path)
+            {
+                *loaded = (stamp, accounts);
+            }
+        }
+        loaded.1.clone()
+    }
+}
+
+impl From<Accounts> for AccountStore {
+    fn from(accounts: Accounts) -> Self {
+        AccountStore {
+            path: None,
+            loaded: Mutex::new((None, accounts)),
+        }
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    path.metadata().and_then(|meta| meta.modified()).ok()
+}
+
+fn random<const N: usize>() -> [u8; N] {
+    let mut bytes = [0; N];
+    getrandom::fill(&mut bytes).expect("the system has a source of randomness");
+    bytes
+}
+
+fn hash(secret: &str) -> String {
+    hex(&Sha256::digest(secret.as_bytes()))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+// Compares in time that does not depend on where the inputs differ.
+fn same(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 10, 18, 9, 0, 0).unwrap()
+    }
+
+    fn accounts() -> (Accounts, String, String) {
+        let mut accounts = Accounts::default();
+        accounts.add_user("alice").unwrap();
+        accounts.add_user("bob").unwrap();
+        let write = accounts.issue_token("alice", Access::Write, now()).unwrap();
+        let read = accounts.issue_token("bob", Access::Read, now()).unwrap();
+        (accounts, write, read)
+    }
+
+    #[test]
+    fn test_tokens_authenticate_as_their_user() {
+        let (accounts, write, read) = accounts();
+        assert!(write.starts_with("mlw_"));
+        assert_eq!(write.len(), 4 + ID_BYTES * 2 + 1 + SECRET_BYTES * 2);
+
+        let alice = accounts.authenticate(&write).unwrap();
+        assert_eq!(alice.user.as_deref(), Some("alice"));
+        assert!(alice.allows(true));
+        let bob = accounts.authenticate(&format!(" {}\n", read)).unwrap();
+        assert_eq!(bob.access, Access::Read);
+        assert!(bob.allows(false));
+        assert!(!bob.allows(true));
+        assert!(Grant::everyone().allows(true));
+    }
+
+    #[test]
+    fn test_bad_tokens_are_refused_alike() {
+        let (accounts, write, _) = accounts();
+        let (id, secret) = write["mlw_".len()..].split_once('_').unwrap();
+        let mut wrong = secret.to_string();
+        wrong.replace_range(..1, if wrong.starts_with('0') { "1" } else { "0" });
+        for token in [
+            "",
+            "mlw_",
+            "nope",
+            &format!("mlw_{}", id),
+            &format!("mlw_{}_{}", id, wrong),
+            &format!("mlw_000000000000_{}", secret),
+            &format!("xyz_{}_{}", id, secret),
+        ] {
+            assert_eq!(accounts.authenticate(token), Err(AuthError::InvalidToken));
+        }
+    }
+
+    #[test]
+    fn test_only_hashes_are_stored() {
+        let (accounts, write, _) = accounts();
+        let json = serde_json::to_string(&accounts).unwrap();
+        let secret = write.rsplit('_').next().unwrap();
+        assert!(!json.contains(secret));
+        assert!(json.contains(&hash(secret)));
+        let loaded: Accounts = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, accounts);
+        assert!(loaded.authenticate(&write).is_ok());
+    }
+
+    #[test]
+    fn test_revoking_and_removing_users() {
+        let (mut accounts, write, read) = accounts();
+        let tokens = accounts.tokens();
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens[0].user, "alice");
+        assert_eq!(tokens[1].access, Access::Read);
+
+        accounts.revoke_token(&tokens[0].id).unwrap();
+        assert_eq!(accounts.authenticate(&write), Err(AuthError::InvalidToken));
+        assert_eq!(
+            accounts.revoke_token(&tokens[0].id),
+            Err(AccountError::UnknownToken(tokens[0].id.clone()))
+        );
+        accounts.remove_user("bob").unwrap();
+        assert_eq!(accounts.authenticate(&read), Err(AuthError::InvalidToken));
+        assert_eq!(accounts.users().collect::<Vec<_>>(), vec!["alice"]);
+        assert!(accounts.remove_user("bob").is_err());
+    }
+
+    #[test]
+    fn test_account_changes_are_checked() {
+        let (mut accounts, _, _) = accounts();
+        assert_eq!(
+            accounts.add_user("alice"),
+            Err(AccountError::UserExists("alice".to_string()))
+        );
+        for name in ["", "a b", "x_y\n"] {
+            assert!(matches!(
+                accounts.add_user(name),
+                Err(AccountError::InvalidName(_))
+            ));
+        }
+        assert_eq!(
+            accounts.issue_token("carol", Access::Read, now()),
+            Err(AccountError::UnknownUser("carol".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_store_rereads_the_file_when_it_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = Accounts::path_for(&dir.path().join("mlw.json"));
+        let store = AccountStore::open(path.clone()).unwrap();
+        assert!(!store.has_tokens());
+
+        let (accounts, write, _) = accounts();
+        serializer::save_accounts(&path, &accounts).unwrap();
+        assert!(store.has_tokens());
+        assert!(store.authenticate(&write).is_ok());
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(store.authenticate(&write).is_ok());
+        let in_memory = AccountStore::from(Accounts::default());
+        assert_eq!(in_memory.authenticate(&write), Err(AuthError::InvalidToken));
+    }
+}
@@ -0,0 +1,463 @@
+// Quick capture: one line of text turned into a task with its metadata.
+//
+//     Call dentist @phone #health +Personal due:fri defer:mon !high // bring insurance card
+//
+// Words claimed by a rule in `RULES` become metadata; every other word is part of the
+// task name. Everything after a standalone `//` is the note. Double quotes group words,
+// so `+"Run a 10k"` names a project with spaces, and a leading backslash keeps a word
+// literal (`\#1` is the name word `#1`). Dates take any expression `dates` reads;
+// quote ones with spaces, as in `due:"next friday"`.
+
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+
+use crate::dates::DateContext;
+use crate::entities::{Priority, ProjectId, Stake, Task};
+use crate::mlw::MLW;
+
+/// A parsed capture line, before any lookup against MLW.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Capture {
+    pub name: String,
+    /// The `+project` handle: an id, a global id or a project name.
+    pub project: Option<String>,
+    pub contexts: Vec<String>,
+    pub tags: Vec<String>,
+    pub due: Option<DateTime<Utc>>,
+    pub defer_until: Option<DateTime<Utc>>,
+    pub priority: Option<Priority>,
+    pub note: Option<String>,
+}
+
+/// Why a capture line could not become a task.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CaptureError {
+    EmptyName,
+    UnterminatedQuote,
+    DuplicateToken(&'static str),
+    InvalidDate { field: &'static str, value: String },
+    InvalidPriority(String),
+    UnknownProject(String),
+    AmbiguousProject { name: String, ids: Vec<u32> },
+}
+
+impl fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CaptureError::EmptyName => f.write_str("a capture needs a task name"),
+            CaptureError::UnterminatedQuote => f.write_str("unterminated quote"),
+            CaptureError::DuplicateToken(token) => {
+                write!(f, "{} may only be given once", token)
+            }
+            CaptureError::InvalidDate { field, value } => {
+                write!(f, "cannot read '{}' as a {} date", value, field)
+            }
+            CaptureError::InvalidPriority(value) => {
+                write!(
+                    f,
+                    "unknown priority '!{}', expected !high, !medium or !low",
+                    value
+                )
+            }
+            CaptureError::UnknownProject(name) => write!(f, "no project matches '{}'", name),
+            CaptureError::AmbiguousProject { name, ids } => {
+                let ids: Vec<String> = ids.iter().map(u32::to_string).collect();
+                write!(
+                    f,
+                    "'{}' matches several projects ({}); use its id",
+                    name,
+                    ids.join(", ")
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for CaptureError {}
+
+type Apply = fn(&mut Capture, &str, &DateContext) -> Result<(), CaptureError>;
+
+/// The token grammar: a word starting with the prefix, followed by a non-empty value,
+/// is handed to the rule. New metadata is added by adding a row here.
+const RULES: &[(&str, Apply)] = &[
+    ("@", |capture, value, _| {
+        push_unique(&mut capture.contexts, value);
+        Ok(())
+    }),
+    ("#", |capture, value, _| {
+        push_unique(&mut capture.tags, value);
+        Ok(())
+    }),
+    ("+", |capture, value, _| {
+        set_once(&mut capture.project, value.to_string(), "+project")
+    }),
+    ("!", |capture, value, _| {
+        let priority = value
+            .parse()
+            .map_err(|_| CaptureError::InvalidPriority(value.to_string()))?;
+        set_once(&mut capture.priority, priority, "!priority")
+    }),
+    ("due:", |capture, value, dates| {
+        let date = dates.parse(value).map_err(|_| CaptureError::InvalidDate {
+            field: "due",
+            value: value.to_string(),
+        })?;
+        set_once(&mut capture.due, date, "due:")
+    }),
+    ("defer:", |capture, value, dates| {
+        let date = dates.parse(value).map_err(|_| CaptureError::InvalidDate {
+            field: "defer",
+            value: value.to_string(),
+        })?;
+        set_once(&mut capture.defer_until, date, "defer:")
+    }),
+];
+
+fn push_unique(values: &mut Vec<String>, value: &str) {
+    if !values.iter().any(|existing| existing == value) {
+        values.push(value.to_string());
+    }
+}
+
+fn set_once<T>(slot: &mut Option<T>, value: T, token: &'static str) -> Result<(), CaptureError> {
+    if slot.is_some() {
+        return Err(CaptureError::DuplicateToken(token));
+    }
+    *slot = Some(value);
+    Ok(())
+}
+
+// Splits off the note at the first standalone `//`, so URLs keep their slashes.
+fn split_note(line: &str) -> (&str, Option<&str>) {
+    let mut search = 0;
+    while let Some(offset) = line[search..].find("//") {
+        let at = search + offset;
+        let before = line[..at].chars().next_back();
+        let after = line[at + 2..].chars().next();
+        if before.is_none_or(char::is_whitespace) && after.is_none_or(char::is_whitespace) {
+            return (&line[..at], Some(line[at + 2..].trim()));
+        }
+        search = at + 2;
+    }
+    (line, None)
+}
+
+// Whitespace-separated words, with double quotes grouping and a leading backslash
+// marking a word literal. Returns (word, literal) pairs.
+fn words(text: &str) -> Result<Vec<(String, bool)>, CaptureError> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut literal = false;
+    let mut started = false;
+    let mut quoted = false;
+    for c in text.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                started = true;
+            }
+            '\\' if !started => {
+                literal = true;
+                started = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if started {
+                    words.push((std::mem::take(&mut current), literal));
+                }
+                literal = false;
+                started = false;
+            }
+            c => {
+                current.push(c);
+                started = true;
+            }
+        }
+    }
+    if quoted {
+        return Err(CaptureError::UnterminatedQuote);
+    }
+    if started {
+        words.push((current, literal));
+    }
+    Ok(words)
+}
+
+/// Parses a capture line. `dates` anchors relative dates such as `tomorrow`.
+pub fn parse(line: &str, dates: &DateContext) -> Result<Capture, CaptureError> {
+    let (text, note) = split_note(line);
+    let mut capture = Capture {
+        note: note.filter(|note| !note.is_empty()).map(str::to_string),
+        ..Capture::default()
+    };
+    let mut name = Vec::new();
+    for (word, literal) in words(text)? {
+        let rule = RULES.iter().find_map(|(prefix, apply)| {
+            word.strip_prefix(prefix)
+                .filter(|value| !value.is_empty())
+                .map(|value| (apply, value))
+        });
+        match rule {
+            Some((apply, value)) if !literal => apply(&mut capture, value, dates)?,
+            _ => name.push(word),
+        }
+    }
+    capture.name = name.join(" ");
+    if capture.name.is_empty() {
+        return Err(CaptureError::EmptyName);
+    }
+    Ok(capture)
+}
+
+/// Finds the project a `+project` handle refers to: an id or global id first, then an
+/// open project with that name, ignoring case.
+pub fn find_project(mlw: &MLW, handle: &str) -> Result<ProjectId, CaptureError> {
+    if let Some(id) = mlw.resolve_project(handle) {
+        return Ok(id);
+    }
+    let ids: Vec<ProjectId> = mlw
+        .active_projects()
+        .into_iter()
+        .filter(|project| project.stake_name.eq_ignore_ascii_case(handle))
+        .map(|project| project.stake_id.clone())
+        .collect();
+    match ids.as_slice() {
+        [] => Err(CaptureError::UnknownProject(handle.to_string())),
+        [id] => Ok(id.clone()),
+        _ => Err(CaptureError::AmbiguousProject {
+            name: handle.to_string(),
+            ids: ids.iter().map(|id| id.0).collect(),
+        }),
+    }
+}
+
+impl Capture {
+    /// Creates the task in `mlw`. The project is looked up first, so an unknown
+    /// project leaves MLW untouched.
+    pub fn into_task(self, mlw: &mut MLW) -> Result<Stake<Task>, CaptureError> {
+        let project = self
+            .project
+            .as_deref()
+            .map(|handle| find_project(mlw, handle))
+            .transpose()?;
+        let mut task = mlw.new_task(self.name, project, self.note);
+        task.contexts = self.contexts;
+        task.tags = self.tags;
+        task.due = self.due;
+        task.defer_until = self.defer_until;
+        task.priority = self.priority;
+        mlw.update_task(task.clone())
+            .expect("a task that was just created exists");
+        Ok(task)
+    }
+}
+
+/// Parses `line` and creates the task it describes.
+pub fn capture(
+    mlw: &mut MLW,
+    line: &str,
+    dates: &DateContext,
+) -> Result<Stake<Task>, CaptureError> {
+    parse(line, dates)?.into_task(mlw)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    // A Wednesday.
+    fn now() -> DateContext {
+        DateContext::new(
+            Utc.with_ymd_and_hms(2026, 10, 14, 15, 30, 0).unwrap(),
+            chrono_tz::UTC,
+        )
+    }
+
+    fn day(y: i32, m: u32, d: u32) -> Option<DateTime<Utc>> {
+        Some(Utc.with_ymd_and_hms(y, m, d, 0, 0, 0).unwrap())
+    }
+
+    #[test]
+    fn test_parse_full_line() {
+        let capture = parse(
+            "Call dentist @phone #health +Personal due:fri defer:mon !high // bring insurance card",
+            &now(),
+        )
+        .unwrap();
+        assert_eq!(
+            capture,
+            Capture {
+                name: "Call dentist".to_string(),
+                project: Some("Personal".to_string()),
+                contexts: vec!["phone".to_string()],
+                tags: vec!["health".to_string()],
+                due: day(2026, 10, 16),
+                defer_until: day(2026, 10, 19),
+                priority: Some(Priority::High),
+                note: Some("bring insurance card".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_plain_text_is_just_a_name() {
+        let capture = parse("  Buy   milk ", &now()).unwrap();
+        assert_eq!(capture.name, "Buy milk");
+        assert_eq!(capture.project, None);
+        assert_eq!(capture.note, None);
+    }
+
+    #[test]
+    fn test_tokens_can_appear_anywhere_and_repeat() {
+        let capture = parse("@home Fix #diy the sink @errands #diy", &now()).unwrap();
+        assert_eq!(capture.name, "Fix the sink");
+        assert_eq!(capture.contexts, vec!["home", "errands"]);
+        assert_eq!(capture.tags, vec!["diy"]);
+    }
+
+    #[test]
+    fn test_quotes_group_project_names() {
+        let capture = parse("Buy shoes +\"Run a 10k\"", &now()).unwrap();
+        assert_eq!(capture.project.as_deref(), Some("Run a 10k"));
+        assert_eq!(capture.name, "Buy shoes");
+    }
+
+    #[test]
+    fn test_backslash_and_bare_prefixes_stay_in_name() {
+        let capture = parse("Fix issue \\#12 + tidy up @ home", &now()).unwrap();
+        assert_eq!(capture.name, "Fix issue #12 + tidy up @ home");
+        assert!(capture.tags.is_empty());
+    }
+
+    #[test]
+    fn test_note_needs_standalone_slashes() {
+        let capture = parse("Read https://example.com/post // for the team", &now()).unwrap();
+        assert_eq!(capture.name, "Read https://example.com/post");
+        assert_eq!(capture.note.as_deref(), Some("for the team"));
+
+        let capture = parse("Empty note //", &now()).unwrap();
+        assert_eq!(capture.note, None);
+    }
+
+    #[test]
+    fn test_priority_spellings() {
+        assert_eq!(
+            parse("a !low", &now()).unwrap().priority,
+            Some(Priority::Low)
+        );
+        assert_eq!(
+            parse("a !2", &now()).unwrap().priority,
+            Some(Priority::Medium)
+        );
+        assert_eq!(
+            parse("a !urgent", &now()),
+            Err(CaptureError::InvalidPriority("urgent".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_dates_accept_any_date_expression() {
+        let capture = parse("Pay rent due:eom defer:\"next monday\"", &now()).unwrap();
+        assert_eq!(capture.due, day(2026, 10, 31));
+        assert_eq!(capture.defer_until, day(2026, 10, 19));
+        assert_eq!(parse("a due:2w", &now()).unwrap().due, day(2026, 10, 28));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            parse("due:later Pay rent", &now()),
+            Err(CaptureError::InvalidDate {
+                field: "due",
+                value: "later".to_string()
+            })
+        );
+        assert_eq!(
+            parse("a +One +Two", &now()),
+            Err(CaptureError::DuplicateToken("+project"))
+        );
+        assert_eq!(
+            parse("@phone #health // note only", &now()),
+            Err(CaptureError::EmptyName)
+        );
+        assert_eq!(
+            parse("Say \"hi", &now()),
+            Err(CaptureError::UnterminatedQuote)
+        );
+    }
+
+    #[test]
+    fn test_error_messages() {
+        assert_eq!(
+            CaptureError::UnknownProject("Garden".to_string()).to_string(),
+            "no project matches 'Garden'"
+        );
+        assert_eq!(
+            CaptureError::AmbiguousProject {
+                name: "Home".to_string(),
+                ids: vec![1, 3]
+            }
+            .to_string(),
+            "'Home' matches several projects (1, 3); use its id"
+        );
+    }
+
+    #[test]
+    fn test_capture_creates_task_under_named_project() {
+        let mut mlw = MLW::new();
+        mlw.new_project("Personal".to_string(), None, None);
+        let task = capture(
+            &mut mlw,
+            "Call dentist @phone +personal due:fri !high // bring card",
+            &now(),
+        )
+        .unwrap();
+
+        let stored = mlw.get_task_by_id(&task.stake_id).unwrap();
+        assert_eq!(stored, &task);
+        assert_eq!(stored.parent_id, Some(ProjectId::new(1)));
+        assert_eq!(stored.contexts, vec!["phone"]);
+        assert_eq!(stored.due, day(2026, 10, 16));
+        assert_eq!(stored.priority, Some(Priority::High));
+        assert_eq!(stored.note.as_deref(), Some("bring card"));
+        assert!(stored.global_id.is_some());
+    }
+
+    #[test]
+    fn test_capture_accepts_project_id() {
+        let mut mlw = MLW::new();
+        mlw.new_project("Garden".to_string(), None, None);
+        let task = capture(&mut mlw, "Buy seeds +1", &now()).unwrap();
+        assert_eq!(task.parent_id, Some(ProjectId::new(1)));
+    }
+
+    #[test]
+    fn test_unknown_project_creates_nothing() {
+        let mut mlw = MLW::new();
+        let error = capture(&mut mlw, "Buy seeds +Garden", &now()).unwrap_err();
+        assert_eq!(error, CaptureError::UnknownProject("Garden".to_string()));
+        assert!(mlw.active_tasks().is_empty());
+    }
+
+    #[test]
+    fn test_ambiguous_and_closed_projects() {
+        let mut mlw = MLW::new();
+        mlw.new_project("Home".to_string(), None, None);
+        mlw.new_project("home".to_string(), None, None);
+        mlw.new_project("Old".to_string(), None, None);
+        mlw.mark_project_complete(&ProjectId::new(3)).unwrap();
+
+        assert_eq!(
+            find_project(&mlw, "HOME"),
+            Err(CaptureError::AmbiguousProject {
+                name: "HOME".to_string(),
+                ids: vec![1, 2]
+            })
+        );
+        assert_eq!(
+            find_project(&mlw, "old"),
+            Err(CaptureError::UnknownProject("old".to_string()))
+        );
+    }
+}
@@ -0,0 +1,838 @@
+// A typed client for `mlw serve`. `MlwClient` mirrors MLW's public API, each call
+// becoming one JSON-RPC request, and also runs CLI commands remotely.
+//
+// Connections are kept in a small pool and reused. A pooled connection the server has
+// since closed is replaced transparently; every other failure, including a timeout,
+// is returned to the caller. A client given a token authenticates each connection
+// as it opens, so reconnects stay signed in.
+
+use std::fmt;
+use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+
+use crate::auth::{AuthError, Grant};
+use crate::controller::protocol::{self, Request, Response, RpcError};
+use crate::entities::{Area, AreaId, Project, ProjectId, Stake, StakeError, Task, TaskId};
+use crate::mlw::MLW;
+use crate::mlw::sync::{Changeset, ReplicaId, SyncReport};
+use crate::operations::{Command, Outcome};
+
+/// Why a call did not produce a result.
+#[derive(Debug)]
+pub enum ClientError {
+    /// The server could not be reached, or the connection broke mid-call.
+    Io(io::Error),
+    /// The server did not answer within the client's timeout.
+    Timeout,
+    /// The server answered with something that is not the expected reply.
+    Protocol(String),
+    /// The server reported a stake error, as MLW would have returned it.
+    Stake(StakeError),
+    /// The server did not accept the client's token for the call.
+    Auth(AuthError),
+    /// The server refused the call for another reason.
+    Server(RpcError),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Io(error) => write!(f, "cannot reach the server: {}", error),
+            ClientError::Timeout => f.write_str("the server did not answer in time"),
+            ClientError::Protocol(detail) => {
+                write!(f, "unexpected reply from the server: {}", detail)
+            }
+            ClientError::Stake(error) => error.fmt(f),
+            ClientError::Auth(error) => error.fmt(f),
+            ClientError::Server(error) => f.write_str(&error.message),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<io::Error> for ClientError {
+    fn from(error: io::Error) -> Self {
+        match error.kind() {
+            ErrorKind::TimedOut | ErrorKind::WouldBlock => ClientError::Timeout,
+            _ => ClientError::Io(error),
+        }
+    }
+}
+
+impl From<RpcError> for ClientError {
+    fn from(error: RpcError) -> Self {
+        match error.code {
+            protocol::STAKE_NOT_FOUND => ClientError::Stake(StakeError::StakeNotFound),
+            protocol::CANNOT_ACTIVATE_DROPPED_STAKE => {
+                ClientError::Stake(StakeError::CannotActivateDroppedStake)
+            }
+            protocol::UNKNOWN_USER => match error.data.as_ref().and_then(Value::as_str) {
+                Some(user) => ClientError::Stake(StakeError::UnknownUser(user.to_string())),
+                None => ClientError::Server(error),
+            },
+            protocol::CANNOT_ASSIGN_AREA => ClientError::Stake(StakeError::CannotAssignArea),
+            protocol::AUTHENTICATION_REQUIRED => ClientError::Auth(AuthError::MissingToken),
+            protocol::INVALID_TOKEN => ClientError::Auth(AuthError::InvalidToken),
+            protocol::READ_ONLY_TOKEN => ClientError::Auth(AuthError::ReadOnlyToken),
+            _ => ClientError::Server(error),
+        }
+    }
+}
+
+/// A client for the server at one address. Safe to share between threads.
+#[derive(Debug)]
+pub struct MlwClient {
+    address: String,
+    timeout: Duration,
+    pool_size: usize,
+    idle: Mutex<Vec<Connection>>,
+    next_id: AtomicU64,
+    token: Option<String>,
+}
+
+impl MlwClient {
+    /// How long a connect, send or reply may take by default.
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+    /// How many idle connections are kept by default.
+    pub const DEFAULT_POOL_SIZE: usize = 4;
+
+    /// A client for `address` (`host:port`). Nothing is connected until the first call.
+    pub fn new(address: impl Into<String>) -> Self {
+        MlwClient {
+            address: address.into(),
+            timeout: Self::DEFAULT_TIMEOUT,
+            pool_size: Self::DEFAULT_POOL_SIZE,
+            idle: Mutex::new(Vec::new()),
+            next_id: AtomicU64::new(1),
+            token: None,
+        }
+    }
+
+    /// Authenticates every connection with `token`, as servers with accounts require.
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Keeps up to `pool_size` idle connections for reuse; 0 connects afresh every call.
+    pub fn with_pool_size(mut self, pool_size: usize) -> Self {
+        self.pool_size = pool_size;
+        self
+    }
+
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// Calls `method` with `params` and reads the result as `T`.
+    pub fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T, ClientError> {
+        let (id, request) = self.request(method, params);
+        read_reply(&self.exchange(&request)?, &id)
+    }
+
+    /// Who the server takes this client to be. Without a token the server refuses,
+    /// unless it does not check tokens.
+    pub fn authenticate(&self) -> Result<Grant, ClientError> {
+        let token = self.token.as_deref().unwrap_or_default();
+        self.call("authenticate", json!({ "token": token }))
+    }
+
+    fn request(&self, method: &str, params: Value) -> (Value, String) {
+        let id = json!(self.next_id.fetch_add(1, Ordering::Relaxed));
+        let request = Request::new(method, params, id.clone());
+        (
+            id,
+            serde_json::to_string(&request).expect("requests serialize"),
+        )
+    }
+
+    // Sends one request line and reads the reply line, on a pooled connection if one
+    // is idle. If that connection turns out to be closed, a fresh one is tried once.
+    fn exchange(&self, request: &str) -> Result<String, ClientError> {
+        let pooled = self.idle().pop();
+        if let Some(mut connection) = pooled {
+            match connection.exchange(request) {
+                Ok(reply) => {
+                    self.release(connection);
+                    return Ok(reply);
+                }
+                Err(error) if is_disconnect(&error) => {}
+                Err(error) => return Err(error.into()),
+            }
+        }
+        let mut connection = Connection::open(&self.address, self.timeout)?;
+        self.log_in(&mut connection)?;
+        let reply = connection.exchange(request)?;
+        self.release(connection);
+        Ok(reply)
+    }
+
+    // Sends the token, if there is one, on a connection that has just opened.
+    fn log_in(&self, connection: &mut Connection) -> Result<(), ClientError> {
+        if let Some(token) = &self.token {
+            let (id, request) = self.request("authenticate", json!({ "token": token }));
+            read_reply::<Grant>(&connection.exchange(&request)?, &id)?;
+        }
+        Ok(())
+    }
+
+    fn idle(&self) -> std::sync::MutexGuard<'_, Vec<Connection>> {
+        self.idle.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn release(&self, connection: Connection) {
+        let mut idle = self.idle();
+        if idle.len() < self.pool_size {
+            idle.push(connection);
+        }
+    }
+
+    // --- Commands ---
+    /// Runs a CLI command on the server, as `operations::execute` would locally.
+    /// Commands that only read are sent as queries, so they never rewrite the file.
+    pub fn execute(&self, command: &Command) -> Result<Outcome, ClientError> {
+        let method = if command.is_mutating() {
+            "execute"
+        } else {
+            "query"
+        };
+        let outcome: Value = self.call(method, json!({ "command": command }))?;
+        protocol::decode_outcome(outcome).map_err(|error| ClientError::Protocol(error.to_string()))
+    }
+
+    // --- Areas ---
+    pub fn new_area(&self, name: String, note: Option<String>) -> Result<Stake<Area>, ClientError> {
+        self.call("new_area", json!({ "name": name, "note": note }))
+    }
+
+    pub fn active_areas(&self) -> Result<Vec<Stake<Area>>, ClientError> {
+        self.call("active_areas", Value::Null)
+    }
+
+    pub fn completed_areas(&self) -> Result<Vec<Stake<Area>>, ClientError> {
+        self.call("completed_areas", Value::Null)
+    }
+
+    pub fn next_area_id(&self) -> Result<AreaId, ClientError> {
+        self.call("next_area_id", Value::Null)
+    }
+
+    pub fn get_area_by_id(&self, id: &AreaId) -> Result<Option<Stake<Area>>, ClientError> {
+        self.call("get_area_by_id", json!({ "id": id }))
+    }
+
+    pub fn update_area(&self, stake: &Stake<Area>) -> Result<(), ClientError> {
+        self.call("update_area", json!({ "stake": stake }))
+    }
+
+    pub fn mark_area_complete(&self, id: &AreaId) -> Result<(), ClientError> {
+        self.call("mark_area_complete", json!({ "id": id }))
+    }
+
+    pub fn mark_area_dropped(&self, id: &AreaId) -> Result<(), ClientError> {
+        self.call("mark_area_dropped", json!({ "id": id }))
+    }
+
+    pub fn mark_area_reviewed(&self, id: &AreaId) -> Result<(), ClientError> {
+        self.call("mark_area_reviewed", json!({ "id": id }))
+    }
+
+    // --- Projects ---
+    pub fn new_project(
+        &self,
+        name: String,
+        parent_id: Option<AreaId>,
+        note: Option<String>,
+    ) -> Result<Stake<Project>, ClientError> {
+        self.call(
+            "new_project",
+            json!({ "name": name, "parent_id": parent_id, "note": note }),
+        )
+    }
+
+    pub fn active_projects(&self) -> Result<Vec<Stake<Project>>, ClientError> {
+        self.call("active_projects", Value::Null)
+    }
+
+    pub fn completed_projects(&self) -> Result<Vec<Stake<Project>>, ClientError> {
+        self.call("completed_projects", Value::Null)
+    }
+
+    pub fn next_project_id(&self) -> Result<ProjectId, ClientError> {
+        self.call("next_project_id", Value::Null)
+    }
+
+    pub fn get_project_by_id(&self, id: &ProjectId) -> Result<Option<Stake<Project>>, ClientError> {
+        self.call("get_project_by_id", json!({ "id": id }))
+    }
+
+    pub fn update_project(&self, stake: &Stake<Project>) -> Result<(), ClientError> {
+        self.call("update_project", json!({ "stake": stake }))
+    }
+
+    pub fn mark_project_complete(&self, id: &ProjectId) -> Result<(), ClientError> {
+        self.call("mark_project_complete", json!({ "id": id }))
+    }
+
+    pub fn mark_project_dropped(&self, id: &ProjectId) -> Result<(), ClientError> {
+        self.call("mark_project_dropped", json!({ "id": id }))
+    }
+
+    pub fn mark_project_reviewed(&self, id: &ProjectId) -> Result<(), ClientError> {
+        self.call("mark_project_reviewed", json!({ "id": id }))
+    }
+
+    pub fn get_project_children(
+        &self,
+        parent_id: &AreaId,
+    ) -> Result<Vec<Stake<Project>>, ClientError> {
+        self.call("get_project_children", json!({ "parent_id": parent_id }))
+    }
+
+    pub fn move_project(
+        &self,
+        id: &ProjectId,
+        new_parent_id: Option<AreaId>,
+    ) -> Result<(), ClientError> {
+        self.call(
+            "move_project",
+            json!({ "id": id, "parent_id": new_parent_id }),
+        )
+    }
+
+    pub fn assign_project(
+        &self,
+        id: &ProjectId,
+        assignee: Option<String>,
+    ) -> Result<(), ClientError> {
+        self.call("assign_project", json!({ "id": id, "assignee": assignee }))
+    }
+
+    pub fn projects_assigned_to(&self, user: &str) -> Result<Vec<Stake<Project>>, ClientError> {
+        self.call("projects_assigned_to", json!({ "user": user }))
+    }
+
+    // --- Tasks ---
+    pub fn new_task(
+        &self,
+        name: String,
+        parent_id: Option<ProjectId>,
+        note: Option<String>,
+    ) -> Result<Stake<Task>, ClientError> {
+        self.call(
+            "new_task",
+            json!({ "name": name, "parent_id": parent_id, "note": note }),
+        )
+    }
+
+    pub fn active_tasks(&self) -> Result<Vec<Stake<Task>>, ClientError> {
+        self.call("active_tasks", Value::Null)
+    }
+
+    pub fn completed_tasks(&self) -> Result<Vec<Stake<Task>>, ClientError> {
+        self.call("completed_tasks", Value::Null)
+    }
+
+    pub fn next_task_id(&self) -> Result<TaskId, ClientError> {
+        self.call("next_task_id", Value::Null)
+    }
+
+    pub fn get_task_by_id(&self, id: &TaskId) -> Result<Option<Stake<Task>>, ClientError> {
+        self.call("get_task_by_id", json!({ "id": id }))
+    }
+
+    pub fn update_task(&self, stake: &Stake<Task>) -> Result<(), ClientError> {
+        self.call("update_task", json!({ "stake": stake }))
+    }
+
+    pub fn mark_task_complete(&self, id: &TaskId) -> Result<(), ClientError> {
+        self.call("mark_task_complete", json!({ "id": id }))
+    }
+
+    pub fn mark_task_dropped(&self, id: &TaskId) -> Result<(), ClientError> {
+        self.call("mark_task_dropped", json!({ "id": id }))
+    }
+
+    pub fn mark_task_reviewed(&self, id: &TaskId) -> Result<(), ClientError> {
+        self.call("mark_task_reviewed", json!({ "id": id }))
+    }
+
+    pub fn get_task_children(
+        &self,
+        parent_id: &ProjectId,
+    ) -> Result<Vec<Stake<Task>>, ClientError> {
+        self.call("get_task_children", json!({ "parent_id": parent_id }))
+    }
+
+    pub fn move_task(
+        &self,
+        id: &TaskId,
+        new_parent_id: Option<ProjectId>,
+    ) -> Result<(), ClientError> {
+        self.call("move_task", json!({ "id": id, "parent_id": new_parent_id }))
+    }
+
+    pub fn assign_task(&self, id: &TaskId, assignee: Option<String>) -> Result<(), ClientError> {
+        self.call("assign_task", json!({ "id": id, "assignee": assignee }))
+    }
+
+    pub fn tasks_assigned_to(&self, user: &str) -> Result<Vec<Stake<Task>>, ClientError> {
+        self.call("tasks_assigned_to", json!({ "user": user }))
+    }
+
+    // --- Members ---
+    pub fn members(&self) -> Result<Vec<String>, ClientError> {
+        self.call("members", Value::Null)
+    }
+
+    /// Returns false if `name` already was a member.
+    pub fn add_member(&self, name: &str) -> Result<bool, ClientError> {
+        self.call("add_member", json!({ "name": name }))
+    }
+
+    /// Returns false if `name` was not a member.
+    pub fn remove_member(&self, name: &str) -> Result<bool, ClientError> {
+        self.call("remove_member", json!({ "name": name }))
+    }
+
+    // --- Collections, identifiers and search ---
+    /// Every area, including completed and dropped ones.
+    pub fn areas(&self) -> Result<Vec<Stake<Area>>, ClientError> {
+        self.call("areas", Value::Null)
+    }
+
+    /// Every project, including completed and dropped ones.
+    pub fn projects(&self) -> Result<Vec<Stake<Project>>, ClientError> {
+        self.call("projects", Value::Null)
+    }
+
+    /// Every task, including completed and dropped ones.
+    pub fn tasks(&self) -> Result<Vec<Stake<Task>>, ClientError> {
+        self.call("tasks", Value::Null)
+    }
+
+    pub fn resolve_area(&self, handle: &str) -> Result<Option<AreaId>, ClientError> {
+        self.call("resolve_area", json!({ "handle": handle }))
+    }
+
+    pub fn resolve_project(&self, handle: &str) -> Result<Option<ProjectId>, ClientError> {
+        self.call("resolve_project", json!({ "handle": handle }))
+    }
+
+    pub fn resolve_task(&self, handle: &str) -> Result<Option<TaskId>, ClientError> {
+        self.call("resolve_task", json!({ "handle": handle }))
+    }
+
+    pub fn assign_global_ids(&self) -> Result<usize, ClientError> {
+        self.call("assign_global_ids", Value::Null)
+    }
+
+    // The views below borrow from MLW on the server, so they arrive as plain JSON in
+    // the shape `FuzzyMatch`, `Hierarchy`, `AreaNode` and `ProjectNode` serialize to.
+
+    pub fn fuzzy_search(&self, query: &str) -> Result<Value, ClientError> {
+        self.call("fuzzy_search", json!({ "query": query }))
+    }
+
+    pub fn hierarchy(&self) -> Result<Value, ClientError> {
+        self.call("hierarchy", Value::Null)
+    }
+
+    pub fn area_tree(&self, id: &AreaId) -> Result<Value, ClientError> {
+        self.call("area_tree", json!({ "id": id }))
+    }
+
+    pub fn project_tree(&self, id: &ProjectId) -> Result<Value, ClientError> {
+        self.call("project_tree", json!({ "id": id }))
+    }
+
+    // --- Sync ---
+    pub fn replica_id(&self) -> Result<ReplicaId, ClientError> {
+        self.call("replica_id", Value::Null)
+    }
+
+    pub fn merge_changes(
+        &self,
+        changes: &Changeset,
+    ) -> Result<(Changeset, SyncReport), ClientError> {
+        self.call("merge_changes", json!({ "changes": changes }))
+    }
+
+    /// Syncs `mlw` with the server's MLW, as `sync::sync` would in process. The
+    /// caller saves `mlw` afterwards.
+    pub fn sync(&self, mlw: &mut MLW) -> Result<SyncReport, ClientError> {
+        let changes = mlw.changes_for(self.replica_id()?);
+        let (reply, mut report) = self.merge_changes(&changes)?;
+        report.unresolved.extend(mlw.accept_merge(reply));
+        Ok(report)
+    }
+
+    // --- Agenda, in the server's timezone and week ---
+    pub fn tasks_due_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<Stake<Task>>, ClientError> {
+        self.call("tasks_due_between", json!({ "start": start, "end": end }))
+    }
+
+    pub fn overdue_tasks(&self) -> Result<Vec<Stake<Task>>, ClientError> {
+        self.call("overdue_tasks", Value::Null)
+    }
+
+    pub fn tasks_due_today(&self) -> Result<Vec<Stake<Task>>, ClientError> {
+        self.call("tasks_due_today", Value::Null)
+    }
+
+    pub fn tasks_due_this_week(&self) -> Result<Vec<Stake<Task>>, ClientError> {
+        self.call("tasks_due_this_week", Value::Null)
+    }
+}
+
+// Errors that mean the other end has gone away, rather than that it is slow.
+fn is_disconnect(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        ErrorKind::UnexpectedEof
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::BrokenPipe
+    )
+}
+
+// Reads the reply to the request `id` as `T`.
+fn read_reply<T: DeserializeOwned>(reply: &str, id: &Value) -> Result<T, ClientError> {
+    let response: Response =
+        serde_json::from_str(reply).map_err(|error| ClientError::Protocol(error.to_string()))?;
+    if response.id != *id {
+        return Err(ClientError::Protocol(format!(
+            "reply to request {} arrived for request {}",
+            response.id, id
+        )));
+    }
+    serde_json::from_value(response.into_result()?)
+        .map_err(|error| ClientError::Protocol(error.to_string()))
+}
+
+#[derive(Debug)]
+struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Connection {
+    // Tries each address `address` resolves to, as `TcpStream::connect` does, but
+    // with a time limit on each attempt.
+    fn open(address: &str, timeout: Duration) -> io::Result<Connection> {
+        let mut last_error = None;
+        for socket in address.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&socket, timeout) {
+                Ok(stream) => {
+                    stream.set_read_timeout(Some(timeout))?;
+                    stream.set_write_timeout(Some(timeout))?;
+                    stream.set_nodelay(true)?;
+                    return Ok(Connection {
+                        reader: BufReader::new(stream.try_clone()?),
+                        writer: stream,
+                    });
+                }
+                Err(error) => last_error = Some(error),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| {
+            io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("{} has no address", address),
+            )
+        }))
+    }
+
+    fn exchange(&mut self, request: &str) -> io::Result<String> {
+        self.writer.write_all(format!("{}\n", request).as_bytes())?;
+        self.writer.flush()?;
+        let mut reply = String::new();
+        if self.reader.read_line(&mut reply)? == 0 {
+            return Err(ErrorKind::UnexpectedEof.into());
+        }
+        Ok(reply)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{Access, AccountStore, Accounts};
+    use crate::config::Config;
+    use crate::controller::Server;
+    use crate::entities::StakeId;
+    use crate::operations::TaskAction;
+    use std::net::TcpListener;
+    use std::thread;
+
+    fn serve(server: Server) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        thread::spawn(move || server.serve_tcp(listener));
+        address
+    }
+
+    fn client() -> (MlwClient, Server) {
+        let server = Server::new(MLW::new(), Config::default());
+        (MlwClient::new(serve(server.clone())), server)
+    }
+
+    #[test]
+    fn test_client_mirrors_mlw() {
+        let (client, server) = client();
+        let area = client.new_area("Health".to_string(), None).unwrap();
+        let project = client
+            .new_project("Run".to_string(), Some(area.stake_id.clone()), None)
+            .unwrap();
+        let task = client
+            .new_task(
+                "Shoes".to_string(),
+                Some(project.stake_id.clone()),
+                Some("size 44".to_string()),
+            )
+            .unwrap();
+        assert_eq!(task.note.as_deref(), Some("size 44"));
+
+        client.mark_task_complete(&task.stake_id).unwrap();
+        assert!(client.active_tasks().unwrap().is_empty());
+        assert_eq!(client.completed_tasks().unwrap()[0].stake_id, task.stake_id);
+        assert_eq!(
+            client.get_project_children(&area.stake_id).unwrap()[0].stake_name,
+            "Run"
+        );
+        client.move_project(&project.stake_id, None).unwrap();
+        assert_eq!(
+            client
+                .get_project_by_id(&project.stake_id)
+                .unwrap()
+                .unwrap()
+                .parent_id,
+            None
+        );
+        assert_eq!(client.next_task_id().unwrap(), StakeId::new(2));
+        assert_eq!(
+            client
+                .resolve_area(&area.global_id.unwrap().to_string())
+                .unwrap(),
+            Some(area.stake_id.clone())
+        );
+
+        let mut renamed = client.get_area_by_id(&area.stake_id).unwrap().unwrap();
+        renamed.stake_name = "Health & fitness".to_string();
+        client.update_area(&renamed).unwrap();
+        assert_eq!(
+            server.snapshot().get_area_by_id(&area.stake_id),
+            Some(&renamed)
+        );
+        assert_eq!(
+            client.hierarchy().unwrap()["areas"][0]["area"]["stake_name"],
+            "Health & fitness"
+        );
+    }
+
+    #[test]
+    fn test_sync_with_server() {
+        let (client, server) = client();
+        client
+            .new_task("From the server".to_string(), None, None)
+            .unwrap();
+        let mut laptop = MLW::new();
+        laptop.new_task("From the laptop".to_string(), None, None);
+
+        let report = client.sync(&mut laptop).unwrap();
+        assert_eq!(report.merged, 2);
+        assert!(report.conflicts.is_empty());
+        let server_mlw = server.snapshot();
+        for task in laptop.tasks().iter() {
+            let there = server_mlw
+                .tasks()
+                .get_by_global_id(&task.global_id.unwrap());
+            assert_eq!(there.unwrap().stake_name, task.stake_name);
+        }
+        assert_eq!(server_mlw.tasks().len(), 2);
+        assert_eq!(client.sync(&mut laptop).unwrap().merged, 0);
+    }
+
+    #[test]
+    fn test_stake_errors_come_back_typed() {
+        let (client, _) = client();
+        assert!(matches!(
+            client.mark_task_dropped(&StakeId::new(9)),
+            Err(ClientError::Stake(StakeError::StakeNotFound))
+        ));
+        assert_eq!(client.get_task_by_id(&StakeId::new(9)).unwrap(), None);
+        let task = client
+            .new_task("Write copy".to_string(), None, None)
+            .unwrap();
+        assert!(matches!(
+            client.assign_task(&task.stake_id, Some("cal".to_string())),
+            Err(ClientError::Stake(StakeError::UnknownUser(user))) if user == "cal"
+        ));
+        let area = client.new_area("Work".to_string(), None).unwrap();
+        assert!(matches!(
+            client.update_area(&Stake {
+                assignee: Some("cal".to_string()),
+                ..area
+            }),
+            Err(ClientError::Stake(StakeError::CannotAssignArea))
+        ));
+        let unknown: Result<Value, _> = client.call("fly", Value::Null);
+        match unknown {
+            Err(ClientError::Server(error)) => assert_eq!(error.code, protocol::METHOD_NOT_FOUND),
+            other => panic!("expected a server error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_tokens_sign_in_every_connection() {
+        let mut accounts = Accounts::default();
+        accounts.add_user("alice").unwrap();
+        let write = accounts
+            .issue_token("alice", Access::Write, Utc::now())
+            .unwrap();
+        let read = accounts
+            .issue_token("alice", Access::Read, Utc::now())
+            .unwrap();
+        let server =
+            Server::new(MLW::new(), Config::default()).with_accounts(AccountStore::from(accounts));
+        let address = serve(server);
+
+        let anonymous = MlwClient::new(address.clone());
+        assert!(matches!(
+            anonymous.active_tasks(),
+            Err(ClientError::Auth(AuthError::MissingToken))
+        ));
+        let forged = MlwClient::new(address.clone()).with_token("mlw_0_0");
+        assert!(matches!(
+            forged.active_tasks(),
+            Err(ClientError::Auth(AuthError::InvalidToken))
+        ));
+
+        // Without a pool every call opens, and signs in, a new connection.
+        let writer = MlwClient::new(address.clone())
+            .with_token(write)
+            .with_pool_size(0);
+        writer.new_task("Call".to_string(), None, None).unwrap();
+        writer.new_task("Write".to_string(), None, None).unwrap();
+        let grant = writer.authenticate().unwrap();
+        assert_eq!(grant.user.as_deref(), Some("alice"));
+        assert_eq!(grant.access, Access::Write);
+
+        let reader = MlwClient::new(address).with_token(read);
+        assert_eq!(reader.active_tasks().unwrap().len(), 2);
+        assert!(matches!(
+            reader.new_task("Nope".to_string(), None, None),
+            Err(ClientError::Auth(AuthError::ReadOnlyToken))
+        ));
+    }
+
+    #[test]
+    fn test_execute_runs_cli_commands_remotely() {
+        let (client, server) = client();
+        let add = Command::Task {
+            action: TaskAction::Add {
+                name: vec!["Buy".to_string(), "shoes".to_string()],
+                project: None,
+                note: None,
+                due: None,
+                defer: None,
+            },
+        };
+        let Outcome::Created(view) = client.execute(&add).unwrap() else {
+            panic!("expected a created task");
+        };
+        assert_eq!(view.name, "Buy shoes");
+        let list = Command::List {
+            kind: crate::entities::StakeKind::Task,
+            all: false,
+        };
+        let Outcome::Stakes(stakes) = client.execute(&list).unwrap() else {
+            panic!("expected a list");
+        };
+        assert_eq!(stakes, vec![view]);
+        assert_eq!(server.snapshot().tasks().len(), 1);
+    }
+
+    #[test]
+    fn test_pool_reuses_connections_and_is_shared_between_threads() {
+        let (client, server) = client();
+        let client = std::sync::Arc::new(client.with_pool_size(2));
+        let workers: Vec<_> = (0..6)
+            .map(|n| {
+                let client = client.clone();
+                thread::spawn(move || {
+                    for i in 0..10 {
+                        client.new_task(format!("{}-{}", n, i), None, None).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+        assert_eq!(server.snapshot().tasks().len(), 60);
+        assert!(client.idle().len() <= 2);
+    }
+
+    #[test]
+    fn test_reconnects_after_server_closes_idle_connection() {
+        // A server that answers one request per connection and then hangs up, as a
+        // restarted server would leave a pooled connection.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let server = Server::new(MLW::new(), Config::default());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let reply = server.handle_line(&mut server.session(), &line).unwrap();
+                writeln!(&stream, "{}", reply).unwrap();
+            }
+        });
+
+        let client = MlwClient::new(address);
+        for n in 1..=3 {
+            let task = client.new_task(format!("Task {}", n), None, None).unwrap();
+            assert_eq!(task.stake_id, StakeId::new(n));
+        }
+    }
+
+    #[test]
+    fn test_timeout_and_unreachable_server() {
+        // Accepts connections but never answers.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        thread::spawn(move || {
+            let _held: Vec<_> = listener.incoming().take(1).collect();
+            thread::sleep(Duration::from_secs(5));
+        });
+        let client = MlwClient::new(address).with_timeout(Duration::from_millis(100));
+        assert!(matches!(client.next_task_id(), Err(ClientError::Timeout)));
+
+        // Nothing listens on a port that was just released.
+        let closed = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = closed.local_addr().unwrap().to_string();
+        drop(closed);
+        let client = MlwClient::new(address);
+        assert!(matches!(client.active_tasks(), Err(ClientError::Io(_))));
+    }
+}
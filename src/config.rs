@@ -0,0 +1,176 @@
+// User preferences that decide how dates are read and shown: the IANA timezone that
+// "today" and "this week" are measured in, and the day weeks start on. Stake dates
+// are always stored in UTC; only reading and display use these settings.
+//
+// The config lives next to the data file (`~/.mlw.json` -> `~/.mlw.config.json`), and
+// a missing file means UTC with weeks starting on Monday.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::dates::DateContext;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub timezone: Tz,
+    pub week_start: Weekday,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            timezone: Tz::UTC,
+            week_start: Weekday::Mon,
+        }
+    }
+}
+
+/// A setting that could not be changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    UnknownKey(String),
+    InvalidValue { key: &'static str, value: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::UnknownKey(key) => write!(
+                f,
+                "unknown setting '{}', expected timezone or week-start",
+                key
+            ),
+            ConfigError::InvalidValue { key, value } => {
+                write!(f, "'{}' is not a valid {}", value, key)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    /// Where the config for `data_file` is kept.
+    pub fn path_for(data_file: &Path) -> PathBuf {
+        data_file.with_extension("config.json")
+    }
+
+    /// The context date expressions and date queries are evaluated in at `now`.
+    pub fn date_context(&self, now: DateTime<Utc>) -> DateContext {
+        DateContext::new(now, self.timezone).with_week_start(self.week_start)
+    }
+
+    /// Changes one setting by name, as typed on the command line.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), ConfigError> {
+        match key {
+            "timezone" | "tz" => {
+                self.timezone = value.parse().map_err(|_| ConfigError::InvalidValue {
+                    key: "timezone",
+                    value: value.to_string(),
+                })?;
+            }
+            "week-start" | "week_start" => {
+                self.week_start = value.parse().map_err(|_| ConfigError::InvalidValue {
+                    key: "week-start",
+                    value: value.to_string(),
+                })?;
+            }
+            _ => return Err(ConfigError::UnknownKey(key.to_string())),
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "timezone:   {}\nweek-start: {}",
+            self.timezone, self.week_start
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, TimeZone};
+
+    #[test]
+    fn test_default_is_utc_monday() {
+        let config = Config::default();
+        assert_eq!(config.timezone, Tz::UTC);
+        assert_eq!(config.week_start, Weekday::Mon);
+    }
+
+    #[test]
+    fn test_path_sits_next_to_data_file() {
+        assert_eq!(
+            Config::path_for(Path::new("/home/me/.mlw.json")),
+            PathBuf::from("/home/me/.mlw.config.json")
+        );
+    }
+
+    #[test]
+    fn test_set_timezone_and_week_start() {
+        let mut config = Config::default();
+        config.set("timezone", "Europe/Berlin").unwrap();
+        config.set("week-start", "sunday").unwrap();
+        assert_eq!(config.timezone, chrono_tz::Europe::Berlin);
+        assert_eq!(config.week_start, Weekday::Sun);
+        assert_eq!(
+            config.to_string(),
+            "timezone:   Europe/Berlin\nweek-start: Sun"
+        );
+    }
+
+    #[test]
+    fn test_set_rejects_bad_input() {
+        let mut config = Config::default();
+        assert_eq!(
+            config.set("timezone", "Mars/Olympus_Mons"),
+            Err(ConfigError::InvalidValue {
+                key: "timezone",
+                value: "Mars/Olympus_Mons".to_string()
+            })
+        );
+        assert_eq!(
+            config.set("locale", "en").unwrap_err().to_string(),
+            "unknown setting 'locale', expected timezone or week-start"
+        );
+        assert_eq!(config, Config::default());
+    }
+
+    #[test]
+    fn test_json_roundtrip_and_partial_files() {
+        let config = Config {
+            timezone: chrono_tz::America::New_York,
+            week_start: Weekday::Sun,
+        };
+        let json = serde_json::to_value(config).unwrap();
+        assert_eq!(json["timezone"], "America/New_York");
+        assert_eq!(serde_json::from_value::<Config>(json).unwrap(), config);
+
+        let partial: Config = serde_json::from_str(r#"{ "timezone": "Asia/Tokyo" }"#).unwrap();
+        assert_eq!(partial.week_start, Weekday::Mon);
+    }
+
+    #[test]
+    fn test_date_context_uses_zone_and_week_start() {
+        let config = Config {
+            timezone: chrono_tz::Asia::Tokyo,
+            week_start: Weekday::Sun,
+        };
+        // 20:00 UTC on Saturday is already Sunday morning in Tokyo.
+        let now = Utc.with_ymd_and_hms(2026, 10, 17, 20, 0, 0).unwrap();
+        let dates = config.date_context(now);
+        let sunday = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+        assert_eq!(dates.today(), sunday);
+        assert_eq!(dates.week_of(sunday), sunday);
+    }
+}
@@ -0,0 +1,85 @@
+// `mlw serve`: a daemon that owns one MLW and answers JSON-RPC 2.0 requests over TCP
+// and Unix sockets, and REST requests over HTTP. `protocol` holds the wire types and
+// error codes, `dispatch` maps method names onto MLW's API, `rest` maps routes onto
+// it, `openapi` describes those routes, and `server` accepts connections and runs
+// the calls.
+
+pub mod dispatch;
+pub mod events;
+pub mod openapi;
+pub mod protocol;
+pub mod rest;
+pub mod server;
+
+use std::net::TcpListener;
+use std::path::Path;
+use std::thread;
+
+pub use protocol::{Request, Response, RpcError};
+pub use server::Server;
+
+use crate::auth::{AccountStore, Accounts};
+use crate::config::Config;
+use crate::serializer;
+
+/// Where `mlw serve` listens when given neither an address nor a socket.
+pub const DEFAULT_ADDRESS: &str = "127.0.0.1:7878";
+
+/// Serves the data file at `path` until the process is stopped. Answers JSON-RPC on
+/// `address` and, on Unix, on the socket at `socket`, and REST on `http`; with none
+/// of them, JSON-RPC on `DEFAULT_ADDRESS`. Clients need a token from the accounts
+/// kept beside the data file.
+pub fn run(
+    path: &Path,
+    address: Option<&str>,
+    socket: Option<&Path>,
+    http: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let config = serializer::load_config(&Config::path_for(path))?;
+    let accounts = AccountStore::open(Accounts::path_for(path))?;
+    if !accounts.has_tokens() {
+        eprintln!(
+            "mlw serve: no access tokens yet, so every request will be refused; \
+             run `mlw user add NAME` and `mlw token issue NAME`"
+        );
+    }
+    let server = Server::new(serializer::load(path)?, config)
+        .with_data_file(path.to_path_buf())
+        .with_accounts(accounts);
+    let address = match (address, socket, http) {
+        (None, None, None) => Some(DEFAULT_ADDRESS),
+        _ => address,
+    };
+
+    let mut listeners = Vec::new();
+    #[cfg(unix)]
+    if let Some(socket) = socket {
+        let listener = server::bind_unix(socket)?;
+        eprintln!("Listening on {}", socket.display());
+        let server = server.clone();
+        listeners.push(thread::spawn(move || server.serve_unix(listener)));
+    }
+    #[cfg(not(unix))]
+    if socket.is_some() {
+        return Err("Unix sockets are not available on this platform".into());
+    }
+    if let Some(address) = address {
+        let listener = TcpListener::bind(address)?;
+        eprintln!("Listening on {}", listener.local_addr()?);
+        let server = server.clone();
+        listeners.push(thread::spawn(move || server.serve_tcp(listener)));
+    }
+    if let Some(http) = http {
+        let listener = server::bind_http(http)?;
+        match listener.server_addr().to_ip() {
+            Some(address) => eprintln!("Listening on http://{}", address),
+            None => eprintln!("Listening on {}", listener.server_addr()),
+        }
+        let server = server.clone();
+        listeners.push(thread::spawn(move || server.serve_http(listener)));
+    }
+    for listener in listeners {
+        listener.join().map_err(|_| "listener thread panicked")??;
+    }
+    Ok(())
+}
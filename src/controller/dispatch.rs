@@ -0,0 +1,762 @@
+// Maps JSON-RPC method names onto MLW's public API. Methods are named after the MLW
+// function they call and take that function's arguments as named params (or, in the
+// same order, positional ones). Results are the function's return value as JSON;
+// functions returning `()` answer null.
+//
+// Constructors and clock plumbing are not exposed: the server owns its MLW.
+
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::protocol::{self, RpcError};
+use crate::dates::DateContext;
+use crate::entities::{Area, Kind, Project, Stake, StakeId, Task};
+use crate::mlw::MLW;
+use crate::mlw::sync::Changeset;
+use crate::operations::{self, Command};
+
+type ReadFn = fn(&MLW, &DateContext, Value) -> Result<Value, RpcError>;
+type WriteFn = fn(&mut MLW, &DateContext, Value) -> Result<Value, RpcError>;
+
+/// How a method gets at the MLW. Reads can run side by side; writes run alone and
+/// are followed by a save.
+#[derive(Clone, Copy)]
+pub enum Handler {
+    Read(ReadFn),
+    Write(WriteFn),
+}
+
+impl Handler {
+    pub fn is_write(&self) -> bool {
+        matches!(self, Handler::Write(_))
+    }
+}
+
+/// Every method the server answers, in the order of MLW's API.
+pub const METHODS: &[(&str, Handler)] = &[
+    // Areas
+    (
+        "new_area",
+        Handler::Write(|mlw, _, params| {
+            let NewArea { name, note } = parse(params)?;
+            reply(mlw.new_area(name, note))
+        }),
+    ),
+    (
+        "active_areas",
+        Handler::Read(|mlw, _, params| {
+            parse::<NoParams>(params)?;
+            reply(mlw.active_areas())
+        }),
+    ),
+    (
+        "completed_areas",
+        Handler::Read(|mlw, _, params| {
+            parse::<NoParams>(params)?;
+            reply(mlw.completed_areas())
+        }),
+    ),
+    (
+        "next_area_id",
+        Handler::Read(|mlw, _, params| {
+            parse::<NoParams>(params)?;
+            reply(mlw.next_area_id())
+        }),
+    ),
+    (
+        "get_area_by_id",
+        Handler::Read(|mlw, _, params| {
+            let ById::<Area> { id } = parse(params)?;
+            reply(mlw.get_area_by_id(&id))
+        }),
+    ),
+    (
+        "update_area",
+        Handler::Write(|mlw, _, params| {
+            let Update::<Area> { stake } = parse(params)?;
+            mlw.update_area(stake)?;
+            Ok(Value::Null)
+        }),
+    ),
+    (
+        "mark_area_complete",
+        Handler::Write(|mlw, _, params| {
+            let ById::<Area> { id } = parse(params)?;
+            mlw.mark_area_complete(&id)?;
+            Ok(Value::Null)
+        }),
+    ),
+    (
+        "mark_area_dropped",
+        Handler::Write(|mlw, _, params| {
+            let ById::<Area> { id } = parse(params)?;
+            mlw.mark_area_dropped(&id)?;
+            Ok(Value::Null)
+        }),
+    ),
+    (
+        "mark_area_reviewed",
+        Handler::Write(|mlw, _, params| {
+            let ById::<Area> { id } = parse(params)?;
+            mlw.mark_area_reviewed(&id)?;
+            Ok(Value::Null)
+        }),
+    ),
+    // Projects
+    (
+        "new_project",
+        Handler::Write(|mlw, _, params| {
+            let New::<Project> {
+                name,
+                parent_id,
+                note,
+            } = parse(params)?;
+            reply(mlw.new_project(name, parent_id, note))
+        }),
+    ),
+    (
+        "active_projects",
+        Handler::Read(|mlw, _, params| {
+            parse::<NoParams>(params)?;
+            reply(mlw.active_projects())
+        }),
+    ),
+    (
+        "completed_projects",
+        Handler::Read(|mlw, _, params| {
+            parse::<NoParams>(params)?;
+            reply(mlw.completed_projects())
+        }),
+    ),
+    (
+        "next_project_id",
+        Handler::Read(|mlw, _, params| {
+            parse::<NoParams>(params)?;
+            reply(mlw.next_project_id())
+        }),
+    ),
+    (
+        "get_project_by_id",
+        Handler::Read(|mlw, _, params| {
+            let ById::<Project> { id } = parse(params)?;
+            reply(mlw.get_project_by_id(&id))
+        }),
+    ),
+    (
+        "update_project",
+        Handler::Write(|mlw, _, params| {
+            let Update::<Project> { stake } = parse(params)?;
+            mlw.update_project(stake)?;
+            Ok(Value::Null)
+        }),
+    ),
+    (
+        "mark_project_complete",
+        Handler::Write(|mlw, _, params| {
+            let ById::<Project> { id } = parse(params)?;
+            mlw.mark_project_complete(&id)?;
+            Ok(Value::Null)
+        }),
+    ),
+    (
+        "mark_project_dropped",
+        Handler::Write(|mlw, _, params| {
+            let ById::<Project> { id } = parse(params)?;
+            mlw.mark_project_dropped(&id)?;
+            Ok(Value::Null)
+        }),
+    ),
+    (
+        "mark_project_reviewed",
+        Handler::Write(|mlw, _, params| {
+            let ById::<Project> { id } = parse(params)?;
+            mlw.mark_project_reviewed(&id)?;
+            Ok(Value::Null)
+        }),
+    ),
+    (
+        "get_project_children",
+        Handler::Read(|mlw, _, params| {
+            let Children::<Area> { parent_id } = parse(params)?;
+            reply(mlw.get_project_children(&parent_id))
+        }),
+    ),
+    (
+        "move_project",
+        Handler::Write(|mlw, _, params| {
+            let Move::<Project> { id, parent_id } = parse(params)?;
+            mlw.move_project(&id, parent_id)?;
+            Ok(Value::Null)
+        }),
+    ),
+    (
+        "assign_project",
+        Handler::Write(|mlw, _, params| {
+            let Assign::<Project> { id, assignee } = parse(params)?;
+            mlw.assign_project(&id, assignee)?;
+            Ok(Value::Null)
+        }),
+    ),
+    (
+        "projects_assigned_to",
+        Handler::Read(|mlw, _, params| {
+            let Assignee { user } = parse(params)?;
+            reply(mlw.projects_assigned_to(&user))
+        }),
+    ),
+    // Tasks
+    (
+        "new_task",
+        Handler::Write(|mlw, _, params| {
+            let New::<Task> {
+                name,
+                parent_id,
+                note,
+            } = parse(params)?;
+            reply(mlw.new_task(name, parent_id, note))
+        }),
+    ),
+    (
+        "active_tasks",
+        Handler::Read(|mlw, _, params| {
+            parse::<NoParams>(params)?;
+            reply(mlw.active_tasks())
+        }),
+    ),
+    (
+        "completed_tasks",
+        Handler::Read(|mlw, _, params| {
+            parse::<NoParams>(params)?;
+            reply(mlw.completed_tasks())
+        }),
+    ),
+    (
+        "next_task_id",
+        Handler::Read(|mlw, _, params| {
+            parse::<NoParams>(params)?;
+            reply(mlw.next_task_id())
+        }),
+    ),
+    (
+        "get_task_by_id",
+        Handler::Read(|mlw, _, params| {
+            let ById::<Task> { id } = parse(params)?;
+            reply(mlw.get_task_by_id(&id))
+        }),
+    ),
+    (
+        "update_task",
+        Handler::Write(|mlw, _, params| {
+            let Update::<Task> { stake } = parse(params)?;
+            mlw.update_task(stake)?;
+            Ok(Value::Null)
+        }),
+    ),
+    (
+        "mark_task_complete",
+        Handler::Write(|mlw, _, params| {
+            let ById::<Task> { id } = parse(params)?;
+            mlw.mark_task_complete(&id)?;
+            Ok(Value::Null)
+        }),
+    ),
+    (
+        "mark_task_dropped",
+        Handler::Write(|mlw, _, params| {
+            let ById::<Task> { id } = parse(params)?;
+            mlw.mark_task_dropped(&id)?;
+            Ok(Value::Null)
+        }),
+    ),
+    (
+        "mark_task_reviewed",
+        Handler::Write(|mlw, _, params| {
+            let ById::<Task> { id } = parse(params)?;
+            mlw.mark_task_reviewed(&id)?;
+            Ok(Value::Null)
+        }),
+    ),
+    (
+        "get_task_children",
+        Handler::Read(|mlw, _, params| {
+            let Children::<Project> { parent_id } = parse(params)?;
+            reply(mlw.get_task_children(&parent_id))
+        }),
+    ),
+    (
+        "move_task",
+        Handler::Write(|mlw, _, params| {
+            let Move::<Task> { id, parent_id } = parse(params)?;
+            mlw.move_task(&id, parent_id)?;
+            Ok(Value::Null)
+        }),
+    ),
+    (
+        "assign_task",
+        Handler::Write(|mlw, _, params| {
+            let Assign::<Task> { id, assignee } = parse(params)?;
+            mlw.assign_task(&id, assignee)?;
+            Ok(Value::Null)
+        }),
+    ),
+    (
+        "tasks_assigned_to",
+        Handler::Read(|mlw, _, params| {
+            let Assignee { user } = parse(params)?;
+            reply(mlw.tasks_assigned_to(&user))
+        }),
+    ),
+    // Members
+    (
+        "members",
+        Handler::Read(|mlw, _, params| {
+            parse::<NoParams>(params)?;
+            reply(mlw.members())
+        }),
+    ),
+    (
+        "add_member",
+        Handler::Write(|mlw, _, params| {
+            let Member { name } = parse(params)?;
+            reply(mlw.add_member(name))
+        }),
+    ),
+    (
+        "remove_member",
+        Handler::Write(|mlw, _, params| {
+            let Member { name } = parse(params)?;
+            reply(mlw.remove_member(&name))
+        }),
+    ),
+    // Collections, identifiers and search
+    (
+        "areas",
+        Handler::Read(|mlw, _, params| {
+            parse::<NoParams>(params)?;
+            reply(mlw.areas().iter().collect::<Vec<_>>())
+        }),
+    ),
+    (
+        "projects",
+        Handler::Read(|mlw, _, params| {
+            parse::<NoParams>(params)?;
+            reply(mlw.projects().iter().collect::<Vec<_>>())
+        }),
+    ),
+    (
+        "tasks",
+        Handler::Read(|mlw, _, params| {
+            parse::<NoParams>(params)?;
+            reply(mlw.tasks().iter().collect::<Vec<_>>())
+        }),
+    ),
+    (
+        "resolve_area",
+        Handler::Read(|mlw, _, params| {
+            let Handle { handle } = parse(params)?;
+            reply(mlw.resolve_area(&handle))
+        }),
+    ),
+    (
+        "resolve_project",
+        Handler::Read(|mlw, _, params| {
+            let Handle { handle } = parse(params)?;
+            reply(mlw.resolve_project(&handle))
+        }),
+    ),
+    (
+        "resolve_task",
+        Handler::Read(|mlw, _, params| {
+            let Handle { handle } = parse(params)?;
+            reply(mlw.resolve_task(&handle))
+        }),
+    ),
+    (
+        "assign_global_ids",
+        Handler::Write(|mlw, _, params| {
+            parse::<NoParams>(params)?;
+            reply(mlw.assign_global_ids())
+        }),
+    ),
+    (
+        "fuzzy_search",
+        Handler::Read(|mlw, _, params| {
+            let Query { query } = parse(params)?;
+            reply(mlw.fuzzy_search(&query))
+        }),
+    ),
+    // Hierarchy
+    (
+        "hierarchy",
+        Handler::Read(|mlw, _, params| {
+            parse::<NoParams>(params)?;
+            reply(mlw.hierarchy())
+        }),
+    ),
+    (
+        "area_tree",
+        Handler::Read(|mlw, _, params| {
+            let ById::<Area> { id } = parse(params)?;
+            reply(mlw.area_tree(&id))
+        }),
+    ),
+    (
+        "project_tree",
+        Handler::Read(|mlw, _, params| {
+            let ById::<Project> { id } = parse(params)?;
+            reply(mlw.project_tree(&id))
+        }),
+    ),
+    // CLI commands. `query` runs only commands that read, so it never needs a save.
+    (
+        "execute",
+        Handler::Write(|mlw, dates, params| {
+            let Run { command } = parse(params)?;
+            let outcome = operations::execute(mlw, &command, dates)?;
+            Ok(protocol::encode_outcome(outcome))
+        }),
+    ),
+    (
+        "query",
+        Handler::Read(|mlw, dates, params| {
+            let Run { command } = parse(params)?;
+            if command.is_mutating() {
+                return Err(RpcError::invalid_params(
+                    "this command changes data; send it with execute",
+                ));
+            }
+            let outcome = operations::query(mlw, &command, dates)?;
+            Ok(protocol::encode_outcome(outcome))
+        }),
+    ),
+    // Sync, answering a replica that sends its changes
+    (
+        "replica_id",
+        Handler::Write(|mlw, _, params| {
+            parse::<NoParams>(params)?;
+            reply(mlw.replica_id())
+        }),
+    ),
+    (
+        "merge_changes",
+        Handler::Write(|mlw, _, params| {
+            let Merge { changes } = parse(params)?;
+            reply(mlw.merge_changes(changes))
+        }),
+    ),
+    // Agenda, in the server's timezone and week
+    (
+        "tasks_due_between",
+        Handler::Read(|mlw, _, params| {
+            let Between { start, end } = parse(params)?;
+            reply(mlw.tasks_due_between(start, end))
+        }),
+    ),
+    (
+        "overdue_tasks",
+        Handler::Read(|mlw, dates, params| {
+            parse::<NoParams>(params)?;
+            reply(mlw.overdue_tasks(dates))
+        }),
+    ),
+    (
+        "tasks_due_today",
+        Handler::Read(|mlw, dates, params| {
+            parse::<NoParams>(params)?;
+            reply(mlw.tasks_due_today(dates))
+        }),
+    ),
+    (
+        "tasks_due_this_week",
+        Handler::Read(|mlw, dates, params| {
+            parse::<NoParams>(params)?;
+            reply(mlw.tasks_due_this_week(dates))
+        }),
+    ),
+];
+
+/// The handler for `method`, if there is one.
+pub fn find(method: &str) -> Option<Handler> {
+    METHODS
+        .iter()
+        .find(|(name, _)| *name == method)
+        .map(|(_, handler)| *handler)
+}
+
+// Missing params are treated as `{}`, so methods without arguments accept either.
+fn parse<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    let params = if params.is_null() {
+        Value::Object(Default::default())
+    } else {
+        params
+    };
+    serde_json::from_value(params).map_err(RpcError::invalid_params)
+}
+
+fn reply<T: Serialize>(value: T) -> Result<Value, RpcError> {
+    serde_json::to_value(value).map_err(RpcError::internal)
+}
+
+// --- Params ---
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NoParams {}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NewArea {
+    name: String,
+    #[serde(default)]
+    note: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields, bound = "")]
+struct New<K: Kind> {
+    name: String,
+    #[serde(default)]
+    parent_id: Option<StakeId<K::Parent>>,
+    #[serde(default)]
+    note: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields, bound = "")]
+struct ById<K> {
+    id: StakeId<K>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields, bound = "")]
+struct Update<K: Kind> {
+    stake: Stake<K>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields, bound = "")]
+struct Children<K> {
+    parent_id: StakeId<K>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields, bound = "")]
+struct Move<K: Kind> {
+    id: StakeId<K>,
+    #[serde(default)]
+    parent_id: Option<StakeId<K::Parent>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields, bound = "")]
+struct Assign<K> {
+    id: StakeId<K>,
+    #[serde(default)]
+    assignee: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Assignee {
+    user: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Member {
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Handle {
+    handle: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Query {
+    query: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Run {
+    command: Command,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Merge {
+    changes: Changeset,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Between {
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controller::protocol::INVALID_PARAMS;
+    use crate::mlw::sync::ReplicaId;
+    use chrono::TimeZone;
+    use serde_json::json;
+    use std::collections::HashSet;
+
+    fn dates() -> DateContext {
+        DateContext::new(
+            Utc.with_ymd_and_hms(2026, 10, 14, 9, 0, 0).unwrap(),
+            chrono_tz::UTC,
+        )
+    }
+
+    fn call(mlw: &mut MLW, method: &str, params: Value) -> Result<Value, RpcError> {
+        match find(method).expect("method exists") {
+            Handler::Read(read) => read(mlw, &dates(), params),
+            Handler::Write(write) => write(mlw, &dates(), params),
+        }
+    }
+
+    #[test]
+    fn test_method_names_are_unique() {
+        let names: HashSet<&str> = METHODS.iter().map(|(name, _)| *name).collect();
+        assert_eq!(names.len(), METHODS.len());
+        assert!(find("new_task").unwrap().is_write());
+        assert!(!find("active_tasks").unwrap().is_write());
+        assert!(find("execute").unwrap().is_write());
+        assert!(!find("query").unwrap().is_write());
+        assert!(find("shutdown").is_none());
+    }
+
+    #[test]
+    fn test_no_params_accepts_null_object_or_empty_array() {
+        let mut mlw = MLW::new();
+        for params in [Value::Null, json!({}), json!([])] {
+            assert_eq!(call(&mut mlw, "next_area_id", params), Ok(json!(1)));
+        }
+        assert!(call(&mut mlw, "next_area_id", json!({"x": 1})).is_err());
+    }
+
+    #[test]
+    fn test_named_and_positional_params() {
+        let mut mlw = MLW::new();
+        call(&mut mlw, "new_area", json!({"name": "Health"})).unwrap();
+        call(&mut mlw, "new_project", json!(["Run", 1])).unwrap();
+        call(
+            &mut mlw,
+            "new_task",
+            json!({"name": "Shoes", "parent_id": 1}),
+        )
+        .unwrap();
+        assert_eq!(mlw.get_project_children(&StakeId::new(1)).len(), 1);
+        assert_eq!(mlw.get_task_children(&StakeId::new(1)).len(), 1);
+
+        let missing = call(&mut mlw, "new_task", json!({})).unwrap_err();
+        assert_eq!(missing.code, INVALID_PARAMS);
+        assert!(missing.message.contains("name"), "{}", missing.message);
+    }
+
+    #[test]
+    fn test_search_resolve_and_trees() {
+        let mut mlw = MLW::new();
+        let area = mlw.new_area("Health".to_string(), None);
+        mlw.new_project("Run".to_string(), Some(area.stake_id.clone()), None);
+
+        let hits = call(&mut mlw, "fuzzy_search", json!({"query": "helth"})).unwrap();
+        assert_eq!(hits[0]["stake"]["stake_name"], "Health");
+        assert_eq!(hits[0]["stake"]["kind"], "area");
+
+        let global = area.global_id.unwrap().to_string();
+        assert_eq!(
+            call(&mut mlw, "resolve_area", json!({ "handle": global })),
+            Ok(json!(1))
+        );
+        assert_eq!(
+            call(&mut mlw, "resolve_task", json!({"handle": "1"})),
+            Ok(Value::Null)
+        );
+
+        let tree = call(&mut mlw, "area_tree", json!({"id": 1})).unwrap();
+        assert_eq!(tree["projects"][0]["project"]["stake_name"], "Run");
+        let hierarchy = call(&mut mlw, "hierarchy", Value::Null).unwrap();
+        assert_eq!(hierarchy["areas"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_execute_and_query_run_cli_commands() {
+        let mut mlw = MLW::new();
+        let added = call(
+            &mut mlw,
+            "execute",
+            json!({"command": {"task": {"action": {"add": {"name": ["Buy", "shoes"]}}}}}),
+        )
+        .unwrap();
+        assert_eq!(added["outcome"], "created");
+        assert_eq!(added["value"]["name"], "Buy shoes");
+
+        let listed = call(
+            &mut mlw,
+            "query",
+            json!({"command": {"list": {"kind": "task", "all": false}}}),
+        )
+        .unwrap();
+        assert_eq!(listed["value"][0]["name"], "Buy shoes");
+
+        let refused = call(
+            &mut mlw,
+            "query",
+            json!({"command": {"done": {"kind": "task", "id": "1"}}}),
+        );
+        assert_eq!(refused.unwrap_err().code, INVALID_PARAMS);
+        let unknown = call(
+            &mut mlw,
+            "execute",
+            json!({"command": {"done": {"kind": "task", "id": "7"}}}),
+        );
+        assert_eq!(unknown.unwrap_err().message, "no task matches '7'");
+    }
+
+    #[test]
+    fn test_merge_changes_answers_a_replica() {
+        let mut server = MLW::new();
+        server.new_task("On the server".to_string(), None, None);
+        let server_id: ReplicaId =
+            serde_json::from_value(call(&mut server, "replica_id", Value::Null).unwrap()).unwrap();
+        let mut laptop = MLW::new();
+        laptop.new_task("On the laptop".to_string(), None, None);
+        let changes = laptop.changes_for(server_id);
+
+        let merged = call(&mut server, "merge_changes", json!({ "changes": changes })).unwrap();
+        assert_eq!(merged[1]["merged"], 2);
+        assert_eq!(server.tasks().len(), 2);
+        let reply: Changeset = serde_json::from_value(merged[0].clone()).unwrap();
+        assert!(laptop.accept_merge(reply).is_empty());
+        assert_eq!(laptop.tasks().len(), 2);
+    }
+
+    #[test]
+    fn test_agenda_uses_server_dates() {
+        let mut mlw = MLW::new();
+        let mut task = mlw.new_task("Report".to_string(), None, None);
+        task.due = Some(Utc.with_ymd_and_hms(2026, 10, 14, 17, 0, 0).unwrap());
+        mlw.update_task(task).unwrap();
+
+        let today = call(&mut mlw, "tasks_due_today", Value::Null).unwrap();
+        assert_eq!(today[0]["stake_name"], "Report");
+        let overdue = call(&mut mlw, "overdue_tasks", Value::Null).unwrap();
+        assert_eq!(overdue, json!([]));
+        let between = call(
+            &mut mlw,
+            "tasks_due_between",
+            json!({"start": "2026-10-14T00:00:00Z", "end": "2026-10-15T00:00:00Z"}),
+        )
+        .unwrap();
+        assert_eq!(between.as_array().unwrap().len(), 1);
+    }
+}
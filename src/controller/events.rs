@@ -0,0 +1,186 @@
+// Live change events, streamed to HTTP clients as server-sent events from
+// `GET /events`. After every write the server compares the MLW with how it was before
+// and publishes one event per changed stake to each subscriber watching that part of
+// the hierarchy. Nothing is compared while nobody is subscribed.
+
+use std::io::{self, ErrorKind, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+use crate::mlw::MLW;
+use crate::mlw::changes::{Change, Scope};
+
+/// How long a quiet stream waits before sending a comment, so that clients which
+/// have gone away are noticed and idle proxies keep the connection open.
+pub const KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+/// The subscribers of one server.
+#[derive(Debug, Default)]
+pub struct Events {
+    subscribers: Mutex<Vec<Subscriber>>,
+    next_id: AtomicU64,
+}
+
+#[derive(Debug)]
+struct Subscriber {
+    scope: Scope,
+    sender: Sender<String>,
+}
+
+impl Events {
+    /// Starts receiving the events inside `scope`, each already framed for the wire.
+    pub fn subscribe(&self, scope: Scope) -> Receiver<String> {
+        let (sender, receiver) = mpsc::channel();
+        self.lock().push(Subscriber { scope, sender });
+        receiver
+    }
+
+    pub fn has_subscribers(&self) -> bool {
+        !self.lock().is_empty()
+    }
+
+    /// Sends `changes`, made to `mlw`, to everyone watching them, and forgets
+    /// subscribers that have gone away.
+    pub fn publish(&self, changes: &[Change], mlw: &MLW) {
+        let mut subscribers = self.lock();
+        for change in changes {
+            let frame = self.frame(change);
+            subscribers.retain(|subscriber| {
+                !change.is_within(&subscriber.scope, mlw)
+                    || subscriber.sender.send(frame.clone()).is_ok()
+            });
+        }
+    }
+
+    // One server-sent event: an id, the kind of change as the event type, and the
+    // change as JSON.
+    fn frame(&self, change: &Change) -> String {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let data = serde_json::to_string(change).expect("changes serialize");
+        format!(
+            "id: {}\nevent: {}\ndata: {}\n\n",
+            id,
+            change.event.as_str(),
+            data
+        )
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Subscriber>> {
+        self.subscribers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Writes an event stream response to `writer`, forwarding events from `receiver`
+/// until the client hangs up, which is not an error.
+pub fn stream(receiver: Receiver<String>, writer: impl Write) -> io::Result<()> {
+    match forward(receiver, writer) {
+        Err(error)
+            if matches!(
+                error.kind(),
+                ErrorKind::BrokenPipe | ErrorKind::ConnectionReset | ErrorKind::ConnectionAborted
+            ) =>
+        {
+            Ok(())
+        }
+        result => result,
+    }
+}
+
+fn forward(receiver: Receiver<String>, mut writer: impl Write) -> io::Result<()> {
+    writer.write_all(
+        b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\n\
+          Connection: close\r\n\r\n",
+    )?;
+    writer.flush()?;
+    loop {
+        let frame = match receiver.recv_timeout(KEEP_ALIVE) {
+            Ok(frame) => frame,
+            Err(RecvTimeoutError::Timeout) => ": keep-alive\n\n".to_string(),
+            Err(RecvTimeoutError::Disconnected) => return Ok(()),
+        };
+        writer.write_all(frame.as_bytes())?;
+        writer.flush()?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::{AreaId, ProjectId};
+
+    fn frames(receiver: &Receiver<String>) -> Vec<String> {
+        receiver.try_iter().collect()
+    }
+
+    #[test]
+    fn test_subscribers_get_the_events_in_their_scope() {
+        let events = Events::default();
+        assert!(!events.has_subscribers());
+        let everything = events.subscribe(Scope::Everything);
+        let home = events.subscribe(Scope::Area(AreaId::new(1)));
+        let taxes = events.subscribe(Scope::Project(ProjectId::new(2)));
+        assert!(events.has_subscribers());
+
+        let before = MLW::new();
+        let mut mlw = before.clone();
+        let area = mlw.new_area("Home".to_string(), None);
+        mlw.new_project("Garden".to_string(), Some(area.stake_id), None);
+        events.publish(&mlw.changes_since(&before), &mlw);
+
+        let all = frames(&everything);
+        assert_eq!(all.len(), 2);
+        assert!(all[0].starts_with("id: 1\nevent: created\ndata: {"));
+        assert!(all[0].ends_with("}\n\n"));
+        assert!(all[1].starts_with("id: 2\nevent: created\n"));
+        let data: serde_json::Value = serde_json::from_str(
+            all[1]
+                .lines()
+                .nth(2)
+                .unwrap()
+                .strip_prefix("data: ")
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(data["event"], "created");
+        assert_eq!(data["stake"]["kind"], "project");
+        assert_eq!(data["stake"]["stake_name"], "Garden");
+        assert_eq!(frames(&home), all);
+        assert!(frames(&taxes).is_empty());
+    }
+
+    #[test]
+    fn test_departed_subscribers_are_forgotten() {
+        let events = Events::default();
+        drop(events.subscribe(Scope::Everything));
+        let before = MLW::new();
+        let mut mlw = before.clone();
+        mlw.new_task("Task".to_string(), None, None);
+
+        assert!(events.has_subscribers());
+        events.publish(&mlw.changes_since(&before), &mlw);
+        assert!(!events.has_subscribers());
+    }
+
+    #[test]
+    fn test_stream_writes_head_then_events() {
+        let events = Events::default();
+        let receiver = events.subscribe(Scope::Everything);
+        let before = MLW::new();
+        let mut mlw = before.clone();
+        mlw.new_task("Task".to_string(), None, None);
+        events.publish(&mlw.changes_since(&before), &mlw);
+        drop(events);
+
+        let mut written = Vec::new();
+        stream(receiver, &mut written).unwrap();
+        let written = String::from_utf8(written).unwrap();
+        let (head, body) = written.split_once("\r\n\r\n").unwrap();
+        assert!(head.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(head.contains("Content-Type: text/event-stream"));
+        assert!(body.starts_with("id: 1\nevent: created\n"));
+    }
+}
@@ -0,0 +1,389 @@
+// The OpenAPI 3.1 description of the REST API, served at /openapi.json. Stake
+// schemas come from the types themselves through `JsonSchema`, so the description
+// follows any change to `Stake`; the paths mirror the routes in `rest`.
+
+use schemars::generate::SchemaSettings;
+use schemars::{JsonSchema, SchemaGenerator};
+use serde_json::{Map, Value, json};
+
+use super::rest::{NewStake, StakePatch};
+use crate::entities::{Area, Kind, Project, Stake, Task};
+
+const SCHEMAS: &str = "#/components/schemas/";
+
+/// The whole OpenAPI document.
+pub fn document() -> Value {
+    let mut generator = SchemaSettings::draft2020_12()
+        .with(|settings| settings.definitions_path = "/components/schemas".into())
+        .into_generator();
+    let mut schemas = Map::new();
+    add_kind::<Area>(&mut generator, &mut schemas, "Area");
+    add_kind::<Project>(&mut generator, &mut schemas, "Project");
+    add_kind::<Task>(&mut generator, &mut schemas, "Task");
+    schemas.extend(generator.take_definitions(true));
+    schemas.insert(
+        "Error".into(),
+        json!({
+            "type": "object",
+            "properties": { "error": { "type": "string" } },
+            "required": ["error"],
+        }),
+    );
+    schemas.insert(
+        "SearchResult".into(),
+        json!({
+            "type": "object",
+            "properties": {
+                "stake": {
+                    "description": "The matching stake, with its kind added.",
+                    "allOf": [
+                        { "oneOf": [
+                            { "$ref": format!("{}Area", SCHEMAS) },
+                            { "$ref": format!("{}Project", SCHEMAS) },
+                            { "$ref": format!("{}Task", SCHEMAS) },
+                        ] },
+                        { "type": "object",
+                          "properties": { "kind": { "enum": ["area", "project", "task"] } },
+                          "required": ["kind"] },
+                    ],
+                },
+                "score": { "type": "number" },
+            },
+            "required": ["stake", "score"],
+        }),
+    );
+
+    let mut paths = Map::new();
+    for (collection, name, child) in [
+        ("areas", "Area", Some(("projects", "Project"))),
+        ("projects", "Project", Some(("tasks", "Task"))),
+        ("tasks", "Task", None),
+    ] {
+        paths.insert(format!("/{}", collection), collection_path(name));
+        paths.insert(format!("/{}/{{id}}", collection), member_path(name));
+        if let Some((children, child_name)) = child {
+            paths.insert(
+                format!("/{}/{{id}}/{}", collection, children),
+                children_path(name, child_name),
+            );
+        }
+    }
+    paths.insert("/search".into(), search_path());
+    paths.insert("/events".into(), events_path());
+
+    json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": "Mind Like Water",
+            "description": "Areas, projects and tasks of one MLW data file. Servers with \
+                            accounts answer 401 without a token and 403 when a read-only \
+                            token is used to write.",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "paths": paths,
+        "components": {
+            "schemas": schemas,
+            "securitySchemes": {
+                "token": { "type": "http", "scheme": "bearer", "bearerFormat": "mlw token" },
+            },
+        },
+        "security": [{ "token": [] }],
+    })
+}
+
+// Adds the stake, create-body and patch-body schemas of one kind, named after it.
+fn add_kind<K: Kind>(generator: &mut SchemaGenerator, schemas: &mut Map<String, Value>, name: &str)
+where
+    Stake<K>: JsonSchema,
+    NewStake<K>: JsonSchema,
+    StakePatch<K>: JsonSchema,
+{
+    schemas.insert(name.into(), Stake::<K>::json_schema(generator).into());
+    schemas.insert(
+        format!("New{}", name),
+        NewStake::<K>::json_schema(generator).into(),
+    );
+    schemas.insert(
+        format!("{}Patch", name),
+        StakePatch::<K>::json_schema(generator).into(),
+    );
+}
+
+fn collection_path(name: &str) -> Value {
+    let lower = name.to_lowercase();
+    json!({
+        "get": {
+            "summary": format!("List {}s", lower),
+            "parameters": [status_parameter()],
+            "responses": {
+                "200": json_response(&format!("The {}s", lower), list_of(name)),
+                "400": error_response("Unknown status"),
+            },
+        },
+        "post": {
+            "summary": format!("Create a {}", lower),
+            "requestBody": json_body(&format!("New{}", name)),
+            "responses": {
+                "201": {
+                    "description": format!("The new {}", lower),
+                    "headers": { "Location": { "schema": { "type": "string" } } },
+                    "content": { "application/json": { "schema": reference(name) } },
+                },
+                "400": error_response("The body is not JSON"),
+                "422": error_response("Blank name, unknown field or missing parent"),
+            },
+        },
+    })
+}
+
+fn member_path(name: &str) -> Value {
+    let lower = name.to_lowercase();
+    json!({
+        "parameters": [id_parameter(&lower)],
+        "get": {
+            "summary": format!("Get a {}", lower),
+            "responses": {
+                "200": json_response(&format!("The {}", lower), reference(name)),
+                "404": error_response("No such stake"),
+            },
+        },
+        "patch": {
+            "summary": format!("Change, complete, drop or move a {}", lower),
+            "requestBody": json_body(&format!("{}Patch", name)),
+            "responses": {
+                "200": json_response(&format!("The changed {}", lower), reference(name)),
+                "400": error_response("The body is not JSON"),
+                "404": error_response("No such stake"),
+                "422": error_response("Blank name, unknown field, missing parent or un-dropping"),
+            },
+        },
+        "delete": {
+            "summary": format!("Drop a {}; it stays listed under status=dropped", lower),
+            "responses": {
+                "204": { "description": "Dropped" },
+                "404": error_response("No such stake"),
+            },
+        },
+    })
+}
+
+fn children_path(parent: &str, child: &str) -> Value {
+    json!({
+        "get": {
+            "summary": format!("List the {}s of a {}", child.to_lowercase(), parent.to_lowercase()),
+            "parameters": [id_parameter(&parent.to_lowercase()), status_parameter()],
+            "responses": {
+                "200": json_response("The children", list_of(child)),
+                "400": error_response("Unknown status"),
+                "404": error_response("No such parent"),
+            },
+        },
+    })
+}
+
+fn search_path() -> Value {
+    json!({
+        "get": {
+            "summary": "Fuzzy search areas, projects and tasks by name, best match first",
+            "parameters": [{
+                "name": "q",
+                "in": "query",
+                "required": true,
+                "schema": { "type": "string" },
+            }],
+            "responses": {
+                "200": json_response("The matches", json!({
+                    "type": "array",
+                    "items": reference("SearchResult"),
+                })),
+                "400": error_response("No q parameter"),
+            },
+        },
+    })
+}
+
+fn events_path() -> Value {
+    let scope = |name: &str| {
+        json!({
+            "name": name,
+            "in": "query",
+            "description": format!("Only changes inside this {}, by local or global id", name),
+            "schema": { "type": "string" },
+        })
+    };
+    json!({
+        "get": {
+            "summary": "Stream changes as server-sent events",
+            "description": "One event per changed stake, named created, updated, completed, \
+                dropped or moved. Its data is a JSON object with the event name, the stake \
+                with its kind added and, for moves, previous_parent_id.",
+            "parameters": [scope("area"), scope("project")],
+            "responses": {
+                "200": {
+                    "description": "An endless event stream",
+                    "content": { "text/event-stream": { "schema": { "type": "string" } } },
+                },
+                "400": error_response("Both area and project given"),
+                "404": error_response("No such area or project"),
+            },
+        },
+    })
+}
+
+fn id_parameter(kind: &str) -> Value {
+    json!({
+        "name": "id",
+        "in": "path",
+        "required": true,
+        "description": format!("The {}'s local id or global id", kind),
+        "schema": { "type": "string" },
+    })
+}
+
+fn status_parameter() -> Value {
+    json!({
+        "name": "status",
+        "in": "query",
+        "schema": {
+            "enum": ["active", "completed", "dropped", "all"],
+            "default": "active",
+        },
+    })
+}
+
+fn reference(name: &str) -> Value {
+    json!({ "$ref": format!("{}{}", SCHEMAS, name) })
+}
+
+fn list_of(name: &str) -> Value {
+    json!({ "type": "array", "items": reference(name) })
+}
+
+fn json_body(name: &str) -> Value {
+    json!({
+        "required": true,
+        "content": { "application/json": { "schema": reference(name) } },
+    })
+}
+
+fn json_response(description: &str, schema: Value) -> Value {
+    json!({
+        "description": description,
+        "content": { "application/json": { "schema": schema } },
+    })
+}
+
+fn error_response(description: &str) -> Value {
+    json_response(description, reference("Error"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::{Priority, StakeId};
+    use chrono::Utc;
+
+    #[test]
+    fn test_document_describes_every_route() {
+        let document = document();
+        assert_eq!(document["openapi"], "3.1.0");
+        let paths = document["paths"].as_object().unwrap();
+        let mut routes: Vec<&str> = paths.keys().map(String::as_str).collect();
+        routes.sort();
+        assert_eq!(
+            routes,
+            vec![
+                "/areas",
+                "/areas/{id}",
+                "/areas/{id}/projects",
+                "/events",
+                "/projects",
+                "/projects/{id}",
+                "/projects/{id}/tasks",
+                "/search",
+                "/tasks",
+                "/tasks/{id}",
+            ]
+        );
+        assert!(paths["/tasks"]["post"]["responses"]["201"].is_object());
+        assert!(paths["/tasks/{id}"]["get"]["responses"]["404"].is_object());
+        assert!(paths["/tasks/{id}"]["patch"]["responses"]["422"].is_object());
+    }
+
+    #[test]
+    fn test_routes_require_a_bearer_token() {
+        let document = document();
+        assert_eq!(document["security"], json!([{ "token": [] }]));
+        let scheme = &document["components"]["securitySchemes"]["token"];
+        assert_eq!(scheme["scheme"], "bearer");
+    }
+
+    #[test]
+    fn test_every_reference_resolves() {
+        fn refs<'a>(value: &'a Value, found: &mut Vec<&'a str>) {
+            match value {
+                Value::Object(map) => {
+                    if let Some(Value::String(target)) = map.get("$ref") {
+                        found.push(target);
+                    }
+                    map.values().for_each(|v| refs(v, found));
+                }
+                Value::Array(items) => items.iter().for_each(|v| refs(v, found)),
+                _ => {}
+            }
+        }
+        let document = document();
+        let mut found = Vec::new();
+        refs(&document, &mut found);
+        assert!(found.contains(&"#/components/schemas/Priority"));
+        for target in found {
+            let name = target.strip_prefix(SCHEMAS).expect("local reference");
+            assert!(
+                document["components"]["schemas"][name].is_object(),
+                "{} is not defined",
+                target
+            );
+        }
+    }
+
+    #[test]
+    fn test_stake_schema_follows_the_stake_type() {
+        let mut task = Stake::<Task>::new(
+            StakeId::new(1),
+            "Call".to_string(),
+            Some(StakeId::new(2)),
+            Some("note".to_string()),
+        );
+        task.ensure_global_id();
+        task.contexts = vec!["phone".to_string()];
+        task.tags = vec!["work".to_string()];
+        task.due = Some(Utc::now());
+        task.defer_until = Some(Utc::now());
+        task.priority = Some(Priority::High);
+        task.assignee = Some("ana".to_string());
+        task.created_by = Some("ana".to_string());
+        task.modified_by = Some("ben".to_string());
+        let serialized = serde_json::to_value(&task).unwrap();
+
+        let document = document();
+        let schema = &document["components"]["schemas"]["Task"];
+        let properties = schema["properties"].as_object().unwrap();
+        let mut fields: Vec<&String> = serialized.as_object().unwrap().keys().collect();
+        let mut described: Vec<&String> = properties.keys().collect();
+        fields.sort();
+        described.sort();
+        assert_eq!(fields, described);
+        assert_eq!(properties["stake_id"]["type"], "integer");
+        assert_eq!(properties["due"]["format"], "date-time");
+        assert!(
+            schema["required"]
+                .as_array()
+                .unwrap()
+                .contains(&json!("stake_name"))
+        );
+        assert_eq!(
+            document["components"]["schemas"]["Priority"]["enum"],
+            json!(["low", "medium", "high"])
+        );
+    }
+}
@@ -0,0 +1,390 @@
+// JSON-RPC 2.0 wire types. Each message is one line of JSON; a line holds either a
+// single request or a batch (an array of requests).
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::auth::AuthError;
+use crate::entities::StakeError;
+use crate::operations::{AgendaReport, OperationError, Outcome, ReviewReport};
+use crate::presenter::{ScoredView, StakeView};
+use crate::serializer::StorageError;
+
+pub const VERSION: &str = "2.0";
+
+// Error codes defined by JSON-RPC 2.0.
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+
+// Application error codes, from the range JSON-RPC leaves to servers.
+pub const STAKE_NOT_FOUND: i64 = -32001;
+pub const CANNOT_ACTIVATE_DROPPED_STAKE: i64 = -32002;
+pub const STORAGE_ERROR: i64 = -32003;
+pub const OPERATION_FAILED: i64 = -32004;
+pub const UNKNOWN_USER: i64 = -32005;
+pub const CANNOT_ASSIGN_AREA: i64 = -32006;
+pub const AUTHENTICATION_REQUIRED: i64 = -32010;
+pub const INVALID_TOKEN: i64 = -32011;
+pub const READ_ONLY_TOKEN: i64 = -32012;
+
+/// A validated call. `id` is `None` for notifications, which get no response.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Request {
+    pub jsonrpc: String,
+    pub method: String,
+    #[serde(skip_serializing_if = "Value::is_null")]
+    pub params: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<Value>,
+}
+
+impl Request {
+    pub fn new(method: impl Into<String>, params: Value, id: Value) -> Self {
+        Request {
+            jsonrpc: VERSION.to_string(),
+            method: method.into(),
+            params,
+            id: Some(id),
+        }
+    }
+
+    /// Checks that `value` is a well-formed request. On failure the error comes with
+    /// the id to answer with: the request's own id when it has a usable one, else null.
+    pub fn from_value(value: Value) -> Result<Request, (Value, RpcError)> {
+        let Value::Object(mut fields) = value else {
+            return Err((Value::Null, RpcError::invalid_request("not an object")));
+        };
+        let id = fields.remove("id");
+        if let Some(id) = &id
+            && !(id.is_string() || id.is_number() || id.is_null())
+        {
+            return Err((
+                Value::Null,
+                RpcError::invalid_request("id must be a string, number or null"),
+            ));
+        }
+        let reply_id = id.clone().unwrap_or(Value::Null);
+        let invalid = |message| Err((reply_id.clone(), RpcError::invalid_request(message)));
+
+        if fields.get("jsonrpc").and_then(Value::as_str) != Some(VERSION) {
+            return invalid("jsonrpc must be \"2.0\"");
+        }
+        let method = match fields.remove("method") {
+            Some(Value::String(method)) => method,
+            _ => return invalid("method must be a string"),
+        };
+        let params = fields.remove("params").unwrap_or(Value::Null);
+        if !(params.is_object() || params.is_array() || params.is_null()) {
+            return invalid("params must be an object or an array");
+        }
+        Ok(Request {
+            jsonrpc: VERSION.to_string(),
+            method,
+            params,
+            id,
+        })
+    }
+}
+
+/// The answer to one request: either `result` or `error` is set.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Response {
+    pub jsonrpc: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcError>,
+    pub id: Value,
+}
+
+impl Response {
+    pub fn new(id: Value, outcome: Result<Value, RpcError>) -> Self {
+        let (result, error) = match outcome {
+            Ok(result) => (Some(result), None),
+            Err(error) => (None, Some(error)),
+        };
+        Response {
+            jsonrpc: VERSION.to_string(),
+            result,
+            error,
+            id,
+        }
+    }
+
+    /// The call's outcome. A missing result means the method returned null.
+    pub fn into_result(self) -> Result<Value, RpcError> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.result.unwrap_or(Value::Null)),
+        }
+    }
+}
+
+/// A JSON-RPC error object.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        RpcError {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    pub fn parse_error(detail: impl fmt::Display) -> Self {
+        Self::new(PARSE_ERROR, format!("parse error: {}", detail))
+    }
+
+    pub fn invalid_request(detail: impl fmt::Display) -> Self {
+        Self::new(INVALID_REQUEST, format!("invalid request: {}", detail))
+    }
+
+    pub fn method_not_found(method: &str) -> Self {
+        Self::new(METHOD_NOT_FOUND, format!("method not found: {}", method))
+    }
+
+    pub fn invalid_params(detail: impl fmt::Display) -> Self {
+        Self::new(INVALID_PARAMS, format!("invalid params: {}", detail))
+    }
+
+    pub fn internal(detail: impl fmt::Display) -> Self {
+        Self::new(INTERNAL_ERROR, format!("internal error: {}", detail))
+    }
+}
+
+// An unknown user's name travels as the error's data, so clients can rebuild the
+// error without reading the message.
+impl From<StakeError> for RpcError {
+    fn from(error: StakeError) -> Self {
+        let (code, data) = match &error {
+            StakeError::StakeNotFound => (STAKE_NOT_FOUND, None),
+            StakeError::CannotActivateDroppedStake => (CANNOT_ACTIVATE_DROPPED_STAKE, None),
+            StakeError::UnknownUser(user) => (UNKNOWN_USER, Some(Value::from(user.as_str()))),
+            StakeError::CannotAssignArea => (CANNOT_ASSIGN_AREA, None),
+        };
+        RpcError {
+            data,
+            ..RpcError::new(code, error.to_string())
+        }
+    }
+}
+
+// Stake errors keep their own codes; anything else a command rejects, such as an
+// unknown handle or an unreadable date, is reported with its message.
+impl From<OperationError> for RpcError {
+    fn from(error: OperationError) -> Self {
+        match error {
+            OperationError::Stake(error) => error.into(),
+            other => RpcError::new(OPERATION_FAILED, other.to_string()),
+        }
+    }
+}
+
+impl From<AuthError> for RpcError {
+    fn from(error: AuthError) -> Self {
+        let code = match error {
+            AuthError::MissingToken => AUTHENTICATION_REQUIRED,
+            AuthError::InvalidToken => INVALID_TOKEN,
+            AuthError::ReadOnlyToken => READ_ONLY_TOKEN,
+        };
+        RpcError::new(code, error.to_string())
+    }
+}
+
+impl From<StorageError> for RpcError {
+    fn from(error: StorageError) -> Self {
+        RpcError::new(STORAGE_ERROR, error.to_string())
+    }
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (code {})", self.message, self.code)
+    }
+}
+
+impl std::error::Error for RpcError {}
+
+/// The result of the `execute` and `query` methods. `Outcome` serializes without
+/// naming its variant, which suits the CLI's JSON output but not a client that must
+/// rebuild the same variant, so on the wire it is tagged: `{"outcome": "created", "value": ...}`.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "outcome", content = "value", rename_all = "snake_case")]
+enum TaggedOutcome {
+    Created(StakeView),
+    Updated(StakeView),
+    Stakes(Vec<StakeView>),
+    Hits(Vec<ScoredView>),
+    Detail {
+        stake: StakeView,
+        children: Vec<StakeView>,
+    },
+    Review(ReviewReport),
+    Agenda(AgendaReport),
+    Members(Vec<String>),
+    Markdown(String),
+}
+
+pub fn encode_outcome(outcome: Outcome) -> Value {
+    let tagged = match outcome {
+        Outcome::Created(stake) => TaggedOutcome::Created(stake),
+        Outcome::Updated(stake) => TaggedOutcome::Updated(stake),
+        Outcome::Stakes(stakes) => TaggedOutcome::Stakes(stakes),
+        Outcome::Hits(hits) => TaggedOutcome::Hits(hits),
+        Outcome::Detail { stake, children } => TaggedOutcome::Detail { stake, children },
+        Outcome::Review(report) => TaggedOutcome::Review(report),
+        Outcome::Agenda(report) => TaggedOutcome::Agenda(report),
+        Outcome::Members(members) => TaggedOutcome::Members(members),
+        Outcome::Markdown(text) => TaggedOutcome::Markdown(text),
+    };
+    serde_json::to_value(tagged).expect("views always serialize")
+}
+
+pub fn decode_outcome(value: Value) -> Result<Outcome, serde_json::Error> {
+    Ok(match serde_json::from_value(value)? {
+        TaggedOutcome::Created(stake) => Outcome::Created(stake),
+        TaggedOutcome::Updated(stake) => Outcome::Updated(stake),
+        TaggedOutcome::Stakes(stakes) => Outcome::Stakes(stakes),
+        TaggedOutcome::Hits(hits) => Outcome::Hits(hits),
+        TaggedOutcome::Detail { stake, children } => Outcome::Detail { stake, children },
+        TaggedOutcome::Review(report) => Outcome::Review(report),
+        TaggedOutcome::Agenda(report) => Outcome::Agenda(report),
+        TaggedOutcome::Members(members) => Outcome::Members(members),
+        TaggedOutcome::Markdown(text) => Outcome::Markdown(text),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn invalid(value: Value) -> (Value, i64) {
+        let (id, error) = Request::from_value(value).unwrap_err();
+        (id, error.code)
+    }
+
+    #[test]
+    fn test_request_accepts_object_array_or_missing_params() {
+        let request = Request::from_value(
+            json!({"jsonrpc": "2.0", "method": "new_area", "params": {"name": "Health"}, "id": 1}),
+        )
+        .unwrap();
+        assert_eq!(request.method, "new_area");
+        assert_eq!(request.params, json!({"name": "Health"}));
+        assert_eq!(request.id, Some(json!(1)));
+
+        let positional =
+            Request::from_value(json!({"jsonrpc": "2.0", "method": "m", "params": ["Health"]}))
+                .unwrap();
+        assert_eq!(positional.id, None, "no id makes a notification");
+
+        let bare = Request::from_value(json!({"jsonrpc": "2.0", "method": "m", "id": null}));
+        assert_eq!(bare.unwrap().id, Some(Value::Null));
+    }
+
+    #[test]
+    fn test_request_validation() {
+        assert_eq!(invalid(json!([1])), (Value::Null, INVALID_REQUEST));
+        assert_eq!(
+            invalid(json!({"method": "m", "id": 7})),
+            (json!(7), INVALID_REQUEST)
+        );
+        assert_eq!(
+            invalid(json!({"jsonrpc": "1.0", "method": "m", "id": 7})),
+            (json!(7), INVALID_REQUEST)
+        );
+        assert_eq!(
+            invalid(json!({"jsonrpc": "2.0", "method": 5, "id": "a"})),
+            (json!("a"), INVALID_REQUEST)
+        );
+        assert_eq!(
+            invalid(json!({"jsonrpc": "2.0", "method": "m", "params": 3, "id": 1})),
+            (json!(1), INVALID_REQUEST)
+        );
+        assert_eq!(
+            invalid(json!({"jsonrpc": "2.0", "method": "m", "id": {"x": 1}})),
+            (Value::Null, INVALID_REQUEST)
+        );
+    }
+
+    #[test]
+    fn test_response_carries_result_or_error() {
+        let ok = Response::new(json!(1), Ok(Value::Null));
+        assert_eq!(
+            serde_json::to_value(&ok).unwrap(),
+            json!({"jsonrpc": "2.0", "result": null, "id": 1})
+        );
+        assert_eq!(ok.into_result(), Ok(Value::Null));
+
+        let failed = Response::new(json!(2), Err(StakeError::StakeNotFound.into()));
+        assert_eq!(
+            serde_json::to_value(&failed).unwrap(),
+            json!({
+                "jsonrpc": "2.0",
+                "error": {"code": STAKE_NOT_FOUND, "message": "stake not found"},
+                "id": 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_outcomes_keep_their_variant_on_the_wire() {
+        let mut mlw = crate::mlw::MLW::new();
+        let area = mlw.new_area("Health".to_string(), None);
+        for outcome in [
+            Outcome::Created((&area).into()),
+            Outcome::Updated((&area).into()),
+            Outcome::Stakes(vec![(&area).into()]),
+        ] {
+            let wire = encode_outcome(outcome.clone());
+            assert_eq!(decode_outcome(wire).unwrap(), outcome);
+        }
+        let wire = encode_outcome(Outcome::Stakes(Vec::new()));
+        assert_eq!(wire, json!({"outcome": "stakes", "value": []}));
+    }
+
+    #[test]
+    fn test_operation_errors_map_to_codes() {
+        let unknown = OperationError::UnknownHandle {
+            kind: crate::entities::StakeKind::Task,
+            handle: "9".to_string(),
+        };
+        assert_eq!(
+            RpcError::from(unknown),
+            RpcError::new(OPERATION_FAILED, "no task matches '9'")
+        );
+        assert_eq!(
+            RpcError::from(OperationError::Stake(StakeError::StakeNotFound)).code,
+            STAKE_NOT_FOUND
+        );
+    }
+
+    #[test]
+    fn test_stake_errors_map_to_codes() {
+        assert_eq!(RpcError::from(StakeError::StakeNotFound).code, -32001);
+        assert_eq!(
+            RpcError::from(StakeError::CannotActivateDroppedStake).code,
+            -32002
+        );
+        let unknown = RpcError::from(StakeError::UnknownUser("cal".to_string()));
+        assert_eq!(unknown.code, -32005);
+        assert_eq!(unknown.data, Some(Value::from("cal")));
+        assert_eq!(
+            RpcError::method_not_found("fly").to_string(),
+            "method not found: fly (code -32601)"
+        );
+    }
+}
@@ -0,0 +1,933 @@
+// REST over HTTP: areas, projects and tasks as JSON resources. Requests run through
+// the same `Server` as JSON-RPC, so both APIs see one MLW and share its locking and
+// saving. `openapi` describes every route below.
+//
+//   GET    /areas                    list (?status=active|completed|dropped|all)
+//   POST   /areas                    create
+//   GET    /areas/{id}               one stake, by local or global id
+//   PATCH  /areas/{id}               change fields, complete, drop or move
+//   DELETE /areas/{id}               drop (MLW keeps dropped stakes)
+//   GET    /areas/{id}/projects      children, filtered like lists
+//   GET    /projects/{id}/tasks
+//   GET    /search?q=text            fuzzy search across every kind
+//   GET    /events                   live changes (?area= or ?project=), see `events`
+//   GET    /openapi.json
+//
+// and likewise for /projects and /tasks.
+
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer};
+use serde_json::{Value, json};
+
+use super::openapi;
+use super::server::Server;
+use crate::auth::AuthError;
+use crate::entities::{
+    Area, Clock, Kind, Priority, Project, Stake, StakeError, StakeId, StakesCollection, Task,
+};
+use crate::mlw::MLW;
+use crate::mlw::changes::Scope;
+use crate::serializer::StorageError;
+
+/// An HTTP response: status, JSON body and any extra headers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reply {
+    pub status: u16,
+    pub body: Option<Value>,
+    pub headers: Vec<(&'static str, String)>,
+}
+
+impl Reply {
+    fn ok(body: Value) -> Self {
+        Reply {
+            status: 200,
+            body: Some(body),
+            headers: Vec::new(),
+        }
+    }
+
+    fn created(location: String, body: Value) -> Self {
+        Reply {
+            status: 201,
+            body: Some(body),
+            headers: vec![("Location", location)],
+        }
+    }
+
+    fn no_content() -> Self {
+        Reply {
+            status: 204,
+            body: None,
+            headers: Vec::new(),
+        }
+    }
+}
+
+impl From<RestError> for Reply {
+    fn from(error: RestError) -> Self {
+        let headers = match &error {
+            RestError::MethodNotAllowed(allow) => vec![("Allow", allow.to_string())],
+            _ => Vec::new(),
+        };
+        Reply {
+            status: error.status(),
+            body: Some(json!({ "error": error.to_string() })),
+            headers,
+        }
+    }
+}
+
+/// The answer to a request whose token was missing, wrong or too weak: 401 with a
+/// challenge, or 403 for a read-only token that tried to write.
+pub fn refused(error: AuthError) -> Reply {
+    let (status, challenge) = match error {
+        AuthError::MissingToken => (401, r#"Bearer realm="mlw""#),
+        AuthError::InvalidToken => (401, r#"Bearer realm="mlw", error="invalid_token""#),
+        AuthError::ReadOnlyToken => (403, r#"Bearer realm="mlw", error="insufficient_scope""#),
+    };
+    Reply {
+        status,
+        body: Some(json!({ "error": error.to_string() })),
+        headers: vec![("WWW-Authenticate", challenge.to_string())],
+    }
+}
+
+/// Why a request failed, and so which status it is answered with.
+#[derive(Debug)]
+pub enum RestError {
+    /// 400: the body is not JSON, or a query parameter is missing or malformed.
+    BadRequest(String),
+    /// 404: no such route, or no stake with that id.
+    NotFound(String),
+    /// 405: the route exists but not for this method; holds the allowed methods.
+    MethodNotAllowed(&'static str),
+    /// 422: well-formed JSON that MLW cannot accept.
+    Unprocessable(String),
+    /// 500: the change was made but the data file could not be saved.
+    Storage(StorageError),
+}
+
+impl RestError {
+    pub fn status(&self) -> u16 {
+        match self {
+            RestError::BadRequest(_) => 400,
+            RestError::NotFound(_) => 404,
+            RestError::MethodNotAllowed(_) => 405,
+            RestError::Unprocessable(_) => 422,
+            RestError::Storage(_) => 500,
+        }
+    }
+}
+
+impl fmt::Display for RestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RestError::BadRequest(message)
+            | RestError::NotFound(message)
+            | RestError::Unprocessable(message) => f.write_str(message),
+            RestError::MethodNotAllowed(allow) => write!(f, "only {} is allowed here", allow),
+            RestError::Storage(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for RestError {}
+
+impl From<StakeError> for RestError {
+    fn from(error: StakeError) -> Self {
+        match error {
+            StakeError::StakeNotFound => RestError::NotFound(error.to_string()),
+            StakeError::CannotActivateDroppedStake
+            | StakeError::UnknownUser(_)
+            | StakeError::CannotAssignArea => RestError::Unprocessable(error.to_string()),
+        }
+    }
+}
+
+impl From<StorageError> for RestError {
+    fn from(error: StorageError) -> Self {
+        RestError::Storage(error)
+    }
+}
+
+// Broken JSON is a bad request; JSON of the wrong shape is unprocessable.
+impl From<serde_json::Error> for RestError {
+    fn from(error: serde_json::Error) -> Self {
+        if error.is_data() {
+            RestError::Unprocessable(error.to_string())
+        } else {
+            RestError::BadRequest(format!("the body is not valid JSON: {}", error))
+        }
+    }
+}
+
+/// Answers one HTTP request. `url` is the path with its query string, as sent.
+pub fn handle(server: &Server, method: &str, url: &str, body: &str) -> Reply {
+    route(server, method, url, body).unwrap_or_else(Reply::from)
+}
+
+/// For `GET /events`, the scope the client wants to watch, or why it cannot;
+/// `None` for every other request.
+pub fn subscription(server: &Server, method: &str, url: &str) -> Option<Result<Scope, Reply>> {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    if path.trim_end_matches('/') != "/events" {
+        return None;
+    }
+    let query = Query::parse(query);
+    let scope = allow(method, "GET").and_then(|()| {
+        server.read(|mlw, _| match (query.get("area"), query.get("project")) {
+            (None, None) => Ok(Scope::Everything),
+            (Some(area), None) => Area::find(mlw, area).map(Scope::Area),
+            (None, Some(project)) => Project::find(mlw, project).map(Scope::Project),
+            (Some(_), Some(_)) => Err(RestError::BadRequest(
+                "watch an area or a project, not both".into(),
+            )),
+        })
+    });
+    Some(scope.map_err(Reply::from))
+}
+
+fn route(server: &Server, method: &str, url: &str, body: &str) -> Result<Reply, RestError> {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let query = Query::parse(query);
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    match segments.as_slice() {
+        ["openapi.json"] => {
+            allow(method, "GET")?;
+            Ok(Reply::ok(openapi::document()))
+        }
+        ["search"] => {
+            allow(method, "GET")?;
+            let text = query
+                .get("q")
+                .ok_or_else(|| RestError::BadRequest("the q parameter is required".into()))?;
+            Ok(server.read(|mlw, _| Reply::ok(json!(mlw.fuzzy_search(text)))))
+        }
+        ["areas"] => collection::<Area>(server, method, &query, body),
+        ["projects"] => collection::<Project>(server, method, &query, body),
+        ["tasks"] => collection::<Task>(server, method, &query, body),
+        ["areas", id] => member::<Area>(server, method, id, body),
+        ["projects", id] => member::<Project>(server, method, id, body),
+        ["tasks", id] => member::<Task>(server, method, id, body),
+        ["areas", id, "projects"] => children::<Project>(server, method, id, &query),
+        ["projects", id, "tasks"] => children::<Task>(server, method, id, &query),
+        _ => Err(RestError::NotFound(format!("no resource at {}", path))),
+    }
+}
+
+fn allow(method: &str, allowed: &'static str) -> Result<(), RestError> {
+    if allowed.split(", ").any(|m| m == method) {
+        Ok(())
+    } else {
+        Err(RestError::MethodNotAllowed(allowed))
+    }
+}
+
+fn collection<K: Resource>(
+    server: &Server,
+    method: &str,
+    query: &Query,
+    body: &str,
+) -> Result<Reply, RestError> {
+    allow(method, "GET, POST")?;
+    if method == "GET" {
+        let status = StatusFilter::from_query(query)?;
+        return Ok(server.read(|mlw, _| {
+            let stakes: Vec<_> = K::collection(mlw)
+                .iter()
+                .filter(|stake| status.admits(stake))
+                .collect();
+            Reply::ok(json!(stakes))
+        }));
+    }
+    let new: NewStake<K> = serde_json::from_str(body)?;
+    let stake = server.write(|mlw, _| new.create(mlw))?;
+    Ok(Reply::created(
+        format!("/{}/{}", K::PATH, stake.stake_id.0),
+        json!(stake),
+    ))
+}
+
+fn member<K: Resource>(
+    server: &Server,
+    method: &str,
+    handle: &str,
+    body: &str,
+) -> Result<Reply, RestError> {
+    allow(method, "GET, PATCH, DELETE")?;
+    match method {
+        "GET" => server.read(|mlw, _| {
+            let id = K::find(mlw, handle)?;
+            Ok(Reply::ok(json!(K::collection(mlw).get_by_id(&id))))
+        }),
+        "PATCH" => {
+            let patch: StakePatch<K> = serde_json::from_str(body)?;
+            let stake = server.write(|mlw, _| {
+                let id = K::find(mlw, handle)?;
+                patch.apply(mlw, &id)
+            })?;
+            Ok(Reply::ok(json!(stake)))
+        }
+        _ => {
+            server.write(|mlw, _| {
+                let id = K::find(mlw, handle)?;
+                K::drop(mlw, &id).map_err(RestError::from)
+            })?;
+            Ok(Reply::no_content())
+        }
+    }
+}
+
+fn children<K: Resource>(
+    server: &Server,
+    method: &str,
+    parent: &str,
+    query: &Query,
+) -> Result<Reply, RestError>
+where
+    K::Parent: Resource,
+{
+    allow(method, "GET")?;
+    let status = StatusFilter::from_query(query)?;
+    server.read(|mlw, _| {
+        let parent_id = <K::Parent as Resource>::find(mlw, parent)?;
+        let stakes: Vec<_> = K::collection(mlw)
+            .get_all_children(&parent_id)
+            .into_iter()
+            .filter(|stake| status.admits(stake))
+            .collect();
+        Ok(Reply::ok(json!(stakes)))
+    })
+}
+
+/// The MLW calls behind one kind's resources.
+pub(super) trait Resource: Kind {
+    /// The collection's path segment, e.g. "tasks".
+    const PATH: &'static str;
+    /// The kind as it reads in messages, e.g. "task".
+    const NAME: &'static str;
+
+    fn collection(mlw: &MLW) -> &StakesCollection<Self>;
+    fn resolve(mlw: &MLW, handle: &str) -> Option<StakeId<Self>>;
+    fn create(
+        mlw: &mut MLW,
+        name: String,
+        parent_id: Option<StakeId<Self::Parent>>,
+        note: Option<String>,
+    ) -> Stake<Self>;
+    fn update(mlw: &mut MLW, stake: Stake<Self>) -> Result<(), StakeError>;
+    fn drop(mlw: &mut MLW, id: &StakeId<Self>) -> Result<(), StakeError>;
+    /// Fails unless a stake of this kind may sit under `parent_id`.
+    fn check_parent(mlw: &MLW, parent_id: &StakeId<Self::Parent>) -> Result<(), RestError>;
+
+    fn find(mlw: &MLW, handle: &str) -> Result<StakeId<Self>, RestError> {
+        Self::resolve(mlw, handle)
+            .ok_or_else(|| RestError::NotFound(format!("no {} matches '{}'", Self::NAME, handle)))
+    }
+}
+
+impl Resource for Area {
+    const PATH: &'static str = "areas";
+    const NAME: &'static str = "area";
+
+    fn collection(mlw: &MLW) -> &StakesCollection<Self> {
+        mlw.areas()
+    }
+    fn resolve(mlw: &MLW, handle: &str) -> Option<StakeId<Self>> {
+        mlw.resolve_area(handle)
+    }
+    fn create(
+        mlw: &mut MLW,
+        name: String,
+        _: Option<StakeId<Self::Parent>>,
+        note: Option<String>,
+    ) -> Stake<Self> {
+        mlw.new_area(name, note)
+    }
+    fn update(mlw: &mut MLW, stake: Stake<Self>) -> Result<(), StakeError> {
+        mlw.update_area(stake)
+    }
+    fn drop(mlw: &mut MLW, id: &StakeId<Self>) -> Result<(), StakeError> {
+        mlw.mark_area_dropped(id)
+    }
+    fn check_parent(_: &MLW, _: &StakeId<Self::Parent>) -> Result<(), RestError> {
+        Err(RestError::Unprocessable("areas have no parent".into()))
+    }
+}
+
+impl Resource for Project {
+    const PATH: &'static str = "projects";
+    const NAME: &'static str = "project";
+
+    fn collection(mlw: &MLW) -> &StakesCollection<Self> {
+        mlw.projects()
+    }
+    fn resolve(mlw: &MLW, handle: &str) -> Option<StakeId<Self>> {
+        mlw.resolve_project(handle)
+    }
+    fn create(
+        mlw: &mut MLW,
+        name: String,
+        parent_id: Option<StakeId<Self::Parent>>,
+        note: Option<String>,
+    ) -> Stake<Self> {
+        mlw.new_project(name, parent_id, note)
+    }
+    fn update(mlw: &mut MLW, stake: Stake<Self>) -> Result<(), StakeError> {
+        mlw.update_project(stake)
+    }
+    fn drop(mlw: &mut MLW, id: &StakeId<Self>) -> Result<(), StakeError> {
+        mlw.mark_project_dropped(id)
+    }
+    fn check_parent(mlw: &MLW, parent_id: &StakeId<Self::Parent>) -> Result<(), RestError> {
+        match mlw.get_area_by_id(parent_id) {
+            Some(_) => Ok(()),
+            None => Err(RestError::Unprocessable(format!(
+                "area {} does not exist",
+                parent_id.0
+            ))),
+        }
+    }
+}
+
+impl Resource for Task {
+    const PATH: &'static str = "tasks";
+    const NAME: &'static str = "task";
+
+    fn collection(mlw: &MLW) -> &StakesCollection<Self> {
+        mlw.tasks()
+    }
+    fn resolve(mlw: &MLW, handle: &str) -> Option<StakeId<Self>> {
+        mlw.resolve_task(handle)
+    }
+    fn create(
+        mlw: &mut MLW,
+        name: String,
+        parent_id: Option<StakeId<Self::Parent>>,
+        note: Option<String>,
+    ) -> Stake<Self> {
+        mlw.new_task(name, parent_id, note)
+    }
+    fn update(mlw: &mut MLW, stake: Stake<Self>) -> Result<(), StakeError> {
+        mlw.update_task(stake)
+    }
+    fn drop(mlw: &mut MLW, id: &StakeId<Self>) -> Result<(), StakeError> {
+        mlw.mark_task_dropped(id)
+    }
+    fn check_parent(mlw: &MLW, parent_id: &StakeId<Self::Parent>) -> Result<(), RestError> {
+        match mlw.get_project_by_id(parent_id) {
+            Some(_) => Ok(()),
+            None => Err(RestError::Unprocessable(format!(
+                "project {} does not exist",
+                parent_id.0
+            ))),
+        }
+    }
+}
+
+/// The body of a POST: a name and, optionally, any of a stake's other fields.
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(bound = "", deny_unknown_fields)]
+#[schemars(bound = "")]
+pub(super) struct NewStake<K: Kind> {
+    stake_name: String,
+    #[serde(default)]
+    parent_id: Option<StakeId<K::Parent>>,
+    #[serde(default)]
+    note: Option<String>,
+    #[serde(default)]
+    contexts: Vec<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    due: Option<DateTime<Utc>>,
+    #[serde(default)]
+    defer_until: Option<DateTime<Utc>>,
+    #[serde(default)]
+    priority: Option<Priority>,
+    /// A member of the workspace. Projects and tasks only.
+    #[serde(default)]
+    assignee: Option<String>,
+}
+
+impl<K: Resource> NewStake<K> {
+    fn create(self, mlw: &mut MLW) -> Result<Stake<K>, RestError> {
+        check_name(&self.stake_name)?;
+        if let Some(parent_id) = &self.parent_id {
+            K::check_parent(mlw, parent_id)?;
+        }
+        let mut stake = K::create(mlw, self.stake_name, self.parent_id, self.note);
+        stake.contexts = self.contexts;
+        stake.tags = self.tags;
+        stake.due = self.due;
+        stake.defer_until = self.defer_until;
+        stake.priority = self.priority;
+        stake.assignee = self.assignee;
+        K::update(mlw, stake.clone())?;
+        Ok(stored(mlw, &stake.stake_id))
+    }
+}
+
+/// The body of a PATCH. Absent fields are left alone; null clears the parent, note,
+/// dates, priority and assignee. `complete` and `dropped` can be set, and `complete` cleared,
+/// but a dropped stake stays dropped.
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[serde(bound = "", deny_unknown_fields)]
+#[schemars(bound = "")]
+pub(super) struct StakePatch<K: Kind> {
+    #[serde(default)]
+    stake_name: Option<String>,
+    #[serde(default, deserialize_with = "present")]
+    parent_id: Option<Option<StakeId<K::Parent>>>,
+    #[serde(default)]
+    complete: Option<bool>,
+    #[serde(default)]
+    dropped: Option<bool>,
+    #[serde(default, deserialize_with = "present")]
+    note: Option<Option<String>>,
+    #[serde(default)]
+    contexts: Option<Vec<String>>,
+    #[serde(default)]
+    tags: Option<Vec<String>>,
+    #[serde(default, deserialize_with = "present")]
+    due: Option<Option<DateTime<Utc>>>,
+    #[serde(default, deserialize_with = "present")]
+    defer_until: Option<Option<DateTime<Utc>>>,
+    #[serde(default, deserialize_with = "present")]
+    priority: Option<Option<Priority>>,
+    #[serde(default, deserialize_with = "present")]
+    assignee: Option<Option<String>>,
+}
+
+impl<K: Resource> StakePatch<K> {
+    fn apply(self, mlw: &mut MLW, id: &StakeId<K>) -> Result<Stake<K>, RestError> {
+        let mut stake = K::collection(mlw)
+            .get_by_id(id)
+            .ok_or(StakeError::StakeNotFound)?
+            .clone();
+        if let Some(name) = self.stake_name {
+            check_name(&name)?;
+            stake.stake_name = name;
+        }
+        if let Some(parent_id) = self.parent_id {
+            if let Some(parent_id) = &parent_id {
+                K::check_parent(mlw, parent_id)?;
+            }
+            stake.parent_id = parent_id;
+        }
+        if stake.dropped && self.dropped == Some(false) {
+            return Err(StakeError::CannotActivateDroppedStake.into());
+        }
+        let clock = mlw.clock().clone();
+        match self.complete {
+            Some(true) if !stake.complete => stake.mark_complete(&clock),
+            Some(false) => stake.complete = false,
+            _ => {}
+        }
+        if self.dropped == Some(true) && !stake.dropped {
+            stake.mark_dropped(&clock);
+        }
+        if let Some(note) = self.note {
+            stake.note = note;
+        }
+        if let Some(contexts) = self.contexts {
+            stake.contexts = contexts;
+        }
+        if let Some(tags) = self.tags {
+            stake.tags = tags;
+        }
+        if let Some(due) = self.due {
+            stake.due = due;
+        }
+        if let Some(defer_until) = self.defer_until {
+            stake.defer_until = defer_until;
+        }
+        if let Some(priority) = self.priority {
+            stake.priority = priority;
+        }
+        if let Some(assignee) = self.assignee {
+            stake.assignee = assignee;
+        }
+        stake.date_modified = clock.now();
+        K::update(mlw, stake)?;
+        Ok(stored(mlw, id))
+    }
+}
+
+// A stake as saved, with the authorship MLW stamped on it.
+fn stored<K: Resource>(mlw: &MLW, id: &StakeId<K>) -> Stake<K> {
+    K::collection(mlw)
+        .get_by_id(id)
+        .cloned()
+        .expect("the stake was just saved")
+}
+
+// Tells a field sent as null (Some(None)) from one left out (None).
+fn present<'de, T: Deserialize<'de>, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<T>, D::Error> {
+    T::deserialize(deserializer).map(Some)
+}
+
+fn check_name(name: &str) -> Result<(), RestError> {
+    if name.trim().is_empty() {
+        Err(RestError::Unprocessable(
+            "stake_name must not be blank".into(),
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Which stakes a list shows, from its `status` parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StatusFilter {
+    Active,
+    Completed,
+    Dropped,
+    All,
+}
+
+impl StatusFilter {
+    fn from_query(query: &Query) -> Result<Self, RestError> {
+        match query.get("status").unwrap_or("active") {
+            "active" => Ok(StatusFilter::Active),
+            "completed" => Ok(StatusFilter::Completed),
+            "dropped" => Ok(StatusFilter::Dropped),
+            "all" => Ok(StatusFilter::All),
+            other => Err(RestError::BadRequest(format!(
+                "unknown status '{}', expected active, completed, dropped or all",
+                other
+            ))),
+        }
+    }
+
+    fn admits<K: Kind>(self, stake: &Stake<K>) -> bool {
+        match self {
+            StatusFilter::Active => stake.is_active(),
+            StatusFilter::Completed => stake.complete,
+            StatusFilter::Dropped => stake.dropped,
+            StatusFilter::All => true,
+        }
+    }
+}
+
+/// Decoded query string parameters, in order.
+#[derive(Debug, Default)]
+struct Query(Vec<(String, String)>);
+
+impl Query {
+    fn parse(query: &str) -> Self {
+        Query(
+            query
+                .split('&')
+                .filter(|pair| !pair.is_empty())
+                .map(|pair| {
+                    let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                    (decode(key), decode(value))
+                })
+                .collect(),
+        )
+    }
+
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+// Undoes form encoding: '+' is a space and %XX a byte. Malformed escapes are kept
+// as they are.
+fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+                match u8::from_str_radix(hex, 16) {
+                    Ok(byte) => {
+                        decoded.push(byte);
+                        i += 2;
+                    }
+                    Err(_) => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn server() -> Server {
+        Server::new(MLW::new(), Config::default())
+    }
+
+    fn send(server: &Server, method: &str, url: &str, body: &str) -> Reply {
+        handle(server, method, url, body)
+    }
+
+    fn ok(server: &Server, method: &str, url: &str, body: &str) -> Value {
+        let reply = send(server, method, url, body);
+        assert!(
+            (200..300).contains(&reply.status),
+            "{} {} answered {:?}",
+            method,
+            url,
+            reply
+        );
+        reply.body.unwrap_or(Value::Null)
+    }
+
+    fn names(list: Value) -> Vec<String> {
+        list.as_array()
+            .unwrap()
+            .iter()
+            .map(|stake| stake["stake_name"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_post_creates_and_get_reads_back() {
+        let server = server();
+        let reply = send(
+            &server,
+            "POST",
+            "/tasks",
+            r#"{"stake_name": "Call mom", "contexts": ["phone"], "priority": "high"}"#,
+        );
+        assert_eq!(reply.status, 201);
+        assert_eq!(reply.headers, vec![("Location", "/tasks/1".to_string())]);
+        let created = reply.body.unwrap();
+        assert_eq!(created["stake_name"], "Call mom");
+        assert_eq!(created["contexts"], json!(["phone"]));
+        assert_eq!(created["priority"], "high");
+
+        assert_eq!(ok(&server, "GET", "/tasks/1", ""), created);
+        let global_id = created["global_id"].as_str().unwrap();
+        assert_eq!(
+            ok(&server, "GET", &format!("/tasks/{}", global_id), ""),
+            created
+        );
+        assert_eq!(names(ok(&server, "GET", "/tasks", "")), vec!["Call mom"]);
+        assert_eq!(server.snapshot().tasks().len(), 1);
+    }
+
+    #[test]
+    fn test_missing_stakes_and_routes_are_404() {
+        let server = server();
+        for (method, url) in [
+            ("GET", "/tasks/9"),
+            ("PATCH", "/projects/9"),
+            ("DELETE", "/areas/9"),
+            ("GET", "/areas/9/projects"),
+            ("GET", "/nowhere"),
+            ("GET", "/tasks/1/tasks"),
+        ] {
+            let reply = send(&server, method, url, "{}");
+            assert_eq!(reply.status, 404, "{} {}", method, url);
+            assert!(reply.body.unwrap()["error"].is_string());
+        }
+        assert_eq!(
+            send(&server, "GET", "/tasks/9", "").body.unwrap()["error"],
+            "no task matches '9'"
+        );
+    }
+
+    #[test]
+    fn test_validation_failures_are_422() {
+        let server = server();
+        ok(&server, "POST", "/tasks", r#"{"stake_name": "Task"}"#);
+        for (method, url, body) in [
+            ("POST", "/tasks", r#"{"stake_name": "  "}"#),
+            ("POST", "/tasks", r#"{"note": "no name"}"#),
+            (
+                "POST",
+                "/tasks",
+                r#"{"stake_name": "Task", "colour": "red"}"#,
+            ),
+            (
+                "POST",
+                "/tasks",
+                r#"{"stake_name": "Task", "parent_id": 4}"#,
+            ),
+            (
+                "POST",
+                "/areas",
+                r#"{"stake_name": "Area", "parent_id": 1}"#,
+            ),
+            ("POST", "/tasks", r#"{"stake_name": 7}"#),
+            ("PATCH", "/tasks/1", r#"{"priority": "urgent"}"#),
+            ("PATCH", "/tasks/1", r#"{"parent_id": 3}"#),
+            ("PATCH", "/tasks/1", r#"{"assignee": "cal"}"#),
+            (
+                "POST",
+                "/areas",
+                r#"{"stake_name": "Area", "assignee": "cal"}"#,
+            ),
+        ] {
+            let reply = send(&server, method, url, body);
+            assert_eq!(reply.status, 422, "{} {} {}", method, url, body);
+        }
+        assert_eq!(server.snapshot().tasks().len(), 1);
+        assert!(server.snapshot().areas().is_empty());
+    }
+
+    #[test]
+    fn test_malformed_requests_are_400_and_wrong_methods_405() {
+        let server = server();
+        assert_eq!(send(&server, "POST", "/tasks", "{not json").status, 400);
+        assert_eq!(send(&server, "POST", "/tasks", "").status, 400);
+        assert_eq!(send(&server, "GET", "/tasks?status=later", "").status, 400);
+        assert_eq!(send(&server, "GET", "/search", "").status, 400);
+
+        let reply = send(&server, "PUT", "/tasks", "{}");
+        assert_eq!(reply.status, 405);
+        assert_eq!(reply.headers, vec![("Allow", "GET, POST".to_string())]);
+        assert_eq!(send(&server, "POST", "/openapi.json", "").status, 405);
+    }
+
+    #[test]
+    fn test_patch_changes_fields_completes_and_moves() {
+        let server = server();
+        ok(&server, "POST", "/projects", r#"{"stake_name": "Garden"}"#);
+        ok(
+            &server,
+            "POST",
+            "/tasks",
+            r#"{"stake_name": "Dig", "note": "spade", "due": "2026-10-20T09:00:00Z"}"#,
+        );
+
+        let patched = ok(
+            &server,
+            "PATCH",
+            "/tasks/1",
+            r#"{"stake_name": "Dig beds", "parent_id": 1, "note": null, "due": null, "tags": ["outside"]}"#,
+        );
+        assert_eq!(patched["stake_name"], "Dig beds");
+        assert_eq!(patched["parent_id"], 1);
+        assert_eq!(patched["note"], Value::Null);
+        assert!(patched.get("due").is_none());
+        assert_eq!(patched["tags"], json!(["outside"]));
+        assert_eq!(
+            names(ok(&server, "GET", "/projects/1/tasks", "")),
+            vec!["Dig beds"]
+        );
+
+        let completed = ok(&server, "PATCH", "/tasks/1", r#"{"complete": true}"#);
+        assert_eq!(completed["complete"], true);
+        assert!(names(ok(&server, "GET", "/tasks", "")).is_empty());
+        let reopened = ok(&server, "PATCH", "/tasks/1", r#"{"complete": false}"#);
+        assert_eq!(reopened["complete"], false);
+
+        let moved_out = ok(&server, "PATCH", "/tasks/1", r#"{"parent_id": null}"#);
+        assert_eq!(moved_out["parent_id"], Value::Null);
+        assert!(names(ok(&server, "GET", "/projects/1/tasks", "")).is_empty());
+    }
+
+    #[test]
+    fn test_delete_drops_and_dropped_stakes_stay_dropped() {
+        let server = server();
+        ok(&server, "POST", "/areas", r#"{"stake_name": "Home"}"#);
+        ok(&server, "POST", "/areas", r#"{"stake_name": "Work"}"#);
+
+        let reply = send(&server, "DELETE", "/areas/1", "");
+        assert_eq!(reply.status, 204);
+        assert_eq!(reply.body, None);
+        assert_eq!(names(ok(&server, "GET", "/areas", "")), vec!["Work"]);
+        assert_eq!(
+            names(ok(&server, "GET", "/areas?status=dropped", "")),
+            vec!["Home"]
+        );
+        assert_eq!(
+            names(ok(&server, "GET", "/areas?status=all", "")),
+            vec!["Home", "Work"]
+        );
+
+        let reply = send(&server, "PATCH", "/areas/1", r#"{"dropped": false}"#);
+        assert_eq!(reply.status, 422);
+        assert_eq!(
+            reply.body.unwrap()["error"],
+            "a dropped stake cannot be activated"
+        );
+        assert_eq!(ok(&server, "GET", "/areas/1", "")["dropped"], true);
+    }
+
+    #[test]
+    fn test_children_and_search() {
+        let server = server();
+        ok(&server, "POST", "/areas", r#"{"stake_name": "Home"}"#);
+        ok(
+            &server,
+            "POST",
+            "/projects",
+            r#"{"stake_name": "Paint kitchen", "parent_id": 1}"#,
+        );
+        ok(&server, "POST", "/projects", r#"{"stake_name": "Taxes"}"#);
+        ok(
+            &server,
+            "POST",
+            "/tasks",
+            r#"{"stake_name": "Buy paint", "parent_id": 1}"#,
+        );
+
+        assert_eq!(
+            names(ok(&server, "GET", "/areas/1/projects", "")),
+            vec!["Paint kitchen"]
+        );
+        assert_eq!(
+            names(ok(&server, "GET", "/projects/1/tasks", "")),
+            vec!["Buy paint"]
+        );
+        let hits = ok(&server, "GET", "/search?q=paint+kit%63hen", "");
+        assert_eq!(hits[0]["stake"]["kind"], "project");
+        assert_eq!(hits[0]["stake"]["stake_name"], "Paint kitchen");
+        assert!(hits[0]["score"].is_number());
+    }
+
+    #[test]
+    fn test_event_subscriptions_pick_a_scope() {
+        let server = server();
+        ok(&server, "POST", "/areas", r#"{"stake_name": "Home"}"#);
+        ok(&server, "POST", "/projects", r#"{"stake_name": "Garden"}"#);
+        let scope = |method, url| subscription(&server, method, url);
+
+        assert_eq!(scope("GET", "/tasks"), None);
+        assert_eq!(scope("GET", "/events"), Some(Ok(Scope::Everything)));
+        assert_eq!(
+            scope("GET", "/events?area=1"),
+            Some(Ok(Scope::Area(StakeId::new(1))))
+        );
+        assert_eq!(
+            scope("GET", "/events/?project=1"),
+            Some(Ok(Scope::Project(StakeId::new(1))))
+        );
+        let status = |url| scope("GET", url).unwrap().unwrap_err().status;
+        assert_eq!(status("/events?project=4"), 404);
+        assert_eq!(status("/events?area=1&project=1"), 400);
+        assert_eq!(scope("POST", "/events").unwrap().unwrap_err().status, 405);
+    }
+
+    #[test]
+    fn test_query_strings_are_decoded() {
+        let query = Query::parse("q=caf%C3%A9+au+lait&status=all&bad=%zz&empty");
+        assert_eq!(query.get("q"), Some("café au lait"));
+        assert_eq!(query.get("status"), Some("all"));
+        assert_eq!(query.get("bad"), Some("%zz"));
+        assert_eq!(query.get("empty"), Some(""));
+        assert_eq!(query.get("missing"), None);
+    }
+}
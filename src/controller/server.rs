@@ -0,0 +1,954 @@
+// Connections carry newline-delimited JSON: each line in is a request or a batch, and
+// each line out is its response. Every connection runs on its own thread against
+// one shared MLW; reads share it, while a write holds it alone until the data file
+// is saved, so a client never sees a change that is not yet on disk. REST requests
+// arrive over HTTP instead, each answered on its own thread by `rest`.
+//
+// A server given accounts admits only clients with a token: a JSON-RPC connection
+// sends `authenticate` once before anything else, and each REST request carries
+// `Authorization: Bearer <token>`. Read-only tokens may call only read methods.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::mpsc::Receiver;
+use std::sync::{Arc, PoisonError, RwLock};
+use std::thread;
+
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+#[cfg(unix)]
+use std::path::Path;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::dispatch::{self, Handler};
+use super::events::{self, Events};
+use super::protocol::{Request, Response, RpcError};
+use super::rest;
+use crate::auth::{AccountStore, AuthError, Grant};
+use crate::config::Config;
+use crate::dates::DateContext;
+use crate::entities::Clock;
+use crate::mlw::MLW;
+use crate::mlw::changes::Scope;
+use crate::serializer::{self, StorageError};
+
+/// A handle to the served MLW. Clones share the same MLW.
+#[derive(Debug, Clone)]
+pub struct Server {
+    mlw: Arc<RwLock<MLW>>,
+    config: Config,
+    data_file: Option<PathBuf>,
+    events: Arc<Events>,
+    accounts: Option<Arc<AccountStore>>,
+    // Who this handle's writes are recorded as made by.
+    author: Option<String>,
+}
+
+/// What one connection has proved about itself so far.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Session {
+    /// `None` until the client authenticates, on a server that checks tokens.
+    pub grant: Option<Grant>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Credentials {
+    token: String,
+}
+
+impl Server {
+    /// Serves `mlw` from memory; dates are read in `config`'s timezone and week.
+    pub fn new(mlw: MLW, config: Config) -> Self {
+        Server {
+            mlw: Arc::new(RwLock::new(mlw)),
+            config,
+            data_file: None,
+            events: Arc::default(),
+            accounts: None,
+            author: None,
+        }
+    }
+
+    /// Saves to `path` after every successful write.
+    pub fn with_data_file(mut self, path: PathBuf) -> Self {
+        self.data_file = Some(path);
+        self
+    }
+
+    /// Admits only clients holding a token from `accounts`. Without them the server
+    /// trusts everyone who can reach it.
+    pub fn with_accounts(mut self, accounts: AccountStore) -> Self {
+        self.accounts = Some(Arc::new(accounts));
+        self
+    }
+
+    /// A handle to the same MLW whose writes are recorded as made by `user`.
+    pub fn acting_as(&self, user: Option<String>) -> Server {
+        Server {
+            author: user,
+            ..self.clone()
+        }
+    }
+
+    /// A new connection's session: trusted on a server without accounts, and
+    /// unauthenticated otherwise.
+    pub fn session(&self) -> Session {
+        Session {
+            grant: self.accounts.is_none().then(Grant::everyone),
+        }
+    }
+
+    /// What `token` lets its holder do. A server without accounts accepts any.
+    pub fn authenticate(&self, token: &str) -> Result<Grant, AuthError> {
+        match &self.accounts {
+            Some(_) if token.trim().is_empty() => Err(AuthError::MissingToken),
+            Some(accounts) => accounts.authenticate(token),
+            None => Ok(Grant::everyone()),
+        }
+    }
+
+    /// A copy of the MLW as it is now.
+    pub fn snapshot(&self) -> MLW {
+        self.mlw
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Answers one line of input. Notifications, and batches made only of them,
+    /// get no answer.
+    pub fn handle_line(&self, session: &mut Session, line: &str) -> Option<String> {
+        let reply = match serde_json::from_str::<Value>(line) {
+            Ok(Value::Array(batch)) if batch.is_empty() => Some(error_reply(
+                Value::Null,
+                RpcError::invalid_request("empty batch"),
+            )),
+            Ok(Value::Array(batch)) => {
+                let replies: Vec<Value> = batch
+                    .into_iter()
+                    .filter_map(|request| self.handle_request(session, request))
+                    .collect();
+                (!replies.is_empty()).then_some(Value::Array(replies))
+            }
+            Ok(request) => self.handle_request(session, request),
+            Err(error) => Some(error_reply(Value::Null, RpcError::parse_error(error))),
+        };
+        reply.map(|reply| reply.to_string())
+    }
+
+    fn handle_request(&self, session: &mut Session, request: Value) -> Option<Value> {
+        let request = match Request::from_value(request) {
+            Ok(request) => request,
+            Err((id, error)) => return Some(error_reply(id, error)),
+        };
+        let outcome = match request.method.as_str() {
+            "authenticate" => self.log_in(session, request.params),
+            method => self.call_as(session, method, request.params),
+        };
+        let response = Response::new(request.id?, outcome);
+        Some(serde_json::to_value(response).expect("responses serialize"))
+    }
+
+    // Answers `authenticate`. A refused token leaves the session as it was.
+    fn log_in(&self, session: &mut Session, params: Value) -> Result<Value, RpcError> {
+        let credentials: Credentials =
+            serde_json::from_value(params).map_err(RpcError::invalid_params)?;
+        let grant = self.authenticate(&credentials.token)?;
+        let reply = serde_json::to_value(&grant).expect("grants serialize");
+        session.grant = Some(grant);
+        Ok(reply)
+    }
+
+    /// Runs `method` for a client, if its session allows it.
+    pub fn call_as(
+        &self,
+        session: &Session,
+        method: &str,
+        params: Value,
+    ) -> Result<Value, RpcError> {
+        let grant = session.grant.as_ref().ok_or(AuthError::MissingToken)?;
+        let handler = dispatch::find(method).ok_or_else(|| RpcError::method_not_found(method))?;
+        if !grant.allows(handler.is_write()) {
+            return Err(AuthError::ReadOnlyToken.into());
+        }
+        self.acting_as(grant.user.clone()).run(handler, params)
+    }
+
+    /// Runs `method` against the MLW, with no checks on who is asking.
+    pub fn call(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+        self.run(
+            dispatch::find(method).ok_or_else(|| RpcError::method_not_found(method))?,
+            params,
+        )
+    }
+
+    fn run(&self, handler: Handler, params: Value) -> Result<Value, RpcError> {
+        match handler {
+            Handler::Read(read) => self.read(|mlw, dates| read(mlw, dates, params)),
+            Handler::Write(write) => self.write(|mlw, dates| write(mlw, dates, params)),
+        }
+    }
+
+    /// Runs `read` alongside any other reads, with "now" taken from the MLW's clock.
+    pub fn read<T>(&self, read: impl FnOnce(&MLW, &DateContext) -> T) -> T {
+        let mlw = self.mlw.read().unwrap_or_else(PoisonError::into_inner);
+        read(&mlw, &self.config.date_context(mlw.clock().now()))
+    }
+
+    /// Runs `write` alone on a copy of the MLW. Only if it succeeds and the data
+    /// file is saved does the copy replace the MLW others see; then subscribers are
+    /// told what changed. A failed write or save leaves the MLW as it was.
+    pub fn write<T, E: From<StorageError>>(
+        &self,
+        write: impl FnOnce(&mut MLW, &DateContext) -> Result<T, E>,
+    ) -> Result<T, E> {
+        let mut mlw = self.mlw.write().unwrap_or_else(PoisonError::into_inner);
+        let mut draft = mlw.clone();
+        draft.set_author(self.author.clone());
+        let dates = self.config.date_context(draft.clock().now());
+        let result = write(&mut draft, &dates)?;
+        if let Some(path) = &self.data_file {
+            serializer::save(path, &draft)?;
+        }
+        let before = std::mem::replace(&mut *mlw, draft);
+        if self.events.has_subscribers() {
+            self.events.publish(&mlw.changes_since(&before), &mlw);
+        }
+        Ok(result)
+    }
+
+    /// Starts receiving change events inside `scope`, framed as server-sent events.
+    pub fn subscribe(&self, scope: Scope) -> Receiver<String> {
+        self.events.subscribe(scope)
+    }
+
+    /// Answers requests from `reader` on `writer` until the client hangs up.
+    pub fn serve_connection(&self, reader: impl BufRead, mut writer: impl Write) -> io::Result<()> {
+        let mut session = self.session();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(mut reply) = self.handle_line(&mut session, &line) {
+                // One write per reply, so Nagle's algorithm never holds back a tail.
+                reply.push('\n');
+                writer.write_all(reply.as_bytes())?;
+                writer.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Accepts TCP clients forever, one thread each.
+    pub fn serve_tcp(&self, listener: TcpListener) -> io::Result<()> {
+        for stream in listener.incoming() {
+            // Replies are small and awaited, so they go out without delay.
+            let stream = stream.and_then(|stream| stream.set_nodelay(true).map(|()| stream));
+            self.spawn_connection(stream);
+        }
+        Ok(())
+    }
+
+    /// Accepts Unix socket clients forever, one thread each.
+    #[cfg(unix)]
+    pub fn serve_unix(&self, listener: UnixListener) -> io::Result<()> {
+        for stream in listener.incoming() {
+            self.spawn_connection(stream);
+        }
+        Ok(())
+    }
+
+    /// Answers REST requests forever, one thread each.
+    pub fn serve_http(&self, listener: tiny_http::Server) -> io::Result<()> {
+        for request in listener.incoming_requests() {
+            let server = self.clone();
+            thread::spawn(move || {
+                if let Err(error) = server.answer_http(request) {
+                    eprintln!("mlw serve: {}", error);
+                }
+            });
+        }
+        Ok(())
+    }
+
+    fn answer_http(&self, mut request: tiny_http::Request) -> io::Result<()> {
+        let write = !matches!(
+            request.method(),
+            tiny_http::Method::Get | tiny_http::Method::Head
+        );
+        let server = match self.admit(bearer(&request), write) {
+            Ok(grant) => self.acting_as(grant.user),
+            Err(error) => return respond(request, rest::refused(error)),
+        };
+        match rest::subscription(&server, request.method().as_str(), request.url()) {
+            Some(Ok(scope)) => {
+                return events::stream(server.subscribe(scope), request.into_writer());
+            }
+            Some(Err(reply)) => return respond(request, reply),
+            None => {}
+        }
+        let mut body = Vec::new();
+        request.as_reader().read_to_end(&mut body)?;
+        let reply = rest::handle(
+            &server,
+            request.method().as_str(),
+            request.url(),
+            &String::from_utf8_lossy(&body),
+        );
+        respond(request, reply)
+    }
+
+    // Checks the token of a REST request that reads or, if `write`, writes.
+    fn admit(&self, token: Option<&str>, write: bool) -> Result<Grant, AuthError> {
+        let grant = match token {
+            Some(token) => self.authenticate(token)?,
+            None => self.session().grant.ok_or(AuthError::MissingToken)?,
+        };
+        match grant.allows(write) {
+            true => Ok(grant),
+            false => Err(AuthError::ReadOnlyToken),
+        }
+    }
+
+    fn spawn_connection<S: Stream>(&self, stream: io::Result<S>) {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(error) => {
+                eprintln!("mlw serve: {}", error);
+                return;
+            }
+        };
+        let server = self.clone();
+        thread::spawn(move || {
+            let result = stream
+                .try_clone()
+                .and_then(|reader| server.serve_connection(BufReader::new(reader), stream));
+            if let Err(error) = result {
+                eprintln!("mlw serve: {}", error);
+            }
+        });
+    }
+}
+
+/// Binds the Unix socket at `path`, replacing a socket file left behind by a server
+/// that is no longer running.
+#[cfg(unix)]
+pub fn bind_unix(path: &Path) -> io::Result<UnixListener> {
+    if path.exists() && UnixStream::connect(path).is_err() {
+        std::fs::remove_file(path)?;
+    }
+    UnixListener::bind(path)
+}
+
+/// Binds the REST API's HTTP listener to `address`.
+pub fn bind_http(address: &str) -> io::Result<tiny_http::Server> {
+    tiny_http::Server::http(address).map_err(io::Error::other)
+}
+
+fn respond(request: tiny_http::Request, reply: rest::Reply) -> io::Result<()> {
+    let mut response = match &reply.body {
+        Some(body) => tiny_http::Response::from_string(body.to_string())
+            .with_header(header("Content-Type", "application/json")),
+        None => tiny_http::Response::from_string(String::new()),
+    }
+    .with_status_code(reply.status);
+    for (name, value) in &reply.headers {
+        response.add_header(header(name, value));
+    }
+    request.respond(response)
+}
+
+// The token in an `Authorization: Bearer` header, if there is one.
+fn bearer(request: &tiny_http::Request) -> Option<&str> {
+    request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv("Authorization"))
+        .and_then(|header| {
+            let value = header.value.as_str().trim();
+            let (scheme, token) = value.split_once(' ')?;
+            scheme.eq_ignore_ascii_case("Bearer").then(|| token.trim())
+        })
+}
+
+fn header(name: &str, value: &str) -> tiny_http::Header {
+    tiny_http::Header::from_bytes(name, value).expect("header names and values are ASCII")
+}
+
+fn error_reply(id: Value, error: RpcError) -> Value {
+    serde_json::to_value(Response::new(id, Err(error))).expect("responses serialize")
+}
+
+// The socket types a connection can run over.
+trait Stream: Read + Write + Send + Sized + 'static {
+    fn try_clone(&self) -> io::Result<Self>;
+}
+
+impl Stream for TcpStream {
+    fn try_clone(&self) -> io::Result<Self> {
+        TcpStream::try_clone(self)
+    }
+}
+
+#[cfg(unix)]
+impl Stream for UnixStream {
+    fn try_clone(&self) -> io::Result<Self> {
+        UnixStream::try_clone(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{Access, Accounts};
+    use crate::controller::protocol::{
+        AUTHENTICATION_REQUIRED, INVALID_PARAMS, INVALID_REQUEST, INVALID_TOKEN, METHOD_NOT_FOUND,
+        PARSE_ERROR, READ_ONLY_TOKEN, STAKE_NOT_FOUND,
+    };
+    use serde_json::json;
+    use std::net::SocketAddr;
+
+    // Starts `server` on a free localhost port.
+    fn start(server: Server) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || server.serve_tcp(listener));
+        address
+    }
+
+    struct Client {
+        reader: BufReader<TcpStream>,
+        writer: TcpStream,
+        next_id: u64,
+    }
+
+    impl Client {
+        fn connect(address: SocketAddr) -> Self {
+            let writer = TcpStream::connect(address).unwrap();
+            writer.set_nodelay(true).unwrap();
+            let reader = BufReader::new(writer.try_clone().unwrap());
+            Client {
+                reader,
+                writer,
+                next_id: 1,
+            }
+        }
+
+        fn send(&mut self, line: &str) -> Value {
+            writeln!(self.writer, "{}", line).unwrap();
+            let mut reply = String::new();
+            self.reader.read_line(&mut reply).unwrap();
+            serde_json::from_str(&reply).unwrap()
+        }
+
+        fn call(&mut self, method: &str, params: Value) -> Result<Value, RpcError> {
+            let request = Request::new(method, params, json!(self.next_id));
+            self.next_id += 1;
+            let reply = self.send(&serde_json::to_string(&request).unwrap());
+            let response: Response = serde_json::from_value(reply).unwrap();
+            assert_eq!(response.id, json!(self.next_id - 1));
+            response.into_result()
+        }
+    }
+
+    fn served() -> (SocketAddr, Server) {
+        let server = Server::new(MLW::new(), Config::default());
+        (start(server.clone()), server)
+    }
+
+    #[test]
+    fn test_round_trip_over_tcp() {
+        let (address, server) = served();
+        let mut client = Client::connect(address);
+
+        let area = client.call("new_area", json!({"name": "Health"})).unwrap();
+        assert_eq!(area["stake_name"], "Health");
+        let project = client
+            .call("new_project", json!({"name": "Run a 10k", "parent_id": 1}))
+            .unwrap();
+        assert_eq!(project["parent_id"], 1);
+        client
+            .call("new_task", json!(["Buy shoes", 1, "size 44"]))
+            .unwrap();
+        client.call("new_task", json!({"name": "Inbox"})).unwrap();
+
+        client.call("mark_task_complete", json!({"id": 2})).unwrap();
+        client
+            .call("move_project", json!({"id": 1, "parent_id": null}))
+            .unwrap();
+
+        let active = client.call("active_tasks", Value::Null).unwrap();
+        assert_eq!(active.as_array().unwrap().len(), 1);
+        let children = client
+            .call("get_task_children", json!({"parent_id": 1}))
+            .unwrap();
+        assert_eq!(children[0]["note"], "size 44");
+        assert_eq!(
+            client.call("get_project_by_id", json!({"id": 1})).unwrap()["parent_id"],
+            Value::Null
+        );
+        assert_eq!(
+            client.call("get_area_by_id", json!({"id": 9})).unwrap(),
+            Value::Null
+        );
+        assert_eq!(client.call("next_task_id", json!({})).unwrap(), json!(3));
+
+        let mlw = server.snapshot();
+        assert_eq!(mlw.tasks().len(), 2);
+        assert!(
+            mlw.get_task_by_id(&crate::entities::StakeId::new(2))
+                .unwrap()
+                .complete
+        );
+    }
+
+    #[test]
+    fn test_update_replaces_stake() {
+        let (address, _) = served();
+        let mut client = Client::connect(address);
+        let mut task = client.call("new_task", json!({"name": "Draft"})).unwrap();
+        task["note"] = json!("Second pass");
+        client
+            .call("update_task", json!({ "stake": task }))
+            .unwrap();
+        assert_eq!(
+            client.call("get_task_by_id", json!({"id": 1})).unwrap()["note"],
+            "Second pass"
+        );
+    }
+
+    #[test]
+    fn test_errors_use_json_rpc_codes() {
+        let (address, _) = served();
+        let mut client = Client::connect(address);
+        let code = |result: Result<Value, RpcError>| result.unwrap_err().code;
+
+        assert_eq!(code(client.call("fly", Value::Null)), METHOD_NOT_FOUND);
+        assert_eq!(
+            code(client.call("mark_task_complete", json!({"id": 7}))),
+            STAKE_NOT_FOUND
+        );
+        assert_eq!(
+            code(client.call("new_area", json!({"title": "Health"}))),
+            INVALID_PARAMS
+        );
+        assert_eq!(
+            code(client.call("get_task_by_id", json!({"id": "one"}))),
+            INVALID_PARAMS
+        );
+        assert_eq!(
+            code(client.call("active_tasks", json!({"all": true}))),
+            INVALID_PARAMS
+        );
+
+        let reply = client.send("{not json");
+        assert_eq!(reply["error"]["code"], PARSE_ERROR);
+        assert_eq!(reply["id"], Value::Null);
+        let reply = client.send(r#"{"jsonrpc": "1.0", "method": "tasks", "id": 4}"#);
+        assert_eq!(reply["error"]["code"], INVALID_REQUEST);
+        assert_eq!(reply["id"], 4);
+        let reply = client.send("[]");
+        assert_eq!(reply["error"]["code"], INVALID_REQUEST);
+    }
+
+    #[test]
+    fn test_batches_and_notifications() {
+        let (address, server) = served();
+        let mut client = Client::connect(address);
+        let reply = client.send(
+            r#"[{"jsonrpc": "2.0", "method": "new_area", "params": {"name": "Quiet"}},
+                {"jsonrpc": "2.0", "method": "new_area", "params": {"name": "Loud"}, "id": "a"},
+                {"jsonrpc": "2.0", "method": "nope", "id": "b"}]"#
+                .replace('\n', " ")
+                .as_str(),
+        );
+        let replies = reply.as_array().unwrap();
+        assert_eq!(replies.len(), 2, "the notification gets no reply");
+        assert_eq!(replies[0]["result"]["stake_name"], "Loud");
+        assert_eq!(replies[1]["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(server.snapshot().areas().len(), 2);
+
+        assert_eq!(
+            server.handle_line(
+                &mut server.session(),
+                r#"{"jsonrpc": "2.0", "method": "new_task", "params": ["x"]}"#
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_concurrent_clients_get_distinct_ids() {
+        let (address, server) = served();
+        let workers: Vec<_> = (0..8)
+            .map(|worker| {
+                thread::spawn(move || {
+                    let mut client = Client::connect(address);
+                    for n in 0..25 {
+                        let name = format!("Task {}-{}", worker, n);
+                        client.call("new_task", json!({ "name": name })).unwrap();
+                        client.call("next_task_id", Value::Null).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+
+        let mlw = server.snapshot();
+        let mut ids: Vec<u32> = mlw.tasks().iter().map(|task| task.stake_id.0).collect();
+        ids.sort();
+        assert_eq!(ids, (1..=200).collect::<Vec<u32>>());
+    }
+
+    #[test]
+    fn test_writes_are_saved_before_replying() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mlw.json");
+        let server = Server::new(MLW::new(), Config::default()).with_data_file(path.clone());
+        let mut client = Client::connect(start(server));
+
+        client.call("new_area", json!({"name": "Health"})).unwrap();
+        assert_eq!(serializer::load(&path).unwrap().areas().len(), 1);
+
+        // A failed write changes nothing, and reads never touch the file.
+        std::fs::remove_file(&path).unwrap();
+        client
+            .call("mark_area_dropped", json!({"id": 5}))
+            .unwrap_err();
+        client.call("areas", Value::Null).unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_failed_writes_and_saves_leave_the_mlw_unchanged() {
+        let dir = tempfile::tempdir().unwrap();
+        // A directory cannot be written as the data file.
+        let server = Server::new(MLW::new(), Config::default()).with_data_file(dir.path().into());
+
+        server
+            .call("new_area", json!({"name": "Health"}))
+            .unwrap_err();
+        assert!(server.snapshot().areas().is_empty());
+
+        let failed: Result<(), StorageError> = server.write(|mlw, _| {
+            mlw.new_area("Half done".to_string(), None);
+            Err(StorageError::Io(
+                dir.path().into(),
+                io::Error::other("gave up"),
+            ))
+        });
+        assert!(failed.is_err());
+        assert!(server.snapshot().areas().is_empty());
+    }
+
+    // A server with accounts, and a read-write and a read-only token for it.
+    fn guarded() -> (Server, String, String) {
+        let mut accounts = Accounts::default();
+        accounts.add_user("alice").unwrap();
+        accounts.add_user("bob").unwrap();
+        let now = chrono::Utc::now();
+        let write = accounts.issue_token("alice", Access::Write, now).unwrap();
+        let read = accounts.issue_token("bob", Access::Read, now).unwrap();
+        let server =
+            Server::new(MLW::new(), Config::default()).with_accounts(AccountStore::from(accounts));
+        (server, write, read)
+    }
+
+    #[test]
+    fn test_connections_must_authenticate() {
+        let (server, write, read) = guarded();
+        let address = start(server.clone());
+        let code = |result: Result<Value, RpcError>| result.unwrap_err().code;
+
+        let mut client = Client::connect(address);
+        assert_eq!(
+            code(client.call("active_tasks", Value::Null)),
+            AUTHENTICATION_REQUIRED
+        );
+        assert_eq!(
+            code(client.call("authenticate", json!({"token": ""}))),
+            AUTHENTICATION_REQUIRED
+        );
+        assert_eq!(
+            code(client.call("authenticate", json!({"token": "mlw_1_2"}))),
+            INVALID_TOKEN
+        );
+        assert_eq!(
+            code(client.call("authenticate", json!({"key": write}))),
+            INVALID_PARAMS
+        );
+        // Unknown methods are not revealed before signing in.
+        assert_eq!(
+            code(client.call("fly", Value::Null)),
+            AUTHENTICATION_REQUIRED
+        );
+
+        let grant = client
+            .call("authenticate", json!({ "token": read }))
+            .unwrap();
+        assert_eq!(grant, json!({"user": "bob", "access": "read"}));
+        client.call("active_tasks", Value::Null).unwrap();
+        assert_eq!(
+            code(client.call("new_task", json!({"name": "x"}))),
+            READ_ONLY_TOKEN
+        );
+        // A refused token keeps the session signed in as before.
+        client
+            .call("authenticate", json!({"token": "nope"}))
+            .unwrap_err();
+        client.call("areas", Value::Null).unwrap();
+
+        let mut writer = Client::connect(address);
+        writer
+            .call("authenticate", json!({ "token": write }))
+            .unwrap();
+        writer.call("new_task", json!({"name": "Call"})).unwrap();
+        assert_eq!(server.snapshot().tasks().len(), 1);
+
+        // Servers without accounts accept any token, or none.
+        let mut open = Client::connect(served().0);
+        let grant = open.call("authenticate", json!({"token": ""})).unwrap();
+        assert_eq!(grant, json!({"user": null, "access": "write"}));
+    }
+
+    #[test]
+    fn test_rest_requires_a_bearer_token() {
+        let (server, write, read) = guarded();
+        let listener = bind_http("127.0.0.1:0").unwrap();
+        let address = listener.server_addr().to_ip().unwrap();
+        thread::spawn(move || server.serve_http(listener));
+
+        let (head, body) = http(address, "GET", "/tasks", "");
+        assert!(head.starts_with("HTTP/1.1 401"), "{}", head);
+        assert!(
+            head.contains(r#"WWW-Authenticate: Bearer realm="mlw""#),
+            "{}",
+            head
+        );
+        assert_eq!(
+            serde_json::from_str::<Value>(&body).unwrap()["error"],
+            AuthError::MissingToken.to_string()
+        );
+        let (head, _) = http_as(address, Some("mlw_1_2"), "GET", "/tasks", "");
+        assert!(head.starts_with("HTTP/1.1 401"), "{}", head);
+        assert!(head.contains(r#"error="invalid_token""#), "{}", head);
+        let (head, _) = http(address, "GET", "/events", "");
+        assert!(head.starts_with("HTTP/1.1 401"), "{}", head);
+
+        let (head, _) = http_as(address, Some(&read), "GET", "/tasks", "");
+        assert!(head.starts_with("HTTP/1.1 200"), "{}", head);
+        let created = r#"{"stake_name": "Call"}"#;
+        let (head, _) = http_as(address, Some(&read), "POST", "/tasks", created);
+        assert!(head.starts_with("HTTP/1.1 403"), "{}", head);
+        assert!(head.contains(r#"error="insufficient_scope""#), "{}", head);
+        let (head, _) = http_as(address, Some(&write), "POST", "/tasks", created);
+        assert!(head.starts_with("HTTP/1.1 201"), "{}", head);
+    }
+
+    #[test]
+    fn test_writes_are_recorded_as_made_by_the_token_user() {
+        let (server, write, _) = guarded();
+        let session = Session {
+            grant: Some(Grant {
+                user: Some("carol".to_string()),
+                access: Access::Write,
+            }),
+        };
+        server
+            .call_as(&session, "new_task", json!({"name": "Plan"}))
+            .unwrap();
+
+        let listener = bind_http("127.0.0.1:0").unwrap();
+        let address = listener.server_addr().to_ip().unwrap();
+        let rest = server.clone();
+        thread::spawn(move || rest.serve_http(listener));
+        let (head, body) = http_as(
+            address,
+            Some(&write),
+            "PATCH",
+            "/tasks/1",
+            r#"{"note": "x"}"#,
+        );
+        assert!(head.starts_with("HTTP/1.1 200"), "{}", head);
+        let task: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(task["created_by"], "carol");
+        assert_eq!(task["modified_by"], "alice");
+
+        // Trusted calls are made by nobody in particular.
+        server.call("mark_task_complete", json!({"id": 1})).unwrap();
+        let task = server.snapshot().tasks().iter().next().unwrap().clone();
+        assert_eq!(task.created_by.as_deref(), Some("carol"));
+        assert_eq!(task.modified_by, None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unix_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mlw.sock");
+        let listener = bind_unix(&path).unwrap();
+        let server = Server::new(MLW::new(), Config::default());
+        thread::spawn(move || server.serve_unix(listener));
+
+        let mut stream = UnixStream::connect(&path).unwrap();
+        writeln!(
+            stream,
+            r#"{{"jsonrpc": "2.0", "method": "new_area", "params": {{"name": "Health"}}, "id": 1}}"#
+        )
+        .unwrap();
+        let mut reply = String::new();
+        BufReader::new(&stream).read_line(&mut reply).unwrap();
+        let reply: Value = serde_json::from_str(&reply).unwrap();
+        assert_eq!(reply["result"]["stake_name"], "Health");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_bind_unix_replaces_stale_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mlw.sock");
+        drop(bind_unix(&path).unwrap());
+        assert!(path.exists());
+        bind_unix(&path).unwrap();
+    }
+
+    // Sends one HTTP/1.1 request and returns the status line, headers and body.
+    fn http(address: SocketAddr, method: &str, path: &str, body: &str) -> (String, String) {
+        http_as(address, None, method, path, body)
+    }
+
+    // Sends one HTTP/1.1 request, with `token` as its bearer token if given.
+    fn http_as(
+        address: SocketAddr,
+        token: Option<&str>,
+        method: &str,
+        path: &str,
+        body: &str,
+    ) -> (String, String) {
+        let authorization = token
+            .map(|token| format!("Authorization: Bearer {}\r\n", token))
+            .unwrap_or_default();
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n{}\
+             Content-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            method,
+            path,
+            authorization,
+            body.len(),
+            body
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        (head.to_string(), body.to_string())
+    }
+
+    #[test]
+    fn test_rest_over_http_shares_the_mlw_and_saves() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mlw.json");
+        let server = Server::new(MLW::new(), Config::default()).with_data_file(path.clone());
+        let listener = bind_http("127.0.0.1:0").unwrap();
+        let address = listener.server_addr().to_ip().unwrap();
+        let rest = server.clone();
+        thread::spawn(move || rest.serve_http(listener));
+
+        let (head, body) = http(address, "POST", "/tasks", r#"{"stake_name": "Call mom"}"#);
+        assert!(head.starts_with("HTTP/1.1 201"), "{}", head);
+        assert!(head.contains("Location: /tasks/1"), "{}", head);
+        assert!(head.contains("Content-Type: application/json"), "{}", head);
+        let created: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(created["stake_name"], "Call mom");
+        assert_eq!(serializer::load(&path).unwrap().tasks().len(), 1);
+
+        // JSON-RPC clients see the same MLW.
+        let mut client = Client::connect(start(server));
+        let task = client.call("get_task_by_id", json!({"id": 1})).unwrap();
+        assert_eq!(task, created);
+
+        let (head, body) = http(address, "GET", "/tasks/7", "");
+        assert!(head.starts_with("HTTP/1.1 404"), "{}", head);
+        assert_eq!(
+            serde_json::from_str::<Value>(&body).unwrap()["error"],
+            "no task matches '7'"
+        );
+        let (head, _) = http(address, "DELETE", "/tasks/1", "");
+        assert!(head.starts_with("HTTP/1.1 204"), "{}", head);
+        assert!(
+            serializer::load(&path)
+                .unwrap()
+                .tasks()
+                .iter()
+                .all(|t| t.dropped)
+        );
+    }
+
+    #[test]
+    fn test_writes_are_streamed_to_event_subscribers() {
+        let server = Server::new(MLW::new(), Config::default());
+        let listener = bind_http("127.0.0.1:0").unwrap();
+        let address = listener.server_addr().to_ip().unwrap();
+        let rest = server.clone();
+        thread::spawn(move || rest.serve_http(listener));
+        let mut client = Client::connect(start(server));
+        client
+            .call("new_project", json!({"name": "Garden"}))
+            .unwrap();
+        client
+            .call("new_project", json!({"name": "Taxes"}))
+            .unwrap();
+
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(
+            stream,
+            "GET /events?project=1 HTTP/1.1\r\nHost: localhost\r\n\r\n"
+        )
+        .unwrap();
+        let mut events = BufReader::new(stream);
+        let mut head = String::new();
+        while head != "\r\n" {
+            head.clear();
+            events.read_line(&mut head).unwrap();
+            assert!(!head.is_empty(), "the stream ended early");
+        }
+
+        // Changes outside project 1 are not sent.
+        client
+            .call("new_task", json!({"name": "Receipts", "parent_id": 2}))
+            .unwrap();
+        client
+            .call("new_task", json!({"name": "Dig", "parent_id": 1}))
+            .unwrap();
+        client.call("mark_task_complete", json!({"id": 2})).unwrap();
+
+        let mut read_event = || {
+            let mut lines = Vec::new();
+            loop {
+                let mut line = String::new();
+                events.read_line(&mut line).unwrap();
+                if line == "\n" {
+                    return lines;
+                }
+                lines.push(line.trim_end().to_string());
+            }
+        };
+        let created = read_event();
+        assert_eq!(created[1], "event: created");
+        let data: Value = serde_json::from_str(created[2].strip_prefix("data: ").unwrap()).unwrap();
+        assert_eq!(data["stake"]["stake_name"], "Dig");
+        assert_eq!(data["stake"]["kind"], "task");
+        let completed = read_event();
+        assert_eq!(completed[1], "event: completed");
+        assert!(completed[2].contains(r#""complete":true"#));
+    }
+}
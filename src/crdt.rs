@@ -0,0 +1,135 @@
+// Conflict-free replicated data types, and an MLW document built from them. Unlike
+// `mlw::sync`, which settles a field both replicas changed by keeping one side, a
+// `Document` keeps concurrent edits to notes, tags and task order from both sides.
+// Any two documents merge to the same result in either order, however often.
+//
+// `lww` holds last-writer-wins registers for scalar fields, `or_set` an observed-
+// remove set for tags, contexts and which stakes exist, `sequence` a replicated
+// sequence for note text and task order, and `document` the MLW built from them.
+// Every write is stamped with a `Dot` from its replica's `Lamport` clock.
+
+pub mod document;
+pub mod lww;
+pub mod or_set;
+pub mod sequence;
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+pub use document::Document;
+pub use lww::LwwRegister;
+pub use or_set::OrSet;
+pub use sequence::Sequence;
+
+use crate::mlw::sync::ReplicaId;
+
+/// One write: the writing replica and its Lamport time. Dots are unique, and a
+/// write always has a greater dot than every write its replica had seen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Dot {
+    pub counter: u64,
+    pub replica: ReplicaId,
+}
+
+impl fmt::Display for Dot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{}", self.counter, self.replica)
+    }
+}
+
+impl FromStr for Dot {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (counter, replica) = s
+            .split_once('@')
+            .ok_or_else(|| format!("'{}' is not a dot", s))?;
+        Ok(Dot {
+            counter: counter
+                .parse()
+                .map_err(|_| format!("'{}' is not a dot", s))?,
+            replica: replica
+                .parse()
+                .map_err(|_| format!("'{}' is not a dot", s))?,
+        })
+    }
+}
+
+// Dots are written as "counter@replica", so they can key JSON objects.
+impl Serialize for Dot {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Dot {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// A replica's Lamport clock, which hands out its dots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lamport {
+    pub replica: ReplicaId,
+    pub counter: u64,
+}
+
+impl Lamport {
+    pub fn new(replica: ReplicaId) -> Self {
+        Lamport {
+            replica,
+            counter: 0,
+        }
+    }
+
+    /// The dot for the next write.
+    pub fn tick(&mut self) -> Dot {
+        self.counter += 1;
+        Dot {
+            counter: self.counter,
+            replica: self.replica,
+        }
+    }
+
+    /// Moves past every write another replica had seen, once its state is merged.
+    pub fn observe(&mut self, counter: u64) {
+        self.counter = self.counter.max(counter);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ticks_outrank_everything_observed() {
+        let (first, second) = (ReplicaId::new(), ReplicaId::new());
+        let mut clock = Lamport::new(first);
+        let early = clock.tick();
+        clock.observe(41);
+        let late = clock.tick();
+        assert_eq!(late.counter, 42);
+        assert!(late > early);
+        assert!(
+            late > Dot {
+                counter: 41,
+                replica: second
+            }
+        );
+    }
+
+    #[test]
+    fn test_dots_serialize_as_strings() {
+        let dot = Lamport::new(ReplicaId::new()).tick();
+        let json = serde_json::to_value(dot).unwrap();
+        assert!(json.as_str().unwrap().starts_with("1@"));
+        assert_eq!(serde_json::from_value::<Dot>(json).unwrap(), dot);
+        assert!("1".parse::<Dot>().is_err());
+        assert!("x@y".parse::<Dot>().is_err());
+    }
+}
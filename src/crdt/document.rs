@@ -0,0 +1,698 @@
+// An MLW kept as CRDTs, so replicas can edit apart and merge without losing either
+// side's work. Scalar fields are LWW registers, tags and contexts OR-sets, note text
+// a sequence of characters, and which stakes exist an OR-set of global ids; the
+// order of tasks is a sequence of global ids, and the team an OR-set of names.
+//
+// Stakes are edited as usual, on an MLW: `to_mlw` gives the current state, and
+// `absorb` records every difference between the document and an edited MLW as new
+// writes. Replicas then exchange documents and `merge` them.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{Lamport, LwwRegister, OrSet, Sequence};
+use crate::entities::{GlobalId, Kind, Priority, Stake, StakeError, StakeKind};
+use crate::mlw::MLW;
+use crate::mlw::sync::ReplicaId;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Document {
+    clock: Lamport,
+    // Which stakes exist. A removed stake keeps its entry, so merges cannot revive it.
+    members: OrSet<GlobalId>,
+    stakes: BTreeMap<GlobalId, Entry>,
+    // The order tasks are listed in. A task moved on two replicas at once may appear
+    // twice; its first place counts.
+    task_order: Sequence<GlobalId>,
+    // The people who share the workspace.
+    #[serde(default)]
+    team: OrSet<String>,
+}
+
+// Two documents are equal when they hold the same state, whichever replica they are.
+impl PartialEq for Document {
+    fn eq(&self, other: &Self) -> bool {
+        self.members == other.members
+            && self.stakes == other.stakes
+            && self.task_order == other.task_order
+            && self.team == other.team
+    }
+}
+
+impl Eq for Document {}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Entry {
+    kind: StakeKind,
+    date_created: DateTime<Utc>,
+    date_modified: DateTime<Utc>,
+    parent: LwwRegister<Option<GlobalId>>,
+    stake_name: LwwRegister<String>,
+    complete: LwwRegister<bool>,
+    dropped: LwwRegister<bool>,
+    note: Sequence<char>,
+    date_reviewed: LwwRegister<Option<DateTime<Utc>>>,
+    contexts: OrSet<String>,
+    tags: OrSet<String>,
+    due: LwwRegister<Option<DateTime<Utc>>>,
+    defer_until: LwwRegister<Option<DateTime<Utc>>>,
+    priority: LwwRegister<Option<Priority>>,
+    assignee: LwwRegister<Option<String>>,
+    created_by: LwwRegister<Option<String>>,
+    modified_by: LwwRegister<Option<String>>,
+}
+
+impl Document {
+    /// An empty document, written to as `replica`.
+    pub fn new(replica: ReplicaId) -> Self {
+        Document {
+            clock: Lamport::new(replica),
+            members: OrSet::new(),
+            stakes: BTreeMap::new(),
+            task_order: Sequence::new(),
+            team: OrSet::new(),
+        }
+    }
+
+    /// A document holding every stake of `mlw`, which gets global ids where missing.
+    pub fn from_mlw(mlw: &mut MLW, replica: ReplicaId) -> Self {
+        let mut document = Self::new(replica);
+        document.absorb(mlw);
+        document
+    }
+
+    pub fn replica(&self) -> ReplicaId {
+        self.clock.replica
+    }
+
+    /// Whether the stake is in the document and not removed.
+    pub fn contains(&self, global_id: &GlobalId) -> bool {
+        self.members.contains(global_id)
+    }
+
+    /// Records how `mlw` differs from this document as writes of this replica: new
+    /// stakes, and changed fields of known ones. `mlw` should be a `to_mlw` of this
+    /// document, edited since; anything older would undo what merges brought in.
+    /// Removed stakes stay removed.
+    pub fn absorb(&mut self, mlw: &mut MLW) {
+        mlw.assign_global_ids();
+        for member in mlw.members() {
+            if !self.team.contains(member) {
+                self.team.add(member.clone(), self.clock.tick());
+            }
+        }
+        let left: Vec<String> = self
+            .team
+            .iter()
+            .filter(|member| !mlw.is_member(member))
+            .cloned()
+            .collect();
+        for member in &left {
+            self.team.remove(member);
+        }
+        for area in mlw.areas().iter() {
+            self.absorb_stake(area, None);
+        }
+        for project in mlw.projects().iter() {
+            let parent = project
+                .parent_id
+                .as_ref()
+                .and_then(|id| mlw.get_area_by_id(id)?.global_id);
+            self.absorb_stake(project, parent);
+        }
+        for task in mlw.tasks().iter() {
+            let parent = task
+                .parent_id
+                .as_ref()
+                .and_then(|id| mlw.get_project_by_id(id)?.global_id);
+            self.absorb_stake(task, parent);
+        }
+    }
+
+    fn absorb_stake<K: Kind>(&mut self, stake: &Stake<K>, parent: Option<GlobalId>) {
+        let global_id = stake.global_id.expect("global ids were assigned");
+        let Document {
+            clock,
+            members,
+            stakes,
+            task_order,
+            ..
+        } = self;
+        let Some(entry) = stakes.get_mut(&global_id) else {
+            let dot = clock.tick();
+            stakes.insert(global_id, Entry::new(stake, parent, clock));
+            members.add(global_id, dot);
+            if K::KIND == StakeKind::Task {
+                task_order.insert(task_order.len(), global_id, clock.tick());
+            }
+            return;
+        };
+        if !members.contains(&global_id) || entry.kind != K::KIND {
+            return;
+        }
+        entry.date_created = entry.date_created.min(stake.date_created);
+        entry.date_modified = entry.date_modified.max(stake.date_modified);
+        write(&mut entry.parent, &parent, clock);
+        write(&mut entry.stake_name, &stake.stake_name, clock);
+        write(&mut entry.complete, &stake.complete, clock);
+        write(&mut entry.dropped, &stake.dropped, clock);
+        entry
+            .note
+            .set_text(stake.note.as_deref().unwrap_or_default(), || clock.tick());
+        write(&mut entry.date_reviewed, &stake.date_reviewed, clock);
+        write_set(&mut entry.contexts, &stake.contexts, clock);
+        write_set(&mut entry.tags, &stake.tags, clock);
+        write(&mut entry.due, &stake.due, clock);
+        write(&mut entry.defer_until, &stake.defer_until, clock);
+        write(&mut entry.priority, &stake.priority, clock);
+        write(&mut entry.assignee, &stake.assignee, clock);
+        write(&mut entry.created_by, &stake.created_by, clock);
+        write(&mut entry.modified_by, &stake.modified_by, clock);
+    }
+
+    /// Removes a stake. Its children lose their parent.
+    pub fn remove(&mut self, global_id: &GlobalId) -> Result<(), StakeError> {
+        if self.members.remove(global_id) {
+            Ok(())
+        } else {
+            Err(StakeError::StakeNotFound)
+        }
+    }
+
+    /// The tasks in the order they are listed.
+    pub fn task_order(&self) -> Vec<GlobalId> {
+        let mut seen = HashSet::new();
+        self.task_order
+            .iter()
+            .filter(|id| self.is_member(id, StakeKind::Task) && seen.insert(**id))
+            .copied()
+            .collect()
+    }
+
+    /// Moves a task to `index` in the task order, or to the end if that is past it.
+    pub fn move_task(&mut self, global_id: &GlobalId, index: usize) -> Result<(), StakeError> {
+        if !self.is_member(global_id, StakeKind::Task) {
+            return Err(StakeError::StakeNotFound);
+        }
+        // Leave every other task where it is listed, hidden or not.
+        while self.task_order.remove_value(global_id) {}
+        let index = self.task_order().len().min(index);
+        let position = match self.task_order().get(index) {
+            Some(next) => self.task_order.iter().position(|id| id == next).unwrap(),
+            None => self.task_order.len(),
+        };
+        self.task_order
+            .insert(position, *global_id, self.clock.tick());
+        Ok(())
+    }
+
+    /// Takes in everything `other` holds. Merging is commutative, associative and
+    /// idempotent, so replicas that have merged the same documents are equal.
+    pub fn merge(&mut self, other: &Document) {
+        self.members.merge(&other.members);
+        for (global_id, theirs) in &other.stakes {
+            match self.stakes.get_mut(global_id) {
+                Some(ours) => ours.merge(theirs),
+                None => {
+                    self.stakes.insert(*global_id, theirs.clone());
+                }
+            }
+        }
+        self.task_order.merge(&other.task_order);
+        self.team.merge(&other.team);
+        self.clock.observe(other.clock.counter);
+    }
+
+    /// The document as an MLW. Areas and projects are numbered in creation order,
+    /// tasks in the task order. A stake whose parent was removed has none.
+    pub fn to_mlw(&self) -> MLW {
+        let mut mlw = MLW::new();
+        for member in self.team.iter() {
+            mlw.add_member(member.clone());
+        }
+        let mut areas = HashMap::new();
+        for (global_id, entry) in self.in_creation_order(StakeKind::Area) {
+            let mut area = mlw.new_area(String::new(), None);
+            entry.fill(&mut area, global_id);
+            areas.insert(global_id, area.stake_id.clone());
+            mlw.restore_area(area).expect("the area was just added");
+        }
+        let mut projects = HashMap::new();
+        for (global_id, entry) in self.in_creation_order(StakeKind::Project) {
+            let parent = entry.parent.get().and_then(|id| areas.get(&id).cloned());
+            let mut project = mlw.new_project(String::new(), parent, None);
+            entry.fill(&mut project, global_id);
+            projects.insert(global_id, project.stake_id.clone());
+            mlw.restore_project(project)
+                .expect("the project was just added");
+        }
+        for global_id in self.task_order() {
+            let entry = &self.stakes[&global_id];
+            let parent = entry.parent.get().and_then(|id| projects.get(&id).cloned());
+            let mut task = mlw.new_task(String::new(), parent, None);
+            entry.fill(&mut task, global_id);
+            mlw.restore_task(task).expect("the task was just added");
+        }
+        mlw
+    }
+
+    fn is_member(&self, global_id: &GlobalId, kind: StakeKind) -> bool {
+        self.members.contains(global_id)
+            && self
+                .stakes
+                .get(global_id)
+                .is_some_and(|entry| entry.kind == kind)
+    }
+
+    fn in_creation_order(&self, kind: StakeKind) -> Vec<(GlobalId, &Entry)> {
+        let mut stakes: Vec<(GlobalId, &Entry)> = self
+            .stakes
+            .iter()
+            .filter(|(id, _)| self.is_member(id, kind))
+            .map(|(id, entry)| (*id, entry))
+            .collect();
+        stakes.sort_by_key(|(id, entry)| (entry.date_created, *id));
+        stakes
+    }
+}
+
+// Writes `value` to `register` if it differs.
+fn write<T: Clone + PartialEq>(register: &mut LwwRegister<T>, value: &T, clock: &mut Lamport) {
+    if register.get() != value {
+        register.set(value.clone(), clock.tick());
+    }
+}
+
+// Adds and removes elements until `set` holds `values`.
+fn write_set(set: &mut OrSet<String>, values: &[String], clock: &mut Lamport) {
+    let gone: Vec<String> = set
+        .iter()
+        .filter(|v| !values.contains(v))
+        .cloned()
+        .collect();
+    for value in &gone {
+        set.remove(value);
+    }
+    for value in values {
+        if !set.contains(value) {
+            set.add(value.clone(), clock.tick());
+        }
+    }
+}
+
+impl Entry {
+    fn new<K: Kind>(stake: &Stake<K>, parent: Option<GlobalId>, clock: &mut Lamport) -> Self {
+        let dot = clock.tick();
+        let mut note = Sequence::new();
+        note.set_text(stake.note.as_deref().unwrap_or_default(), || clock.tick());
+        let mut set = |values: &[String]| {
+            let mut set = OrSet::new();
+            for value in values {
+                set.add(value.clone(), clock.tick());
+            }
+            set
+        };
+        Entry {
+            kind: K::KIND,
+            date_created: stake.date_created,
+            date_modified: stake.date_modified,
+            parent: LwwRegister::new(parent, dot),
+            stake_name: LwwRegister::new(stake.stake_name.clone(), dot),
+            complete: LwwRegister::new(stake.complete, dot),
+            dropped: LwwRegister::new(stake.dropped, dot),
+            note,
+            date_reviewed: LwwRegister::new(stake.date_reviewed, dot),
+            contexts: set(&stake.contexts),
+            tags: set(&stake.tags),
+            due: LwwRegister::new(stake.due, dot),
+            defer_until: LwwRegister::new(stake.defer_until, dot),
+            priority: LwwRegister::new(stake.priority, dot),
+            assignee: LwwRegister::new(stake.assignee.clone(), dot),
+            created_by: LwwRegister::new(stake.created_by.clone(), dot),
+            modified_by: LwwRegister::new(stake.modified_by.clone(), dot),
+        }
+    }
+
+    fn merge(&mut self, other: &Entry) {
+        // Global ids are unique, so kinds only differ in a damaged document. Either
+        // way, every replica settles on the same one.
+        self.kind = self.kind.min(other.kind);
+        self.date_created = self.date_created.min(other.date_created);
+        self.date_modified = self.date_modified.max(other.date_modified);
+        self.parent.merge(&other.parent);
+        self.stake_name.merge(&other.stake_name);
+        self.complete.merge(&other.complete);
+        self.dropped.merge(&other.dropped);
+        self.note.merge(&other.note);
+        self.date_reviewed.merge(&other.date_reviewed);
+        self.contexts.merge(&other.contexts);
+        self.tags.merge(&other.tags);
+        self.due.merge(&other.due);
+        self.defer_until.merge(&other.defer_until);
+        self.priority.merge(&other.priority);
+        self.assignee.merge(&other.assignee);
+        self.created_by.merge(&other.created_by);
+        self.modified_by.merge(&other.modified_by);
+    }
+
+    // Copies everything but the ids and parent onto `stake`.
+    fn fill<K: Kind>(&self, stake: &mut Stake<K>, global_id: GlobalId) {
+        let note = self.note.text();
+        stake.global_id = Some(global_id);
+        stake.stake_name = self.stake_name.get().clone();
+        stake.complete = *self.complete.get();
+        stake.dropped = *self.dropped.get();
+        stake.note = (!note.is_empty()).then_some(note);
+        stake.date_created = self.date_created;
+        stake.date_modified = self.date_modified;
+        stake.date_reviewed = *self.date_reviewed.get();
+        stake.contexts = self.contexts.iter().cloned().collect();
+        stake.tags = self.tags.iter().cloned().collect();
+        stake.due = *self.due.get();
+        stake.defer_until = *self.defer_until.get();
+        stake.priority = *self.priority.get();
+        stake.assignee = self.assignee.get().clone();
+        stake.created_by = self.created_by.get().clone();
+        stake.modified_by = self.modified_by.get().clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::{ProjectId, Task, TaskId};
+    use proptest::prelude::*;
+    use uuid::Uuid;
+
+    fn replica(n: u8) -> ReplicaId {
+        ReplicaId::from(Uuid::from_u128(n as u128))
+    }
+
+    // Edits the document's stakes through an MLW, as a user would.
+    fn edit(document: &mut Document, change: impl FnOnce(&mut MLW)) {
+        let mut mlw = document.to_mlw();
+        change(&mut mlw);
+        document.absorb(&mut mlw);
+    }
+
+    fn edit_task(document: &mut Document, name: &str, change: impl FnOnce(&mut Stake<Task>)) {
+        edit(document, |mlw| {
+            let mut task = mlw
+                .tasks()
+                .iter()
+                .find(|task| task.stake_name == name)
+                .expect("task exists")
+                .clone();
+            change(&mut task);
+            mlw.update_task(task).unwrap();
+        });
+    }
+
+    fn merged(a: &Document, b: &Document) -> Document {
+        let mut result = a.clone();
+        result.merge(b);
+        result
+    }
+
+    // Two replicas that share one project and one task.
+    fn shared() -> (Document, Document) {
+        let mut laptop = Document::new(replica(1));
+        edit(&mut laptop, |mlw| {
+            let project = mlw.new_project("Garden".to_string(), None, None);
+            mlw.new_task(
+                "Dig".to_string(),
+                Some(project.stake_id),
+                Some("Beds".to_string()),
+            );
+        });
+        let mut phone = Document::new(replica(2));
+        phone.merge(&laptop);
+        (laptop, phone)
+    }
+
+    #[test]
+    fn test_round_trip_through_mlw() {
+        let mut mlw = MLW::new();
+        mlw.add_member("ana".to_string());
+        mlw.set_author(Some("ana".to_string()));
+        let area = mlw.new_area("Home".to_string(), None);
+        let project = mlw.new_project("Garden".to_string(), Some(area.stake_id), None);
+        let mut task = mlw.new_task("Dig".to_string(), Some(project.stake_id), None);
+        task.note = Some("By the fence".to_string());
+        task.tags = vec!["outside".to_string()];
+        task.contexts = vec!["garden".to_string()];
+        task.priority = Some(Priority::High);
+        task.assignee = Some("ana".to_string());
+        mlw.update_task(task).unwrap();
+        mlw.new_task("Water".to_string(), None, None);
+
+        let mut document = Document::from_mlw(&mut mlw, replica(1));
+        assert_eq!(document.to_mlw(), mlw);
+        // Absorbing an unchanged MLW writes nothing.
+        let before = document.clock;
+        document.absorb(&mut document.to_mlw());
+        assert_eq!(document.clock, before);
+
+        let json = serde_json::to_string(&document).unwrap();
+        let loaded: Document = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, document);
+        assert_eq!(loaded.replica(), replica(1));
+    }
+
+    #[test]
+    fn test_concurrent_note_edits_keep_both() {
+        let (mut laptop, mut phone) = shared();
+        edit_task(&mut laptop, "Dig", |task| {
+            task.note = Some("Raised beds".to_string())
+        });
+        edit_task(&mut phone, "Dig", |task| {
+            task.note = Some("Beds, before rain".to_string())
+        });
+
+        let result = merged(&laptop, &phone);
+        assert_eq!(result, merged(&phone, &laptop));
+        let task = result.to_mlw().get_task_by_id(&TaskId::new(1)).cloned();
+        assert_eq!(
+            task.unwrap().note.as_deref(),
+            Some("Raised beds, before rain")
+        );
+    }
+
+    #[test]
+    fn test_concurrent_field_writes_go_to_the_later_write() {
+        let (mut laptop, mut phone) = shared();
+        edit_task(&mut laptop, "Dig", |task| {
+            task.stake_name = "Dig deep".to_string()
+        });
+        edit_task(&mut phone, "Dig", |task| {
+            task.tags = vec!["outside".to_string()];
+            task.stake_name = "Dig now".to_string();
+        });
+        // The phone wrote twice as often, so its Lamport time is later.
+        edit_task(&mut phone, "Dig now", |task| task.complete = true);
+        edit_task(&mut phone, "Dig now", |task| {
+            task.stake_name = "Dig today".to_string()
+        });
+
+        let mlw = merged(&laptop, &phone).to_mlw();
+        let task = mlw.get_task_by_id(&TaskId::new(1)).unwrap();
+        assert_eq!(task.stake_name, "Dig today");
+        assert!(task.complete);
+        assert_eq!(task.tags, vec!["outside".to_string()]);
+        assert_eq!(task.parent_id, Some(ProjectId::new(1)));
+    }
+
+    #[test]
+    fn test_concurrent_tag_add_survives_removal() {
+        let (mut laptop, mut phone) = shared();
+        edit_task(&mut laptop, "Dig", |task| {
+            task.tags = vec!["soon".to_string()]
+        });
+        phone.merge(&laptop);
+        edit_task(&mut laptop, "Dig", |task| task.tags.clear());
+        edit_task(&mut phone, "Dig", |task| {
+            task.tags = vec!["outside".to_string(), "soon".to_string()]
+        });
+
+        let mlw = merged(&laptop, &phone).to_mlw();
+        let task = mlw.get_task_by_id(&TaskId::new(1)).unwrap();
+        assert_eq!(task.tags, vec!["outside".to_string()]);
+    }
+
+    #[test]
+    fn test_team_members_added_apart_are_kept_and_removals_travel() {
+        let (mut laptop, mut phone) = shared();
+        edit(&mut laptop, |mlw| {
+            mlw.add_member("ana".to_string());
+        });
+        edit(&mut phone, |mlw| {
+            mlw.add_member("ben".to_string());
+        });
+        phone.merge(&laptop);
+        assert_eq!(phone.to_mlw().members(), ["ana", "ben"]);
+
+        edit(&mut phone, |mlw| {
+            mlw.remove_member("ana");
+        });
+        laptop.merge(&phone);
+        assert_eq!(laptop.to_mlw().members(), ["ben"]);
+        assert_eq!(laptop, phone);
+    }
+
+    #[test]
+    fn test_task_order_and_removal() {
+        let (mut laptop, mut phone) = shared();
+        edit(&mut laptop, |mlw| {
+            mlw.new_task("Water".to_string(), None, None);
+        });
+        phone.merge(&laptop);
+        let order = laptop.task_order();
+        let (dig, water) = (order[0], order[1]);
+
+        laptop.move_task(&water, 0).unwrap();
+        phone.move_task(&water, 7).unwrap();
+        edit(&mut phone, |mlw| {
+            mlw.new_task("Weed".to_string(), None, None);
+        });
+        let project = laptop
+            .to_mlw()
+            .get_project_by_id(&ProjectId::new(1))
+            .cloned();
+        laptop.remove(&project.unwrap().global_id.unwrap()).unwrap();
+        assert!(laptop.remove(&GlobalId::new()).is_err());
+        assert!(laptop.move_task(&GlobalId::new(), 0).is_err());
+
+        let result = merged(&laptop, &phone);
+        assert_eq!(result, merged(&phone, &laptop));
+        let order = result.task_order();
+        assert_eq!(order.len(), 3);
+        assert!(order.contains(&water) && order.contains(&dig));
+        let mlw = result.to_mlw();
+        let names: Vec<&str> = mlw.tasks().iter().map(|t| t.stake_name.as_str()).collect();
+        assert_eq!(names.len(), 3);
+        assert!(mlw.projects().is_empty());
+        assert!(mlw.tasks().iter().all(|task| task.parent_id.is_none()));
+        assert!(!result.contains(&project_id(&laptop)));
+    }
+
+    fn project_id(document: &Document) -> GlobalId {
+        *document
+            .stakes
+            .keys()
+            .find(|id| document.stakes[id].kind == StakeKind::Project)
+            .unwrap()
+    }
+
+    #[derive(Debug, Clone)]
+    enum Op {
+        AddTask(u8),
+        AddProject,
+        Rename(usize, u8),
+        Note(usize, String),
+        Tag(usize, u8),
+        Complete(usize),
+        Move(usize, usize),
+        Remove(usize),
+        Merge(usize),
+    }
+
+    fn op() -> impl Strategy<Value = (usize, Op)> {
+        let op = prop_oneof![
+            any::<u8>().prop_map(Op::AddTask),
+            Just(Op::AddProject),
+            (any::<usize>(), any::<u8>()).prop_map(|(i, n)| Op::Rename(i, n)),
+            (any::<usize>(), "[a-c ]{0,6}").prop_map(|(i, s)| Op::Note(i, s)),
+            (any::<usize>(), 0..3u8).prop_map(|(i, t)| Op::Tag(i, t)),
+            any::<usize>().prop_map(Op::Complete),
+            (any::<usize>(), any::<usize>()).prop_map(|(i, j)| Op::Move(i, j)),
+            any::<usize>().prop_map(Op::Remove),
+            (0..3usize).prop_map(Op::Merge),
+        ];
+        (0..3usize, op)
+    }
+
+    fn apply(document: &mut Document, op: Op) {
+        let tasks = document.task_order();
+        let pick = |i: usize| (!tasks.is_empty()).then(|| tasks[i % tasks.len()]);
+        let edit_picked = |document: &mut Document, i: usize, change: &dyn Fn(&mut Stake<Task>)| {
+            let Some(global_id) = pick(i) else { return };
+            edit(document, |mlw| {
+                let id = mlw.tasks().local_id(&global_id).unwrap();
+                let mut task = mlw.get_task_by_id(&id).unwrap().clone();
+                change(&mut task);
+                mlw.update_task(task).unwrap();
+            });
+        };
+        match op {
+            Op::AddTask(parent) => edit(document, |mlw| {
+                let projects = mlw.projects().len() as u32;
+                let parent = (projects > 0).then(|| ProjectId::new(parent as u32 % projects + 1));
+                mlw.new_task("Task".to_string(), parent, None);
+            }),
+            Op::AddProject => edit(document, |mlw| {
+                mlw.new_project("Project".to_string(), None, None);
+            }),
+            Op::Rename(i, n) => {
+                edit_picked(document, i, &|task| task.stake_name = format!("Task {}", n))
+            }
+            Op::Note(i, note) => edit_picked(document, i, &|task| task.note = Some(note.clone())),
+            Op::Tag(i, tag) => edit_picked(document, i, &|task| {
+                let tag = format!("t{}", tag);
+                match task.tags.iter().position(|t| *t == tag) {
+                    Some(at) => {
+                        task.tags.remove(at);
+                    }
+                    None => task.tags.push(tag),
+                }
+            }),
+            Op::Complete(i) => edit_picked(document, i, &|task| task.complete = !task.complete),
+            Op::Move(i, to) => {
+                if let Some(global_id) = pick(i) {
+                    document
+                        .move_task(&global_id, to % (tasks.len() + 1))
+                        .unwrap();
+                }
+            }
+            Op::Remove(i) => {
+                if let Some(global_id) = pick(i) {
+                    document.remove(&global_id).unwrap();
+                }
+            }
+            Op::Merge(_) => unreachable!("merges need the other replicas"),
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn replicas_converge_whatever_the_merge_order(
+            ops in prop::collection::vec(op(), 0..30),
+            order in Just(vec![0usize, 1, 2]).prop_shuffle(),
+        ) {
+            let mut replicas: Vec<Document> = (1..=3).map(|n| Document::new(replica(n))).collect();
+            for (at, op) in ops {
+                match op {
+                    Op::Merge(from) => {
+                        let other = replicas[from].clone();
+                        replicas[at].merge(&other);
+                    }
+                    op => apply(&mut replicas[at], op),
+                }
+            }
+            let mut forward = Document::new(replica(9));
+            for &i in &order {
+                forward.merge(&replicas[i]);
+            }
+            let mut backward = Document::new(replica(9));
+            for &i in order.iter().rev() {
+                backward.merge(&replicas[i]);
+            }
+            let pairwise = merged(&merged(&replicas[order[0]], &replicas[order[1]]), &replicas[order[2]]);
+            prop_assert_eq!(&forward, &backward);
+            prop_assert_eq!(&forward, &pairwise);
+            prop_assert_eq!(forward.to_mlw(), backward.to_mlw());
+            prop_assert_eq!(merged(&forward, &replicas[order[0]]), forward);
+        }
+    }
+}
@@ -0,0 +1,98 @@
+// A last-writer-wins register: one value and the dot of the write that set it. Of
+// two concurrent writes the one with the greater dot wins, so the later Lamport
+// time, then the greater replica id.
+
+use serde::{Deserialize, Serialize};
+
+use super::Dot;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LwwRegister<T> {
+    value: T,
+    written: Dot,
+}
+
+impl<T: Clone> LwwRegister<T> {
+    pub fn new(value: T, dot: Dot) -> Self {
+        LwwRegister {
+            value,
+            written: dot,
+        }
+    }
+
+    pub fn get(&self) -> &T {
+        &self.value
+    }
+
+    /// The dot of the write the value came from.
+    pub fn written(&self) -> Dot {
+        self.written
+    }
+
+    /// Writes `value`, unless a later write is already here.
+    pub fn set(&mut self, value: T, dot: Dot) {
+        if dot > self.written {
+            self.value = value;
+            self.written = dot;
+        }
+    }
+
+    pub fn merge(&mut self, other: &Self) {
+        self.set(other.value.clone(), other.written);
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+    use crate::mlw::sync::ReplicaId;
+    use proptest::prelude::*;
+    use uuid::Uuid;
+
+    /// The dot of write `counter` on test replica `replica`.
+    pub fn dot(counter: u64, replica: u8) -> Dot {
+        Dot {
+            counter,
+            replica: ReplicaId::from(Uuid::from_u128(replica as u128)),
+        }
+    }
+
+    #[test]
+    fn test_later_write_wins_and_ties_go_to_greater_replica() {
+        let mut register = LwwRegister::new("first", dot(1, 1));
+        register.set("second", dot(2, 1));
+        register.set("stale", dot(1, 2));
+        assert_eq!(*register.get(), "second");
+
+        let mut other = LwwRegister::new("other", dot(2, 2));
+        other.merge(&register);
+        register.merge(&LwwRegister::new("other", dot(2, 2)));
+        assert_eq!(*register.get(), "other");
+        assert_eq!(register, other);
+        assert_eq!(register.written(), dot(2, 2));
+    }
+
+    fn register() -> impl Strategy<Value = LwwRegister<u8>> {
+        // The value follows from the dot, as it does when every write is unique.
+        (1..20u64, 0..3u8).prop_map(|(counter, replica)| {
+            LwwRegister::new(counter as u8 * 3 + replica, dot(counter, replica))
+        })
+    }
+
+    fn merged(a: &LwwRegister<u8>, b: &LwwRegister<u8>) -> LwwRegister<u8> {
+        let mut result = a.clone();
+        result.merge(b);
+        result
+    }
+
+    proptest! {
+        #[test]
+        fn merge_is_commutative_associative_and_idempotent(
+            a in register(), b in register(), c in register()
+        ) {
+            prop_assert_eq!(merged(&a, &b), merged(&b, &a));
+            prop_assert_eq!(merged(&merged(&a, &b), &c), merged(&a, &merged(&b, &c)));
+            prop_assert_eq!(merged(&a, &a), a);
+        }
+    }
+}
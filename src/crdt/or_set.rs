@@ -0,0 +1,175 @@
+// An observed-remove set. Each add is tagged with its dot; a remove takes away only
+// the adds its replica had seen. So when one replica removes a tag while another
+// adds it again, the new add survives the merge.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use super::Dot;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "T: Serialize + Ord",
+    deserialize = "T: Deserialize<'de> + Ord"
+))]
+pub struct OrSet<T> {
+    // The live adds of each element. An element with none is not in the set.
+    adds: BTreeMap<T, BTreeSet<Dot>>,
+    // Every add that has been removed, so merges do not bring it back.
+    removed: BTreeSet<Dot>,
+}
+
+impl<T> Default for OrSet<T> {
+    fn default() -> Self {
+        OrSet {
+            adds: BTreeMap::new(),
+            removed: BTreeSet::new(),
+        }
+    }
+}
+
+impl<T: Ord + Clone> OrSet<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, value: T, dot: Dot) {
+        if !self.removed.contains(&dot) {
+            self.adds.entry(value).or_default().insert(dot);
+        }
+    }
+
+    /// Removes `value` as this replica sees it. Returns whether it was there.
+    pub fn remove(&mut self, value: &T) -> bool {
+        match self.adds.remove(value) {
+            Some(dots) => {
+                self.removed.extend(dots);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        self.adds.contains_key(value)
+    }
+
+    /// The elements, in order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.adds.keys()
+    }
+
+    pub fn len(&self) -> usize {
+        self.adds.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.adds.is_empty()
+    }
+
+    pub fn merge(&mut self, other: &Self) {
+        self.removed.extend(other.removed.iter().copied());
+        for (value, dots) in &other.adds {
+            self.adds
+                .entry(value.clone())
+                .or_default()
+                .extend(dots.iter().copied());
+        }
+        let removed = &self.removed;
+        self.adds.retain(|_, dots| {
+            dots.retain(|dot| !removed.contains(dot));
+            !dots.is_empty()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crdt::lww::tests::dot;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_concurrent_add_survives_remove() {
+        let mut laptop = OrSet::new();
+        laptop.add("errand", dot(1, 1));
+        let mut phone = laptop.clone();
+
+        assert!(laptop.remove(&"errand"));
+        assert!(!laptop.remove(&"errand"));
+        phone.add("errand", dot(2, 2));
+        phone.add("phone", dot(3, 2));
+
+        let mut merged = laptop.clone();
+        merged.merge(&phone);
+        assert_eq!(merged.iter().collect::<Vec<_>>(), vec![&"errand", &"phone"]);
+        phone.merge(&laptop);
+        assert_eq!(merged, phone);
+    }
+
+    #[test]
+    fn test_removed_add_does_not_come_back() {
+        let mut laptop = OrSet::new();
+        laptop.add("errand", dot(1, 1));
+        let phone = laptop.clone();
+        laptop.remove(&"errand");
+        laptop.merge(&phone);
+        assert!(!laptop.contains(&"errand"));
+        assert!(laptop.is_empty());
+    }
+
+    #[derive(Debug, Clone)]
+    enum Op {
+        Add(u8),
+        Remove(u8),
+        Merge(usize),
+    }
+
+    fn op() -> impl Strategy<Value = (usize, Op)> {
+        let op = prop_oneof![
+            (0..4u8).prop_map(Op::Add),
+            (0..4u8).prop_map(Op::Remove),
+            (0..3usize).prop_map(Op::Merge),
+        ];
+        (0..3usize, op)
+    }
+
+    proptest! {
+        #[test]
+        fn replicas_converge_whatever_the_merge_order(
+            ops in prop::collection::vec(op(), 0..40),
+            order in Just(vec![0usize, 1, 2]).prop_shuffle(),
+        ) {
+            let mut replicas = vec![OrSet::new(); 3];
+            let mut counters = [0u64; 3];
+            for (at, op) in ops {
+                match op {
+                    Op::Add(value) => {
+                        counters[at] += 1;
+                        replicas[at].add(value, dot(counters[at], at as u8));
+                    }
+                    Op::Remove(value) => {
+                        replicas[at].remove(&value);
+                    }
+                    Op::Merge(from) => {
+                        let other = replicas[from].clone();
+                        replicas[at].merge(&other);
+                    }
+                }
+            }
+            let mut forward = OrSet::new();
+            for &i in &order {
+                forward.merge(&replicas[i]);
+            }
+            let mut backward = OrSet::new();
+            for &i in order.iter().rev() {
+                backward.merge(&replicas[i]);
+            }
+            prop_assert_eq!(&forward, &backward);
+            let mut again = forward.clone();
+            again.merge(&replicas[order[0]]);
+            prop_assert_eq!(again, forward);
+        }
+    }
+}
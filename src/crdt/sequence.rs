@@ -0,0 +1,291 @@
+// A replicated sequence (RGA). Every element remembers the element it was inserted
+// after, its origin, and elements are never removed, only hidden. The order follows
+// from those links alone: each element comes after its origin, and elements with the
+// same origin come newest first. So two replicas typing into one note at once keep
+// both texts, each in one piece.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::Dot;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sequence<T> {
+    elements: BTreeMap<Dot, Element<T>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Element<T> {
+    origin: Option<Dot>,
+    value: T,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    hidden: bool,
+}
+
+impl<T> Default for Sequence<T> {
+    fn default() -> Self {
+        Sequence {
+            elements: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T: Clone + PartialEq> Sequence<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The visible elements, in order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.visible()
+            .into_iter()
+            .map(|dot| &self.elements[&dot].value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.elements.values().filter(|e| !e.hidden).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Inserts `value` so that it becomes the visible element at `index`, which may
+    /// be the length to append. `dot` must be newer than every element here.
+    pub fn insert(&mut self, index: usize, value: T, dot: Dot) {
+        let visible = self.visible();
+        assert!(index <= visible.len(), "insert index out of range");
+        let origin = index.checked_sub(1).map(|before| visible[before]);
+        self.elements.insert(
+            dot,
+            Element {
+                origin,
+                value,
+                hidden: false,
+            },
+        );
+    }
+
+    /// Hides the visible element at `index`.
+    pub fn remove(&mut self, index: usize) {
+        let dot = self.visible()[index];
+        self.elements
+            .get_mut(&dot)
+            .expect("visible elements exist")
+            .hidden = true;
+    }
+
+    /// Hides the first visible element equal to `value`, if any.
+    pub fn remove_value(&mut self, value: &T) -> bool {
+        let index = self.iter().position(|v| v == value);
+        match index {
+            Some(index) => {
+                self.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Makes the visible elements equal `values` with as few edits as possible: the
+    /// common start and end stay, and only what lies between is replaced. Each
+    /// insert takes a dot from `tick`.
+    pub fn replace(&mut self, values: &[T], mut tick: impl FnMut() -> Dot) {
+        let current: Vec<T> = self.iter().cloned().collect();
+        let prefix = current
+            .iter()
+            .zip(values)
+            .take_while(|(a, b)| a == b)
+            .count();
+        let suffix = current[prefix..]
+            .iter()
+            .rev()
+            .zip(values[prefix..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        for _ in prefix..current.len() - suffix {
+            self.remove(prefix);
+        }
+        for (offset, value) in values[prefix..values.len() - suffix].iter().enumerate() {
+            self.insert(prefix + offset, value.clone(), tick());
+        }
+    }
+
+    pub fn merge(&mut self, other: &Self) {
+        for (dot, element) in &other.elements {
+            self.elements
+                .entry(*dot)
+                .and_modify(|mine| mine.hidden |= element.hidden)
+                .or_insert_with(|| element.clone());
+        }
+    }
+
+    // The dots of the visible elements, in order: a depth-first walk of the origin
+    // links, newest sibling first.
+    fn visible(&self) -> Vec<Dot> {
+        let mut children: BTreeMap<Option<Dot>, Vec<Dot>> = BTreeMap::new();
+        for (dot, element) in &self.elements {
+            children.entry(element.origin).or_default().push(*dot);
+        }
+        let mut order = Vec::new();
+        // Siblings are pushed oldest first, so the newest is walked first.
+        let mut stack: Vec<Dot> = children.get(&None).cloned().unwrap_or_default();
+        while let Some(dot) = stack.pop() {
+            if !self.elements[&dot].hidden {
+                order.push(dot);
+            }
+            if let Some(after) = children.get(&Some(dot)) {
+                stack.extend(after);
+            }
+        }
+        order
+    }
+}
+
+impl Sequence<char> {
+    pub fn text(&self) -> String {
+        self.iter().collect()
+    }
+
+    /// Makes the text equal `text`, keeping what it shares with the current text.
+    pub fn set_text(&mut self, text: &str, tick: impl FnMut() -> Dot) {
+        let chars: Vec<char> = text.chars().collect();
+        self.replace(&chars, tick);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crdt::lww::tests::dot;
+    use proptest::prelude::*;
+
+    fn text(values: &str, replica: u8, first: u64) -> Sequence<char> {
+        let mut sequence = Sequence::new();
+        let mut counter = first;
+        sequence.set_text(values, || {
+            counter += 1;
+            dot(counter, replica)
+        });
+        sequence
+    }
+
+    #[test]
+    fn test_insert_and_remove_by_index() {
+        let mut sequence = Sequence::new();
+        sequence.insert(0, 'b', dot(1, 1));
+        sequence.insert(0, 'a', dot(2, 1));
+        sequence.insert(2, 'd', dot(3, 1));
+        sequence.insert(2, 'c', dot(4, 1));
+        assert_eq!(sequence.text(), "abcd");
+        sequence.remove(1);
+        assert!(sequence.remove_value(&'d'));
+        assert!(!sequence.remove_value(&'x'));
+        assert_eq!(sequence.text(), "ac");
+        assert_eq!(sequence.len(), 2);
+    }
+
+    #[test]
+    fn test_concurrent_typing_keeps_both_runs_whole() {
+        let base = text("Buy milk", 1, 0);
+        let mut laptop = base.clone();
+        let mut phone = base.clone();
+        let mut counter = 100;
+        laptop.set_text("Buy oat milk", || {
+            counter += 1;
+            dot(counter, 1)
+        });
+        let mut counter = 100;
+        phone.set_text("Buy milk and eggs", || {
+            counter += 1;
+            dot(counter, 2)
+        });
+
+        let mut merged = laptop.clone();
+        merged.merge(&phone);
+        assert_eq!(merged.text(), "Buy oat milk and eggs");
+        phone.merge(&laptop);
+        assert_eq!(merged, phone);
+    }
+
+    #[test]
+    fn test_replace_keeps_shared_start_and_end() {
+        let mut sequence = text("call the bank", 1, 0);
+        let before = sequence.elements.len();
+        let mut counter = 50;
+        sequence.set_text("call the post office", || {
+            counter += 1;
+            dot(counter, 1)
+        });
+        assert_eq!(sequence.text(), "call the post office");
+        // Only "post office" was new; the hidden "bank" stays as a tombstone.
+        assert_eq!(sequence.elements.len(), before + "post office".len());
+        sequence.set_text("", || unreachable!());
+        assert!(sequence.is_empty());
+    }
+
+    #[derive(Debug, Clone)]
+    enum Op {
+        Insert(usize, char),
+        Remove(usize),
+        Merge(usize),
+    }
+
+    fn op() -> impl Strategy<Value = (usize, Op)> {
+        let op = prop_oneof![
+            (any::<usize>(), prop::char::range('a', 'e')).prop_map(|(i, c)| Op::Insert(i, c)),
+            any::<usize>().prop_map(Op::Remove),
+            (0..3usize).prop_map(Op::Merge),
+        ];
+        (0..3usize, op)
+    }
+
+    proptest! {
+        #[test]
+        fn replicas_converge_whatever_the_merge_order(
+            ops in prop::collection::vec(op(), 0..60),
+            order in Just(vec![0usize, 1, 2]).prop_shuffle(),
+        ) {
+            let mut replicas = vec![Sequence::new(); 3];
+            // Lamport clocks, so each insert is newer than everything its replica saw.
+            let mut clocks = [0u64; 3];
+            for (at, op) in ops {
+                let sequence = &mut replicas[at];
+                match op {
+                    Op::Insert(index, value) => {
+                        clocks[at] += 1;
+                        let index = index % (sequence.len() + 1);
+                        sequence.insert(index, value, dot(clocks[at], at as u8));
+                    }
+                    Op::Remove(index) if !sequence.is_empty() => {
+                        let index = index % sequence.len();
+                        sequence.remove(index);
+                    }
+                    Op::Remove(_) => {}
+                    Op::Merge(from) => {
+                        let other = replicas[from].clone();
+                        replicas[at].merge(&other);
+                        clocks[at] = clocks[at].max(clocks[from]);
+                    }
+                }
+            }
+            let mut forward = Sequence::new();
+            for &i in &order {
+                forward.merge(&replicas[i]);
+            }
+            let mut backward = Sequence::new();
+            for &i in order.iter().rev() {
+                backward.merge(&replicas[i]);
+            }
+            prop_assert_eq!(forward.text(), backward.text());
+            prop_assert_eq!(&forward, &backward);
+            // Merging any replica into the result changes nothing.
+            for replica in &replicas {
+                let mut again = replica.clone();
+                again.merge(&forward);
+                prop_assert_eq!(again.text(), forward.text());
+            }
+        }
+    }
+}
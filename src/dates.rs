@@ -0,0 +1,442 @@
+// Date expressions as people type them: "tomorrow", "next friday", "in 3 days",
+// "end of month", "2w", "oct 20", "2026-11-01 9am".
+//
+// Everything is evaluated against a `DateContext`: a fixed "now", the user's timezone
+// and the first day of their week. Nothing reads the system clock, so results are
+// the same on every run. Expressions name a day in the user's zone; the result is the
+// start of that day (or the given time) converted to UTC for storage.
+
+use std::fmt;
+
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+
+/// Why an expression could not be read as a date.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DateError {
+    pub input: String,
+}
+
+impl fmt::Display for DateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cannot read '{}' as a date", self.input)
+    }
+}
+
+impl std::error::Error for DateError {}
+
+/// The moment and place date expressions are read relative to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateContext {
+    pub now: DateTime<Utc>,
+    pub timezone: Tz,
+    pub week_start: Weekday,
+}
+
+impl DateContext {
+    /// A context in `timezone` with weeks starting on Monday.
+    pub fn new(now: DateTime<Utc>, timezone: Tz) -> Self {
+        DateContext {
+            now,
+            timezone,
+            week_start: Weekday::Mon,
+        }
+    }
+
+    pub fn with_week_start(self, week_start: Weekday) -> Self {
+        DateContext { week_start, ..self }
+    }
+
+    /// The user's current calendar day.
+    pub fn today(&self) -> NaiveDate {
+        self.now.with_timezone(&self.timezone).date_naive()
+    }
+
+    /// The first day of the week containing `date`.
+    pub fn week_of(&self, date: NaiveDate) -> NaiveDate {
+        let back = days_until(self.week_start, date.weekday());
+        date - Duration::days(back)
+    }
+
+    /// `time` on `date` in the user's zone, as UTC. Times skipped by a clock change
+    /// move forward an hour at a time until they exist, so a skipped day starts
+    /// when the next one does; repeated times take the earlier instant.
+    pub fn at(&self, date: NaiveDate, time: NaiveTime) -> DateTime<Utc> {
+        let local = date.and_time(time);
+        // No zone has skipped more than a day; past that, read the time as UTC.
+        (0..=48)
+            .find_map(|hours| {
+                let shifted = local.checked_add_signed(Duration::hours(hours))?;
+                self.timezone.from_local_datetime(&shifted).earliest()
+            })
+            .map(|instant| instant.with_timezone(&Utc))
+            .unwrap_or_else(|| Utc.from_utc_datetime(&local))
+    }
+
+    /// The instant `date` begins in the user's zone.
+    pub fn start_of_day(&self, date: NaiveDate) -> DateTime<Utc> {
+        self.at(date, NaiveTime::MIN)
+    }
+
+    /// Reads a date expression. See the module comment for the accepted forms.
+    pub fn parse(&self, input: &str) -> Result<DateTime<Utc>, DateError> {
+        let error = || DateError {
+            input: input.to_string(),
+        };
+        let lowered = input.trim().to_lowercase();
+        let mut words: Vec<&str> = lowered.split_whitespace().collect();
+        if words.is_empty() {
+            return Err(error());
+        }
+
+        let time = match words.last().and_then(|word| parse_time(word)) {
+            Some(time) => {
+                words.pop();
+                if words.last() == Some(&"at") {
+                    words.pop();
+                }
+                time
+            }
+            None => NaiveTime::MIN,
+        };
+        let day = if words.is_empty() {
+            Some(self.today())
+        } else {
+            self.parse_day(&words)
+        };
+        day.map(|day| self.at(day, time)).ok_or_else(error)
+    }
+
+    fn parse_day(&self, words: &[&str]) -> Option<NaiveDate> {
+        let today = self.today();
+        match words {
+            ["today"] | ["now"] => Some(today),
+            ["tomorrow"] | ["tmr"] | ["tmrw"] => Some(today + Duration::days(1)),
+            ["yesterday"] => Some(today - Duration::days(1)),
+            [day] | ["this", day] if day.parse::<Weekday>().is_ok() => {
+                let weekday = day.parse().ok()?;
+                Some(today + Duration::days(days_until(today.weekday(), weekday)))
+            }
+            ["next", day] if day.parse::<Weekday>().is_ok() => {
+                let weekday = day.parse().ok()?;
+                let ahead = match days_until(today.weekday(), weekday) {
+                    0 => 7,
+                    ahead => ahead,
+                };
+                Some(today + Duration::days(ahead))
+            }
+            ["next", "week"] => Some(self.week_of(today) + Duration::days(7)),
+            ["next", "month"] => first_of_month(today).checked_add_months(Months::new(1)),
+            ["next", "year"] => NaiveDate::from_ymd_opt(today.year() + 1, 1, 1),
+            ["end", "of", "week"] | ["eow"] => Some(self.week_of(today) + Duration::days(6)),
+            ["end", "of", "month"] | ["eom"] => first_of_month(today)
+                .checked_add_months(Months::new(1))?
+                .pred_opt(),
+            ["end", "of", "year"] | ["eoy"] => NaiveDate::from_ymd_opt(today.year(), 12, 31),
+            ["in", amount, unit] => offset(today, parse_amount(amount)?, unit),
+            [amount, unit] => amount
+                .parse()
+                .ok()
+                .and_then(|amount| offset(today, amount, unit))
+                .or_else(|| parse_explicit(today, words)),
+            [compact] => {
+                parse_compact(today, compact).or_else(|| parse_explicit(today, &[compact]))
+            }
+            explicit => parse_explicit(today, explicit),
+        }
+    }
+}
+
+// Days from `from` forward to the next `to`, 0 when they are the same day.
+fn days_until(from: Weekday, to: Weekday) -> i64 {
+    i64::from((to.num_days_from_monday() + 7 - from.num_days_from_monday()) % 7)
+}
+
+fn first_of_month(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).expect("every month has a first day")
+}
+
+fn parse_amount(word: &str) -> Option<u32> {
+    match word {
+        "a" | "an" | "one" => Some(1),
+        word => word.parse().ok(),
+    }
+}
+
+fn offset(from: NaiveDate, amount: u32, unit: &str) -> Option<NaiveDate> {
+    match unit {
+        "d" | "day" | "days" => from.checked_add_signed(Duration::days(amount.into())),
+        "w" | "wk" | "week" | "weeks" => from.checked_add_signed(Duration::weeks(amount.into())),
+        // Month and year steps clamp to the end of shorter months.
+        "m" | "mo" | "month" | "months" => from.checked_add_months(Months::new(amount)),
+        "y" | "yr" | "year" | "years" => {
+            from.checked_add_months(Months::new(amount.checked_mul(12)?))
+        }
+        _ => None,
+    }
+}
+
+// "3d", "2w", "+1m", "1y".
+fn parse_compact(today: NaiveDate, word: &str) -> Option<NaiveDate> {
+    let word = word.strip_prefix('+').unwrap_or(word);
+    let split = word.find(|c: char| !c.is_ascii_digit())?;
+    let (amount, unit) = word.split_at(split);
+    offset(today, amount.parse().ok()?, unit)
+}
+
+// "9am", "9:30pm", "17:00", "noon", "midnight".
+fn parse_time(word: &str) -> Option<NaiveTime> {
+    match word {
+        "noon" => return NaiveTime::from_hms_opt(12, 0, 0),
+        "midnight" => return Some(NaiveTime::MIN),
+        _ => {}
+    }
+    let (clock, pm) = match (word.strip_suffix("am"), word.strip_suffix("pm")) {
+        (Some(clock), _) => (clock, Some(false)),
+        (_, Some(clock)) => (clock, Some(true)),
+        _ => (word, None),
+    };
+    let (hour, minute) = match clock.split_once(':') {
+        Some((hour, minute)) if minute.len() == 2 => (hour.parse().ok()?, minute.parse().ok()?),
+        // A bare number is only a time with am/pm, so "3" stays an amount.
+        None if pm.is_some() => (clock.parse().ok()?, 0),
+        _ => return None,
+    };
+    let hour = match pm {
+        Some(_) if !(1..=12).contains(&hour) => return None,
+        Some(true) => hour % 12 + 12,
+        Some(false) => hour % 12,
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+fn parse_month(word: &str) -> Option<u32> {
+    const MONTHS: [&str; 12] = [
+        "january",
+        "february",
+        "march",
+        "april",
+        "may",
+        "june",
+        "july",
+        "august",
+        "september",
+        "october",
+        "november",
+        "december",
+    ];
+    let word = word.trim_end_matches('.');
+    if word.len() < 3 {
+        return None;
+    }
+    MONTHS
+        .iter()
+        .position(|month| month.starts_with(word))
+        .map(|index| index as u32 + 1)
+}
+
+fn parse_day_of_month(word: &str) -> Option<u32> {
+    let digits = word.trim_end_matches(['s', 't', 'n', 'd', 'r', 'h', ',']);
+    digits.parse().ok().filter(|day| (1..=31).contains(day))
+}
+
+// ISO dates, and "oct 20", "20 october", "oct 20 2027". Without a year, the next
+// time that day comes round (today included).
+fn parse_explicit(today: NaiveDate, words: &[&str]) -> Option<NaiveDate> {
+    if let [word] = words {
+        return NaiveDate::parse_from_str(word, "%Y-%m-%d")
+            .or_else(|_| NaiveDate::parse_from_str(word, "%Y/%m/%d"))
+            .ok();
+    }
+    let (month, day, year) = match words {
+        [first, second] | [first, second, _] => match parse_month(first) {
+            Some(month) => (month, parse_day_of_month(second)?, words.get(2)),
+            None => (
+                parse_month(second)?,
+                parse_day_of_month(first)?,
+                words.get(2),
+            ),
+        },
+        _ => return None,
+    };
+    match year {
+        Some(year) => NaiveDate::from_ymd_opt(year.parse().ok()?, month, day),
+        None => {
+            let this_year = NaiveDate::from_ymd_opt(today.year(), month, day);
+            match this_year {
+                Some(date) if date >= today => Some(date),
+                _ => NaiveDate::from_ymd_opt(today.year() + 1, month, day),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Wednesday 14 October 2026, mid-afternoon in UTC.
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 10, 14, 15, 30, 0).unwrap()
+    }
+
+    fn utc() -> DateContext {
+        DateContext::new(now(), Tz::UTC)
+    }
+
+    fn day(y: i32, m: u32, d: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, 0, 0, 0).unwrap()
+    }
+
+    fn parse(input: &str) -> DateTime<Utc> {
+        utc()
+            .parse(input)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    #[test]
+    fn test_relative_days() {
+        assert_eq!(parse("today"), day(2026, 10, 14));
+        assert_eq!(parse("Tomorrow"), day(2026, 10, 15));
+        assert_eq!(parse("yesterday"), day(2026, 10, 13));
+    }
+
+    #[test]
+    fn test_weekdays() {
+        assert_eq!(parse("fri"), day(2026, 10, 16));
+        assert_eq!(parse("wednesday"), day(2026, 10, 14));
+        assert_eq!(parse("this monday"), day(2026, 10, 19));
+        assert_eq!(parse("next friday"), day(2026, 10, 16));
+        assert_eq!(parse("next wed"), day(2026, 10, 21));
+    }
+
+    #[test]
+    fn test_offsets() {
+        assert_eq!(parse("in 3 days"), day(2026, 10, 17));
+        assert_eq!(parse("in a week"), day(2026, 10, 21));
+        assert_eq!(parse("2 weeks"), day(2026, 10, 28));
+        assert_eq!(parse("2w"), day(2026, 10, 28));
+        assert_eq!(parse("+10d"), day(2026, 10, 24));
+        assert_eq!(parse("1m"), day(2026, 11, 14));
+        assert_eq!(parse("in 1 year"), day(2027, 10, 14));
+    }
+
+    #[test]
+    fn test_month_offsets_clamp_to_month_end() {
+        let context =
+            DateContext::new(Utc.with_ymd_and_hms(2026, 1, 31, 9, 0, 0).unwrap(), Tz::UTC);
+        assert_eq!(context.parse("1m").unwrap(), day(2026, 2, 28));
+    }
+
+    #[test]
+    fn test_period_boundaries() {
+        assert_eq!(parse("end of month"), day(2026, 10, 31));
+        assert_eq!(parse("eom"), day(2026, 10, 31));
+        assert_eq!(parse("end of week"), day(2026, 10, 18));
+        assert_eq!(parse("end of year"), day(2026, 12, 31));
+        assert_eq!(parse("next week"), day(2026, 10, 19));
+        assert_eq!(parse("next month"), day(2026, 11, 1));
+        assert_eq!(parse("next year"), day(2027, 1, 1));
+    }
+
+    #[test]
+    fn test_week_start_moves_week_boundaries() {
+        let sunday = utc().with_week_start(Weekday::Sun);
+        assert_eq!(sunday.parse("end of week").unwrap(), day(2026, 10, 17));
+        assert_eq!(sunday.parse("next week").unwrap(), day(2026, 10, 18));
+    }
+
+    #[test]
+    fn test_explicit_dates() {
+        assert_eq!(parse("2026-11-01"), day(2026, 11, 1));
+        assert_eq!(parse("2027/02/03"), day(2027, 2, 3));
+        assert_eq!(parse("oct 20"), day(2026, 10, 20));
+        assert_eq!(parse("20th October"), day(2026, 10, 20));
+        assert_eq!(parse("Jan 5"), day(2027, 1, 5));
+        assert_eq!(parse("march 3 2028"), day(2028, 3, 3));
+    }
+
+    #[test]
+    fn test_times() {
+        let at = |h, m| Utc.with_ymd_and_hms(2026, 10, 15, h, m, 0).unwrap();
+        assert_eq!(parse("tomorrow 9am"), at(9, 0));
+        assert_eq!(parse("tomorrow at 5:30pm"), at(17, 30));
+        assert_eq!(parse("tomorrow 17:45"), at(17, 45));
+        assert_eq!(parse("tomorrow noon"), at(12, 0));
+        assert_eq!(parse("12am"), day(2026, 10, 14));
+        assert_eq!(
+            parse("5pm"),
+            Utc.with_ymd_and_hms(2026, 10, 14, 17, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_timezone_decides_which_day_is_today() {
+        // 15:30 UTC is already 04:30 on Thursday in Auckland (UTC+13).
+        let auckland = DateContext::new(now(), chrono_tz::Pacific::Auckland);
+        assert_eq!(
+            auckland.today(),
+            NaiveDate::from_ymd_opt(2026, 10, 15).unwrap()
+        );
+        assert_eq!(
+            auckland.parse("today").unwrap(),
+            Utc.with_ymd_and_hms(2026, 10, 14, 11, 0, 0).unwrap()
+        );
+
+        let los_angeles = DateContext::new(now(), chrono_tz::America::Los_Angeles);
+        assert_eq!(
+            los_angeles.parse("tomorrow 9am").unwrap(),
+            Utc.with_ymd_and_hms(2026, 10, 15, 16, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_dst_gap_moves_forward() {
+        // Clocks in New York jump from 02:00 to 03:00 on 8 March 2026.
+        let context = DateContext::new(now(), chrono_tz::America::New_York);
+        let date = NaiveDate::from_ymd_opt(2026, 3, 8).unwrap();
+        assert_eq!(
+            context.at(date, NaiveTime::from_hms_opt(2, 30, 0).unwrap()),
+            Utc.with_ymd_and_hms(2026, 3, 8, 7, 30, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_skipped_day_starts_with_the_next_one() {
+        // Samoa skipped 30 December 2011, going from 29 December straight to the 31st.
+        let context = DateContext::new(now(), chrono_tz::Pacific::Apia);
+        let date = NaiveDate::from_ymd_opt(2011, 12, 30).unwrap();
+        assert_eq!(
+            context.start_of_day(date),
+            Utc.with_ymd_and_hms(2011, 12, 30, 10, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_rejects_nonsense() {
+        for input in [
+            "",
+            "someday",
+            "in x days",
+            "3 parsecs",
+            "13pm",
+            "feb 30 2026",
+            "25:00",
+            "in 400000000 years",
+            "400000000y",
+        ] {
+            assert_eq!(
+                utc().parse(input),
+                Err(DateError {
+                    input: input.to_string()
+                }),
+                "{input}"
+            );
+        }
+        assert_eq!(
+            utc().parse("later").unwrap_err().to_string(),
+            "cannot read 'later' as a date"
+        );
+    }
+}
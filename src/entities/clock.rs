@@ -0,0 +1,109 @@
+use chrono::{DateTime, Duration, Utc};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// Where stakes get the current time from. Production code uses `SystemClock`;
+/// tests use `FakeClock` to control time exactly.
+pub trait Clock: fmt::Debug + Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The real wall clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that only moves when told to. Clones share the same time, so a test can
+/// keep one handle and advance the clock an MLW instance is using.
+#[derive(Debug, Clone)]
+pub struct FakeClock {
+    now: Arc<Mutex<DateTime<Utc>>>,
+}
+
+impl FakeClock {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        FakeClock {
+            now: Arc::new(Mutex::new(now)),
+        }
+    }
+
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.now.lock().expect("fake clock lock poisoned") = now;
+    }
+
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock().expect("fake clock lock poisoned") += by;
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().expect("fake clock lock poisoned")
+    }
+}
+
+/// A cheaply cloned handle to a clock, as held by MLW and its collections.
+/// Defaults to the system clock.
+#[derive(Debug, Clone)]
+pub struct SharedClock(Arc<dyn Clock>);
+
+impl SharedClock {
+    pub fn new(clock: impl Clock + 'static) -> Self {
+        SharedClock(Arc::new(clock))
+    }
+}
+
+impl Default for SharedClock {
+    fn default() -> Self {
+        SharedClock::new(SystemClock)
+    }
+}
+
+impl Clock for SharedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0.now()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn start() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 10, 14, 9, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_fake_clock_stands_still_until_moved() {
+        let clock = FakeClock::new(start());
+        assert_eq!(clock.now(), start());
+        assert_eq!(clock.now(), start());
+
+        clock.advance(Duration::minutes(90));
+        assert_eq!(clock.now(), start() + Duration::minutes(90));
+
+        clock.set(start());
+        assert_eq!(clock.now(), start());
+    }
+
+    #[test]
+    fn test_fake_clock_clones_share_time() {
+        let clock = FakeClock::new(start());
+        let shared = SharedClock::new(clock.clone());
+        clock.advance(Duration::days(1));
+        assert_eq!(shared.now(), start() + Duration::days(1));
+    }
+
+    #[test]
+    fn test_system_clock_is_the_default() {
+        let before = Utc::now();
+        let now = SharedClock::default().now();
+        assert!(before <= now && now <= Utc::now());
+    }
+}
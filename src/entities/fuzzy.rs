@@ -0,0 +1,182 @@
+// Fuzzy matching of a typed query against stake names.
+//
+// Scores fall in (0, 1]. Tiers are kept apart so that an exact or substring hit
+// always outranks a scattered subsequence match, which in turn outranks a match
+// that needed typo correction.
+
+const SUBSTRING_SCORE: f64 = 0.9;
+const SUBSEQUENCE_BASE: f64 = 0.4;
+const TYPO_BASE: f64 = 0.1;
+
+/// Scores how well `query` matches `candidate`, or `None` if it does not match at all.
+///
+/// Tried in order: exact match, substring, in-order subsequence (like a command
+/// palette, rewarding consecutive and word-start characters), and finally
+/// per-word edit distance so that misspellings such as "fianncial" still match.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<f64> {
+    let query = query.trim().to_lowercase();
+    let candidate = candidate.to_lowercase();
+    if query.is_empty() {
+        return None;
+    }
+
+    if candidate == query {
+        return Some(1.0);
+    }
+    if candidate.starts_with(&query) {
+        return Some(SUBSTRING_SCORE + 0.05);
+    }
+    if candidate.contains(&query) {
+        return Some(SUBSTRING_SCORE);
+    }
+    if let Some(quality) = subsequence_quality(&query, &candidate) {
+        return Some(SUBSEQUENCE_BASE + (SUBSTRING_SCORE - SUBSEQUENCE_BASE) * quality);
+    }
+    typo_quality(&query, &candidate)
+        .map(|quality| TYPO_BASE + (SUBSEQUENCE_BASE - TYPO_BASE) * quality)
+}
+
+// Greedy left-to-right subsequence match. Each matched character earns a point,
+// plus a point for following the previous match directly and a point for starting
+// a word. Returns the fraction of the best possible score.
+fn subsequence_quality(query: &str, candidate: &str) -> Option<f64> {
+    let needle: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).collect();
+    let haystack: Vec<char> = candidate.chars().collect();
+
+    let mut points = 0usize;
+    let mut position = 0usize;
+    let mut previous_match: Option<usize> = None;
+    for wanted in &needle {
+        let offset = haystack[position..].iter().position(|c| c == wanted)?;
+        let index = position + offset;
+        points += 1;
+        if previous_match.is_some_and(|previous| previous + 1 == index) {
+            points += 1;
+        }
+        if index == 0 || !haystack[index - 1].is_alphanumeric() {
+            points += 1;
+        }
+        previous_match = Some(index);
+        position = index + 1;
+    }
+
+    Some(points as f64 / (3 * needle.len()) as f64)
+}
+
+// Every query word must be within a few edits of some candidate word.
+// Returns 1 - (total edits / total query characters).
+fn typo_quality(query: &str, candidate: &str) -> Option<f64> {
+    let candidate_words: Vec<&str> = candidate.split_whitespace().collect();
+    let mut total_edits = 0usize;
+    let mut total_chars = 0usize;
+
+    for word in query.split_whitespace() {
+        let length = word.chars().count();
+        let allowed = match length {
+            0..=2 => 0,
+            3..=5 => 1,
+            6..=9 => 2,
+            _ => 3,
+        };
+        let best = candidate_words
+            .iter()
+            .map(|candidate_word| edit_distance(word, candidate_word))
+            .min()?;
+        if best > allowed {
+            return None;
+        }
+        total_edits += best;
+        total_chars += length;
+    }
+
+    if total_chars == 0 {
+        return None;
+    }
+    Some(1.0 - total_edits as f64 / total_chars as f64)
+}
+
+/// Optimal string alignment distance: insertions, deletions, substitutions and
+/// transpositions of adjacent characters each cost one edit.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_distance_counts_basic_edits() {
+        assert_eq!(edit_distance("kitten", "kitten"), 0);
+        assert_eq!(edit_distance("kitten", "sitten"), 1);
+        assert_eq!(edit_distance("kitten", "kittens"), 1);
+        assert_eq!(edit_distance("kitten", "kiten"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_edit_distance_counts_transposition_once() {
+        assert_eq!(edit_distance("financail", "financial"), 1);
+    }
+
+    #[test]
+    fn test_fuzzy_score_exact_beats_substring() {
+        let exact = fuzzy_score("financial", "Financial").unwrap();
+        let prefix = fuzzy_score("financial", "Financial Management").unwrap();
+        let substring = fuzzy_score("management", "Financial Management").unwrap();
+        assert!(exact > prefix);
+        assert!(prefix > substring);
+    }
+
+    #[test]
+    fn test_fuzzy_score_matches_missing_letter_as_subsequence() {
+        let score = fuzzy_score("finacial", "Financial Management");
+        assert!(
+            score.is_some(),
+            "'finacial' should match 'Financial Management'"
+        );
+        assert!(score.unwrap() < SUBSTRING_SCORE);
+    }
+
+    #[test]
+    fn test_fuzzy_score_prefers_compact_subsequence() {
+        let compact = fuzzy_score("fm", "Financial Management").unwrap();
+        let scattered = fuzzy_score("fm", "Free time for me").unwrap();
+        assert!(compact > scattered);
+    }
+
+    #[test]
+    fn test_fuzzy_score_tolerates_transposed_letters() {
+        let score = fuzzy_score("financail", "Financial Management");
+        assert!(score.is_some(), "a transposition should still match");
+        assert!(score.unwrap() < SUBSEQUENCE_BASE);
+    }
+
+    #[test]
+    fn test_fuzzy_score_rejects_unrelated_text() {
+        assert_eq!(fuzzy_score("garden", "Financial Management"), None);
+        assert_eq!(fuzzy_score("   ", "Anything"), None);
+    }
+}
@@ -0,0 +1,94 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Globally unique stake identifier, safe to create on several machines at once.
+///
+/// A UUIDv7, so ids also sort by creation time. The numeric `StakeId` stays the
+/// short local handle; `StakesCollection` maps between the two.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(transparent)]
+pub struct GlobalId(Uuid);
+
+impl GlobalId {
+    pub fn new() -> Self {
+        GlobalId(Uuid::now_v7())
+    }
+
+    pub fn as_uuid(&self) -> &Uuid {
+        &self.0
+    }
+}
+
+impl Default for GlobalId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<Uuid> for GlobalId {
+    fn from(uuid: Uuid) -> Self {
+        GlobalId(uuid)
+    }
+}
+
+impl fmt::Display for GlobalId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.hyphenated().fmt(f)
+    }
+}
+
+impl FromStr for GlobalId {
+    type Err = uuid::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Uuid::parse_str(s.trim()).map(GlobalId)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_global_id_new_is_unique_and_v7() {
+        let a = GlobalId::new();
+        let b = GlobalId::new();
+        assert_ne!(a, b);
+        assert_eq!(a.as_uuid().get_version_num(), 7);
+    }
+
+    #[test]
+    fn test_global_id_sorts_by_creation() {
+        let ids: Vec<GlobalId> = (0..100).map(|_| GlobalId::new()).collect();
+        let mut sorted = ids.clone();
+        sorted.sort();
+        assert_eq!(ids, sorted);
+    }
+
+    #[test]
+    fn test_global_id_display_parses_back() {
+        let id = GlobalId::new();
+        let text = id.to_string();
+        assert_eq!(text.len(), 36);
+        assert_eq!(text.parse::<GlobalId>().unwrap(), id);
+    }
+
+    #[test]
+    fn test_global_id_rejects_garbage() {
+        assert!("not-an-id".parse::<GlobalId>().is_err());
+        assert!("42".parse::<GlobalId>().is_err());
+    }
+
+    #[test]
+    fn test_global_id_serializes_as_string() {
+        let id = GlobalId::new();
+        let json = serde_json::to_value(id).unwrap();
+        assert_eq!(json, serde_json::Value::String(id.to_string()));
+        assert_eq!(serde_json::from_value::<GlobalId>(json).unwrap(), id);
+    }
+}
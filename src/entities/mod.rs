@@ -1,4 +1,14 @@
+pub mod clock;
+pub mod fuzzy;
+pub mod global_id;
+pub mod search_index;
 pub mod stake;
 pub mod stakes_collection;
-pub use stake::{Stake, StakeId};
+pub use clock::{Clock, FakeClock, SharedClock, SystemClock};
+pub use global_id::GlobalId;
+pub use search_index::SearchHit;
+pub use stake::{
+    Area, AreaId, Kind, NoParent, Priority, Project, ProjectId, Stake, StakeError, StakeId,
+    StakeKind, StakeRef, Task, TaskId,
+};
 pub use stakes_collection::StakesCollection;
@@ -0,0 +1,382 @@
+use std::collections::{BTreeMap, HashMap};
+
+use super::stake::{Kind, Stake, StakeId};
+
+// Name matches count for more than note matches when ranking results.
+const NAME_WEIGHT: f64 = 3.0;
+const NOTE_WEIGHT: f64 = 1.0;
+
+/// A single ranked result returned by `StakesCollection::search`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchHit<'a, K: Kind> {
+    pub stake: &'a Stake<K>,
+    pub score: f64,
+}
+
+// Token positions for one stake, split by the field they were found in.
+#[derive(Debug, Clone, Default)]
+struct Occurrences {
+    name: Vec<usize>,
+    note: Vec<usize>,
+}
+
+// One clause of a parsed query. Every clause must match for a stake to be returned.
+#[derive(Debug, Clone, PartialEq)]
+enum Clause {
+    Term(String),
+    Prefix(String),
+    Phrase(Vec<String>),
+}
+
+/// Inverted index over `stake_name` and `note`, kept in sync by `StakesCollection`.
+#[derive(Debug, Clone)]
+pub struct SearchIndex<K: Kind> {
+    // token -> stake -> positions. A BTreeMap so prefix queries are a range scan.
+    postings: BTreeMap<String, HashMap<StakeId<K>, Occurrences>>,
+    // stake -> distinct tokens it was indexed under, so it can be removed cheaply.
+    indexed_terms: HashMap<StakeId<K>, Vec<String>>,
+}
+
+impl<K: Kind> Default for SearchIndex<K> {
+    fn default() -> Self {
+        SearchIndex {
+            postings: BTreeMap::new(),
+            indexed_terms: HashMap::new(),
+        }
+    }
+}
+
+impl<K: Kind> SearchIndex<K> {
+    pub fn new() -> Self {
+        SearchIndex::default()
+    }
+
+    /// Indexes a stake, replacing any previous entry for the same id.
+    pub fn insert(&mut self, stake: &Stake<K>) {
+        self.remove(&stake.stake_id);
+
+        let mut terms: Vec<String> = Vec::new();
+        let fields = [
+            (tokenize(&stake.stake_name), true),
+            (
+                stake.note.as_deref().map(tokenize).unwrap_or_default(),
+                false,
+            ),
+        ];
+        for (tokens, is_name) in fields {
+            for (position, token) in tokens.into_iter().enumerate() {
+                let occurrences = self
+                    .postings
+                    .entry(token.clone())
+                    .or_default()
+                    .entry(stake.stake_id.clone())
+                    .or_default();
+                if is_name {
+                    occurrences.name.push(position);
+                } else {
+                    occurrences.note.push(position);
+                }
+                if !terms.contains(&token) {
+                    terms.push(token);
+                }
+            }
+        }
+        self.indexed_terms.insert(stake.stake_id.clone(), terms);
+    }
+
+    /// Removes every posting for the given stake id. Unknown ids are ignored.
+    pub fn remove(&mut self, id: &StakeId<K>) {
+        let Some(terms) = self.indexed_terms.remove(id) else {
+            return;
+        };
+        for term in terms {
+            if let Some(docs) = self.postings.get_mut(&term) {
+                docs.remove(id);
+                if docs.is_empty() {
+                    self.postings.remove(&term);
+                }
+            }
+        }
+    }
+
+    /// Runs a query and returns matching stake ids with their relevance scores, unsorted.
+    ///
+    /// Whitespace separated words must all match (AND). A word ending in `*` matches any
+    /// token starting with it, and text inside double quotes must appear as a phrase.
+    pub fn search(&self, query: &str) -> Vec<(StakeId<K>, f64)> {
+        let clauses = parse_query(query);
+        if clauses.is_empty() {
+            return Vec::new();
+        }
+
+        let mut totals: Option<HashMap<StakeId<K>, f64>> = None;
+        for clause in &clauses {
+            let scores = self.score_clause(clause);
+            totals = Some(match totals {
+                None => scores,
+                Some(previous) => previous
+                    .into_iter()
+                    .filter_map(|(id, score)| scores.get(&id).map(|extra| (id, score + extra)))
+                    .collect(),
+            });
+            if totals.as_ref().is_some_and(|t| t.is_empty()) {
+                break;
+            }
+        }
+        totals.unwrap_or_default().into_iter().collect()
+    }
+
+    fn document_count(&self) -> usize {
+        self.indexed_terms.len()
+    }
+
+    fn idf(&self, docs_with_term: usize) -> f64 {
+        (1.0 + self.document_count() as f64 / docs_with_term as f64).ln()
+    }
+
+    fn score_term(
+        &self,
+        docs: &HashMap<StakeId<K>, Occurrences>,
+        scores: &mut HashMap<StakeId<K>, f64>,
+    ) {
+        let idf = self.idf(docs.len());
+        for (id, occurrences) in docs {
+            let weight = NAME_WEIGHT * occurrences.name.len() as f64
+                + NOTE_WEIGHT * occurrences.note.len() as f64;
+            *scores.entry(id.clone()).or_insert(0.0) += weight * idf;
+        }
+    }
+
+    fn score_clause(&self, clause: &Clause) -> HashMap<StakeId<K>, f64> {
+        let mut scores = HashMap::new();
+        match clause {
+            Clause::Term(token) => {
+                if let Some(docs) = self.postings.get(token) {
+                    self.score_term(docs, &mut scores);
+                }
+            }
+            Clause::Prefix(prefix) => {
+                for (_, docs) in self
+                    .postings
+                    .range(prefix.clone()..)
+                    .take_while(|(token, _)| token.starts_with(prefix.as_str()))
+                {
+                    self.score_term(docs, &mut scores);
+                }
+            }
+            Clause::Phrase(tokens) => {
+                // Every token has to be indexed somewhere for the phrase to match at all.
+                let Some(postings) = tokens
+                    .iter()
+                    .map(|token| self.postings.get(token))
+                    .collect::<Option<Vec<_>>>()
+                else {
+                    return scores;
+                };
+                let idf: f64 = postings.iter().map(|docs| self.idf(docs.len())).sum();
+                for (id, first) in postings[0] {
+                    let Some(rest) = postings[1..]
+                        .iter()
+                        .map(|docs| docs.get(id))
+                        .collect::<Option<Vec<_>>>()
+                    else {
+                        continue;
+                    };
+                    let name_hits = phrase_hits(&first.name, &rest, |o| &o.name);
+                    let note_hits = phrase_hits(&first.note, &rest, |o| &o.note);
+                    if name_hits + note_hits > 0 {
+                        let weight =
+                            NAME_WEIGHT * name_hits as f64 + NOTE_WEIGHT * note_hits as f64;
+                        scores.insert(id.clone(), weight * idf);
+                    }
+                }
+            }
+        }
+        scores
+    }
+}
+
+// Counts the starting positions in one field where the remaining phrase tokens follow in order.
+fn phrase_hits(
+    starts: &[usize],
+    rest: &[&Occurrences],
+    field: fn(&Occurrences) -> &Vec<usize>,
+) -> usize {
+    starts
+        .iter()
+        .filter(|&&start| {
+            rest.iter()
+                .enumerate()
+                .all(|(offset, occurrences)| field(occurrences).contains(&(start + offset + 1)))
+        })
+        .count()
+}
+
+/// Splits text into lowercase alphanumeric tokens.
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(|token| token.to_lowercase())
+        .collect()
+}
+
+fn parse_query(query: &str) -> Vec<Clause> {
+    let mut clauses = Vec::new();
+    // Splitting on quotes leaves phrases at the odd indices.
+    for (index, segment) in query.split('"').enumerate() {
+        if index % 2 == 1 {
+            let tokens = tokenize(segment);
+            match tokens.len() {
+                0 => {}
+                1 => clauses.push(Clause::Term(tokens[0].clone())),
+                _ => clauses.push(Clause::Phrase(tokens)),
+            }
+            continue;
+        }
+        for word in segment.split_whitespace() {
+            let mut tokens = tokenize(word);
+            // A trailing `*` only applies to the final token of the word.
+            let prefix = if word.ends_with('*') {
+                tokens.pop()
+            } else {
+                None
+            };
+            // Words joined by punctuation ("q3-plan") are matched as a phrase.
+            match tokens.len() {
+                0 => {}
+                1 => clauses.push(Clause::Term(tokens.remove(0))),
+                _ => clauses.push(Clause::Phrase(tokens)),
+            }
+            clauses.extend(prefix.map(Clause::Prefix));
+        }
+    }
+    clauses
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::stake::Task;
+
+    fn stake(id: u32, name: &str, note: Option<&str>) -> Stake<Task> {
+        Stake::new(
+            StakeId::new(id),
+            name.to_string(),
+            None,
+            note.map(str::to_string),
+        )
+    }
+
+    fn ids(results: &[(StakeId<Task>, f64)]) -> Vec<u32> {
+        let mut ids: Vec<u32> = results.iter().map(|(id, _)| id.0).collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn test_tokenize_lowercases_and_splits_on_punctuation() {
+        assert_eq!(
+            tokenize("Call Bob's office, re: Q3-plan!"),
+            vec!["call", "bob", "s", "office", "re", "q3", "plan"]
+        );
+    }
+
+    #[test]
+    fn test_parse_query_recognises_terms_prefixes_and_phrases() {
+        assert_eq!(
+            parse_query(r#"plan* "annual budget" review"#),
+            vec![
+                Clause::Prefix("plan".to_string()),
+                Clause::Phrase(vec!["annual".to_string(), "budget".to_string()]),
+                Clause::Term("review".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_search_matches_name_and_note() {
+        let mut index = SearchIndex::new();
+        index.insert(&stake(1, "Budget review", None));
+        index.insert(&stake(2, "Quarterly planning", Some("include the budget")));
+        index.insert(&stake(3, "Holiday", None));
+
+        assert_eq!(ids(&index.search("budget")), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_search_requires_every_word() {
+        let mut index = SearchIndex::new();
+        index.insert(&stake(1, "Budget review", None));
+        index.insert(&stake(2, "Budget planning", None));
+
+        assert_eq!(ids(&index.search("budget review")), vec![1]);
+    }
+
+    #[test]
+    fn test_search_prefix_only_with_star() {
+        let mut index = SearchIndex::new();
+        index.insert(&stake(1, "Planning session", None));
+        index.insert(&stake(2, "Plan holiday", None));
+
+        assert_eq!(ids(&index.search("plan")), vec![2]);
+        assert_eq!(ids(&index.search("plan*")), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_parse_query_treats_punctuated_word_as_phrase() {
+        assert_eq!(
+            parse_query("q3-plan*"),
+            vec![
+                Clause::Term("q3".to_string()),
+                Clause::Prefix("plan".to_string()),
+            ]
+        );
+        assert_eq!(
+            parse_query("perf_target"),
+            vec![Clause::Phrase(vec![
+                "perf".to_string(),
+                "target".to_string()
+            ])]
+        );
+    }
+
+    #[test]
+    fn test_search_phrase_requires_adjacent_tokens() {
+        let mut index = SearchIndex::new();
+        index.insert(&stake(1, "Annual budget", None));
+        index.insert(&stake(2, "Budget for annual trip", None));
+
+        assert_eq!(ids(&index.search(r#""annual budget""#)), vec![1]);
+    }
+
+    #[test]
+    fn test_name_match_scores_above_note_match() {
+        let mut index = SearchIndex::new();
+        index.insert(&stake(1, "Taxes", Some("budget impact")));
+        index.insert(&stake(2, "Budget", None));
+
+        let mut results = index.search("budget");
+        results.sort_by(|a, b| b.1.total_cmp(&a.1));
+        assert_eq!(results[0].0, StakeId::new(2));
+    }
+
+    #[test]
+    fn test_reinsert_replaces_old_terms() {
+        let mut index = SearchIndex::new();
+        index.insert(&stake(1, "Old name", None));
+        index.insert(&stake(1, "New name", None));
+
+        assert!(index.search("old").is_empty());
+        assert_eq!(ids(&index.search("new")), vec![1]);
+    }
+
+    #[test]
+    fn test_remove_drops_empty_postings() {
+        let mut index = SearchIndex::new();
+        index.insert(&stake(1, "Lonely", None));
+        index.remove(&StakeId::new(1));
+
+        assert!(index.postings.is_empty());
+        assert!(index.indexed_terms.is_empty());
+    }
+}
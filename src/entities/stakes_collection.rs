@@ -1,28 +1,123 @@
-use chrono::Utc;
 use indexmap::IndexMap;
 use serde::de::{self, MapAccess, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use std::collections::HashSet;
+use std::collections::HashMap;
 use std::fmt;
-use std::time::Instant;
+use std::marker::PhantomData;
+
+use super::clock::{Clock, SharedClock};
+use super::fuzzy::fuzzy_score;
+use super::global_id::GlobalId;
+use super::search_index::{SearchHit, SearchIndex};
+use super::stake::{Kind, Stake, StakeError, StakeId};
+
+#[derive(Debug, Clone)] // Removed Serialize, Deserialize for custom impl
+pub struct StakesCollection<K: Kind> {
+    stakes: IndexMap<StakeId<K>, Stake<K>>,
+    next_id: StakeId<K>,
+    // Derived from `stakes`; rebuilt on load rather than serialized.
+    search_index: SearchIndex<K>,
+    // parent id -> ids of its children, in the order they sit in `stakes`.
+    children: HashMap<StakeId<K::Parent>, Vec<StakeId<K>>>,
+    // global id -> local id, for stakes that carry a global id.
+    global_ids: HashMap<GlobalId, StakeId<K>>,
+    // Where modification times come from; not part of the data.
+    clock: SharedClock,
+}
 
-use super::stake::{Stake, StakeError, StakeId};
+// The indexes are derived data and the clock is not data at all, so equality only
+// looks at the stakes themselves.
+impl<K: Kind> PartialEq for StakesCollection<K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.stakes == other.stakes && self.next_id == other.next_id
+    }
+}
 
-#[derive(Debug, Clone, PartialEq, Eq)] // Removed Serialize, Deserialize for custom impl
-pub struct StakesCollection {
-    stakes: IndexMap<StakeId, Stake>,
-    next_id: StakeId,
+impl<K: Kind> Eq for StakesCollection<K> {}
+
+impl<K: Kind> Default for StakesCollection<K> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
-impl StakesCollection {
+impl<K: Kind> StakesCollection<K> {
     pub fn new() -> Self {
         StakesCollection {
             stakes: IndexMap::new(),
-            next_id: StakeId(1),
+            next_id: StakeId::new(1),
+            search_index: SearchIndex::new(),
+            children: HashMap::new(),
+            global_ids: HashMap::new(),
+            clock: SharedClock::default(),
         }
     }
 
-    pub fn add_stake(&mut self, stake: Stake) {
+    /// Builds a collection from already loaded stakes, rebuilding the search and children indexes.
+    fn from_parts(stakes: IndexMap<StakeId<K>, Stake<K>>, next_id: StakeId<K>) -> Self {
+        let mut collection = StakesCollection {
+            stakes: IndexMap::new(),
+            next_id,
+            search_index: SearchIndex::new(),
+            children: HashMap::new(),
+            global_ids: HashMap::new(),
+            clock: SharedClock::default(),
+        };
+        for stake in stakes.into_values() {
+            collection.add_stake(stake);
+        }
+        collection
+    }
+
+    // Brings the derived indexes in line with `stake`, which is about to replace
+    // whatever is stored under its id (or be appended if the id is new).
+    fn reindex(&mut self, stake: &Stake<K>) {
+        self.search_index.insert(stake);
+
+        let old_global_id = self
+            .stakes
+            .get(&stake.stake_id)
+            .and_then(|old| old.global_id);
+        if old_global_id != stake.global_id
+            && let Some(old_global_id) = old_global_id
+        {
+            self.global_ids.remove(&old_global_id);
+        }
+        if let Some(global_id) = stake.global_id {
+            self.global_ids.insert(global_id, stake.stake_id.clone());
+        }
+
+        let old_parent = match self.stakes.get(&stake.stake_id) {
+            Some(old) if old.parent_id == stake.parent_id => return,
+            Some(old) => old.parent_id.clone(),
+            None => None,
+        };
+        if let Some(old_parent) = old_parent
+            && let Some(siblings) = self.children.get_mut(&old_parent)
+        {
+            siblings.retain(|id| id != &stake.stake_id);
+            if siblings.is_empty() {
+                self.children.remove(&old_parent);
+            }
+        }
+        if let Some(new_parent) = &stake.parent_id {
+            // A new stake goes last, after everything already stored.
+            let stakes = &self.stakes;
+            let position = |id: &StakeId<K>| stakes.get_index_of(id).unwrap_or(stakes.len());
+            let at = position(&stake.stake_id);
+            let siblings = self.children.entry(new_parent.clone()).or_default();
+            let slot = siblings.partition_point(|sibling| position(sibling) < at);
+            siblings.insert(slot, stake.stake_id.clone());
+        }
+    }
+
+    /// Replaces the clock used to stamp `date_modified` on moves.
+    pub fn set_clock(&mut self, clock: SharedClock) {
+        self.clock = clock;
+    }
+
+    pub fn add_stake(&mut self, stake: Stake<K>) {
+        self.reindex(&stake);
         self.stakes.insert(stake.stake_id.clone(), stake);
     }
 
@@ -30,8 +125,9 @@ impl StakesCollection {
         self.stakes.len()
     }
 
-    pub fn update_stake(&mut self, new_stake: Stake) -> Result<(), StakeError> {
+    pub fn update_stake(&mut self, new_stake: Stake<K>) -> Result<(), StakeError> {
         if self.stakes.contains_key(&new_stake.stake_id) {
+            self.reindex(&new_stake);
             self.stakes.insert(new_stake.stake_id.clone(), new_stake);
             Ok(())
         } else {
@@ -39,7 +135,7 @@ impl StakesCollection {
         }
     }
 
-    pub fn search_by_name(&self, query: &str) -> Vec<&Stake> {
+    pub fn search_by_name(&self, query: &str) -> Vec<&Stake<K>> {
         // Prepare the query for case-insensitive partial matching
         let lower_query = query.trim().to_lowercase(); // Trim whitespace and convert to lowercase
 
@@ -58,63 +154,185 @@ impl StakesCollection {
             .collect() // Collect references to matching stakes
     }
 
+    /// Full-text search over stake names and notes, most relevant first.
+    /// Supports multi-word queries (all words must match), `"quoted phrases"`
+    /// and `prefix*` terms. Ties keep insertion order; an empty query returns all stakes.
+    pub fn search(&self, query: &str) -> Vec<SearchHit<'_, K>> {
+        if query.trim().is_empty() {
+            return self
+                .stakes
+                .values()
+                .map(|stake| SearchHit { stake, score: 0.0 })
+                .collect();
+        }
+
+        let mut ranked: Vec<(usize, &Stake<K>, f64)> = self
+            .search_index
+            .search(query)
+            .into_iter()
+            .filter_map(|(id, score)| {
+                self.stakes
+                    .get_full(&id)
+                    .map(|(position, _, stake)| (position, stake, score))
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.2.total_cmp(&a.2).then(a.0.cmp(&b.0)));
+        ranked
+            .into_iter()
+            .map(|(_, stake, score)| SearchHit { stake, score })
+            .collect()
+    }
+
+    /// Typo-tolerant search over stake names, best match first.
+    /// Ties keep insertion order; an empty query matches nothing.
+    pub fn fuzzy_search(&self, query: &str) -> Vec<SearchHit<'_, K>> {
+        let mut matches: Vec<SearchHit<'_, K>> = self
+            .stakes
+            .values()
+            .filter_map(|stake| {
+                fuzzy_score(query, &stake.stake_name).map(|score| SearchHit { stake, score })
+            })
+            .collect();
+        // sort_by is stable, so equal scores stay in insertion order.
+        matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+        matches
+    }
+
     pub fn is_empty(&self) -> bool {
         self.stakes.is_empty()
     }
 
-    pub fn get_by_id(&self, id: &StakeId) -> Option<&Stake> {
-        self.stakes.values().find(|stake| &stake.stake_id == id)
+    /// Iterates over every stake in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = &Stake<K>> {
+        self.stakes.values()
+    }
+
+    pub fn get_by_id(&self, id: &StakeId<K>) -> Option<&Stake<K>> {
+        self.stakes.get(id)
+    }
+
+    /// Maps a global id to the local id it is stored under.
+    pub fn local_id(&self, global_id: &GlobalId) -> Option<StakeId<K>> {
+        self.global_ids.get(global_id).cloned()
+    }
+
+    pub fn get_by_global_id(&self, global_id: &GlobalId) -> Option<&Stake<K>> {
+        self.global_ids
+            .get(global_id)
+            .and_then(|id| self.stakes.get(id))
     }
 
-    pub fn active_stakes(&self) -> Vec<&Stake> {
+    /// Resolves a user-supplied handle, either the short numeric local id or a
+    /// full global id, to the local id of a stake in this collection.
+    pub fn resolve(&self, handle: &str) -> Option<StakeId<K>> {
+        let handle = handle.trim();
+        if let Ok(number) = handle.parse::<u32>() {
+            let id = StakeId::new(number);
+            return self.stakes.contains_key(&id).then_some(id);
+        }
+        handle
+            .parse::<GlobalId>()
+            .ok()
+            .and_then(|global_id| self.local_id(&global_id))
+    }
+
+    /// Gives every stake without a global id a fresh one. Returns how many were assigned.
+    pub fn assign_global_ids(&mut self) -> usize {
+        let mut assigned = 0;
+        for stake in self.stakes.values_mut() {
+            if stake.global_id.is_none() {
+                let global_id = stake.ensure_global_id();
+                self.global_ids.insert(global_id, stake.stake_id.clone());
+                assigned += 1;
+            }
+        }
+        assigned
+    }
+
+    pub fn active_stakes(&self) -> Vec<&Stake<K>> {
         self.stakes.values().filter(|s| s.is_active()).collect()
     }
 
-    pub fn completed_stakes(&self) -> Vec<&Stake> {
+    pub fn completed_stakes(&self) -> Vec<&Stake<K>> {
         self.stakes.values().filter(|s| s.complete).collect()
     }
 
-    pub fn next_id(&self) -> StakeId {
+    pub fn next_id(&self) -> StakeId<K> {
         self.next_id.clone()
     }
 
-    pub fn generate_id(&mut self) -> StakeId {
+    pub fn generate_id(&mut self) -> StakeId<K> {
         let current_id = self.next_id.clone();
         self.next_id.0 += 1;
         current_id
     }
 
-    pub fn get_children(&self, parent_id: &StakeId) -> Vec<&Stake> {
-        self.stakes
-            .values()
-            .filter(|stake| {
-                // Check if stake.parent_id is Some(id) AND that inner id matches the provided parent_id
-                stake.parent_id.as_ref() == Some(parent_id) && stake.is_active() == true
+    /// Returns every child of `parent_id` regardless of status, in insertion order.
+    pub fn get_all_children(&self, parent_id: &StakeId<K::Parent>) -> Vec<&Stake<K>> {
+        self.children
+            .get(parent_id)
+            .map(|ids| ids.iter().filter_map(|id| self.stakes.get(id)).collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns the active children of `parent_id`, in insertion order.
+    pub fn get_children(&self, parent_id: &StakeId<K::Parent>) -> Vec<&Stake<K>> {
+        self.children
+            .get(parent_id)
+            .map(|ids| {
+                ids.iter()
+                    .filter_map(|id| self.stakes.get(id))
+                    .filter(|stake| stake.is_active())
+                    .collect()
             })
-            .collect() // Collect into a new Vec
+            .unwrap_or_default()
     }
-}
 
-// ... (rest of the file remains the same until the tests module)
+    /// Re-parents a stake and updates its `date_modified`.
+    /// Returns `Err(StakeError::StakeNotFound)` if no stake has the given id.
+    pub fn move_stake(
+        &mut self,
+        id: &StakeId<K>,
+        new_parent_id: Option<StakeId<K::Parent>>,
+    ) -> Result<(), StakeError> {
+        let mut stake = self.get_by_id(id).ok_or(StakeError::StakeNotFound)?.clone();
+        stake.parent_id = new_parent_id;
+        stake.date_modified = self.clock.now();
+        self.update_stake(stake)
+    }
+}
 
 // --- Unit Tests for StakesCollection ---
 #[cfg(test)]
+#[allow(clippy::items_after_test_module)]
 mod tests {
     use super::*;
-    use crate::entities::stake::{Stake, StakeId};
-    use chrono::TimeZone;
-    use serde_json; // Needed for Utc.with_ymd_and_hms in the fixed_time setup
+    use crate::entities::clock::{FakeClock, SystemClock};
+    use crate::entities::stake::{Stake, StakeId, StakeKind};
+    use chrono::{Duration, TimeZone, Utc};
+    use serde_json;
+    use std::collections::HashSet;
+
+    // A kind that parents itself, so these tests can keep parents and children
+    // in one collection. Collection behaviour does not depend on the kind.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    enum Node {}
+
+    impl Kind for Node {
+        const KIND: StakeKind = StakeKind::Task;
+        type Parent = Node;
+    }
 
     // Helper function (copied here as discussed)
     fn create_test_stake(
         id: u32,
         name: &str,
-        parent_id: Option<StakeId>,
+        parent_id: Option<StakeId<Node>>,
         complete: bool,
         dropped: bool,
         note: Option<String>,
-    ) -> Stake {
-        let mut stake = Stake::new(StakeId(id), name.to_string(), parent_id, note);
+    ) -> Stake<Node> {
+        let mut stake = Stake::new(StakeId::new(id), name.to_string(), parent_id, note);
         stake.complete = complete;
         stake.dropped = dropped;
         stake
@@ -129,7 +347,7 @@ mod tests {
     #[test]
     fn test_stakes_collection_serialization_roundtrip() {
         let mut original_collection = StakesCollection::new();
-        original_collection.next_id = StakeId(5); // Set a specific next_id for testing
+        original_collection.next_id = StakeId::new(5); // Set a specific next_id for testing
 
         // Create stakes with fixed times for consistent comparison
         let fixed_time = Utc.with_ymd_and_hms(2024, 7, 19, 8, 30, 0).unwrap();
@@ -145,14 +363,15 @@ mod tests {
         stake1.date_created = fixed_time;
         stake1.date_modified = fixed_time;
 
-        let mut stake2 = create_test_stake(2, "Second Stake", Some(StakeId(1)), true, false, None);
+        let mut stake2 =
+            create_test_stake(2, "Second Stake", Some(StakeId::new(1)), true, false, None);
         stake2.date_created = fixed_time;
         stake2.date_modified = fixed_time;
 
         let mut stake3 = create_test_stake(
             3,
             "Third Stake",
-            Some(StakeId(2)),
+            Some(StakeId::new(2)),
             false,
             true,
             Some("Note 3".to_string()),
@@ -170,7 +389,7 @@ mod tests {
         println!("Serialized collection for roundtrip test:\n{}", serialized); // Print for debugging/inspection
 
         // 2. Deserialize the string back into a new collection
-        let deserialized_collection: StakesCollection =
+        let deserialized_collection: StakesCollection<Node> =
             serde_json::from_str(&serialized).expect("Failed to deserialize collection");
 
         // 3. Compare the original collection with the deserialized one
@@ -187,6 +406,7 @@ mod tests {
     // Keep test_stakes_collection_deserialization as it is, as it's a good test
     // for deserializing from a specific known JSON string.
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_stakes_collection_deserialization() {
         let fixed_time = Utc.with_ymd_and_hms(2024, 7, 19, 8, 30, 0).unwrap();
 
@@ -211,18 +431,18 @@ mod tests {
             fixed_time.to_rfc3339()
         );
 
-        let deserialized: StakesCollection =
+        let deserialized: StakesCollection<Node> =
             serde_json::from_str(&json_input).expect("Failed to deserialize collection");
 
-        assert_eq!(deserialized.next_id, StakeId(5));
+        assert_eq!(deserialized.next_id, StakeId::new(5));
         assert_eq!(deserialized.len(), 1);
 
         let stake = deserialized
             .stakes
-            .get(&StakeId(1))
+            .get(&StakeId::new(1))
             .expect("Stake with ID 1 should be present");
 
-        assert_eq!(stake.stake_id, StakeId(1));
+        assert_eq!(stake.stake_id, StakeId::new(1));
         assert_eq!(stake.stake_name, "Loaded Stake 1");
         assert_eq!(stake.parent_id, None);
         assert_eq!(stake.complete, false);
@@ -262,7 +482,7 @@ mod tests {
         );
         assert_eq!(
             collection.next_id,
-            StakeId((num_stakes + 1) as u32),
+            StakeId::new((num_stakes + 1) as u32),
             "next_id should be correctly incremented"
         );
         assert_eq!(
@@ -277,7 +497,7 @@ mod tests {
         let mut collection = StakesCollection::new();
 
         let stake1 = create_test_stake(1, "Stake A", None, false, false, None);
-        let stake2 = create_test_stake(2, "Stake B", Some(StakeId(1)), true, false, None);
+        let stake2 = create_test_stake(2, "Stake B", Some(StakeId::new(1)), true, false, None);
         let stake3 = create_test_stake(3, "Stake C", None, false, true, None);
 
         collection.add_stake(stake1.clone());
@@ -285,7 +505,7 @@ mod tests {
         collection.add_stake(stake3.clone());
 
         // Test finding an existing stake
-        let found_stake_1 = collection.get_by_id(&StakeId(1));
+        let found_stake_1 = collection.get_by_id(&StakeId::new(1));
         assert!(found_stake_1.is_some(), "Should find Stake 1");
         assert_eq!(
             found_stake_1.unwrap(),
@@ -293,7 +513,7 @@ mod tests {
             "Found Stake 1 should match original"
         ); // Note: compare reference to owned
 
-        let found_stake_2 = collection.get_by_id(&StakeId(2));
+        let found_stake_2 = collection.get_by_id(&StakeId::new(2));
         assert!(found_stake_2.is_some(), "Should find Stake 2");
         assert_eq!(
             found_stake_2.unwrap(),
@@ -302,15 +522,15 @@ mod tests {
         ); // Note: compare reference to owned
 
         // Test not finding a non-existent stake
-        let not_found_stake = collection.get_by_id(&StakeId(999));
+        let not_found_stake = collection.get_by_id(&StakeId::new(999));
         assert!(
             not_found_stake.is_none(),
             "Should not find non-existent stake"
         );
 
         // Test with empty collection
-        let empty_collection = StakesCollection::new();
-        let not_found_in_empty = empty_collection.get_by_id(&StakeId(1));
+        let empty_collection: StakesCollection<Node> = StakesCollection::new();
+        let not_found_in_empty = empty_collection.get_by_id(&StakeId::new(1));
         assert!(
             not_found_in_empty.is_none(),
             "Should not find stake in empty collection"
@@ -395,7 +615,7 @@ mod tests {
         assert!(children_of_c.is_empty(), "Stake C should have no children");
 
         // Test retrieving children for a non-existent parent ID
-        let non_existent_parent_id = StakeId(999);
+        let non_existent_parent_id = StakeId::new(999);
         let children_non_existent = collection.get_children(&non_existent_parent_id);
         assert!(
             children_non_existent.is_empty(),
@@ -403,8 +623,8 @@ mod tests {
         );
 
         // Test with an empty collection
-        let empty_collection = StakesCollection::new();
-        let not_found_in_empty = empty_collection.get_children(&StakeId(1));
+        let empty_collection: StakesCollection<Node> = StakesCollection::new();
+        let not_found_in_empty = empty_collection.get_children(&StakeId::new(1));
         assert!(
             not_found_in_empty.is_empty(),
             "Empty collection should have no children"
@@ -412,14 +632,14 @@ mod tests {
     }
 
     #[test]
-    fn test_performance_get_children() {
+    fn test_get_children_among_many_stakes() {
         let mut collection = StakesCollection::new();
         let num_stakes = 10_000;
-        let target_parent_id = StakeId(55); // The parent ID we'll search for
+        let target_parent_id = StakeId::new(55); // The parent ID we'll search for
         let mut expected_children_count = 0;
 
         // Populate the collection with 10,000 stakes
-        // We'll make about 10% of them children of StakeId(55) for testing
+        // We'll make about 10% of them children of StakeId::new(55) for testing
         for i in 1..=num_stakes {
             let parent_id_option = if i % 10 == 0 && i > 0 {
                 // Every 10th stake (that's not the first itself)
@@ -434,11 +654,11 @@ mod tests {
                 // Ensure target_parent_id itself is added
                 None
             } else {
-                Some(StakeId(i % 50 + 1)) // Random-ish other parent_ids
+                Some(StakeId::new(i % 50 + 1)) // Random-ish other parent_ids
             };
             let stake_name = format!("Stake {}", i);
             collection.add_stake(create_test_stake(
-                i as u32,
+                i,
                 &stake_name,
                 parent_id_option,
                 false,
@@ -447,18 +667,18 @@ mod tests {
             ));
         }
 
-        println!(
-            "\nPerformance Test: Finding children of StakeId({}) in {} stakes.",
-            target_parent_id.0, num_stakes
-        );
-
-        let start_time = Instant::now(); // Start measuring time
-
         let children = collection.get_children(&target_parent_id);
 
-        let elapsed_time = start_time.elapsed(); // Stop measuring time
-
-        println!("Found {} children in {:?}", children.len(), elapsed_time);
+        // The full scan get_children did before the children index existed; how the
+        // two compare in speed is measured in benches/domain.rs.
+        let scanned: Vec<&Stake<Node>> = collection
+            .stakes
+            .values()
+            .filter(|stake| {
+                stake.parent_id.as_ref() == Some(&target_parent_id) && stake.is_active()
+            })
+            .collect();
+        assert_eq!(children, scanned, "Index and scan should agree");
 
         // Assert that we found the expected number of children
         // The helper `create_test_stake` will generate child_a1/child_a2 for id 11 and 12, etc.
@@ -483,6 +703,179 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_get_by_id_among_many_stakes() {
+        let mut collection = StakesCollection::new();
+        let num_stakes: u32 = 10_000;
+        for i in 1..=num_stakes {
+            collection.add_stake(create_test_stake(i, "Stake", None, false, false, None));
+        }
+
+        for i in (1..=1_000).map(|i| i * 97 % num_stakes + 1) {
+            let id = StakeId::new(i);
+            assert_eq!(
+                collection.get_by_id(&id).map(|stake| &stake.stake_id),
+                Some(&id)
+            );
+        }
+        assert!(
+            collection
+                .get_by_id(&StakeId::new(num_stakes + 1))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_children_keep_insertion_order_across_moves() {
+        let mut collection = StakesCollection::new();
+        for i in 1..=4 {
+            let parent = if i == 4 { 2 } else { 1 };
+            collection.add_stake(create_test_stake(
+                i,
+                "Child",
+                Some(StakeId::new(parent)),
+                false,
+                false,
+                None,
+            ));
+        }
+        let ids = |collection: &StakesCollection<Node>, parent: u32| -> Vec<u32> {
+            collection
+                .get_all_children(&StakeId::new(parent))
+                .iter()
+                .map(|stake| stake.stake_id.0)
+                .collect()
+        };
+
+        collection
+            .move_stake(&StakeId::new(2), Some(StakeId::new(2)))
+            .unwrap();
+        assert_eq!(ids(&collection, 1), [1, 3]);
+        assert_eq!(ids(&collection, 2), [2, 4]);
+        collection
+            .move_stake(&StakeId::new(2), Some(StakeId::new(1)))
+            .unwrap();
+        assert_eq!(ids(&collection, 1), [1, 2, 3]);
+        assert_eq!(ids(&collection, 2), [4]);
+        collection.move_stake(&StakeId::new(4), None).unwrap();
+        assert!(ids(&collection, 2).is_empty());
+    }
+
+    #[test]
+    fn test_stakes_collection_get_children_follows_parent_changes() {
+        let mut collection = StakesCollection::new();
+        collection.add_stake(create_test_stake(1, "Parent A", None, false, false, None));
+        collection.add_stake(create_test_stake(2, "Parent B", None, false, false, None));
+        let mut child = create_test_stake(3, "Child", Some(StakeId::new(1)), false, false, None);
+        collection.add_stake(child.clone());
+
+        child.parent_id = Some(StakeId::new(2));
+        collection.update_stake(child.clone()).unwrap();
+
+        assert!(collection.get_children(&StakeId::new(1)).is_empty());
+        assert_eq!(collection.get_children(&StakeId::new(2)), vec![&child]);
+    }
+
+    #[test]
+    fn test_stakes_collection_get_children_after_deserialization() {
+        let mut collection = StakesCollection::new();
+        collection.add_stake(create_test_stake(1, "Parent", None, false, false, None));
+        collection.add_stake(create_test_stake(
+            2,
+            "Child",
+            Some(StakeId::new(1)),
+            false,
+            false,
+            None,
+        ));
+
+        let json = serde_json::to_string(&collection).unwrap();
+        let loaded: StakesCollection<Node> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(loaded.get_children(&StakeId::new(1)).len(), 1);
+    }
+
+    #[test]
+    fn test_stakes_collection_get_all_children_includes_inactive() {
+        let mut collection = StakesCollection::new();
+        collection.add_stake(create_test_stake(1, "Parent", None, false, false, None));
+        let open = create_test_stake(2, "Open", Some(StakeId::new(1)), false, false, None);
+        let done = create_test_stake(3, "Done", Some(StakeId::new(1)), true, false, None);
+        let dropped = create_test_stake(4, "Dropped", Some(StakeId::new(1)), false, true, None);
+        collection.add_stake(open.clone());
+        collection.add_stake(done.clone());
+        collection.add_stake(dropped.clone());
+
+        assert_eq!(collection.get_children(&StakeId::new(1)), vec![&open]);
+        assert_eq!(
+            collection.get_all_children(&StakeId::new(1)),
+            vec![&open, &done, &dropped]
+        );
+    }
+
+    #[test]
+    fn test_stakes_collection_iter_in_insertion_order() {
+        let mut collection = StakesCollection::new();
+        for id in [3, 1, 2] {
+            collection.add_stake(create_test_stake(id, "Stake", None, false, false, None));
+        }
+
+        let ids: Vec<u32> = collection.iter().map(|stake| stake.stake_id.0).collect();
+        assert_eq!(ids, vec![3, 1, 2]);
+    }
+
+    #[test]
+    fn test_stakes_collection_move_stake() {
+        let moved_at = Utc.with_ymd_and_hms(2026, 10, 14, 9, 0, 0).unwrap();
+        let clock = FakeClock::new(moved_at);
+        let mut collection = StakesCollection::new();
+        collection.set_clock(SharedClock::new(clock.clone()));
+        collection.add_stake(create_test_stake(1, "Old Parent", None, false, false, None));
+        collection.add_stake(create_test_stake(2, "New Parent", None, false, false, None));
+        let child = create_test_stake(3, "Child", Some(StakeId::new(1)), false, false, None);
+        collection.add_stake(child.clone());
+
+        clock.advance(Duration::minutes(1));
+        let result = collection.move_stake(&StakeId::new(3), Some(StakeId::new(2)));
+
+        assert!(result.is_ok());
+        let moved = collection.get_by_id(&StakeId::new(3)).unwrap();
+        assert_eq!(moved.parent_id, Some(StakeId::new(2)));
+        assert_eq!(moved.date_modified, moved_at + Duration::minutes(1));
+        assert_eq!(moved.date_created, child.date_created);
+        assert!(collection.get_children(&StakeId::new(1)).is_empty());
+        assert_eq!(collection.get_children(&StakeId::new(2)), vec![moved]);
+    }
+
+    #[test]
+    fn test_stakes_collection_move_stake_to_top_level() {
+        let mut collection = StakesCollection::new();
+        collection.add_stake(create_test_stake(1, "Parent", None, false, false, None));
+        collection.add_stake(create_test_stake(
+            2,
+            "Child",
+            Some(StakeId::new(1)),
+            false,
+            false,
+            None,
+        ));
+
+        collection.move_stake(&StakeId::new(2), None).unwrap();
+
+        assert_eq!(
+            collection.get_by_id(&StakeId::new(2)).unwrap().parent_id,
+            None
+        );
+        assert!(collection.get_children(&StakeId::new(1)).is_empty());
+    }
+
+    #[test]
+    fn test_stakes_collection_move_stake_not_found() {
+        let mut collection: StakesCollection<Node> = StakesCollection::new();
+        let result = collection.move_stake(&StakeId::new(999), None);
+        assert_eq!(result, Err(StakeError::StakeNotFound));
+    }
+
     #[test]
     fn test_stakes_collection_is_empty() {
         let mut collection = StakesCollection::new();
@@ -503,9 +896,10 @@ mod tests {
         let mut collection = StakesCollection::new();
         let active_stake1 = create_test_stake(1, "Active 1", None, false, false, None);
         let completed_stake =
-            create_test_stake(2, "Completed", Some(StakeId(1)), true, false, None);
+            create_test_stake(2, "Completed", Some(StakeId::new(1)), true, false, None);
         let dropped_stake = create_test_stake(3, "Dropped", None, false, true, None);
-        let active_stake2 = create_test_stake(4, "Active 2", Some(StakeId(1)), false, false, None);
+        let active_stake2 =
+            create_test_stake(4, "Active 2", Some(StakeId::new(1)), false, false, None);
 
         collection.add_stake(active_stake1.clone());
         collection.add_stake(completed_stake.clone());
@@ -526,10 +920,10 @@ mod tests {
         let mut collection = StakesCollection::new();
         let active_stake = create_test_stake(1, "Active", None, false, false, None);
         let completed_stake1 =
-            create_test_stake(2, "Completed 1", Some(StakeId(1)), true, false, None);
+            create_test_stake(2, "Completed 1", Some(StakeId::new(1)), true, false, None);
         let dropped_stake = create_test_stake(3, "Dropped", None, false, true, None);
         let completed_stake2 =
-            create_test_stake(4, "Completed 2", Some(StakeId(1)), true, true, None); // Completed AND Dropped
+            create_test_stake(4, "Completed 2", Some(StakeId::new(1)), true, true, None); // Completed AND Dropped
 
         collection.add_stake(active_stake.clone());
         collection.add_stake(completed_stake1.clone());
@@ -553,8 +947,14 @@ mod tests {
         let stake1 = create_test_stake(1, "Website Redesign", None, false, false, None);
         let stake2 = create_test_stake(2, "Mobile App Development", None, true, false, None);
         let stake3 = create_test_stake(3, "Marketing Campaign Launch", None, false, true, None);
-        let stake4 =
-            create_test_stake(4, "Redesign Homepage", Some(StakeId(1)), false, false, None); // Partial match 'Redesign'
+        let stake4 = create_test_stake(
+            4,
+            "Redesign Homepage",
+            Some(StakeId::new(1)),
+            false,
+            false,
+            None,
+        ); // Partial match 'Redesign'
         let stake5 = create_test_stake(
             5,
             "Internal Review",
@@ -627,9 +1027,8 @@ mod tests {
         assert!(results7.contains(&&stake4));
     }
 
-    // NEW FAILING TEST: test_performance_search_by_name
     #[test]
-    fn test_performance_search_by_name() {
+    fn test_search_by_name_among_many_stakes() {
         let mut collection = StakesCollection::new();
         let num_stakes = 10_000;
         let search_query = "perf_target"; // A unique string to search for
@@ -658,19 +1057,9 @@ mod tests {
             ));
         }
 
-        println!(
-            "\nPerformance Test: Searching for '{}' in {} stakes.",
-            search_query, num_stakes
-        );
-
-        let start_time = Instant::now(); // Start measuring time
-
+        // How long this takes is measured in benches/domain.rs.
         let results = collection.search_by_name(search_query);
 
-        let elapsed_time = start_time.elapsed(); // Stop measuring time
-
-        println!("Found {} matches in {:?}", results.len(), elapsed_time);
-
         assert_eq!(
             results.len(),
             expected_matches,
@@ -682,6 +1071,167 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_stakes_collection_search_ranks_and_covers_notes() {
+        let mut collection = StakesCollection::new();
+        let note_only = create_test_stake(
+            1,
+            "Internal Review",
+            None,
+            false,
+            false,
+            Some("Review all marketing materials".to_string()),
+        );
+        let name_match =
+            create_test_stake(2, "Marketing Campaign Launch", None, false, false, None);
+        let unrelated = create_test_stake(3, "Brand Refresh", None, false, false, None);
+        collection.add_stake(note_only.clone());
+        collection.add_stake(name_match.clone());
+        collection.add_stake(unrelated);
+
+        let results = collection.search("marketing");
+        assert_eq!(results.len(), 2, "Should match names and notes");
+        assert_eq!(
+            results[0].stake, &name_match,
+            "Name match should rank first"
+        );
+        assert_eq!(results[1].stake, &note_only);
+        assert!(results[0].score > results[1].score);
+    }
+
+    #[test]
+    fn test_stakes_collection_search_phrase_and_prefix() {
+        let mut collection = StakesCollection::new();
+        let stake1 = create_test_stake(1, "Website Redesign", None, false, false, None);
+        let stake2 = create_test_stake(2, "Redesign the website", None, false, false, None);
+        collection.add_stake(stake1.clone());
+        collection.add_stake(stake2.clone());
+
+        let phrase = collection.search("\"website redesign\"");
+        assert_eq!(phrase.len(), 1);
+        assert_eq!(phrase[0].stake, &stake1);
+
+        let prefix = collection.search("web* redes*");
+        assert_eq!(prefix.len(), 2);
+    }
+
+    #[test]
+    fn test_stakes_collection_search_ties_keep_insertion_order() {
+        let mut collection = StakesCollection::new();
+        for id in [3, 1, 2] {
+            collection.add_stake(create_test_stake(id, "Same name", None, false, false, None));
+        }
+
+        let order: Vec<u32> = collection
+            .search("same")
+            .iter()
+            .map(|hit| hit.stake.stake_id.0)
+            .collect();
+        assert_eq!(order, vec![3, 1, 2]);
+    }
+
+    #[test]
+    fn test_stakes_collection_search_empty_query_returns_all() {
+        let mut collection = StakesCollection::new();
+        collection.add_stake(create_test_stake(1, "A", None, false, false, None));
+        collection.add_stake(create_test_stake(2, "B", None, false, false, None));
+
+        assert_eq!(collection.search("   ").len(), 2);
+    }
+
+    #[test]
+    fn test_stakes_collection_search_follows_updates() {
+        let mut collection = StakesCollection::new();
+        let mut stake = create_test_stake(1, "Draft proposal", None, false, false, None);
+        collection.add_stake(stake.clone());
+
+        stake.stake_name = "Final proposal".to_string();
+        stake.note = Some("Send to client".to_string());
+        collection.update_stake(stake).unwrap();
+
+        assert!(collection.search("draft").is_empty());
+        assert_eq!(collection.search("final").len(), 1);
+        assert_eq!(collection.search("client").len(), 1);
+    }
+
+    #[test]
+    fn test_stakes_collection_search_after_deserialization() {
+        let mut collection = StakesCollection::new();
+        collection.add_stake(create_test_stake(1, "Tax return", None, false, false, None));
+
+        let json = serde_json::to_string(&collection).unwrap();
+        let loaded: StakesCollection<Node> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(loaded.search("tax").len(), 1);
+    }
+
+    #[test]
+    fn test_stakes_collection_fuzzy_search_tolerates_typos() {
+        let mut collection = StakesCollection::new();
+        let finance = create_test_stake(1, "Financial Management", None, false, false, None);
+        let health = create_test_stake(2, "Health", None, false, false, None);
+        collection.add_stake(finance.clone());
+        collection.add_stake(health);
+
+        assert!(collection.search_by_name("finacial").is_empty());
+
+        let results = collection.fuzzy_search("finacial");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].stake, &finance);
+    }
+
+    #[test]
+    fn test_stakes_collection_fuzzy_search_orders_by_score() {
+        let mut collection = StakesCollection::new();
+        let scattered = create_test_stake(1, "Plan a new trip", None, false, false, None);
+        let exact = create_test_stake(2, "Plant", None, false, false, None);
+        let prefix = create_test_stake(3, "Plants to water", None, false, false, None);
+        collection.add_stake(scattered.clone());
+        collection.add_stake(exact.clone());
+        collection.add_stake(prefix.clone());
+
+        let results = collection.fuzzy_search("plant");
+        let order: Vec<&Stake<Node>> = results.iter().map(|hit| hit.stake).collect();
+        assert_eq!(order, vec![&exact, &prefix, &scattered]);
+    }
+
+    #[test]
+    fn test_stakes_collection_fuzzy_search_empty_query() {
+        let mut collection = StakesCollection::new();
+        collection.add_stake(create_test_stake(1, "Anything", None, false, false, None));
+
+        assert!(collection.fuzzy_search("").is_empty());
+    }
+
+    #[test]
+    fn test_search_index_agrees_with_linear_scan() {
+        let mut collection = StakesCollection::new();
+        let search_query = "perf_target";
+
+        // Same population as test_search_by_name_among_many_stakes, smaller. How the two
+        // compare in speed is measured by the `search` benchmarks in benches/domain.rs.
+        for i in 1..=1_000 {
+            let stake_name = if i % 100 == 0 {
+                format!("Stake {} - {} - other text", i, search_query)
+            } else if i == 500 {
+                search_query.to_string()
+            } else {
+                format!("Stake {}", i)
+            };
+            collection.add_stake(create_test_stake(i, &stake_name, None, false, false, None));
+        }
+
+        let linear = collection.search_by_name(search_query);
+        // The tokenizer splits on '_', so the index runs this as the phrase "perf target".
+        let mut indexed: Vec<&Stake<Node>> = collection
+            .search(search_query)
+            .iter()
+            .map(|hit| hit.stake)
+            .collect();
+        indexed.sort_by_key(|stake| stake.stake_id.0);
+        assert_eq!(indexed, linear);
+    }
+
     #[test]
     fn test_stakes_collection_update_stake() {
         let mut collection = StakesCollection::new();
@@ -698,7 +1248,7 @@ mod tests {
 
         // Create a modified version of the stake
         let mut modified_stake = initial_stake.clone();
-        modified_stake.mark_complete();
+        modified_stake.mark_complete(&SystemClock);
         modified_stake.note = Some("Updated note after completion".to_string());
 
         // Attempt to update the stake in the collection
@@ -732,9 +1282,116 @@ mod tests {
             "Error should indicate stake not found"
         );
     }
+
+    // --- Global ids and handle resolution ---
+
+    fn create_global_stake(id: u32, name: &str) -> Stake<Node> {
+        let mut stake = create_test_stake(id, name, None, false, false, None);
+        stake.ensure_global_id();
+        stake
+    }
+
+    #[test]
+    fn test_stakes_collection_get_by_global_id() {
+        let mut collection = StakesCollection::new();
+        let stake = create_global_stake(1, "Global");
+        collection.add_stake(stake.clone());
+        collection.add_stake(create_test_stake(2, "Local only", None, false, false, None));
+
+        let global_id = stake.global_id.unwrap();
+        assert_eq!(collection.get_by_global_id(&global_id), Some(&stake));
+        assert_eq!(collection.local_id(&global_id), Some(StakeId::new(1)));
+        assert_eq!(collection.get_by_global_id(&GlobalId::new()), None);
+    }
+
+    #[test]
+    fn test_stakes_collection_update_replaces_global_id_mapping() {
+        let mut collection = StakesCollection::new();
+        let stake = create_global_stake(1, "Global");
+        collection.add_stake(stake.clone());
+
+        let old_global_id = stake.global_id.unwrap();
+        let mut replaced = stake.clone();
+        replaced.global_id = Some(GlobalId::new());
+        collection.update_stake(replaced.clone()).unwrap();
+
+        assert_eq!(collection.local_id(&old_global_id), None);
+        assert_eq!(
+            collection.local_id(&replaced.global_id.unwrap()),
+            Some(StakeId::new(1))
+        );
+    }
+
+    #[test]
+    fn test_stakes_collection_resolve_accepts_local_and_global_handles() {
+        let mut collection = StakesCollection::new();
+        let stake = create_global_stake(7, "Global");
+        collection.add_stake(stake.clone());
+
+        let global_id = stake.global_id.unwrap();
+        assert_eq!(collection.resolve("7"), Some(StakeId::new(7)));
+        assert_eq!(collection.resolve(" 7 "), Some(StakeId::new(7)));
+        assert_eq!(
+            collection.resolve(&global_id.to_string()),
+            Some(StakeId::new(7))
+        );
+    }
+
+    #[test]
+    fn test_stakes_collection_resolve_rejects_unknown_handles() {
+        let mut collection = StakesCollection::new();
+        collection.add_stake(create_global_stake(1, "Global"));
+
+        assert_eq!(collection.resolve("2"), None);
+        assert_eq!(collection.resolve(&GlobalId::new().to_string()), None);
+        assert_eq!(collection.resolve("not a handle"), None);
+        assert_eq!(collection.resolve(""), None);
+    }
+
+    #[test]
+    fn test_stakes_collection_assign_global_ids_backfills_missing() {
+        let mut collection = StakesCollection::new();
+        let existing = create_global_stake(1, "Already global");
+        collection.add_stake(existing.clone());
+        collection.add_stake(create_test_stake(2, "Legacy", None, false, false, None));
+        collection.add_stake(create_test_stake(3, "Legacy too", None, false, false, None));
+
+        assert_eq!(collection.assign_global_ids(), 2);
+        assert_eq!(collection.assign_global_ids(), 0);
+        assert_eq!(
+            collection.get_by_id(&StakeId::new(1)).unwrap().global_id,
+            existing.global_id
+        );
+        for stake in collection.iter() {
+            let global_id = stake
+                .global_id
+                .expect("every stake should have a global id");
+            assert_eq!(
+                collection.local_id(&global_id),
+                Some(stake.stake_id.clone())
+            );
+        }
+    }
+
+    #[test]
+    fn test_stakes_collection_global_ids_survive_serialization() {
+        let mut collection = StakesCollection::new();
+        let stake = create_global_stake(1, "Global");
+        collection.add_stake(stake.clone());
+        collection.add_stake(create_test_stake(2, "Legacy", None, false, false, None));
+
+        let json = serde_json::to_string(&collection).unwrap();
+        let loaded: StakesCollection<Node> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(loaded, collection);
+        assert_eq!(
+            loaded.resolve(&stake.global_id.unwrap().to_string()),
+            Some(StakeId::new(1))
+        );
+    }
 }
 // --- Custom Serialize implementation for StakesCollection ---
-impl Serialize for StakesCollection {
+impl<K: Kind> Serialize for StakesCollection<K> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
@@ -751,7 +1408,7 @@ impl Serialize for StakesCollection {
 }
 
 // --- Custom Deserialize implementation for StakesCollection ---
-impl<'de> Deserialize<'de> for StakesCollection {
+impl<'de, K: Kind> Deserialize<'de> for StakesCollection<K> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
@@ -791,21 +1448,21 @@ impl<'de> Deserialize<'de> for StakesCollection {
             }
         }
 
-        struct StakesCollectionVisitor;
+        struct StakesCollectionVisitor<K>(PhantomData<K>);
 
-        impl<'de> Visitor<'de> for StakesCollectionVisitor {
-            type Value = StakesCollection;
+        impl<'de, K: Kind> Visitor<'de> for StakesCollectionVisitor<K> {
+            type Value = StakesCollection<K>;
 
             fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
                 formatter.write_str("struct StakesCollection")
             }
 
-            fn visit_map<V>(self, mut map: V) -> Result<StakesCollection, V::Error>
+            fn visit_map<V>(self, mut map: V) -> Result<StakesCollection<K>, V::Error>
             where
                 V: MapAccess<'de>,
             {
                 let mut next_id: Option<u32> = None;
-                let mut stakes: Option<IndexMap<StakeId, Stake>> = None;
+                let mut stakes: Option<IndexMap<StakeId<K>, Stake<K>>> = None;
 
                 while let Some(key) = map.next_key()? {
                     match key {
@@ -827,14 +1484,15 @@ impl<'de> Deserialize<'de> for StakesCollection {
                 let next_id = next_id.ok_or_else(|| de::Error::missing_field("nextId"))?;
                 let stakes = stakes.ok_or_else(|| de::Error::missing_field("stakes"))?;
 
-                Ok(StakesCollection {
-                    stakes,
-                    next_id: StakeId(next_id),
-                })
+                Ok(StakesCollection::from_parts(stakes, StakeId::new(next_id)))
             }
         }
 
-        const FIELDS: &'static [&'static str] = &["nextId", "stakes"];
-        deserializer.deserialize_struct("StakesCollection", FIELDS, StakesCollectionVisitor)
+        const FIELDS: &[&str] = &["nextId", "stakes"];
+        deserializer.deserialize_struct(
+            "StakesCollection",
+            FIELDS,
+            StakesCollectionVisitor(PhantomData),
+        )
     }
 }
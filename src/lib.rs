@@ -0,0 +1,17 @@
+// Library target so benchmarks and integration tests can use the domain model.
+pub mod auth;
+pub mod capture;
+pub mod client;
+pub mod config;
+pub mod controller;
+pub mod crdt;
+pub mod dates;
+pub mod entities;
+pub mod markdown;
+pub mod mlw;
+pub mod operations;
+pub mod presenter;
+pub mod serializer;
+pub mod shell;
+pub mod tui;
+pub mod workspaces;
@@ -1,33 +1,415 @@
-#![allow(dead_code)]
-#![allow(unused_variables)]
-#![allow(unused_imports)]
-// Declare the 'entities' module. This points to src/entities/mod.rs
-mod entities;
-mod mlw;
-// Bring the structs and enums into scope from the re-exports in entities/mod.rs
-use entities::{Stake, StakeId, StakesCollection};
-use mlw::MLW; // StakeError is not used here directly
-
-fn main() {
-    let mlw_app = MLW::new();
-    let mut areas = StakesCollection::new();
-    // let projects = StakesCollection::new();
-    // let mut tasks = StakesCollection::new();
-
-    let mut finance = Stake::new(
-        areas.generate_id(),
-        "Financial Management".to_string(),
-        None,
-        None,
-    );
-
-    areas.add_stake(finance.clone());
-
-    finance.mark_complete();
-    let id = finance.stake_id.clone();
-    let _ = areas.update_stake(finance);
-    println!(
-        "Debug representation of my_stake: {:?}",
-        areas.get_by_id(&id)
-    );
+// The `mlw` command-line interface. Each invocation loads the data file, runs one
+// command through `operations`, saves if anything changed and prints the result;
+// with --remote the command runs on a `mlw serve` server instead.
+// `mlw shell` instead keeps the file open in an interactive session, `mlw tui` in a
+// full-screen terminal UI, and `mlw serve` in a JSON-RPC and REST server. `mlw sync`
+// merges the data file with a server's. `mlw user` and `mlw token` manage who may
+// use that server, and `mlw team` who shares the data and can be assigned stakes.
+// `mlw workspace` keeps several named data files, one of which is current, and the
+// data file is the current workspace's unless --file says otherwise.
+// `mlw import` adds the stakes in a Markdown file, the format `mlw export` writes.
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use chrono::Utc;
+use clap::{Parser, Subcommand};
+use mlw_rust::auth::{Access, Accounts};
+use mlw_rust::client::MlwClient;
+use mlw_rust::config::Config;
+use mlw_rust::entities::Clock;
+use mlw_rust::operations::{self, Command};
+use mlw_rust::workspaces::{self, Workspaces};
+use mlw_rust::{controller, markdown, presenter, serializer, shell, tui};
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "mlw",
+    version,
+    about = "Mind Like Water: a GTD system for the command line"
+)]
+struct Cli {
+    /// Data file to read and write [default: the current workspace's, ~/.mlw.json at first]
+    #[arg(long, global = true, env = "MLW_FILE")]
+    file: Option<PathBuf>,
+    /// Use this workspace's data file instead of the current workspace's
+    #[arg(
+        long,
+        global = true,
+        env = "MLW_WORKSPACE",
+        value_name = "NAME",
+        conflicts_with = "file"
+    )]
+    workspace: Option<String>,
+    /// Print machine-readable JSON instead of tables
+    #[arg(long, global = true)]
+    json: bool,
+    /// Run commands on a `mlw serve` server at HOST:PORT instead of the data file
+    #[arg(long, global = true, env = "MLW_REMOTE", value_name = "HOST:PORT")]
+    remote: Option<String>,
+    /// Access token for the server used by --remote and sync
+    #[arg(long, global = true, env = "MLW_TOKEN", hide_env_values = true)]
+    token: Option<String>,
+    /// Record changes as made by this team member; a server uses the token's user instead
+    #[arg(long = "user", global = true, env = "MLW_USER", value_name = "NAME")]
+    author: Option<String>,
+    #[command(subcommand)]
+    command: CliCommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum CliCommand {
+    #[command(flatten)]
+    Run(Command),
+    /// Start an interactive shell that keeps your data in memory until you exit
+    Shell,
+    /// Browse and edit your data in a full-screen terminal UI
+    Tui,
+    /// Serve your data to JSON-RPC clients over TCP or a Unix socket, and over HTTP
+    Serve {
+        /// Address to listen on [default: 127.0.0.1:7878 unless --socket or --http is given]
+        #[arg(long, value_name = "HOST:PORT")]
+        listen: Option<String>,
+        /// Also (or only) listen on this Unix domain socket
+        #[arg(long, value_name = "PATH")]
+        socket: Option<PathBuf>,
+        /// Also (or only) serve the REST API over HTTP at this address
+        #[arg(long, value_name = "HOST:PORT")]
+        http: Option<String>,
+    },
+    /// Exchange changes with a `mlw serve` server, so both end up with the same data
+    Sync {
+        /// The server's JSON-RPC address [default: --remote]
+        #[arg(value_name = "HOST:PORT")]
+        server: Option<String>,
+    },
+    /// Show or change settings: timezone and week-start
+    Config {
+        #[command(subcommand)]
+        action: Option<ConfigAction>,
+    },
+    /// Manage who may use `mlw serve`; lists users and their tokens by default
+    User {
+        #[command(subcommand)]
+        action: Option<UserAction>,
+    },
+    /// Issue or revoke the access tokens `mlw serve` asks clients for
+    Token {
+        #[command(subcommand)]
+        action: TokenAction,
+    },
+    /// Add the areas, projects and tasks in a Markdown file, such as one `export` wrote
+    Import {
+        #[arg(value_name = "FILE")]
+        document: PathBuf,
+    },
+    /// Keep separate data files by name, e.g. work and personal; lists them by default
+    Workspace {
+        #[command(subcommand)]
+        action: Option<WorkspaceAction>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum ConfigAction {
+    /// Change a setting, e.g. `config set timezone Europe/Berlin`
+    Set { key: String, value: String },
+}
+
+#[derive(Debug, Subcommand)]
+enum UserAction {
+    /// Add a user, who can then be issued tokens
+    Add { name: String },
+    /// Remove a user and revoke all their tokens
+    Remove { name: String },
+    /// List users and their tokens
+    List,
+}
+
+#[derive(Debug, Subcommand)]
+enum TokenAction {
+    /// Issue a token for a user; it is shown only this once
+    Issue {
+        user: String,
+        /// Allow only reads: listing, searching and the like
+        #[arg(long)]
+        read_only: bool,
+    },
+    /// Revoke a token by the id `user list` shows
+    Revoke { id: String },
+}
+
+#[derive(Debug, Subcommand)]
+enum WorkspaceAction {
+    /// List workspaces, marking the current one with *
+    List,
+    /// Create a workspace with a data file of its own
+    Create {
+        name: String,
+        /// Keep its data in this file, which may already exist [default: ~/.mlw/NAME.json]
+        #[arg(long, value_name = "PATH")]
+        data: Option<PathBuf>,
+        /// Also make it the current workspace
+        #[arg(long)]
+        switch: bool,
+    },
+    /// Make a workspace the current one
+    Switch { name: String },
+    /// Rename a workspace; its data file stays where it is
+    Rename { name: String, new_name: String },
+    /// Delete a workspace and its data file
+    Delete {
+        name: String,
+        /// Only forget the workspace, leaving its data file in place
+        #[arg(long)]
+        keep_file: bool,
+    },
+    /// Search every workspace at once, best match first
+    Search {
+        #[arg(required = true)]
+        query: Vec<String>,
+        /// Match names approximately instead of by whole words
+        #[arg(long)]
+        fuzzy: bool,
+    },
+}
+
+fn home_dir() -> PathBuf {
+    std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .unwrap_or_default()
+}
+
+fn load_workspaces(home: &Path) -> Result<Workspaces, Box<dyn std::error::Error>> {
+    let workspaces = serializer::load_workspaces(&Workspaces::path_for(home))?;
+    Ok(workspaces.with_default(home.join(".mlw.json")))
+}
+
+fn run(cli: Cli) -> Result<String, Box<dyn std::error::Error>> {
+    let home = home_dir();
+    if let CliCommand::Workspace { action } = cli.command {
+        if cli.remote.is_some() {
+            return Err("--remote works with one-shot commands only".into());
+        }
+        return manage_workspaces(&home, action.unwrap_or(WorkspaceAction::List), cli.json);
+    }
+    let path = match (cli.file, cli.workspace) {
+        (Some(file), _) => file,
+        (None, workspace) => {
+            let workspaces = load_workspaces(&home)?;
+            let name = workspace.as_deref().unwrap_or(workspaces.current());
+            workspaces.file(name)?.to_path_buf()
+        }
+    };
+    let command = match cli.command {
+        CliCommand::Run(command) => command,
+        CliCommand::Shell
+        | CliCommand::Tui
+        | CliCommand::Serve { .. }
+        | CliCommand::User { .. }
+        | CliCommand::Token { .. }
+        | CliCommand::Import { .. }
+            if cli.remote.is_some() =>
+        {
+            return Err("--remote works with one-shot commands only".into());
+        }
+        CliCommand::Shell => {
+            shell::run(&path, cli.json, cli.author)?;
+            return Ok(String::new());
+        }
+        CliCommand::Tui => {
+            tui::run(&path, cli.author)?;
+            return Ok(String::new());
+        }
+        CliCommand::Serve {
+            listen,
+            socket,
+            http,
+        } => {
+            controller::run(&path, listen.as_deref(), socket.as_deref(), http.as_deref())?;
+            return Ok(String::new());
+        }
+        CliCommand::Sync { server } => {
+            let Some(address) = server.or(cli.remote) else {
+                return Err("sync needs a server address, or --remote".into());
+            };
+            let mut mlw = serializer::load(&path)?;
+            let report = client(address, cli.token).sync(&mut mlw)?;
+            serializer::save(&path, &mlw)?;
+            return Ok(presenter::render_sync(&report, cli.json));
+        }
+        CliCommand::Config { action } => return configure(&path, action),
+        CliCommand::User { action } => {
+            return manage_users(&path, action.unwrap_or(UserAction::List), cli.json);
+        }
+        CliCommand::Token { action } => return manage_tokens(&path, action),
+        CliCommand::Import { document } => {
+            let text = std::fs::read_to_string(&document)
+                .map_err(|error| format!("cannot read {}: {}", document.display(), error))?;
+            let mut mlw = serializer::load(&path)?;
+            mlw.set_author(cli.author);
+            let imported = markdown::import(&mut mlw, &text)?;
+            serializer::save(&path, &mlw)?;
+            return Ok(presenter::render_import(&imported, cli.json));
+        }
+        CliCommand::Workspace { .. } => unreachable!("workspace commands are handled first"),
+    };
+    // Times are shown in the local config's timezone, even for a remote server.
+    let config = serializer::load_config(&Config::path_for(&path))?;
+    if let Some(address) = cli.remote {
+        let outcome = client(address, cli.token).execute(&command)?;
+        return Ok(presenter::render(&outcome, cli.json, config.timezone));
+    }
+    let mut mlw = serializer::load(&path)?;
+    mlw.set_author(cli.author);
+    let dates = config.date_context(mlw.clock().now());
+    let outcome = operations::execute(&mut mlw, &command, &dates)?;
+    if command.is_mutating() {
+        serializer::save(&path, &mlw)?;
+    }
+    Ok(presenter::render(&outcome, cli.json, config.timezone))
+}
+
+fn configure(
+    data_file: &Path,
+    action: Option<ConfigAction>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let path = Config::path_for(data_file);
+    let mut config = serializer::load_config(&path)?;
+    if let Some(ConfigAction::Set { key, value }) = action {
+        config.set(&key, &value)?;
+        serializer::save_config(&path, &config)?;
+    }
+    Ok(config.to_string())
+}
+
+fn client(address: String, token: Option<String>) -> MlwClient {
+    match token {
+        Some(token) => MlwClient::new(address).with_token(token),
+        None => MlwClient::new(address),
+    }
+}
+
+fn manage_users(
+    data_file: &Path,
+    action: UserAction,
+    json: bool,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let path = Accounts::path_for(data_file);
+    let mut accounts = serializer::load_accounts(&path)?;
+    let message = match action {
+        UserAction::Add { name } => {
+            accounts.add_user(&name)?;
+            format!("Added user {}.", name)
+        }
+        UserAction::Remove { name } => {
+            accounts.remove_user(&name)?;
+            format!("Removed user {} and their tokens.", name)
+        }
+        UserAction::List => {
+            let config = serializer::load_config(&Config::path_for(data_file))?;
+            return Ok(presenter::render_accounts(&accounts, json, config.timezone));
+        }
+    };
+    serializer::save_accounts(&path, &accounts)?;
+    Ok(message)
+}
+
+fn manage_tokens(
+    data_file: &Path,
+    action: TokenAction,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let path = Accounts::path_for(data_file);
+    let mut accounts = serializer::load_accounts(&path)?;
+    let message = match action {
+        TokenAction::Issue { user, read_only } => {
+            let access = if read_only {
+                Access::Read
+            } else {
+                Access::Write
+            };
+            let token = accounts.issue_token(&user, access, Utc::now())?;
+            eprintln!(
+                "Issued a {} token for {}. It is shown only once:",
+                access, user
+            );
+            token
+        }
+        TokenAction::Revoke { id } => {
+            accounts.revoke_token(&id)?;
+            format!("Revoked token {}.", id)
+        }
+    };
+    serializer::save_accounts(&path, &accounts)?;
+    Ok(message)
+}
+
+fn manage_workspaces(
+    home: &Path,
+    action: WorkspaceAction,
+    json: bool,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let mut workspaces = load_workspaces(home)?;
+    let message = match action {
+        WorkspaceAction::List => return Ok(presenter::render_workspaces(&workspaces, json)),
+        WorkspaceAction::Search { query, fuzzy } => {
+            let hits = workspaces.search(&query.join(" "), fuzzy)?;
+            return Ok(presenter::render_workspace_hits(&hits, json));
+        }
+        WorkspaceAction::Create { name, data, switch } => {
+            // Made absolute, so it means the same file from any directory and is
+            // recognised when another workspace names it differently.
+            let file = match data {
+                Some(data) => std::path::absolute(data)?,
+                None => Workspaces::file_for(home, &name),
+            };
+            let message = format!("Created workspace {} in {}.", name, file.display());
+            workspaces.create(&name, file)?;
+            if switch {
+                workspaces.switch(&name)?;
+            }
+            message
+        }
+        WorkspaceAction::Switch { name } => {
+            workspaces.switch(&name)?;
+            format!("Switched to workspace {}.", name)
+        }
+        WorkspaceAction::Rename { name, new_name } => {
+            workspaces.rename(&name, &new_name)?;
+            format!("Renamed workspace {} to {}.", name, new_name)
+        }
+        WorkspaceAction::Delete { name, keep_file } => {
+            let file = workspaces.remove(&name)?;
+            if keep_file {
+                format!(
+                    "Removed workspace {}; its data stays in {}.",
+                    name,
+                    file.display()
+                )
+            } else {
+                // Forgotten first: a failed save must not leave a workspace listed
+                // whose data is gone.
+                serializer::save_workspaces(&Workspaces::path_for(home), &workspaces)?;
+                workspaces::delete_files(&file)?;
+                return Ok(format!("Deleted workspace {} and its data.", name));
+            }
+        }
+    };
+    serializer::save_workspaces(&Workspaces::path_for(home), &workspaces)?;
+    Ok(message)
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(output) => {
+            if !output.is_empty() {
+                println!("{}", output);
+            }
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -0,0 +1,83 @@
+// MLW as Markdown, for pasting into wikis and status documents. `export` writes the
+// hierarchy as headings and checkbox lists, and `import` reads them back:
+//
+//     - [ ] Task without a project
+//
+//     ## Project without an area
+//
+//     # Area
+//
+//     > A note on the area
+//
+//     ## [x] Completed project
+//
+//     - [x] Completed task
+//     - [ ] ~~Dropped task~~
+//       A note on the task, indented under it
+//
+// Tasks without a project come first and projects without an area next, so that
+// every heading owns what follows it up to the next heading of its level. Names are
+// escaped so that Markdown shows them as typed; notes are written as they are.
+
+pub mod export;
+pub mod import;
+
+pub use export::{ExportOptions, export};
+pub use import::{Imported, import};
+
+// Characters that could make a name read as formatting: emphasis, strikethrough,
+// links, checkboxes, code, HTML and heading markers.
+const SPECIAL: [char; 9] = ['\\', '`', '*', '_', '~', '[', ']', '<', '#'];
+
+// A name as Markdown text that shows it as typed.
+fn escape(name: &str) -> String {
+    let mut escaped = String::with_capacity(name.len());
+    for c in name.chars() {
+        if SPECIAL.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+// The name a Markdown text shows: backslashes before punctuation are dropped.
+fn unescape(text: &str) -> String {
+    let mut name = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match chars.peek() {
+            Some(next) if c == '\\' && next.is_ascii_punctuation() => {
+                name.push(*next);
+                chars.next();
+            }
+            _ => name.push(c),
+        }
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_leaves_plain_names_alone() {
+        assert_eq!(escape("Call mom"), "Call mom");
+        assert_eq!(escape("Learn C# and *Rust*"), r"Learn C\# and \*Rust\*");
+        assert_eq!(escape(r"[x] a\b ~~c~~"), r"\[x\] a\\b \~\~c\~\~");
+    }
+
+    #[test]
+    fn test_unescape_undoes_escape() {
+        for name in [
+            "Call mom",
+            "Learn C# and *Rust*",
+            r"[x] a\b ~~c~~",
+            r"ends in \",
+        ] {
+            assert_eq!(unescape(&escape(name)), name);
+        }
+        assert_eq!(unescape(r"C:\Users \d"), r"C:\Users \d");
+    }
+}
@@ -0,0 +1,270 @@
+// Writes MLW as Markdown; `markdown` shows the layout. Completed stakes carry `[x]`
+// and dropped ones are struck through, so a stake keeps its status wherever it sits.
+
+use super::escape;
+use crate::entities::{Kind, Stake, StakeError, Task};
+use crate::mlw::MLW;
+use crate::mlw::changes::Scope;
+use crate::mlw::hierarchy::{AreaNode, ProjectNode};
+
+/// What to export. The default is everything, open stakes only.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportOptions {
+    /// The whole hierarchy, or one area or project with everything under it.
+    pub scope: Scope,
+    /// Include completed stakes.
+    pub completed: bool,
+    /// Include dropped stakes.
+    pub dropped: bool,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        ExportOptions {
+            scope: Scope::Everything,
+            completed: false,
+            dropped: false,
+        }
+    }
+}
+
+impl ExportOptions {
+    /// Everything, completed and dropped stakes included.
+    pub fn all() -> Self {
+        ExportOptions {
+            completed: true,
+            dropped: true,
+            ..Self::default()
+        }
+    }
+
+    // Whether a stake is shown. Leaving one out leaves out everything under it.
+    fn includes<K: Kind>(&self, stake: &Stake<K>) -> bool {
+        if stake.dropped {
+            self.dropped
+        } else if stake.complete {
+            self.completed
+        } else {
+            true
+        }
+    }
+}
+
+/// Renders the stakes `options` selects as Markdown, ending in a newline unless
+/// there is nothing to show. An exported area or project is shown even if its own
+/// status is left out. Fails if that area or project does not exist.
+pub fn export(mlw: &MLW, options: &ExportOptions) -> Result<String, StakeError> {
+    let mut document = Document {
+        options,
+        blocks: Vec::new(),
+    };
+    match &options.scope {
+        Scope::Everything => {
+            let hierarchy = mlw.hierarchy();
+            document.tasks(&hierarchy.unfiled_tasks);
+            for project in &hierarchy.unfiled_projects {
+                document.project(project, false);
+            }
+            for area in &hierarchy.areas {
+                document.area(area, false);
+            }
+        }
+        Scope::Area(id) => {
+            let area = mlw.area_tree(id).ok_or(StakeError::StakeNotFound)?;
+            document.area(&area, true);
+        }
+        Scope::Project(id) => {
+            let project = mlw.project_tree(id).ok_or(StakeError::StakeNotFound)?;
+            document.project(&project, true);
+        }
+    }
+    Ok(document.finish())
+}
+
+// The export so far, as blocks to be separated by blank lines.
+struct Document<'a> {
+    options: &'a ExportOptions,
+    blocks: Vec<String>,
+}
+
+impl Document<'_> {
+    fn area(&mut self, node: &AreaNode, chosen: bool) {
+        if !chosen && !self.options.includes(node.area) {
+            return;
+        }
+        self.heading(1, node.area);
+        for project in &node.projects {
+            self.project(project, false);
+        }
+    }
+
+    fn project(&mut self, node: &ProjectNode, chosen: bool) {
+        if !chosen && !self.options.includes(node.project) {
+            return;
+        }
+        self.heading(2, node.project);
+        self.tasks(&node.tasks);
+    }
+
+    fn heading<K: Kind>(&mut self, level: usize, stake: &Stake<K>) {
+        let done = if stake.complete { "[x] " } else { "" };
+        self.blocks
+            .push(format!("{} {}{}", "#".repeat(level), done, name(stake)));
+        if let Some(note) = &stake.note {
+            let quoted: Vec<String> = note
+                .lines()
+                .map(|line| format!("> {}", line).trim_end().to_string())
+                .collect();
+            self.blocks.push(quoted.join("\n"));
+        }
+    }
+
+    fn tasks(&mut self, tasks: &[&Stake<Task>]) {
+        let mut lines = Vec::new();
+        for task in tasks.iter().filter(|task| self.options.includes(task)) {
+            let checkbox = if task.complete { "[x]" } else { "[ ]" };
+            lines.push(format!("- {} {}", checkbox, name(task)));
+            // Indented under the item, blank lines included, so Markdown keeps the
+            // note with its task.
+            for line in task.note.iter().flat_map(|note| note.lines()) {
+                lines.push(format!("  {}", line).trim_end().to_string());
+            }
+        }
+        if !lines.is_empty() {
+            self.blocks.push(lines.join("\n"));
+        }
+    }
+
+    fn finish(self) -> String {
+        if self.blocks.is_empty() {
+            return String::new();
+        }
+        let mut text = self.blocks.join("\n\n");
+        text.push('\n');
+        text
+    }
+}
+
+fn name<K: Kind>(stake: &Stake<K>) -> String {
+    let name = escape(&stake.stake_name);
+    if stake.dropped {
+        format!("~~{}~~", name)
+    } else {
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::{AreaId, ProjectId};
+
+    // Home > Garden > (Dig beds, Water, Weed) with notes on Home and Dig beds, a
+    // completed and a dropped task, a project without an area (with a note) and a
+    // task without a project.
+    fn sample() -> MLW {
+        let mut mlw = MLW::new();
+        let home = mlw.new_area("Home".to_string(), Some("Where we live".to_string()));
+        let garden = mlw.new_project("Garden".to_string(), Some(home.stake_id), None);
+        mlw.new_task(
+            "Dig beds".to_string(),
+            Some(garden.stake_id.clone()),
+            Some("By the fence\n\nBefore the rain".to_string()),
+        );
+        let water = mlw.new_task("Water".to_string(), Some(garden.stake_id.clone()), None);
+        let weed = mlw.new_task("Weed".to_string(), Some(garden.stake_id), None);
+        mlw.mark_task_complete(&water.stake_id).unwrap();
+        mlw.mark_task_dropped(&weed.stake_id).unwrap();
+        mlw.new_project("Taxes".to_string(), None, Some("Due in April".to_string()));
+        mlw.new_task("Call *mom*".to_string(), None, None);
+        mlw
+    }
+
+    #[test]
+    fn test_export_everything_open_only_by_default() {
+        let text = export(&sample(), &ExportOptions::default()).unwrap();
+        let expected = [
+            r"- [ ] Call \*mom\*",
+            "",
+            "## Taxes",
+            "",
+            "> Due in April",
+            "",
+            "# Home",
+            "",
+            "> Where we live",
+            "",
+            "## Garden",
+            "",
+            "- [ ] Dig beds",
+            "  By the fence",
+            "",
+            "  Before the rain",
+            "",
+        ];
+        assert_eq!(text, expected.join("\n"));
+    }
+
+    #[test]
+    fn test_export_marks_completed_and_dropped_stakes() {
+        let mut mlw = sample();
+        mlw.mark_project_complete(&ProjectId::new(2)).unwrap();
+        let text = export(&mlw, &ExportOptions::all()).unwrap();
+        assert!(text.contains("## [x] Taxes\n"));
+        assert!(text.contains("- [x] Water\n- [ ] ~~Weed~~\n"));
+
+        let completed = ExportOptions {
+            completed: true,
+            ..ExportOptions::default()
+        };
+        let text = export(&mlw, &completed).unwrap();
+        assert!(text.contains("- [x] Water\n"));
+        assert!(!text.contains("Weed"));
+    }
+
+    #[test]
+    fn test_leaving_out_a_stake_leaves_out_what_is_under_it() {
+        let mut mlw = sample();
+        mlw.mark_area_dropped(&AreaId::new(1)).unwrap();
+        let text = export(&mlw, &ExportOptions::default()).unwrap();
+        assert!(!text.contains("Home"));
+        assert!(!text.contains("Garden"));
+        assert!(!text.contains("Dig"));
+        assert!(text.contains("## Taxes"));
+    }
+
+    #[test]
+    fn test_export_one_area_or_project() {
+        let mut mlw = sample();
+        mlw.mark_project_complete(&ProjectId::new(1)).unwrap();
+        let project = ExportOptions {
+            scope: Scope::Project(ProjectId::new(1)),
+            ..ExportOptions::default()
+        };
+        let text = export(&mlw, &project).unwrap();
+        assert!(text.starts_with("## [x] Garden\n\n- [ ] Dig beds\n"));
+        assert!(!text.contains("Home"));
+        assert!(!text.contains("Taxes"));
+
+        let area = ExportOptions {
+            scope: Scope::Area(AreaId::new(1)),
+            ..ExportOptions::all()
+        };
+        let text = export(&mlw, &area).unwrap();
+        assert!(text.starts_with("# Home\n"));
+        assert!(text.contains("## [x] Garden\n"));
+        assert!(!text.contains("Taxes"));
+        assert!(!text.contains("Call"));
+
+        let missing = ExportOptions {
+            scope: Scope::Area(AreaId::new(9)),
+            ..ExportOptions::default()
+        };
+        assert_eq!(export(&mlw, &missing), Err(StakeError::StakeNotFound));
+    }
+
+    #[test]
+    fn test_export_of_nothing_is_empty() {
+        assert_eq!(export(&MLW::new(), &ExportOptions::default()).unwrap(), "");
+    }
+}
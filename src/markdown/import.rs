@@ -0,0 +1,317 @@
+// Reads Markdown into MLW, the other way from `export`: `# ` headings become areas,
+// deeper headings projects in the area above them, and list items tasks in the
+// project above them, with `[x]` marking completed stakes and `~~name~~` dropped
+// ones. Tasks before any project heading and projects before any area heading are
+// left unfiled. Quoted or plain lines after a heading are its note, and lines
+// indented under a task are the task's note; text before the first stake is
+// skipped. Whatever `export` writes reads back as the same stakes.
+
+use super::unescape;
+use crate::entities::StakeError;
+use crate::mlw::MLW;
+use serde::Serialize;
+
+/// How many stakes an import added, and how many lines it could not place.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Imported {
+    pub areas: usize,
+    pub projects: usize,
+    pub tasks: usize,
+    /// Lines before the first heading or list item, and headings or items without
+    /// a name.
+    pub skipped: usize,
+}
+
+/// Adds the areas, projects and tasks `text` describes to `mlw`, after what is
+/// already there.
+pub fn import(mlw: &mut MLW, text: &str) -> Result<Imported, StakeError> {
+    let (entries, skipped) = parse(text);
+    let mut imported = Imported {
+        skipped,
+        ..Imported::default()
+    };
+    let mut area = None;
+    let mut project = None;
+    for entry in entries {
+        let note = (!entry.note.is_empty()).then(|| entry.note.join("\n"));
+        match entry.level {
+            Level::Area => {
+                let id = mlw.new_area(entry.name, note).stake_id;
+                if entry.complete {
+                    mlw.mark_area_complete(&id)?;
+                }
+                if entry.dropped {
+                    mlw.mark_area_dropped(&id)?;
+                }
+                area = Some(id);
+                project = None;
+                imported.areas += 1;
+            }
+            Level::Project => {
+                let id = mlw.new_project(entry.name, area.clone(), note).stake_id;
+                if entry.complete {
+                    mlw.mark_project_complete(&id)?;
+                }
+                if entry.dropped {
+                    mlw.mark_project_dropped(&id)?;
+                }
+                project = Some(id);
+                imported.projects += 1;
+            }
+            Level::Task => {
+                let id = mlw.new_task(entry.name, project.clone(), note).stake_id;
+                if entry.complete {
+                    mlw.mark_task_complete(&id)?;
+                }
+                if entry.dropped {
+                    mlw.mark_task_dropped(&id)?;
+                }
+                imported.tasks += 1;
+            }
+        }
+    }
+    Ok(imported)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Level {
+    Area,
+    Project,
+    Task,
+}
+
+// One heading or list item, in document order, with the note lines under it.
+#[derive(Debug, PartialEq, Eq)]
+struct Entry {
+    level: Level,
+    name: String,
+    complete: bool,
+    dropped: bool,
+    note: Vec<String>,
+}
+
+impl Entry {
+    // `text` is what follows the heading marker or bullet; None if it has no name.
+    fn parse(level: Level, text: &str) -> Option<Entry> {
+        let (complete, text) = checkbox(text.trim());
+        let (dropped, text) = match text.strip_prefix("~~").and_then(|t| t.strip_suffix("~~")) {
+            Some(inner) => (true, inner.trim()),
+            None => (false, text),
+        };
+        let name = unescape(text);
+        (!name.is_empty()).then_some(Entry {
+            level,
+            name,
+            complete,
+            dropped,
+            note: Vec::new(),
+        })
+    }
+}
+
+// The entries in `text`, and how many lines were skipped.
+fn parse(text: &str) -> (Vec<Entry>, usize) {
+    let mut entries: Vec<Entry> = Vec::new();
+    let mut skipped = 0;
+    // Blank lines since the last line with text, kept inside notes.
+    let mut blanks = 0;
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            blanks += 1;
+            continue;
+        }
+        let in_task = entries
+            .last()
+            .is_some_and(|entry| entry.level == Level::Task);
+        let trimmed = line.trim_start();
+        let note = match indented(line) {
+            Some(rest) if in_task => Some(rest),
+            _ => {
+                let parsed = heading(trimmed)
+                    .or_else(|| item(trimmed).map(|text| (Level::Task, text)))
+                    .map(|(level, text)| Entry::parse(level, text));
+                match parsed {
+                    Some(Some(entry)) => {
+                        entries.push(entry);
+                        None
+                    }
+                    Some(None) => {
+                        skipped += 1;
+                        None
+                    }
+                    None => Some(quoted(trimmed).unwrap_or(trimmed)),
+                }
+            }
+        };
+        if let Some(text) = note {
+            match entries.last_mut() {
+                Some(entry) => {
+                    if !entry.note.is_empty() {
+                        entry.note.extend((0..blanks).map(|_| String::new()));
+                    }
+                    entry.note.push(text.to_string());
+                }
+                None => skipped += 1,
+            }
+        }
+        blanks = 0;
+    }
+    (entries, skipped)
+}
+
+// A heading's level and text, without an optional closing run of `#`.
+fn heading(line: &str) -> Option<(Level, &str)> {
+    let text = line.trim_start_matches('#');
+    let hashes = line.len() - text.len();
+    if !(1..=6).contains(&hashes) || !(text.is_empty() || text.starts_with([' ', '\t'])) {
+        return None;
+    }
+    let text = text.trim();
+    let open = text.trim_end_matches('#');
+    let text = if open.is_empty() || open.ends_with([' ', '\t']) {
+        open.trim_end()
+    } else {
+        text
+    };
+    let level = if hashes == 1 {
+        Level::Area
+    } else {
+        Level::Project
+    };
+    Some((level, text))
+}
+
+// The text of a bulleted (`-`, `*`, `+`) or numbered (`1.`, `1)`) list item.
+fn item(line: &str) -> Option<&str> {
+    let rest = line.strip_prefix(['-', '*', '+']).or_else(|| {
+        let rest = line.trim_start_matches(|c: char| c.is_ascii_digit());
+        if rest.len() == line.len() {
+            return None;
+        }
+        rest.strip_prefix(['.', ')'])
+    })?;
+    (rest.is_empty() || rest.starts_with([' ', '\t'])).then_some(rest)
+}
+
+// Whether `text` starts with a checked box, and the text after any box.
+fn checkbox(text: &str) -> (bool, &str) {
+    for (mark, complete) in [("[ ]", false), ("[x]", true), ("[X]", true)] {
+        if let Some(rest) = text.strip_prefix(mark)
+            && (rest.is_empty() || rest.starts_with([' ', '\t']))
+        {
+            return (complete, rest.trim_start());
+        }
+    }
+    (false, text)
+}
+
+// A blockquote line without its `>` and the space after it.
+fn quoted(line: &str) -> Option<&str> {
+    let rest = line.strip_prefix('>')?;
+    Some(rest.strip_prefix(' ').unwrap_or(rest))
+}
+
+// A line indented under a list item, without the indent.
+fn indented(line: &str) -> Option<&str> {
+    line.strip_prefix("  ").or_else(|| line.strip_prefix('\t'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::{AreaId, ProjectId, TaskId};
+
+    fn entry(level: Level, name: &str) -> Entry {
+        Entry {
+            level,
+            name: name.to_string(),
+            complete: false,
+            dropped: false,
+            note: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_headings_and_items_become_entries() {
+        let (entries, skipped) =
+            parse("# Home\n## Garden ##\n### Beds\n- Dig\n* [X] Water\n2. Weed\n");
+        assert_eq!(skipped, 0);
+        let mut water = entry(Level::Task, "Water");
+        water.complete = true;
+        assert_eq!(
+            entries,
+            vec![
+                entry(Level::Area, "Home"),
+                entry(Level::Project, "Garden"),
+                entry(Level::Project, "Beds"),
+                entry(Level::Task, "Dig"),
+                water,
+                entry(Level::Task, "Weed"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_marks_and_escapes_are_read() {
+        let (entries, _) =
+            parse("## [x] ~~Old \\*plan\\*~~\n- [ ] \\[x\\] C\\# \\~\\~\n- ~~a\\~~~\n");
+        assert!(entries[0].complete && entries[0].dropped);
+        assert_eq!(entries[0].name, "Old *plan*");
+        assert!(!entries[1].complete && !entries[1].dropped);
+        assert_eq!(entries[1].name, "[x] C# ~~");
+        assert!(entries[2].dropped);
+        assert_eq!(entries[2].name, "a~");
+    }
+
+    #[test]
+    fn test_notes_follow_their_stake() {
+        let text = "Title text\n\n# Home\n\n> Where\n>\n> we live\n\nAnd more\n\n- [ ] Dig\n  By the fence\n\n    indented\n- [ ] Weed\n";
+        let (entries, skipped) = parse(text);
+        assert_eq!(skipped, 1);
+        assert_eq!(entries[0].note, ["Where", "", "we live", "", "And more"]);
+        assert_eq!(entries[1].note, ["By the fence", "", "  indented"]);
+        assert!(entries[2].note.is_empty());
+    }
+
+    #[test]
+    fn test_text_that_is_not_a_heading_or_item() {
+        let (entries, skipped) = parse("#hashtag\n- \n#######\n# Home\n---\n**bold**\n");
+        assert_eq!(skipped, 3);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].note, ["---", "**bold**"]);
+    }
+
+    #[test]
+    fn test_import_links_parents_and_sets_status() {
+        let mut mlw = MLW::new();
+        mlw.new_area("Existing".to_string(), None);
+        let text = "- [ ] Loose\n\n## Taxes\n\n# Home\n\n- [x] Straight under the area\n\n## ~~Garden~~\n\n- [x] Water\n";
+        let imported = import(&mut mlw, text).unwrap();
+        assert_eq!(
+            imported,
+            Imported {
+                areas: 1,
+                projects: 2,
+                tasks: 3,
+                skipped: 0
+            }
+        );
+
+        let home = mlw.get_area_by_id(&AreaId::new(2)).unwrap();
+        assert_eq!(home.stake_name, "Home");
+        let taxes = mlw.get_project_by_id(&ProjectId::new(1)).unwrap();
+        assert_eq!(taxes.parent_id, None);
+        let garden = mlw.get_project_by_id(&ProjectId::new(2)).unwrap();
+        assert_eq!(garden.parent_id, Some(AreaId::new(2)));
+        assert!(garden.dropped && !garden.complete);
+
+        let loose = mlw.get_task_by_id(&TaskId::new(1)).unwrap();
+        assert_eq!(loose.parent_id, None);
+        let under_area = mlw.get_task_by_id(&TaskId::new(2)).unwrap();
+        assert_eq!(under_area.parent_id, None);
+        assert!(under_area.complete);
+        let water = mlw.get_task_by_id(&TaskId::new(3)).unwrap();
+        assert_eq!(water.parent_id, Some(ProjectId::new(2)));
+        assert!(water.complete);
+    }
+}
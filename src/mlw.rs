@@ -1,12 +1,67 @@
-use crate::entities::stake::{Stake, StakeError, StakeId};
+use crate::entities::stake::{
+    Area, AreaId, Kind, Project, ProjectId, Stake, StakeError, StakeRef, Task, TaskId,
+};
 use crate::entities::stakes_collection::StakesCollection;
+use crate::entities::{Clock, SharedClock};
 use serde::{Deserialize, Serialize};
+use sync::SyncState;
+
+pub mod agenda;
+pub mod changes;
+pub mod hierarchy;
+pub mod sync;
+
+/// A fuzzy search result from any of MLW's collections.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FuzzyMatch<'a> {
+    pub stake: StakeRef<'a>,
+    pub score: f64,
+}
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(clippy::upper_case_acronyms)]
 pub struct MLW {
-    areas: StakesCollection,
-    projects: StakesCollection,
-    tasks: StakesCollection,
+    areas: StakesCollection<Area>,
+    projects: StakesCollection<Project>,
+    tasks: StakesCollection<Task>,
+    // Stamps creation, modification and review times. Not saved; a loaded MLW
+    // starts on the system clock.
+    #[serde(skip)]
+    clock: SharedClock,
+    // This replica's id and where each sync with another replica left off.
+    #[serde(default, skip_serializing_if = "SyncState::is_empty")]
+    sync: SyncState,
+    // The people who share this workspace; only they can be assigned stakes.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    members: Vec<String>,
+    // Who is making changes, recorded as `created_by` and `modified_by`. Not saved,
+    // like the clock; it is set by whoever opens the MLW.
+    #[serde(skip)]
+    author: Option<String>,
+}
+
+// Two MLWs are equal when they hold the same stakes and team, whatever clock they run
+// on, whoever they have synced with and in whichever order their members joined.
+impl PartialEq for MLW {
+    fn eq(&self, other: &Self) -> bool {
+        let team = |mlw: &MLW| {
+            let mut members = mlw.members.clone();
+            members.sort();
+            members
+        };
+        self.areas == other.areas
+            && self.projects == other.projects
+            && self.tasks == other.tasks
+            && team(self) == team(other)
+    }
+}
+
+impl Eq for MLW {}
+
+impl Default for MLW {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl MLW {
@@ -16,45 +71,163 @@ impl MLW {
             areas: StakesCollection::new(),
             projects: StakesCollection::new(),
             tasks: StakesCollection::new(),
+            clock: SharedClock::default(),
+            sync: SyncState::default(),
+            members: Vec::new(),
+            author: None,
+        }
+    }
+
+    /// Creates an empty MLW that takes the time from `clock` instead of the system.
+    pub fn with_clock(clock: SharedClock) -> Self {
+        let mut mlw = Self::new();
+        mlw.set_clock(clock);
+        mlw
+    }
+
+    /// Switches this MLW, including its collections, to `clock`.
+    pub fn set_clock(&mut self, clock: SharedClock) {
+        self.areas.set_clock(clock.clone());
+        self.projects.set_clock(clock.clone());
+        self.tasks.set_clock(clock.clone());
+        self.clock = clock;
+    }
+
+    /// The clock this MLW stamps times with.
+    pub fn clock(&self) -> &SharedClock {
+        &self.clock
+    }
+
+    /// Records later changes as made by `author`, or by nobody in particular.
+    pub fn set_author(&mut self, author: Option<String>) {
+        self.author = author;
+    }
+
+    /// Who changes made now are recorded as made by.
+    pub fn author(&self) -> Option<&str> {
+        self.author.as_deref()
+    }
+
+    // Stamps a new stake with its author.
+    fn created<K: Kind>(&self, mut stake: Stake<K>) -> Stake<K> {
+        stake.created_by = self.author.clone();
+        stake.modified_by = self.author.clone();
+        stake
+    }
+
+    // Stamps a changed stake with its author. The creator cannot be changed.
+    fn modified<K: Kind>(
+        &self,
+        collection: &StakesCollection<K>,
+        mut stake: Stake<K>,
+    ) -> Result<Stake<K>, StakeError> {
+        let existing = collection
+            .get_by_id(&stake.stake_id)
+            .ok_or(StakeError::StakeNotFound)?;
+        stake.created_by = existing.created_by.clone();
+        stake.modified_by = self.author.clone();
+        Ok(stake)
+    }
+
+    // --- Member Methods ---
+    /// The people who share this workspace, in the order they joined.
+    pub fn members(&self) -> &[String] {
+        &self.members
+    }
+
+    /// Adds a member. Returns false if they already were one.
+    pub fn add_member(&mut self, name: String) -> bool {
+        if self.is_member(&name) {
+            return false;
+        }
+        self.members.push(name);
+        true
+    }
+
+    /// Removes a member and unassigns everything assigned to them. Returns false if
+    /// they were not a member.
+    pub fn remove_member(&mut self, name: &str) -> bool {
+        let Some(position) = self.members.iter().position(|member| member == name) else {
+            return false;
+        };
+        self.members.remove(position);
+        let projects: Vec<ProjectId> = self
+            .projects_assigned_to(name)
+            .iter()
+            .map(|project| project.stake_id.clone())
+            .collect();
+        for id in projects {
+            self.assign_project(&id, None)
+                .expect("the project was just found");
+        }
+        let tasks: Vec<TaskId> = self
+            .tasks_assigned_to(name)
+            .iter()
+            .map(|task| task.stake_id.clone())
+            .collect();
+        for id in tasks {
+            self.assign_task(&id, None)
+                .expect("the task was just found");
+        }
+        true
+    }
+
+    /// Whether `name` is one of the people sharing this workspace.
+    pub fn is_member(&self, name: &str) -> bool {
+        self.members.iter().any(|member| member == name)
+    }
+
+    fn check_member(&self, assignee: Option<&String>) -> Result<(), StakeError> {
+        match assignee {
+            Some(name) if !self.is_member(name) => Err(StakeError::UnknownUser(name.clone())),
+            _ => Ok(()),
         }
     }
 
     // --- Area Management Methods ---
     /// Creates a new area Stake, assigns it an ID, and adds it to the areas collection.
-    pub fn new_area(&mut self, name: String, note: Option<String>) -> Stake {
+    pub fn new_area(&mut self, name: String, note: Option<String>) -> Stake<Area> {
         let id = self.areas.generate_id();
-        let new_area_stake = Stake::new(id, name, None, note); // Areas typically have no parent_id
+        // Areas typically have no parent_id
+        let mut new_area_stake =
+            self.created(Stake::new_with_clock(id, name, None, note, &self.clock));
+        new_area_stake.ensure_global_id();
         self.areas.add_stake(new_area_stake.clone()); // Add a clone to the collection
         new_area_stake // Return the owned Stake
     }
 
     /// Returns a vector of references to active area Stakes.
-    pub fn active_areas(&self) -> Vec<&Stake> {
+    pub fn active_areas(&self) -> Vec<&Stake<Area>> {
         self.areas.active_stakes()
     }
 
     /// Returns a vector of references to completed area Stakes.
-    pub fn completed_areas(&self) -> Vec<&Stake> {
+    pub fn completed_areas(&self) -> Vec<&Stake<Area>> {
         self.areas.completed_stakes()
     }
 
     /// Returns the next available ID for an area Stake without consuming it.
-    pub fn next_area_id(&self) -> StakeId {
+    pub fn next_area_id(&self) -> AreaId {
         self.areas.next_id()
     }
 
     /// Retrieves a reference to an area Stake by its ID.
-    pub fn get_area_by_id(&self, id: &StakeId) -> Option<&Stake> {
+    pub fn get_area_by_id(&self, id: &AreaId) -> Option<&Stake<Area>> {
         self.areas.get_by_id(id)
     }
 
     /// Updates an existing area Stake in the collection.
     /// Returns `Ok(())` if the stake was found and updated, `Err(StakeError::StakeNotFound)` otherwise.
-    pub fn update_area(&mut self, stake: Stake) -> Result<(), StakeError> {
+    /// Areas are never assigned, so an assignee is `Err(StakeError::CannotAssignArea)`.
+    pub fn update_area(&mut self, stake: Stake<Area>) -> Result<(), StakeError> {
+        if stake.assignee.is_some() {
+            return Err(StakeError::CannotAssignArea);
+        }
+        let stake = self.modified(&self.areas, stake)?;
         self.areas.update_stake(stake)
     }
 
-    fn locate_area(&self, id: &StakeId) -> Result<Stake, StakeError> {
+    fn locate_area(&self, id: &AreaId) -> Result<Stake<Area>, StakeError> {
         Ok(self
             .areas
             .get_by_id(id)
@@ -64,17 +237,28 @@ impl MLW {
 
     /// Marks an area Stake as complete and updates its modified date.
     /// Returns `Ok(())` if the stake was found and updated, `Err(StakeError::StakeNotFound)` otherwise.
-    pub fn mark_area_complete(&mut self, id: &StakeId) -> Result<(), StakeError> {
+    pub fn mark_area_complete(&mut self, id: &AreaId) -> Result<(), StakeError> {
         let mut area_to_update = self.locate_area(id)?;
-        area_to_update.complete = true;
+        area_to_update.mark_complete(&self.clock);
+        area_to_update.modified_by = self.author.clone();
         self.areas.update_stake(area_to_update)
     }
 
     /// Marks an area Stake as dropped and updates its modified date.
     /// Returns `Ok(())` if the stake was found and updated, `Err(StakeError::StakeNotFound)` otherwise.
-    pub fn mark_area_dropped(&mut self, id: &StakeId) -> Result<(), StakeError> {
+    pub fn mark_area_dropped(&mut self, id: &AreaId) -> Result<(), StakeError> {
         let mut area_to_update = self.locate_area(id)?;
-        area_to_update.dropped = true;
+        area_to_update.mark_dropped(&self.clock);
+        area_to_update.modified_by = self.author.clone();
+        self.areas.update_stake(area_to_update)
+    }
+
+    /// Stamps an area Stake's `date_reviewed` with the current time.
+    /// Returns `Ok(())` if the stake was found and updated, `Err(StakeError::StakeNotFound)` otherwise.
+    pub fn mark_area_reviewed(&mut self, id: &AreaId) -> Result<(), StakeError> {
+        let mut area_to_update = self.locate_area(id)?;
+        area_to_update.mark_reviewed(&self.clock);
+        area_to_update.modified_by = self.author.clone();
         self.areas.update_stake(area_to_update)
     }
 
@@ -82,119 +266,299 @@ impl MLW {
     pub fn new_project(
         &mut self,
         name: String,
-        parent_id: Option<StakeId>,
+        parent_id: Option<AreaId>,
         note: Option<String>,
-    ) -> Stake {
+    ) -> Stake<Project> {
         let id = self.projects.generate_id();
-        let new_project_stake = Stake::new(id, name, parent_id, note);
+        let mut new_project_stake = self.created(Stake::new_with_clock(
+            id,
+            name,
+            parent_id,
+            note,
+            &self.clock,
+        ));
+        new_project_stake.ensure_global_id();
         self.projects.add_stake(new_project_stake.clone());
         new_project_stake
     }
-    pub fn active_projects(&self) -> Vec<&Stake> {
+    pub fn active_projects(&self) -> Vec<&Stake<Project>> {
         self.projects.active_stakes()
     }
-    pub fn completed_projects(&self) -> Vec<&Stake> {
+    pub fn completed_projects(&self) -> Vec<&Stake<Project>> {
         self.projects.completed_stakes()
     }
-    pub fn next_project_id(&self) -> StakeId {
+    pub fn next_project_id(&self) -> ProjectId {
         self.projects.next_id()
     }
-    pub fn get_project_by_id(&self, id: &StakeId) -> Option<&Stake> {
+    pub fn get_project_by_id(&self, id: &ProjectId) -> Option<&Stake<Project>> {
         self.projects.get_by_id(id)
     }
-    pub fn update_project(&mut self, stake: Stake) -> Result<(), StakeError> {
+    pub fn update_project(&mut self, stake: Stake<Project>) -> Result<(), StakeError> {
+        self.check_member(stake.assignee.as_ref())?;
+        let stake = self.modified(&self.projects, stake)?;
         self.projects.update_stake(stake)
     }
-    fn locate_project(&self, id: &StakeId) -> Result<Stake, StakeError> {
+    fn locate_project(&self, id: &ProjectId) -> Result<Stake<Project>, StakeError> {
         Ok(self
             .projects
             .get_by_id(id)
             .ok_or(StakeError::StakeNotFound)?
             .clone())
     }
-    pub fn mark_project_complete(&mut self, id: &StakeId) -> Result<(), StakeError> {
+    pub fn mark_project_complete(&mut self, id: &ProjectId) -> Result<(), StakeError> {
+        let mut project_to_update = self.locate_project(id)?;
+        project_to_update.mark_complete(&self.clock);
+        project_to_update.modified_by = self.author.clone();
+        self.projects.update_stake(project_to_update)
+    }
+    pub fn mark_project_dropped(&mut self, id: &ProjectId) -> Result<(), StakeError> {
         let mut project_to_update = self.locate_project(id)?;
-        project_to_update.complete = true;
+        project_to_update.mark_dropped(&self.clock);
+        project_to_update.modified_by = self.author.clone();
         self.projects.update_stake(project_to_update)
     }
-    pub fn mark_project_dropped(&mut self, id: &StakeId) -> Result<(), StakeError> {
+    pub fn mark_project_reviewed(&mut self, id: &ProjectId) -> Result<(), StakeError> {
         let mut project_to_update = self.locate_project(id)?;
-        project_to_update.dropped = true;
+        project_to_update.mark_reviewed(&self.clock);
+        project_to_update.modified_by = self.author.clone();
         self.projects.update_stake(project_to_update)
     }
-    pub fn get_project_children(&self, parent_id: &StakeId) -> Vec<&Stake> {
+    pub fn get_project_children(&self, parent_id: &AreaId) -> Vec<&Stake<Project>> {
         self.projects.get_children(parent_id)
     }
+    pub fn move_project(
+        &mut self,
+        id: &ProjectId,
+        new_parent_id: Option<AreaId>,
+    ) -> Result<(), StakeError> {
+        self.projects.move_stake(id, new_parent_id)?;
+        let mut project = self.locate_project(id)?;
+        project.modified_by = self.author.clone();
+        self.projects.update_stake(project)
+    }
+
+    /// Assigns a project to a member, or to nobody.
+    pub fn assign_project(
+        &mut self,
+        id: &ProjectId,
+        assignee: Option<String>,
+    ) -> Result<(), StakeError> {
+        let mut project = self.locate_project(id)?;
+        project.assignee = assignee;
+        project.date_modified = self.clock.now();
+        self.update_project(project)
+    }
+
+    /// Every project assigned to `user`, including completed and dropped ones.
+    pub fn projects_assigned_to(&self, user: &str) -> Vec<&Stake<Project>> {
+        self.projects
+            .iter()
+            .filter(|project| project.assignee.as_deref() == Some(user))
+            .collect()
+    }
 
     // --- Task Management Methods (Placeholder - you'll build these out next) ---
     pub fn new_task(
         &mut self,
         name: String,
-        parent_id: Option<StakeId>,
+        parent_id: Option<ProjectId>,
         note: Option<String>,
-    ) -> Stake {
+    ) -> Stake<Task> {
         let id = self.tasks.generate_id();
-        let new_task_stake = Stake::new(id, name, parent_id, note);
+        let mut new_task_stake = self.created(Stake::new_with_clock(
+            id,
+            name,
+            parent_id,
+            note,
+            &self.clock,
+        ));
+        new_task_stake.ensure_global_id();
         self.tasks.add_stake(new_task_stake.clone());
         new_task_stake
     }
-    pub fn active_tasks(&self) -> Vec<&Stake> {
+    pub fn active_tasks(&self) -> Vec<&Stake<Task>> {
         self.tasks.active_stakes()
     }
-    pub fn completed_tasks(&self) -> Vec<&Stake> {
+    pub fn completed_tasks(&self) -> Vec<&Stake<Task>> {
         self.tasks.completed_stakes()
     }
-    pub fn next_task_id(&self) -> StakeId {
+    pub fn next_task_id(&self) -> TaskId {
         self.tasks.next_id()
     }
-    pub fn get_task_by_id(&self, id: &StakeId) -> Option<&Stake> {
+    pub fn get_task_by_id(&self, id: &TaskId) -> Option<&Stake<Task>> {
         self.tasks.get_by_id(id)
     }
-    pub fn update_task(&mut self, stake: Stake) -> Result<(), StakeError> {
+    pub fn update_task(&mut self, stake: Stake<Task>) -> Result<(), StakeError> {
+        self.check_member(stake.assignee.as_ref())?;
+        let stake = self.modified(&self.tasks, stake)?;
         self.tasks.update_stake(stake)
     }
 
-    fn locate_task(&self, id: &StakeId) -> Result<Stake, StakeError> {
+    fn locate_task(&self, id: &TaskId) -> Result<Stake<Task>, StakeError> {
         Ok(self
             .tasks
             .get_by_id(id)
             .ok_or(StakeError::StakeNotFound)?
             .clone())
     }
-    pub fn mark_task_complete(&mut self, id: &StakeId) -> Result<(), StakeError> {
+    pub fn mark_task_complete(&mut self, id: &TaskId) -> Result<(), StakeError> {
         let mut task_to_update = self.locate_task(id)?;
-        task_to_update.complete = true;
+        task_to_update.mark_complete(&self.clock);
+        task_to_update.modified_by = self.author.clone();
         self.tasks.update_stake(task_to_update)
     }
-    pub fn mark_task_dropped(&mut self, id: &StakeId) -> Result<(), StakeError> {
+    pub fn mark_task_dropped(&mut self, id: &TaskId) -> Result<(), StakeError> {
         let mut task_to_update = self.locate_task(id)?;
-        task_to_update.dropped = true;
+        task_to_update.mark_dropped(&self.clock);
+        task_to_update.modified_by = self.author.clone();
         self.tasks.update_stake(task_to_update)
     }
-    pub fn get_task_children(&self, parent_id: &StakeId) -> Vec<&Stake> {
+    pub fn mark_task_reviewed(&mut self, id: &TaskId) -> Result<(), StakeError> {
+        let mut task_to_update = self.locate_task(id)?;
+        task_to_update.mark_reviewed(&self.clock);
+        task_to_update.modified_by = self.author.clone();
+        self.tasks.update_stake(task_to_update)
+    }
+    pub fn get_task_children(&self, parent_id: &ProjectId) -> Vec<&Stake<Task>> {
         self.tasks.get_children(parent_id)
     }
+    pub fn move_task(
+        &mut self,
+        id: &TaskId,
+        new_parent_id: Option<ProjectId>,
+    ) -> Result<(), StakeError> {
+        self.tasks.move_stake(id, new_parent_id)?;
+        let mut task = self.locate_task(id)?;
+        task.modified_by = self.author.clone();
+        self.tasks.update_stake(task)
+    }
+
+    /// Assigns a task to a member, or to nobody.
+    pub fn assign_task(&mut self, id: &TaskId, assignee: Option<String>) -> Result<(), StakeError> {
+        let mut task = self.locate_task(id)?;
+        task.assignee = assignee;
+        task.date_modified = self.clock.now();
+        self.update_task(task)
+    }
+
+    /// Every task assigned to `user`, including completed and dropped ones.
+    pub fn tasks_assigned_to(&self, user: &str) -> Vec<&Stake<Task>> {
+        self.tasks
+            .iter()
+            .filter(|task| task.assignee.as_deref() == Some(user))
+            .collect()
+    }
+
+    // --- Collection Access ---
+    /// Read-only view of every area, including completed and dropped ones.
+    pub fn areas(&self) -> &StakesCollection<Area> {
+        &self.areas
+    }
+
+    /// Read-only view of every project, including completed and dropped ones.
+    pub fn projects(&self) -> &StakesCollection<Project> {
+        &self.projects
+    }
+
+    /// Read-only view of every task, including completed and dropped ones.
+    pub fn tasks(&self) -> &StakesCollection<Task> {
+        &self.tasks
+    }
+
+    // --- Restoring ---
+    // These write a stake exactly as given, authors and assignee included, for
+    // rebuilding an MLW from another copy of it. Changes made here go through the
+    // methods above instead.
+    pub(crate) fn restore_area(&mut self, stake: Stake<Area>) -> Result<(), StakeError> {
+        self.areas.update_stake(stake)
+    }
+
+    pub(crate) fn restore_project(&mut self, stake: Stake<Project>) -> Result<(), StakeError> {
+        self.projects.update_stake(stake)
+    }
+
+    pub(crate) fn restore_task(&mut self, stake: Stake<Task>) -> Result<(), StakeError> {
+        self.tasks.update_stake(stake)
+    }
+
+    // --- Identifier Methods ---
+    /// Resolves a short numeric handle or a full global id to an area id.
+    pub fn resolve_area(&self, handle: &str) -> Option<AreaId> {
+        self.areas.resolve(handle)
+    }
+
+    /// Resolves a short numeric handle or a full global id to a project id.
+    pub fn resolve_project(&self, handle: &str) -> Option<ProjectId> {
+        self.projects.resolve(handle)
+    }
+
+    /// Resolves a short numeric handle or a full global id to a task id.
+    pub fn resolve_task(&self, handle: &str) -> Option<TaskId> {
+        self.tasks.resolve(handle)
+    }
+
+    /// Gives stakes loaded from older files a global id. Returns how many were assigned.
+    pub fn assign_global_ids(&mut self) -> usize {
+        self.areas.assign_global_ids()
+            + self.projects.assign_global_ids()
+            + self.tasks.assign_global_ids()
+    }
+
+    // --- Search Methods ---
+    /// Typo-tolerant search across areas, projects and tasks in one call.
+    /// Results are ordered by score; equal scores list areas, then projects, then tasks.
+    pub fn fuzzy_search(&self, query: &str) -> Vec<FuzzyMatch<'_>> {
+        let mut matches: Vec<FuzzyMatch<'_>> = Vec::new();
+        matches.extend(
+            self.areas
+                .fuzzy_search(query)
+                .into_iter()
+                .map(|hit| FuzzyMatch {
+                    stake: StakeRef::Area(hit.stake),
+                    score: hit.score,
+                }),
+        );
+        matches.extend(
+            self.projects
+                .fuzzy_search(query)
+                .into_iter()
+                .map(|hit| FuzzyMatch {
+                    stake: StakeRef::Project(hit.stake),
+                    score: hit.score,
+                }),
+        );
+        matches.extend(
+            self.tasks
+                .fuzzy_search(query)
+                .into_iter()
+                .map(|hit| FuzzyMatch {
+                    stake: StakeRef::Task(hit.stake),
+                    score: hit.score,
+                }),
+        );
+        matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+        matches
+    }
 }
 
 // --- Unit Tests for MLW ---
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::entities::stake::{Stake, StakeError, StakeId};
-    use crate::entities::stakes_collection::StakesCollection;
-    use chrono::{TimeZone, Utc};
-    use serde_json;
+    use crate::entities::FakeClock;
+    use crate::entities::stake::{Kind, Stake, StakeError, StakeId, StakeKind};
+    use chrono::{Duration, TimeZone, Utc};
 
     // Helper function (copied here for self-contained tests)
-    fn create_test_stake(
+    fn create_test_stake<K: Kind>(
         id: u32,
         name: &str,
-        parent_id: Option<StakeId>,
+        parent_id: Option<StakeId<K::Parent>>,
         complete: bool,
         dropped: bool,
         note: Option<String>,
-    ) -> Stake {
-        let mut stake = Stake::new(StakeId(id), name.to_string(), parent_id, note);
+    ) -> Stake<K> {
+        let mut stake = Stake::new(StakeId::new(id), name.to_string(), parent_id, note);
         stake.complete = complete;
         stake.dropped = dropped;
         stake
@@ -209,7 +573,7 @@ mod tests {
         assert!(mlw.areas.is_empty(), "Areas collection should be empty");
         assert_eq!(
             mlw.areas.next_id(),
-            StakeId(1),
+            StakeId::new(1),
             "Areas next_id should start at 1"
         );
         assert_eq!(mlw.projects.len(), 0, "Projects collection should be empty");
@@ -219,14 +583,14 @@ mod tests {
         );
         assert_eq!(
             mlw.projects.next_id(),
-            StakeId(1),
+            StakeId::new(1),
             "Projects next_id should start at 1"
         );
         assert_eq!(mlw.tasks.len(), 0, "Tasks collection should be empty");
         assert!(mlw.tasks.is_empty(), "Tasks collection should be empty");
         assert_eq!(
             mlw.tasks.next_id(),
-            StakeId(1),
+            StakeId::new(1),
             "Tasks next_id should start at 1"
         );
     }
@@ -288,6 +652,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_mlw_deserializes_files_written_before_typed_ids() {
+        // Ids were plain numbers on disk before they carried their kind, and still are.
+        let legacy = r#"{
+            "areas": {"nextId": 2, "stakes": {"1": {
+                "stake_id": 1, "stake_name": "Finance", "parent_id": null,
+                "complete": false, "dropped": false, "note": null,
+                "date_modified": "2024-07-19T08:30:00Z", "date_created": "2024-07-19T08:30:00Z",
+                "date_reviewed": null}}},
+            "projects": {"nextId": 2, "stakes": {"1": {
+                "stake_id": 1, "stake_name": "Budget", "parent_id": 1,
+                "complete": false, "dropped": false, "note": null,
+                "date_modified": "2024-07-19T08:30:00Z", "date_created": "2024-07-19T08:30:00Z",
+                "date_reviewed": null}}},
+            "tasks": {"nextId": 2, "stakes": {"1": {
+                "stake_id": 1, "stake_name": "Collect receipts", "parent_id": 1,
+                "complete": true, "dropped": false, "note": null,
+                "date_modified": "2024-07-19T08:30:00Z", "date_created": "2024-07-19T08:30:00Z",
+                "date_reviewed": null}}}
+        }"#;
+
+        let mlw: MLW = serde_json::from_str(legacy).expect("legacy file should load");
+        let project = mlw.get_project_by_id(&StakeId::new(1)).unwrap();
+        assert_eq!(project.parent_id, Some(StakeId::new(1)));
+        assert_eq!(mlw.get_task_children(&project.stake_id).len(), 0);
+        assert_eq!(mlw.tasks.get_all_children(&project.stake_id).len(), 1);
+
+        let rewritten = serde_json::to_value(&mlw).unwrap();
+        let original: serde_json::Value = serde_json::from_str(legacy).unwrap();
+        assert_eq!(rewritten, original);
+    }
+
     // REMOVED: test_mlw_area_management (will be replaced by smaller tests)
     // REMOVED: test_mlw_project_management (will be replaced by smaller tests)
     // REMOVED: test_mlw_task_management (will be replaced by smaller tests)
@@ -298,7 +694,7 @@ mod tests {
     fn test_mlw_new_area_id_correctly_generated() {
         let mut mlw = MLW::new();
         let area = mlw.new_area("Test Area".to_string(), None);
-        assert_eq!(area.stake_id, StakeId(1));
+        assert_eq!(area.stake_id, StakeId::new(1));
     }
 
     #[test]
@@ -333,7 +729,7 @@ mod tests {
     fn test_mlw_new_area_increments_next_id() {
         let mut mlw = MLW::new();
         mlw.new_area("Test Area".to_string(), None);
-        assert_eq!(mlw.next_area_id(), StakeId(2));
+        assert_eq!(mlw.next_area_id(), StakeId::new(2));
     }
 
     #[test]
@@ -402,7 +798,7 @@ mod tests {
     #[test]
     fn test_mlw_get_area_by_id_not_found() {
         let mlw = MLW::new();
-        let not_found = mlw.get_area_by_id(&StakeId(999));
+        let not_found = mlw.get_area_by_id(&StakeId::new(999));
         assert!(not_found.is_none());
     }
 
@@ -455,7 +851,7 @@ mod tests {
     #[test]
     fn test_mlw_mark_area_complete_not_found_error() {
         let mut mlw = MLW::new();
-        let result = mlw.mark_area_complete(&StakeId(999));
+        let result = mlw.mark_area_complete(&StakeId::new(999));
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), StakeError::StakeNotFound);
     }
@@ -478,11 +874,81 @@ mod tests {
     #[test]
     fn test_mlw_mark_area_dropped_not_found_error() {
         let mut mlw = MLW::new();
-        let result = mlw.mark_area_dropped(&StakeId(999));
+        let result = mlw.mark_area_dropped(&StakeId::new(999));
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), StakeError::StakeNotFound);
     }
 
+    #[test]
+    fn test_mlw_mark_area_reviewed_sets_date() {
+        let mut mlw = MLW::new();
+        let area = mlw.new_area("Test Area".to_string(), None);
+        mlw.mark_area_reviewed(&area.stake_id).unwrap();
+        let reviewed = mlw.get_area_by_id(&area.stake_id).unwrap();
+        assert!(reviewed.date_reviewed.is_some());
+        assert!(reviewed.is_active());
+    }
+
+    #[test]
+    fn test_mlw_stamps_times_from_its_clock() {
+        let start = Utc.with_ymd_and_hms(2026, 10, 14, 9, 0, 0).unwrap();
+        let clock = FakeClock::new(start);
+        let mut mlw = MLW::with_clock(SharedClock::new(clock.clone()));
+        let area = mlw.new_area("Area".to_string(), None);
+        let project = mlw.new_project("Project".to_string(), None, None);
+        let task = mlw.new_task("Task".to_string(), None, None);
+        assert_eq!(area.date_created, start);
+        assert_eq!(task.date_modified, start);
+
+        clock.advance(Duration::hours(1));
+        mlw.mark_area_reviewed(&area.stake_id).unwrap();
+        mlw.mark_project_dropped(&project.stake_id).unwrap();
+        mlw.mark_task_complete(&task.stake_id).unwrap();
+        clock.advance(Duration::hours(1));
+        mlw.move_task(&task.stake_id, Some(project.stake_id.clone()))
+            .unwrap();
+
+        let hour = |n| start + Duration::hours(n);
+        let area = mlw.get_area_by_id(&area.stake_id).unwrap();
+        assert_eq!(area.date_reviewed, Some(hour(1)));
+        assert_eq!(area.date_modified, hour(0));
+        let project = mlw.get_project_by_id(&project.stake_id).unwrap();
+        assert_eq!(project.date_modified, hour(1));
+        let task = mlw.get_task_by_id(&task.stake_id).unwrap();
+        assert_eq!(task.date_modified, hour(2));
+        assert_eq!(task.date_created, hour(0));
+    }
+
+    #[test]
+    fn test_mlw_equality_ignores_clock() {
+        let clock = FakeClock::new(Utc.with_ymd_and_hms(2026, 10, 14, 9, 0, 0).unwrap());
+        let mlw = MLW::with_clock(SharedClock::new(clock));
+        assert_eq!(mlw, MLW::new());
+    }
+
+    #[test]
+    fn test_mlw_mark_area_reviewed_not_found_error() {
+        let mut mlw = MLW::new();
+        let result = mlw.mark_area_reviewed(&StakeId::new(999));
+        assert_eq!(result, Err(StakeError::StakeNotFound));
+    }
+
+    #[test]
+    fn test_mlw_collection_accessors_include_inactive_stakes() {
+        let mut mlw = MLW::new();
+        let area = mlw.new_area("Area".to_string(), None);
+        let project = mlw.new_project("Project".to_string(), None, None);
+        let task = mlw.new_task("Task".to_string(), None, None);
+        mlw.mark_area_dropped(&area.stake_id).unwrap();
+        mlw.mark_project_complete(&project.stake_id).unwrap();
+        mlw.mark_task_dropped(&task.stake_id).unwrap();
+
+        assert_eq!(mlw.areas().len(), 1);
+        assert_eq!(mlw.projects().len(), 1);
+        assert_eq!(mlw.tasks().len(), 1);
+        assert!(mlw.active_tasks().is_empty());
+    }
+
     #[cfg(test)] // Ensures this module is only compiled for tests
     pub mod project_tests {
         // Use `pub` so it's accessible within `tests` module
@@ -498,7 +964,7 @@ mod tests {
         fn test_mlw_new_project_id_correctly_generated() {
             let mut mlw = MLW::new();
             let project = mlw.new_project("Test Project".to_string(), None, None);
-            assert_eq!(project.stake_id, StakeId(1));
+            assert_eq!(project.stake_id, StakeId::new(1));
         }
 
         #[test]
@@ -534,7 +1000,7 @@ mod tests {
         fn test_mlw_new_project_increments_next_id() {
             let mut mlw = MLW::new();
             mlw.new_project("Test Project".to_string(), None, None);
-            assert_eq!(mlw.next_project_id(), StakeId(2));
+            assert_eq!(mlw.next_project_id(), StakeId::new(2));
         }
 
         #[test]
@@ -611,7 +1077,7 @@ mod tests {
         #[test]
         fn test_mlw_get_project_by_id_not_found() {
             let mlw = MLW::new();
-            let not_found = mlw.get_project_by_id(&StakeId(999));
+            let not_found = mlw.get_project_by_id(&StakeId::new(999));
             assert!(not_found.is_none());
         }
 
@@ -660,7 +1126,7 @@ mod tests {
         #[test]
         fn test_mlw_mark_project_complete_not_found_error() {
             let mut mlw = MLW::new();
-            let result = mlw.mark_project_complete(&StakeId(999));
+            let result = mlw.mark_project_complete(&StakeId::new(999));
             assert!(result.is_err());
             assert_eq!(result.unwrap_err(), StakeError::StakeNotFound);
         }
@@ -682,30 +1148,46 @@ mod tests {
         #[test]
         fn test_mlw_mark_project_dropped_not_found_error() {
             let mut mlw = MLW::new();
-            let result = mlw.mark_project_dropped(&StakeId(999));
+            let result = mlw.mark_project_dropped(&StakeId::new(999));
             assert!(result.is_err());
             assert_eq!(result.unwrap_err(), StakeError::StakeNotFound);
         }
 
+        #[test]
+        fn test_mlw_mark_project_reviewed_sets_date() {
+            let mut mlw = MLW::new();
+            let project = mlw.new_project("Test Project".to_string(), None, None);
+            mlw.mark_project_reviewed(&project.stake_id).unwrap();
+            let reviewed = mlw.get_project_by_id(&project.stake_id).unwrap();
+            assert!(reviewed.date_reviewed.is_some());
+        }
+
+        #[test]
+        fn test_mlw_mark_project_reviewed_not_found_error() {
+            let mut mlw = MLW::new();
+            let result = mlw.mark_project_reviewed(&StakeId::new(999));
+            assert_eq!(result, Err(StakeError::StakeNotFound));
+        }
+
         // --- Granular Tests for MLW Get Project Children ---
 
         #[test]
         fn test_mlw_get_project_children_found() {
             let mut mlw = MLW::new();
-            let parent_project = mlw.new_project("Parent".to_string(), None, None);
+            let parent_area = mlw.new_area("Parent".to_string(), None);
             let child1 = mlw.new_project(
                 "Child 1".to_string(),
-                Some(parent_project.stake_id.clone()),
+                Some(parent_area.stake_id.clone()),
                 None,
             );
             let child2 = mlw.new_project(
                 "Child 2".to_string(),
-                Some(parent_project.stake_id.clone()),
+                Some(parent_area.stake_id.clone()),
                 None,
             );
             let unrelated_project = mlw.new_project("Unrelated".to_string(), None, None);
 
-            let children = mlw.get_project_children(&parent_project.stake_id);
+            let children = mlw.get_project_children(&parent_area.stake_id);
             assert_eq!(children.len(), 2);
             assert!(children.contains(&&child1)); // Use && since get_project_children returns Vec<&Stake>
             assert!(children.contains(&&child2));
@@ -715,18 +1197,43 @@ mod tests {
         #[test]
         fn test_mlw_get_project_children_empty_if_no_children() {
             let mut mlw = MLW::new();
-            let parent_project = mlw.new_project("Parent".to_string(), None, None);
-            let children = mlw.get_project_children(&parent_project.stake_id);
+            let parent_area = mlw.new_area("Parent".to_string(), None);
+            let children = mlw.get_project_children(&parent_area.stake_id);
             assert!(children.is_empty());
         }
 
         #[test]
         fn test_mlw_get_project_children_non_existent_parent() {
             let mlw = MLW::new();
-            let non_existent_parent_id = StakeId(999);
+            let non_existent_parent_id = StakeId::new(999);
             let children = mlw.get_project_children(&non_existent_parent_id);
             assert!(children.is_empty());
         }
+
+        // --- Granular Tests for MLW Move Project ---
+
+        #[test]
+        fn test_mlw_move_project_success() {
+            let mut mlw = MLW::new();
+            let old_area = mlw.new_area("Old Area".to_string(), None);
+            let new_area = mlw.new_area("New Area".to_string(), None);
+            let project =
+                mlw.new_project("Project".to_string(), Some(old_area.stake_id.clone()), None);
+
+            let result = mlw.move_project(&project.stake_id, Some(new_area.stake_id.clone()));
+            assert!(result.is_ok());
+            let moved = mlw.get_project_by_id(&project.stake_id).unwrap();
+            assert_eq!(moved.parent_id, Some(new_area.stake_id.clone()));
+            assert!(mlw.get_project_children(&old_area.stake_id).is_empty());
+            assert_eq!(mlw.get_project_children(&new_area.stake_id).len(), 1);
+        }
+
+        #[test]
+        fn test_mlw_move_project_not_found_error() {
+            let mut mlw = MLW::new();
+            let result = mlw.move_project(&StakeId::new(999), None);
+            assert_eq!(result.unwrap_err(), StakeError::StakeNotFound);
+        }
     } // End of project_tests module
 
     #[cfg(test)] // Ensures this module is only compiled for tests
@@ -740,7 +1247,7 @@ mod tests {
         fn test_mlw_new_task_id_correctly_generated() {
             let mut mlw = MLW::new();
             let task = mlw.new_task("Test Task".to_string(), None, None);
-            assert_eq!(task.stake_id, StakeId(1));
+            assert_eq!(task.stake_id, StakeId::new(1));
         }
 
         #[test]
@@ -775,7 +1282,7 @@ mod tests {
         fn test_mlw_new_task_increments_next_id() {
             let mut mlw = MLW::new();
             mlw.new_task("Test Task".to_string(), None, None);
-            assert_eq!(mlw.next_task_id(), StakeId(2));
+            assert_eq!(mlw.next_task_id(), StakeId::new(2));
         }
 
         #[test]
@@ -856,7 +1363,7 @@ mod tests {
         #[test]
         fn test_mlw_get_task_by_id_not_found() {
             let mlw = MLW::new();
-            let not_found = mlw.get_task_by_id(&StakeId(999));
+            let not_found = mlw.get_task_by_id(&StakeId::new(999));
             assert!(not_found.is_none());
         }
 
@@ -905,7 +1412,7 @@ mod tests {
         #[test]
         fn test_mlw_mark_task_complete_not_found_error() {
             let mut mlw = MLW::new();
-            let result = mlw.mark_task_complete(&StakeId(999));
+            let result = mlw.mark_task_complete(&StakeId::new(999));
             assert!(result.is_err());
             assert_eq!(result.unwrap_err(), StakeError::StakeNotFound);
         }
@@ -927,11 +1434,20 @@ mod tests {
         #[test]
         fn test_mlw_mark_task_dropped_not_found_error() {
             let mut mlw = MLW::new();
-            let result = mlw.mark_task_dropped(&StakeId(999));
+            let result = mlw.mark_task_dropped(&StakeId::new(999));
             assert!(result.is_err());
             assert_eq!(result.unwrap_err(), StakeError::StakeNotFound);
         }
 
+        #[test]
+        fn test_mlw_mark_task_reviewed_sets_date() {
+            let mut mlw = MLW::new();
+            let task = mlw.new_task("Test Task".to_string(), None, None);
+            mlw.mark_task_reviewed(&task.stake_id).unwrap();
+            let reviewed = mlw.get_task_by_id(&task.stake_id).unwrap();
+            assert!(reviewed.date_reviewed.is_some());
+        }
+
         // --- Granular Tests for MLW Get Task Children ---
 
         #[test]
@@ -961,17 +1477,348 @@ mod tests {
         #[test]
         fn test_mlw_get_task_children_empty_if_no_children() {
             let mut mlw = MLW::new();
-            let parent_task = mlw.new_task("Parent Task".to_string(), None, None);
-            let children = mlw.get_task_children(&parent_task.stake_id);
+            let parent_project = mlw.new_project("Parent Project".to_string(), None, None);
+            let children = mlw.get_task_children(&parent_project.stake_id);
             assert!(children.is_empty());
         }
 
         #[test]
         fn test_mlw_get_task_children_non_existent_parent() {
             let mlw = MLW::new();
-            let non_existent_parent_id = StakeId(999);
+            let non_existent_parent_id = StakeId::new(999);
             let children = mlw.get_task_children(&non_existent_parent_id);
             assert!(children.is_empty());
         }
+
+        // --- Granular Tests for MLW Move Task ---
+
+        #[test]
+        fn test_mlw_move_task_success() {
+            let mut mlw = MLW::new();
+            let old_project = mlw.new_project("Old Project".to_string(), None, None);
+            let new_project = mlw.new_project("New Project".to_string(), None, None);
+            let task = mlw.new_task("Task".to_string(), Some(old_project.stake_id.clone()), None);
+
+            let result = mlw.move_task(&task.stake_id, Some(new_project.stake_id.clone()));
+            assert!(result.is_ok());
+            let moved = mlw.get_task_by_id(&task.stake_id).unwrap();
+            assert_eq!(moved.parent_id, Some(new_project.stake_id.clone()));
+            assert!(mlw.get_task_children(&old_project.stake_id).is_empty());
+            assert_eq!(mlw.get_task_children(&new_project.stake_id).len(), 1);
+        }
+
+        #[test]
+        fn test_mlw_move_task_not_found_error() {
+            let mut mlw = MLW::new();
+            let result = mlw.move_task(&StakeId::new(999), None);
+            assert_eq!(result.unwrap_err(), StakeError::StakeNotFound);
+        }
     } // E
+
+    #[cfg(test)]
+    pub mod search_tests {
+        use super::*;
+
+        #[test]
+        fn test_mlw_fuzzy_search_spans_all_collections() {
+            let mut mlw = MLW::new();
+            let area = mlw.new_area("Financial Management".to_string(), None);
+            let project = mlw.new_project(
+                "Financial plan 2025".to_string(),
+                Some(area.stake_id.clone()),
+                None,
+            );
+            let task = mlw.new_task("File financial forms".to_string(), None, None);
+            mlw.new_task("Water plants".to_string(), None, None);
+
+            let results = mlw.fuzzy_search("finacial");
+            assert_eq!(results.len(), 3);
+            assert!(results.iter().any(|m| m.stake == StakeRef::Area(&area)));
+            assert!(
+                results
+                    .iter()
+                    .any(|m| m.stake == StakeRef::Project(&project))
+            );
+            assert!(results.iter().any(|m| m.stake == StakeRef::Task(&task)));
+        }
+
+        #[test]
+        fn test_mlw_fuzzy_search_orders_by_score_then_kind() {
+            let mut mlw = MLW::new();
+            mlw.new_task("Budget".to_string(), None, None);
+            mlw.new_area("Budgeting".to_string(), None);
+            mlw.new_project("Budget".to_string(), None, None);
+
+            let results = mlw.fuzzy_search("budget");
+            let kinds: Vec<StakeKind> = results.iter().map(|m| m.stake.kind()).collect();
+            assert_eq!(
+                kinds,
+                vec![StakeKind::Project, StakeKind::Task, StakeKind::Area]
+            );
+        }
+
+        #[test]
+        fn test_mlw_fuzzy_search_no_matches() {
+            let mut mlw = MLW::new();
+            mlw.new_area("Health".to_string(), None);
+            assert!(mlw.fuzzy_search("garden").is_empty());
+        }
+    }
+    #[cfg(test)]
+    pub mod global_id_tests {
+        use super::*;
+
+        #[test]
+        fn test_mlw_new_stakes_get_distinct_global_ids() {
+            let mut mlw = MLW::new();
+            let area = mlw.new_area("Area".to_string(), None);
+            let project = mlw.new_project("Project".to_string(), None, None);
+            let task = mlw.new_task("Task".to_string(), None, None);
+
+            let ids = [area.global_id, project.global_id, task.global_id];
+            assert!(ids.iter().all(Option::is_some));
+            assert_ne!(ids[0], ids[1]);
+            assert_ne!(ids[1], ids[2]);
+            assert_eq!(mlw.get_task_by_id(&task.stake_id), Some(&task));
+        }
+
+        #[test]
+        fn test_mlw_resolve_by_short_handle_and_global_id() {
+            let mut mlw = MLW::new();
+            mlw.new_task("First".to_string(), None, None);
+            let task = mlw.new_task("Second".to_string(), None, None);
+
+            assert_eq!(mlw.resolve_task("2"), Some(task.stake_id.clone()));
+            assert_eq!(
+                mlw.resolve_task(&task.global_id.unwrap().to_string()),
+                Some(task.stake_id.clone())
+            );
+            assert_eq!(mlw.resolve_task("3"), None);
+        }
+
+        #[test]
+        fn test_mlw_resolve_does_not_cross_kinds() {
+            let mut mlw = MLW::new();
+            let task = mlw.new_task("Task".to_string(), None, None);
+            let global = task.global_id.unwrap().to_string();
+
+            assert_eq!(mlw.resolve_project(&global), None);
+            assert_eq!(mlw.resolve_area(&global), None);
+            assert_eq!(mlw.resolve_area("1"), None);
+        }
+
+        #[test]
+        fn test_mlw_assign_global_ids_after_loading_legacy_file() {
+            let mut mlw = MLW::new();
+            let project_id = mlw.projects.generate_id();
+            mlw.projects.add_stake(Stake::new(
+                project_id.clone(),
+                "Legacy".to_string(),
+                None,
+                None,
+            ));
+            mlw.new_task("Modern".to_string(), Some(project_id.clone()), None);
+
+            assert_eq!(mlw.assign_global_ids(), 1);
+            let project = mlw.get_project_by_id(&project_id).unwrap();
+            let global = project.global_id.unwrap().to_string();
+            assert_eq!(mlw.resolve_project(&global), Some(project_id));
+        }
+    }
+    #[cfg(test)]
+    pub mod team_tests {
+        use super::*;
+
+        fn team() -> MLW {
+            let mut mlw = MLW::new();
+            assert!(mlw.add_member("ana".to_string()));
+            assert!(mlw.add_member("ben".to_string()));
+            mlw
+        }
+
+        #[test]
+        fn test_mlw_members_are_unique_and_kept_in_order() {
+            let mut mlw = team();
+            assert!(!mlw.add_member("ana".to_string()));
+            assert_eq!(mlw.members(), ["ana", "ben"]);
+            assert!(mlw.is_member("ben"));
+            assert!(!mlw.remove_member("cal"));
+        }
+
+        #[test]
+        fn test_mlw_assign_only_to_members() {
+            let mut mlw = team();
+            let project = mlw.new_project("Launch".to_string(), None, None);
+            let task = mlw.new_task("Write copy".to_string(), None, None);
+
+            mlw.assign_project(&project.stake_id, Some("ana".to_string()))
+                .unwrap();
+            mlw.assign_task(&task.stake_id, Some("ben".to_string()))
+                .unwrap();
+            assert_eq!(
+                mlw.assign_task(&task.stake_id, Some("cal".to_string())),
+                Err(StakeError::UnknownUser("cal".to_string()))
+            );
+            assert_eq!(
+                mlw.assign_task(&TaskId::new(9), None),
+                Err(StakeError::StakeNotFound)
+            );
+
+            let assigned = mlw.get_task_by_id(&task.stake_id).unwrap();
+            assert_eq!(assigned.assignee.as_deref(), Some("ben"));
+            assert_eq!(
+                mlw.get_project_by_id(&project.stake_id)
+                    .unwrap()
+                    .assignee
+                    .as_deref(),
+                Some("ana")
+            );
+
+            mlw.assign_task(&task.stake_id, None).unwrap();
+            assert_eq!(mlw.get_task_by_id(&task.stake_id).unwrap().assignee, None);
+        }
+
+        #[test]
+        fn test_mlw_update_cannot_assign_to_strangers() {
+            let mut mlw = team();
+            let mut task = mlw.new_task("Write copy".to_string(), None, None);
+            task.assignee = Some("cal".to_string());
+            assert_eq!(
+                mlw.update_task(task),
+                Err(StakeError::UnknownUser("cal".to_string()))
+            );
+        }
+
+        #[test]
+        fn test_mlw_areas_cannot_be_assigned() {
+            let mut mlw = team();
+            let mut area = mlw.new_area("Work".to_string(), None);
+            area.assignee = Some("ana".to_string());
+            assert_eq!(mlw.update_area(area), Err(StakeError::CannotAssignArea));
+            assert_eq!(mlw.get_area_by_id(&AreaId::new(1)).unwrap().assignee, None);
+        }
+
+        #[test]
+        fn test_mlw_assigned_to_finds_every_stake_of_one_member() {
+            let mut mlw = team();
+            let project = mlw.new_project("Launch".to_string(), None, None);
+            let first = mlw.new_task("Write copy".to_string(), None, None);
+            let second = mlw.new_task("Ship".to_string(), None, None);
+            mlw.new_task("Unassigned".to_string(), None, None);
+            mlw.assign_project(&project.stake_id, Some("ana".to_string()))
+                .unwrap();
+            mlw.assign_task(&first.stake_id, Some("ana".to_string()))
+                .unwrap();
+            mlw.assign_task(&second.stake_id, Some("ana".to_string()))
+                .unwrap();
+            mlw.mark_task_complete(&second.stake_id).unwrap();
+
+            let names = |tasks: Vec<&Stake<Task>>| -> Vec<String> {
+                tasks.iter().map(|task| task.stake_name.clone()).collect()
+            };
+            assert_eq!(names(mlw.tasks_assigned_to("ana")), ["Write copy", "Ship"]);
+            assert!(mlw.tasks_assigned_to("ben").is_empty());
+            assert_eq!(mlw.projects_assigned_to("ana").len(), 1);
+        }
+
+        #[test]
+        fn test_mlw_removing_a_member_unassigns_their_stakes() {
+            let mut mlw = team();
+            let task = mlw.new_task("Write copy".to_string(), None, None);
+            mlw.assign_task(&task.stake_id, Some("ana".to_string()))
+                .unwrap();
+
+            assert!(mlw.remove_member("ana"));
+            assert_eq!(mlw.members(), ["ben"]);
+            assert_eq!(mlw.get_task_by_id(&task.stake_id).unwrap().assignee, None);
+        }
+
+        #[test]
+        fn test_mlw_records_who_created_and_who_changed_a_stake() {
+            let mut mlw = team();
+            mlw.set_author(Some("ana".to_string()));
+            let area = mlw.new_area("Work".to_string(), None);
+            let project = mlw.new_project("Launch".to_string(), None, None);
+            let task = mlw.new_task("Write copy".to_string(), None, None);
+            assert_eq!(area.created_by.as_deref(), Some("ana"));
+            assert_eq!(project.modified_by.as_deref(), Some("ana"));
+            assert_eq!(task.created_by.as_deref(), Some("ana"));
+
+            mlw.set_author(Some("ben".to_string()));
+            mlw.move_project(&project.stake_id, Some(area.stake_id.clone()))
+                .unwrap();
+            mlw.mark_task_complete(&task.stake_id).unwrap();
+            let task = mlw.get_task_by_id(&task.stake_id).unwrap();
+            assert_eq!(
+                (task.created_by.as_deref(), task.modified_by.as_deref()),
+                (Some("ana"), Some("ben"))
+            );
+            let project = mlw.get_project_by_id(&project.stake_id).unwrap();
+            assert_eq!(
+                (
+                    project.created_by.as_deref(),
+                    project.modified_by.as_deref()
+                ),
+                (Some("ana"), Some("ben"))
+            );
+        }
+
+        #[test]
+        fn test_mlw_reviews_record_who_reviewed() {
+            let mut mlw = team();
+            mlw.set_author(Some("ana".to_string()));
+            let area = mlw.new_area("Work".to_string(), None);
+            let project = mlw.new_project("Launch".to_string(), None, None);
+            let task = mlw.new_task("Write copy".to_string(), None, None);
+
+            mlw.set_author(Some("ben".to_string()));
+            mlw.mark_area_reviewed(&area.stake_id).unwrap();
+            mlw.mark_project_reviewed(&project.stake_id).unwrap();
+            mlw.mark_task_reviewed(&task.stake_id).unwrap();
+            let area = mlw.get_area_by_id(&area.stake_id).unwrap();
+            let project = mlw.get_project_by_id(&project.stake_id).unwrap();
+            let task = mlw.get_task_by_id(&task.stake_id).unwrap();
+            for (created_by, modified_by) in [
+                (&area.created_by, &area.modified_by),
+                (&project.created_by, &project.modified_by),
+                (&task.created_by, &task.modified_by),
+            ] {
+                assert_eq!(
+                    (created_by.as_deref(), modified_by.as_deref()),
+                    (Some("ana"), Some("ben"))
+                );
+            }
+        }
+
+        #[test]
+        fn test_mlw_update_keeps_the_creator() {
+            let mut mlw = team();
+            mlw.set_author(Some("ana".to_string()));
+            let mut task = mlw.new_task("Write copy".to_string(), None, None);
+
+            mlw.set_author(Some("ben".to_string()));
+            task.created_by = Some("ben".to_string());
+            task.stake_name = "Write the copy".to_string();
+            mlw.update_task(task.clone()).unwrap();
+
+            let task = mlw.get_task_by_id(&task.stake_id).unwrap();
+            assert_eq!(task.created_by.as_deref(), Some("ana"));
+            assert_eq!(task.modified_by.as_deref(), Some("ben"));
+        }
+
+        #[test]
+        fn test_mlw_members_are_saved_but_the_author_is_not() {
+            let mut mlw = team();
+            mlw.set_author(Some("ana".to_string()));
+            mlw.new_task("Write copy".to_string(), None, None);
+
+            let loaded: MLW = serde_json::from_str(&serde_json::to_string(&mlw).unwrap()).unwrap();
+            assert_eq!(loaded.members(), ["ana", "ben"]);
+            assert_eq!(loaded.author(), None);
+            assert_eq!(
+                loaded.tasks().iter().next().unwrap().created_by.as_deref(),
+                Some("ana")
+            );
+        }
+    }
 }
@@ -0,0 +1,136 @@
+use chrono::{DateTime, Duration, Utc};
+
+use super::MLW;
+use crate::dates::DateContext;
+use crate::entities::stake::{Stake, Task};
+
+impl MLW {
+    /// Open tasks due in `[start, end)`, earliest first.
+    pub fn tasks_due_between(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<&Stake<Task>> {
+        let mut tasks: Vec<&Stake<Task>> = self
+            .tasks
+            .iter()
+            .filter(|task| task.is_active())
+            .filter(|task| task.due.is_some_and(|due| start <= due && due < end))
+            .collect();
+        tasks.sort_by_key(|task| task.due);
+        tasks
+    }
+
+    /// Open tasks due before the user's today began.
+    pub fn overdue_tasks(&self, dates: &DateContext) -> Vec<&Stake<Task>> {
+        let start_of_today = dates.start_of_day(dates.today());
+        self.tasks_due_between(DateTime::<Utc>::MIN_UTC, start_of_today)
+    }
+
+    /// Open tasks due at any time on the user's today.
+    pub fn tasks_due_today(&self, dates: &DateContext) -> Vec<&Stake<Task>> {
+        let today = dates.today();
+        self.tasks_due_between(
+            dates.start_of_day(today),
+            dates.start_of_day(today + Duration::days(1)),
+        )
+    }
+
+    /// Open tasks due in the user's current week, from its first day to its last,
+    /// so this includes today and any earlier days of the week.
+    pub fn tasks_due_this_week(&self, dates: &DateContext) -> Vec<&Stake<Task>> {
+        let week = dates.week_of(dates.today());
+        self.tasks_due_between(
+            dates.start_of_day(week),
+            dates.start_of_day(week + Duration::days(7)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Weekday};
+
+    // Wednesday 14 October 2026, 23:30 UTC: already Thursday in Berlin (UTC+2).
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 10, 14, 23, 30, 0).unwrap()
+    }
+
+    fn utc() -> DateContext {
+        DateContext::new(now(), chrono_tz::UTC)
+    }
+
+    fn berlin() -> DateContext {
+        DateContext::new(now(), chrono_tz::Europe::Berlin)
+    }
+
+    fn add_due(mlw: &mut MLW, name: &str, due: DateTime<Utc>) {
+        let mut task = mlw.new_task(name.to_string(), None, None);
+        task.due = Some(due);
+        mlw.update_task(task).unwrap();
+    }
+
+    fn names(tasks: Vec<&Stake<Task>>) -> Vec<&str> {
+        tasks.iter().map(|task| task.stake_name.as_str()).collect()
+    }
+
+    fn sample() -> MLW {
+        let mut mlw = MLW::new();
+        let utc = |d, h| Utc.with_ymd_and_hms(2026, 10, d, h, 0, 0).unwrap();
+        add_due(&mut mlw, "Last week", utc(9, 12));
+        add_due(&mut mlw, "Monday", utc(12, 9));
+        add_due(&mut mlw, "Wednesday noon", utc(14, 12));
+        add_due(&mut mlw, "Thursday early", utc(14, 22)); // Thursday 00:00 in Berlin
+        add_due(&mut mlw, "Sunday", utc(18, 10));
+        add_due(&mut mlw, "Next week", utc(20, 10));
+        mlw.new_task("No due date".to_string(), None, None);
+        mlw
+    }
+
+    #[test]
+    fn test_overdue_is_before_start_of_today() {
+        let mlw = sample();
+        assert_eq!(
+            names(mlw.overdue_tasks(&utc())),
+            vec!["Last week", "Monday"]
+        );
+        // In Berlin it is already Thursday, so Wednesday's task is overdue too.
+        assert_eq!(
+            names(mlw.overdue_tasks(&berlin())),
+            vec!["Last week", "Monday", "Wednesday noon"]
+        );
+    }
+
+    #[test]
+    fn test_due_today_depends_on_timezone() {
+        let mlw = sample();
+        assert_eq!(
+            names(mlw.tasks_due_today(&utc())),
+            vec!["Wednesday noon", "Thursday early"]
+        );
+        assert_eq!(
+            names(mlw.tasks_due_today(&berlin())),
+            vec!["Thursday early"]
+        );
+    }
+
+    #[test]
+    fn test_this_week_follows_week_start() {
+        let mlw = sample();
+        assert_eq!(
+            names(mlw.tasks_due_this_week(&utc())),
+            vec!["Monday", "Wednesday noon", "Thursday early", "Sunday"]
+        );
+        // A week starting on Sunday runs 11 to 17 October.
+        let sunday_weeks = utc().with_week_start(Weekday::Sun);
+        assert_eq!(
+            names(mlw.tasks_due_this_week(&sunday_weeks)),
+            vec!["Monday", "Wednesday noon", "Thursday early"]
+        );
+    }
+
+    #[test]
+    fn test_closed_tasks_are_left_out() {
+        let mut mlw = sample();
+        let monday = mlw.overdue_tasks(&utc())[1].stake_id.clone();
+        mlw.mark_task_complete(&monday).unwrap();
+        assert_eq!(names(mlw.overdue_tasks(&utc())), vec!["Last week"]);
+    }
+}
@@ -0,0 +1,256 @@
+// What changed between two versions of an MLW, stake by stake, so a server can tell
+// its clients about writes they did not make themselves.
+
+use serde::Serialize;
+
+use super::MLW;
+use crate::entities::stake::{AreaId, Kind, ProjectId, Stake, StakeRef};
+use crate::entities::stakes_collection::StakesCollection;
+
+/// What happened to a stake.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Created,
+    Updated,
+    Completed,
+    Dropped,
+    Moved,
+}
+
+impl ChangeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeKind::Created => "created",
+            ChangeKind::Updated => "updated",
+            ChangeKind::Completed => "completed",
+            ChangeKind::Dropped => "dropped",
+            ChangeKind::Moved => "moved",
+        }
+    }
+}
+
+/// One stake that is new or different, as it is now.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Change<'a> {
+    pub event: ChangeKind,
+    pub stake: StakeRef<'a>,
+    /// For moves only: the parent the stake left, which may be none.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_parent_id: Option<Option<u32>>,
+}
+
+/// The part of the hierarchy someone is watching.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Scope {
+    Everything,
+    /// An area, its projects and their tasks.
+    Area(AreaId),
+    /// A project and its tasks.
+    Project(ProjectId),
+}
+
+impl MLW {
+    /// Every stake that is new or different here compared with `before`: areas,
+    /// then projects, then tasks, each in insertion order. A stake with several
+    /// changes is reported once, under the first of created, dropped, completed,
+    /// moved and updated that applies.
+    pub fn changes_since<'a>(&'a self, before: &MLW) -> Vec<Change<'a>> {
+        let mut changes = Vec::new();
+        diff(&before.areas, &self.areas, StakeRef::Area, &mut changes);
+        diff(
+            &before.projects,
+            &self.projects,
+            StakeRef::Project,
+            &mut changes,
+        );
+        diff(&before.tasks, &self.tasks, StakeRef::Task, &mut changes);
+        changes
+    }
+}
+
+fn diff<'a, K: Kind>(
+    before: &StakesCollection<K>,
+    after: &'a StakesCollection<K>,
+    wrap: fn(&'a Stake<K>) -> StakeRef<'a>,
+    changes: &mut Vec<Change<'a>>,
+) {
+    for stake in after.iter() {
+        let old = before.get_by_id(&stake.stake_id);
+        if old == Some(stake) {
+            continue;
+        }
+        let moved = old.filter(|old| old.parent_id != stake.parent_id);
+        let event = match old {
+            None => ChangeKind::Created,
+            Some(old) if stake.dropped && !old.dropped => ChangeKind::Dropped,
+            Some(old) if stake.complete && !old.complete => ChangeKind::Completed,
+            Some(_) if moved.is_some() => ChangeKind::Moved,
+            Some(_) => ChangeKind::Updated,
+        };
+        let previous_parent_id = match event {
+            ChangeKind::Moved => moved.map(|old| old.parent_id.as_ref().map(|id| id.0)),
+            _ => None,
+        };
+        changes.push(Change {
+            event,
+            stake: wrap(stake),
+            previous_parent_id,
+        });
+    }
+}
+
+impl Change<'_> {
+    /// Whether the change happened inside `scope` of `mlw`, the MLW it came from.
+    /// A move is inside both the parent it left and the one it joined.
+    pub fn is_within(&self, scope: &Scope, mlw: &MLW) -> bool {
+        match (scope, self.stake) {
+            (Scope::Everything, _) => true,
+            (Scope::Area(area), StakeRef::Area(stake)) => stake.stake_id == *area,
+            (Scope::Area(area), StakeRef::Project(_)) => self.parents().any(|id| id == area.0),
+            (Scope::Area(area), StakeRef::Task(_)) => self.parents().any(|project| {
+                mlw.get_project_by_id(&ProjectId::new(project))
+                    .and_then(|project| project.parent_id.as_ref())
+                    == Some(area)
+            }),
+            (Scope::Project(_), StakeRef::Area(_)) => false,
+            (Scope::Project(project), StakeRef::Project(stake)) => stake.stake_id == *project,
+            (Scope::Project(project), StakeRef::Task(_)) => {
+                self.parents().any(|id| id == project.0)
+            }
+        }
+    }
+
+    // The stake's parent now and, after a move, the one before.
+    fn parents(&self) -> impl Iterator<Item = u32> {
+        let current = match self.stake {
+            StakeRef::Area(_) => None,
+            StakeRef::Project(stake) => stake.parent_id.as_ref().map(|id| id.0),
+            StakeRef::Task(stake) => stake.parent_id.as_ref().map(|id| id.0),
+        };
+        current.into_iter().chain(self.previous_parent_id.flatten())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::{FakeClock, SharedClock, TaskId};
+    use chrono::{Duration, TimeZone, Utc};
+
+    fn events(changes: &[Change]) -> Vec<(ChangeKind, &'static str, u32)> {
+        changes
+            .iter()
+            .map(|change| {
+                let kind = match change.stake {
+                    StakeRef::Area(_) => "area",
+                    StakeRef::Project(_) => "project",
+                    StakeRef::Task(_) => "task",
+                };
+                (change.event, kind, change.stake.id())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_no_changes_between_equal_mlws() {
+        let mut mlw = MLW::new();
+        mlw.new_task("Task".to_string(), None, None);
+        assert!(mlw.changes_since(&mlw.clone()).is_empty());
+    }
+
+    #[test]
+    fn test_each_kind_of_change_is_named() {
+        let clock = FakeClock::new(Utc.with_ymd_and_hms(2026, 10, 14, 9, 0, 0).unwrap());
+        let mut mlw = MLW::with_clock(SharedClock::new(clock.clone()));
+        let area = mlw.new_area("Home".to_string(), None);
+        let project = mlw.new_project("Garden".to_string(), None, None);
+        for name in ["Rename me", "Finish me", "Drop me", "Move me", "Keep me"] {
+            mlw.new_task(name.to_string(), None, None);
+        }
+        let before = mlw.clone();
+        clock.advance(Duration::minutes(1));
+
+        let mut renamed = mlw.get_task_by_id(&TaskId::new(1)).unwrap().clone();
+        renamed.stake_name = "Renamed".to_string();
+        mlw.update_task(renamed).unwrap();
+        mlw.mark_task_complete(&TaskId::new(2)).unwrap();
+        mlw.mark_task_dropped(&TaskId::new(3)).unwrap();
+        mlw.move_task(&TaskId::new(4), Some(project.stake_id.clone()))
+            .unwrap();
+        mlw.move_project(&project.stake_id, Some(area.stake_id.clone()))
+            .unwrap();
+        mlw.new_task("New".to_string(), None, None);
+
+        let changes = mlw.changes_since(&before);
+        assert_eq!(
+            events(&changes),
+            vec![
+                (ChangeKind::Moved, "project", 1),
+                (ChangeKind::Updated, "task", 1),
+                (ChangeKind::Completed, "task", 2),
+                (ChangeKind::Dropped, "task", 3),
+                (ChangeKind::Moved, "task", 4),
+                (ChangeKind::Created, "task", 6),
+            ]
+        );
+        assert_eq!(changes[4].previous_parent_id, Some(None));
+        assert_eq!(changes[2].previous_parent_id, None);
+
+        let json = serde_json::to_value(&changes[4]).unwrap();
+        assert_eq!(json["event"], "moved");
+        assert_eq!(json["stake"]["kind"], "task");
+        assert_eq!(json["stake"]["parent_id"], 1);
+        assert_eq!(json["previous_parent_id"], serde_json::Value::Null);
+        assert!(
+            serde_json::to_value(&changes[2])
+                .unwrap()
+                .get("previous_parent_id")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_completing_and_moving_at_once_reports_completion() {
+        let mut mlw = MLW::new();
+        let project = mlw.new_project("Project".to_string(), None, None);
+        let mut task = mlw.new_task("Task".to_string(), None, None);
+        let before = mlw.clone();
+
+        task.complete = true;
+        task.parent_id = Some(project.stake_id);
+        mlw.update_task(task).unwrap();
+        assert_eq!(
+            events(&mlw.changes_since(&before)),
+            vec![(ChangeKind::Completed, "task", 1)]
+        );
+    }
+
+    #[test]
+    fn test_scopes_follow_the_hierarchy() {
+        let mut mlw = MLW::new();
+        let home = mlw.new_area("Home".to_string(), None);
+        let work = mlw.new_area("Work".to_string(), None);
+        let garden = mlw.new_project("Garden".to_string(), Some(home.stake_id.clone()), None);
+        let report = mlw.new_project("Report".to_string(), Some(work.stake_id.clone()), None);
+        let task = mlw.new_task("Dig".to_string(), Some(garden.stake_id.clone()), None);
+        let before = mlw.clone();
+
+        mlw.move_task(&task.stake_id, Some(report.stake_id.clone()))
+            .unwrap();
+        mlw.new_area("Health".to_string(), None);
+        let changes = mlw.changes_since(&before);
+        let moved = &changes[1];
+        let new_area = &changes[0];
+
+        assert!(moved.is_within(&Scope::Everything, &mlw));
+        assert!(moved.is_within(&Scope::Project(garden.stake_id.clone()), &mlw));
+        assert!(moved.is_within(&Scope::Project(report.stake_id.clone()), &mlw));
+        assert!(moved.is_within(&Scope::Area(home.stake_id.clone()), &mlw));
+        assert!(moved.is_within(&Scope::Area(work.stake_id.clone()), &mlw));
+        assert!(!moved.is_within(&Scope::Area(AreaId::new(3)), &mlw));
+        assert!(new_area.is_within(&Scope::Area(AreaId::new(3)), &mlw));
+        assert!(!new_area.is_within(&Scope::Area(home.stake_id.clone()), &mlw));
+        assert!(!new_area.is_within(&Scope::Project(garden.stake_id), &mlw));
+    }
+}
@@ -0,0 +1,398 @@
+use serde::Serialize;
+
+use super::MLW;
+use crate::entities::stake::{
+    Area, AreaId, Kind, Project, ProjectId, Stake, StakeId, StakeRef, Task,
+};
+use crate::entities::stakes_collection::StakesCollection;
+
+/// Tally of stakes by status. Dropped stakes are counted apart from completed ones.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct WorkCounts {
+    pub open: usize,
+    pub completed: usize,
+    pub dropped: usize,
+}
+
+impl WorkCounts {
+    fn tally<'a, K: Kind>(stakes: impl IntoIterator<Item = &'a Stake<K>>) -> Self {
+        let mut counts = WorkCounts::default();
+        for stake in stakes {
+            counts.add(stake);
+        }
+        counts
+    }
+
+    fn add<K: Kind>(&mut self, stake: &Stake<K>) {
+        if stake.dropped {
+            self.dropped += 1;
+        } else if stake.complete {
+            self.completed += 1;
+        } else {
+            self.open += 1;
+        }
+    }
+
+    fn merge(&mut self, other: WorkCounts) {
+        self.open += other.open;
+        self.completed += other.completed;
+        self.dropped += other.dropped;
+    }
+
+    /// Total number of stakes counted.
+    pub fn total(&self) -> usize {
+        self.open + self.completed + self.dropped
+    }
+}
+
+/// A project and every task filed under it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProjectNode<'a> {
+    pub project: &'a Stake<Project>,
+    pub tasks: Vec<&'a Stake<Task>>,
+    /// Counts over `tasks`.
+    pub task_counts: WorkCounts,
+}
+
+/// An area and every project (with its tasks) filed under it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AreaNode<'a> {
+    pub area: &'a Stake<Area>,
+    pub projects: Vec<ProjectNode<'a>>,
+    /// Counts over `projects`.
+    pub project_counts: WorkCounts,
+    /// Counts over the tasks of every project in the area.
+    pub task_counts: WorkCounts,
+}
+
+/// The whole MLW as one tree: Area -> Projects -> Tasks.
+///
+/// Projects without an existing area and tasks without an existing project are
+/// not dropped; they are listed under `unfiled_projects` and `unfiled_tasks`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Hierarchy<'a> {
+    pub areas: Vec<AreaNode<'a>>,
+    pub unfiled_projects: Vec<ProjectNode<'a>>,
+    pub unfiled_tasks: Vec<&'a Stake<Task>>,
+}
+
+/// One stake visited by `Hierarchy::iter`, with its depth in the tree.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TreeEntry<'a> {
+    pub stake: StakeRef<'a>,
+    pub depth: usize,
+}
+
+impl<'a> ProjectNode<'a> {
+    /// Visits the project followed by its tasks.
+    pub fn iter(&self) -> impl Iterator<Item = TreeEntry<'a>> + '_ {
+        self.iter_at(0)
+    }
+
+    fn iter_at(&self, depth: usize) -> impl Iterator<Item = TreeEntry<'a>> + '_ {
+        std::iter::once(TreeEntry {
+            stake: StakeRef::Project(self.project),
+            depth,
+        })
+        .chain(self.tasks.iter().map(move |&task| TreeEntry {
+            stake: StakeRef::Task(task),
+            depth: depth + 1,
+        }))
+    }
+}
+
+impl<'a> AreaNode<'a> {
+    /// Visits the area, then each project followed by its tasks.
+    pub fn iter(&self) -> impl Iterator<Item = TreeEntry<'a>> + '_ {
+        std::iter::once(TreeEntry {
+            stake: StakeRef::Area(self.area),
+            depth: 0,
+        })
+        .chain(self.projects.iter().flat_map(|project| project.iter_at(1)))
+    }
+
+    /// Every task in the area, across all of its projects.
+    pub fn tasks(&self) -> impl Iterator<Item = &'a Stake<Task>> + '_ {
+        self.projects
+            .iter()
+            .flat_map(|project| project.tasks.iter().copied())
+    }
+}
+
+impl<'a> Hierarchy<'a> {
+    /// Depth-first walk of the tree: areas first, then unfiled projects, then unfiled tasks.
+    pub fn iter(&self) -> impl Iterator<Item = TreeEntry<'a>> + '_ {
+        self.areas
+            .iter()
+            .flat_map(|area| area.iter())
+            .chain(
+                self.unfiled_projects
+                    .iter()
+                    .flat_map(|project| project.iter()),
+            )
+            .chain(self.unfiled_tasks.iter().map(|&task| TreeEntry {
+                stake: StakeRef::Task(task),
+                depth: 0,
+            }))
+    }
+
+    /// Counts over every task in the tree, filed or not.
+    pub fn task_counts(&self) -> WorkCounts {
+        let mut counts = WorkCounts::tally(self.unfiled_tasks.iter().copied());
+        for area in &self.areas {
+            counts.merge(area.task_counts);
+        }
+        for project in &self.unfiled_projects {
+            counts.merge(project.task_counts);
+        }
+        counts
+    }
+}
+
+impl MLW {
+    /// Builds the full Area -> Projects -> Tasks tree, including completed and dropped stakes.
+    pub fn hierarchy(&self) -> Hierarchy<'_> {
+        let areas = self
+            .areas
+            .iter()
+            .map(|area| self.build_area_node(area))
+            .collect();
+        let unfiled_projects = self
+            .projects
+            .iter()
+            .filter(|project| !has_parent_in(&self.areas, project.parent_id.as_ref()))
+            .map(|project| self.build_project_node(project))
+            .collect();
+        let unfiled_tasks = self
+            .tasks
+            .iter()
+            .filter(|task| !has_parent_in(&self.projects, task.parent_id.as_ref()))
+            .collect();
+        Hierarchy {
+            areas,
+            unfiled_projects,
+            unfiled_tasks,
+        }
+    }
+
+    /// Builds the subtree for a single area: everything filed under area `id`.
+    pub fn area_tree(&self, id: &AreaId) -> Option<AreaNode<'_>> {
+        self.areas
+            .get_by_id(id)
+            .map(|area| self.build_area_node(area))
+    }
+
+    /// Builds the node for a single project and its tasks.
+    pub fn project_tree(&self, id: &ProjectId) -> Option<ProjectNode<'_>> {
+        self.projects
+            .get_by_id(id)
+            .map(|project| self.build_project_node(project))
+    }
+
+    fn build_area_node<'a>(&'a self, area: &'a Stake<Area>) -> AreaNode<'a> {
+        let projects: Vec<ProjectNode<'a>> = self
+            .projects
+            .get_all_children(&area.stake_id)
+            .into_iter()
+            .map(|project| self.build_project_node(project))
+            .collect();
+        let project_counts = WorkCounts::tally(projects.iter().map(|node| node.project));
+        let mut task_counts = WorkCounts::default();
+        for node in &projects {
+            task_counts.merge(node.task_counts);
+        }
+        AreaNode {
+            area,
+            projects,
+            project_counts,
+            task_counts,
+        }
+    }
+
+    fn build_project_node<'a>(&'a self, project: &'a Stake<Project>) -> ProjectNode<'a> {
+        let tasks = self.tasks.get_all_children(&project.stake_id);
+        let task_counts = WorkCounts::tally(tasks.iter().copied());
+        ProjectNode {
+            project,
+            tasks,
+            task_counts,
+        }
+    }
+}
+
+fn has_parent_in<K: Kind>(parents: &StakesCollection<K>, parent_id: Option<&StakeId<K>>) -> bool {
+    parent_id.is_some_and(|parent_id| parents.get_by_id(parent_id).is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::stake::StakeKind;
+
+    // Finance has two projects; Health has none; one project and one task are unfiled.
+    fn sample_mlw() -> MLW {
+        let mut mlw = MLW::new();
+        let finance = mlw.new_area("Finance".to_string(), None);
+        mlw.new_area("Health".to_string(), None);
+
+        let taxes = mlw.new_project("Taxes".to_string(), Some(finance.stake_id.clone()), None);
+        let budget = mlw.new_project("Budget".to_string(), Some(finance.stake_id.clone()), None);
+        mlw.new_project("Someday trip".to_string(), None, None);
+
+        let gather = mlw.new_task(
+            "Gather forms".to_string(),
+            Some(taxes.stake_id.clone()),
+            None,
+        );
+        mlw.new_task(
+            "File return".to_string(),
+            Some(taxes.stake_id.clone()),
+            None,
+        );
+        let review = mlw.new_task(
+            "Review spend".to_string(),
+            Some(budget.stake_id.clone()),
+            None,
+        );
+        mlw.new_task("Buy milk".to_string(), None, None);
+
+        mlw.mark_task_complete(&gather.stake_id).unwrap();
+        mlw.mark_task_dropped(&review.stake_id).unwrap();
+        mlw
+    }
+
+    #[test]
+    fn test_hierarchy_groups_projects_under_areas() {
+        let mlw = sample_mlw();
+        let tree = mlw.hierarchy();
+
+        assert_eq!(tree.areas.len(), 2);
+        let finance = &tree.areas[0];
+        assert_eq!(finance.area.stake_name, "Finance");
+        let names: Vec<&str> = finance
+            .projects
+            .iter()
+            .map(|node| node.project.stake_name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Taxes", "Budget"]);
+        assert!(tree.areas[1].projects.is_empty());
+    }
+
+    #[test]
+    fn test_hierarchy_includes_inactive_tasks() {
+        let mlw = sample_mlw();
+        let tree = mlw.hierarchy();
+
+        let taxes = &tree.areas[0].projects[0];
+        assert_eq!(taxes.tasks.len(), 2);
+        assert_eq!(
+            taxes.task_counts,
+            WorkCounts {
+                open: 1,
+                completed: 1,
+                dropped: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_hierarchy_area_counts_roll_up() {
+        let mlw = sample_mlw();
+        let finance = &mlw.hierarchy().areas[0];
+
+        assert_eq!(
+            finance.task_counts,
+            WorkCounts {
+                open: 1,
+                completed: 1,
+                dropped: 1
+            }
+        );
+        assert_eq!(finance.project_counts.open, 2);
+        assert_eq!(finance.tasks().count(), 3);
+    }
+
+    #[test]
+    fn test_hierarchy_lists_unfiled_stakes() {
+        let mlw = sample_mlw();
+        let tree = mlw.hierarchy();
+
+        assert_eq!(tree.unfiled_projects.len(), 1);
+        assert_eq!(tree.unfiled_projects[0].project.stake_name, "Someday trip");
+        assert_eq!(tree.unfiled_tasks.len(), 1);
+        assert_eq!(tree.unfiled_tasks[0].stake_name, "Buy milk");
+        assert_eq!(tree.task_counts().total(), 4);
+    }
+
+    #[test]
+    fn test_hierarchy_project_with_missing_area_is_unfiled() {
+        let mut mlw = MLW::new();
+        mlw.new_project("Orphan".to_string(), Some(StakeId::new(42)), None);
+
+        let tree = mlw.hierarchy();
+        assert_eq!(tree.unfiled_projects.len(), 1);
+    }
+
+    #[test]
+    fn test_hierarchy_iter_walks_depth_first() {
+        let mlw = sample_mlw();
+        let tree = mlw.hierarchy();
+
+        let walk: Vec<(StakeKind, &str, usize)> = tree
+            .iter()
+            .map(|entry| (entry.stake.kind(), entry.stake.stake_name(), entry.depth))
+            .collect();
+        assert_eq!(
+            walk,
+            vec![
+                (StakeKind::Area, "Finance", 0),
+                (StakeKind::Project, "Taxes", 1),
+                (StakeKind::Task, "Gather forms", 2),
+                (StakeKind::Task, "File return", 2),
+                (StakeKind::Project, "Budget", 1),
+                (StakeKind::Task, "Review spend", 2),
+                (StakeKind::Area, "Health", 0),
+                (StakeKind::Project, "Someday trip", 0),
+                (StakeKind::Task, "Buy milk", 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_area_tree_for_single_area() {
+        let mlw = sample_mlw();
+
+        let finance = mlw
+            .area_tree(&StakeId::new(1))
+            .expect("Finance should exist");
+        assert_eq!(finance.iter().count(), 6);
+        assert!(mlw.area_tree(&StakeId::new(999)).is_none());
+    }
+
+    #[test]
+    fn test_project_tree_for_single_project() {
+        let mlw = sample_mlw();
+
+        let taxes = mlw
+            .project_tree(&StakeId::new(1))
+            .expect("Taxes should exist");
+        assert_eq!(taxes.tasks.len(), 2);
+        assert!(mlw.project_tree(&StakeId::new(999)).is_none());
+    }
+
+    #[test]
+    fn test_hierarchy_serializes_for_front_ends() {
+        let mlw = sample_mlw();
+        let json = serde_json::to_value(mlw.hierarchy()).expect("tree should serialize");
+
+        let finance = &json["areas"][0];
+        assert_eq!(finance["area"]["stake_name"], "Finance");
+        assert_eq!(finance["task_counts"]["open"], 1);
+        assert_eq!(finance["projects"][0]["project"]["stake_name"], "Taxes");
+        assert_eq!(
+            finance["projects"][0]["tasks"][1]["stake_name"],
+            "File return"
+        );
+        assert_eq!(json["unfiled_tasks"][0]["stake_name"], "Buy milk");
+    }
+}
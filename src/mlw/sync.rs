@@ -0,0 +1,869 @@
+// Two-way sync between replicas of one MLW, such as a laptop and a server, that may
+// each have changed while apart. Stakes travel as `Record`s keyed by global id, since
+// local ids differ from replica to replica.
+//
+// Each replica remembers, per peer, every record as it stood after their last sync.
+// That base tells which side changed a field: a field changed on one side takes that
+// side's value, and a field changed on both sides is a conflict, settled by last
+// writer wins on the later `date_modified`, then the greater replica id. Dropping is
+// final, so a stake dropped on either side stays dropped. The team travels too: each
+// side adds the members it did not have, so a stake is never assigned to someone who
+// is not a member where it lands.
+//
+// A sync is one round trip, after which both replicas hold the same stakes and the
+// same base:
+//
+//   initiator                                     responder
+//   changes_for(responder)  ---- Changeset ---->  merge_changes
+//   accept_merge           <---- Changeset ----
+//
+// `sync` runs both sides in process.
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::MLW;
+use crate::entities::stake::{Kind, Priority, Stake, StakeId, StakeKind};
+use crate::entities::stakes_collection::StakesCollection;
+use crate::entities::{Clock, GlobalId};
+
+/// Identifies one replica, so that equal timestamps still have a single winner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ReplicaId(Uuid);
+
+impl ReplicaId {
+    pub fn new() -> Self {
+        ReplicaId(Uuid::now_v7())
+    }
+}
+
+impl Default for ReplicaId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<Uuid> for ReplicaId {
+    fn from(uuid: Uuid) -> Self {
+        ReplicaId(uuid)
+    }
+}
+
+impl FromStr for ReplicaId {
+    type Err = uuid::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Uuid::parse_str(s).map(ReplicaId)
+    }
+}
+
+impl fmt::Display for ReplicaId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.hyphenated().fmt(f)
+    }
+}
+
+/// What a replica knows about syncing: its own id and, for each peer, where their
+/// last sync left off. Saved with the data file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncState {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    replica_id: Option<ReplicaId>,
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    peers: IndexMap<ReplicaId, Peer>,
+}
+
+impl SyncState {
+    pub fn is_empty(&self) -> bool {
+        self.replica_id.is_none() && self.peers.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Peer {
+    last_sync: DateTime<Utc>,
+    base: IndexMap<GlobalId, Record>,
+}
+
+/// A stake as it travels between replicas: its parent by global id and no local id.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Record {
+    pub kind: StakeKind,
+    pub global_id: GlobalId,
+    pub parent: Option<GlobalId>,
+    pub stake_name: String,
+    pub complete: bool,
+    pub dropped: bool,
+    pub note: Option<String>,
+    pub date_created: DateTime<Utc>,
+    pub date_modified: DateTime<Utc>,
+    pub date_reviewed: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contexts: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub defer_until: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<Priority>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assignee: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_by: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified_by: Option<String>,
+}
+
+impl Record {
+    fn from_stake<K: Kind>(
+        stake: &Stake<K>,
+        global_id: GlobalId,
+        parent: Option<GlobalId>,
+    ) -> Self {
+        Record {
+            kind: K::KIND,
+            global_id,
+            parent,
+            stake_name: stake.stake_name.clone(),
+            complete: stake.complete,
+            dropped: stake.dropped,
+            note: stake.note.clone(),
+            date_created: stake.date_created,
+            date_modified: stake.date_modified,
+            date_reviewed: stake.date_reviewed,
+            contexts: stake.contexts.clone(),
+            tags: stake.tags.clone(),
+            due: stake.due,
+            defer_until: stake.defer_until,
+            priority: stake.priority,
+            assignee: stake.assignee.clone(),
+            created_by: stake.created_by.clone(),
+            modified_by: stake.modified_by.clone(),
+        }
+    }
+
+    // Copies everything but the parent onto `stake`.
+    fn fill<K: Kind>(&self, stake: &mut Stake<K>) {
+        stake.global_id = Some(self.global_id);
+        stake.stake_name = self.stake_name.clone();
+        stake.complete = self.complete;
+        stake.dropped = self.dropped;
+        stake.note = self.note.clone();
+        stake.date_created = self.date_created;
+        stake.date_modified = self.date_modified;
+        stake.date_reviewed = self.date_reviewed;
+        stake.contexts = self.contexts.clone();
+        stake.tags = self.tags.clone();
+        stake.due = self.due;
+        stake.defer_until = self.defer_until;
+        stake.priority = self.priority;
+        stake.assignee = self.assignee.clone();
+        stake.created_by = self.created_by.clone();
+        stake.modified_by = self.modified_by.clone();
+    }
+}
+
+/// The records one replica sends another.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Changeset {
+    pub replica: ReplicaId,
+    pub records: Vec<Record>,
+    /// The sender's team members.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub members: Vec<String>,
+}
+
+/// A field both replicas changed, and which value was kept.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Conflict {
+    pub kind: StakeKind,
+    pub global_id: GlobalId,
+    pub stake_name: String,
+    pub field: String,
+    pub kept: Value,
+    pub discarded: Value,
+    /// The replica whose value was kept.
+    pub winner: ReplicaId,
+}
+
+/// A change that could not be applied as sent.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Unresolved {
+    pub kind: StakeKind,
+    pub global_id: GlobalId,
+    pub stake_name: String,
+    pub reason: String,
+}
+
+/// The outcome of one sync.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SyncReport {
+    /// How many stakes either side had changed.
+    pub merged: usize,
+    /// Fields changed on both sides, settled by last writer wins.
+    pub conflicts: Vec<Conflict>,
+    /// Changes that need a person: the stake was kept as it was.
+    pub unresolved: Vec<Unresolved>,
+}
+
+/// Syncs two replicas in process. Afterwards both hold the same stakes.
+pub fn sync(initiator: &mut MLW, responder: &mut MLW) -> SyncReport {
+    let changes = initiator.changes_for(responder.replica_id());
+    let (reply, mut report) = responder.merge_changes(changes);
+    report.unresolved.extend(initiator.accept_merge(reply));
+    report
+}
+
+impl MLW {
+    /// This replica's id, chosen the first time it is asked for.
+    pub fn replica_id(&mut self) -> ReplicaId {
+        *self.sync.replica_id.get_or_insert_with(ReplicaId::new)
+    }
+
+    /// When this replica last synced with `peer`, if ever.
+    pub fn last_sync_with(&self, peer: ReplicaId) -> Option<DateTime<Utc>> {
+        self.sync.peers.get(&peer).map(|peer| peer.last_sync)
+    }
+
+    /// Every stake that changed here since the last sync with `peer`; every stake
+    /// if they never synced. Stakes from older files get a global id first.
+    pub fn changes_for(&mut self, peer: ReplicaId) -> Changeset {
+        self.assign_global_ids();
+        let base = self.sync.peers.get(&peer).map(|peer| &peer.base);
+        let records = self
+            .records()
+            .into_iter()
+            .filter(|record| base.and_then(|base| base.get(&record.global_id)) != Some(record))
+            .collect();
+        Changeset {
+            replica: self.replica_id(),
+            records,
+            members: self.members.clone(),
+        }
+    }
+
+    /// Merges a peer's changes into this replica. Answers with the merged record of
+    /// every stake either side changed, for the peer's `accept_merge`.
+    pub fn merge_changes(&mut self, theirs: Changeset) -> (Changeset, SyncReport) {
+        let ours = self.changes_for(theirs.replica);
+        let base = self
+            .sync
+            .peers
+            .get(&theirs.replica)
+            .map(|peer| peer.base.clone())
+            .unwrap_or_default();
+        let mut report = SyncReport::default();
+        let mut merged: IndexMap<GlobalId, Record> = ours
+            .records
+            .into_iter()
+            .map(|record| (record.global_id, record))
+            .collect();
+        for record in theirs.records {
+            let Some(our_record) = merged.get(&record.global_id) else {
+                merged.insert(record.global_id, record);
+                continue;
+            };
+            if our_record.kind != record.kind {
+                report.unresolved.push(unresolved(
+                    our_record,
+                    format!("the other replica has it as a {}", record.kind),
+                ));
+                continue;
+            }
+            // The later writer wins; of two writes at the same instant, the one
+            // from the greater replica id.
+            let ours_wins =
+                (our_record.date_modified, ours.replica) > (record.date_modified, theirs.replica);
+            let sides = Sides {
+                base: base.get(&record.global_id),
+                ours: our_record,
+                theirs: &record,
+                ours_wins,
+                winner: if ours_wins {
+                    ours.replica
+                } else {
+                    theirs.replica
+                },
+            };
+            let merged_record = sides.merge(&mut report.conflicts);
+            merged.insert(record.global_id, merged_record);
+        }
+        report.merged = merged.len();
+        self.join_members(&theirs.members);
+
+        let mut records: Vec<Record> = merged.into_values().collect();
+        records.sort_by_key(|record| record.kind);
+        report.unresolved.extend(self.apply(&records));
+        // Records that could not be applied as merged are sent as they stand here.
+        let records = self.current(records);
+        self.remember(theirs.replica, &records);
+        let reply = Changeset {
+            replica: ours.replica,
+            records,
+            members: self.members.clone(),
+        };
+        (reply, report)
+    }
+
+    /// Applies the answer to `changes_for`, completing a sync this replica started.
+    pub fn accept_merge(&mut self, reply: Changeset) -> Vec<Unresolved> {
+        self.join_members(&reply.members);
+        let unresolved = self.apply(&reply.records);
+        let records = self.current(reply.records);
+        self.remember(reply.replica, &records);
+        unresolved
+    }
+
+    // Adds the members of `members` this replica does not have yet, after its own.
+    fn join_members(&mut self, members: &[String]) {
+        for member in members {
+            self.add_member(member.clone());
+        }
+    }
+
+    fn records(&self) -> Vec<Record> {
+        let areas = self
+            .areas
+            .iter()
+            .filter_map(|area| Some(Record::from_stake(area, area.global_id?, None)));
+        let projects = self.projects.iter().filter_map(|project| {
+            let parent = parent_global_id(&self.areas, project.parent_id.as_ref());
+            Some(Record::from_stake(project, project.global_id?, parent))
+        });
+        let tasks = self.tasks.iter().filter_map(|task| {
+            let parent = parent_global_id(&self.projects, task.parent_id.as_ref());
+            Some(Record::from_stake(task, task.global_id?, parent))
+        });
+        areas.chain(projects).chain(tasks).collect()
+    }
+
+    // The records of the stakes in `records` as they now stand here.
+    fn current(&self, records: Vec<Record>) -> Vec<Record> {
+        let mut here: IndexMap<GlobalId, Record> = self
+            .records()
+            .into_iter()
+            .map(|record| (record.global_id, record))
+            .collect();
+        records
+            .into_iter()
+            .map(|record| here.swap_remove(&record.global_id).unwrap_or(record))
+            .collect()
+    }
+
+    fn remember(&mut self, peer: ReplicaId, records: &[Record]) {
+        let last_sync = self.clock.now();
+        let state = self.sync.peers.entry(peer).or_insert_with(|| Peer {
+            last_sync,
+            base: IndexMap::new(),
+        });
+        state.last_sync = last_sync;
+        for record in records {
+            state.base.insert(record.global_id, record.clone());
+        }
+    }
+
+    // Writes `records`, parents before children, into the collections.
+    fn apply(&mut self, records: &[Record]) -> Vec<Unresolved> {
+        let mut unresolved = Vec::new();
+        for record in records {
+            if let Some(kind) = self
+                .kind_of(&record.global_id)
+                .filter(|k| *k != record.kind)
+            {
+                unresolved.push(self::unresolved(record, format!("it is a {} here", kind)));
+                continue;
+            }
+            // A peer that does not send its team still assigns only to its members.
+            if let Some(assignee) = &record.assignee {
+                self.add_member(assignee.clone());
+            }
+            match record.kind {
+                StakeKind::Area => {
+                    if record.parent.is_some() {
+                        unresolved.push(self::unresolved(record, "areas have no parent".into()));
+                    }
+                    upsert(&mut self.areas, record, Parent::Keep);
+                }
+                StakeKind::Project => {
+                    let parent = self.find_parent(&self.areas, record, &mut unresolved);
+                    upsert(&mut self.projects, record, parent);
+                }
+                StakeKind::Task => {
+                    let parent = self.find_parent(&self.projects, record, &mut unresolved);
+                    upsert(&mut self.tasks, record, parent);
+                }
+            }
+        }
+        unresolved
+    }
+
+    fn kind_of(&self, global_id: &GlobalId) -> Option<StakeKind> {
+        if self.areas.local_id(global_id).is_some() {
+            Some(StakeKind::Area)
+        } else if self.projects.local_id(global_id).is_some() {
+            Some(StakeKind::Project)
+        } else if self.tasks.local_id(global_id).is_some() {
+            Some(StakeKind::Task)
+        } else {
+            None
+        }
+    }
+
+    fn find_parent<P: Kind>(
+        &self,
+        parents: &StakesCollection<P>,
+        record: &Record,
+        unresolved: &mut Vec<Unresolved>,
+    ) -> Parent<P> {
+        let Some(parent) = &record.parent else {
+            return Parent::Set(None);
+        };
+        match parents.local_id(parent) {
+            Some(id) => Parent::Set(Some(id)),
+            None => {
+                unresolved.push(self::unresolved(
+                    record,
+                    format!("its parent {} is not known here", parent),
+                ));
+                Parent::Keep
+            }
+        }
+    }
+}
+
+fn parent_global_id<P: Kind>(
+    parents: &StakesCollection<P>,
+    parent_id: Option<&StakeId<P>>,
+) -> Option<GlobalId> {
+    parents.get_by_id(parent_id?)?.global_id
+}
+
+fn unresolved(record: &Record, reason: String) -> Unresolved {
+    Unresolved {
+        kind: record.kind,
+        global_id: record.global_id,
+        stake_name: record.stake_name.clone(),
+        reason,
+    }
+}
+
+// Where an applied record's stake goes: under a given parent, or wherever it is now
+// (nowhere, for a new stake).
+enum Parent<P> {
+    Set(Option<StakeId<P>>),
+    Keep,
+}
+
+fn upsert<K: Kind>(
+    collection: &mut StakesCollection<K>,
+    record: &Record,
+    parent: Parent<K::Parent>,
+) {
+    let existing = collection.local_id(&record.global_id);
+    let mut stake = match &existing {
+        Some(id) => collection
+            .get_by_id(id)
+            .cloned()
+            .expect("global ids map to stakes"),
+        None => Stake::new(collection.generate_id(), String::new(), None, None),
+    };
+    record.fill(&mut stake);
+    if let Parent::Set(parent_id) = parent {
+        stake.parent_id = parent_id;
+    }
+    match existing {
+        Some(_) => collection
+            .update_stake(stake)
+            .expect("the stake was just found"),
+        None => collection.add_stake(stake),
+    }
+}
+
+// One stake as changed on both sides, and as it was when they last agreed.
+struct Sides<'a> {
+    base: Option<&'a Record>,
+    ours: &'a Record,
+    theirs: &'a Record,
+    ours_wins: bool,
+    winner: ReplicaId,
+}
+
+impl Sides<'_> {
+    fn merge(&self, conflicts: &mut Vec<Conflict>) -> Record {
+        let mut found = Vec::new();
+        let merged = Record {
+            kind: self.ours.kind,
+            global_id: self.ours.global_id,
+            parent: self.field("parent", |r| &r.parent, &mut found),
+            stake_name: self.field("stake_name", |r| &r.stake_name, &mut found),
+            complete: self.field("complete", |r| &r.complete, &mut found),
+            dropped: self.ours.dropped || self.theirs.dropped,
+            note: self.field("note", |r| &r.note, &mut found),
+            date_created: self.ours.date_created.min(self.theirs.date_created),
+            date_modified: self.ours.date_modified.max(self.theirs.date_modified),
+            date_reviewed: self.ours.date_reviewed.max(self.theirs.date_reviewed),
+            contexts: self.field("contexts", |r| &r.contexts, &mut found),
+            tags: self.field("tags", |r| &r.tags, &mut found),
+            due: self.field("due", |r| &r.due, &mut found),
+            defer_until: self.field("defer_until", |r| &r.defer_until, &mut found),
+            priority: self.field("priority", |r| &r.priority, &mut found),
+            assignee: self.field("assignee", |r| &r.assignee, &mut found),
+            // Whoever created it first, and whoever made the winning change.
+            created_by: self.created_by(),
+            modified_by: if self.ours_wins {
+                self.ours.modified_by.clone()
+            } else {
+                self.theirs.modified_by.clone()
+            },
+        };
+        conflicts.extend(found.into_iter().map(|(field, kept, discarded)| Conflict {
+            kind: merged.kind,
+            global_id: merged.global_id,
+            stake_name: merged.stake_name.clone(),
+            field: field.to_string(),
+            kept,
+            discarded,
+            winner: self.winner,
+        }));
+        merged
+    }
+
+    fn created_by(&self) -> Option<String> {
+        let first = if self.theirs.date_created < self.ours.date_created {
+            self.theirs
+        } else {
+            self.ours
+        };
+        first
+            .created_by
+            .clone()
+            .or_else(|| self.ours.created_by.clone())
+            .or_else(|| self.theirs.created_by.clone())
+    }
+
+    // The merged value of one field. A field only one side changed keeps that
+    // change; one both changed goes to the last writer and is noted in `found`.
+    fn field<T: Clone + PartialEq + Serialize>(
+        &self,
+        name: &'static str,
+        get: impl Fn(&Record) -> &T,
+        found: &mut Vec<(&'static str, Value, Value)>,
+    ) -> T {
+        let (ours, theirs) = (get(self.ours), get(self.theirs));
+        let base = self.base.map(&get);
+        if ours == theirs || base == Some(theirs) {
+            return ours.clone();
+        }
+        if base == Some(ours) {
+            return theirs.clone();
+        }
+        let (kept, discarded) = if self.ours_wins {
+            (ours, theirs)
+        } else {
+            (theirs, ours)
+        };
+        found.push((name, json(kept), json(discarded)));
+        kept.clone()
+    }
+}
+
+fn json<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).expect("stake fields serialize")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::{FakeClock, ProjectId, SharedClock, TaskId};
+    use chrono::{Duration, TimeZone};
+
+    // Two replicas on their own clocks, starting from the same moment.
+    fn replicas() -> (MLW, FakeClock, MLW, FakeClock) {
+        let start = Utc.with_ymd_and_hms(2026, 10, 18, 9, 0, 0).unwrap();
+        let (laptop_clock, server_clock) = (FakeClock::new(start), FakeClock::new(start));
+        let laptop = MLW::with_clock(SharedClock::new(laptop_clock.clone()));
+        let server = MLW::with_clock(SharedClock::new(server_clock.clone()));
+        (laptop, laptop_clock, server, server_clock)
+    }
+
+    fn task_named<'a>(mlw: &'a MLW, name: &str) -> &'a Stake<crate::entities::Task> {
+        mlw.tasks
+            .iter()
+            .find(|task| task.stake_name == name)
+            .expect("task exists")
+    }
+
+    fn edit_task(
+        mlw: &mut MLW,
+        clock: &FakeClock,
+        id: u32,
+        edit: impl FnOnce(&mut Stake<crate::entities::Task>),
+    ) {
+        clock.advance(Duration::minutes(1));
+        let mut task = mlw.get_task_by_id(&TaskId::new(id)).unwrap().clone();
+        edit(&mut task);
+        task.date_modified = clock.now();
+        mlw.update_task(task).unwrap();
+    }
+
+    // Both replicas after a first sync, holding one project and its task.
+    fn synced() -> (MLW, FakeClock, MLW, FakeClock) {
+        let (mut laptop, laptop_clock, mut server, server_clock) = replicas();
+        let project = laptop.new_project("Garden".to_string(), None, None);
+        laptop.new_task("Dig".to_string(), Some(project.stake_id), None);
+        let report = sync(&mut laptop, &mut server);
+        assert!(report.conflicts.is_empty());
+        (laptop, laptop_clock, server, server_clock)
+    }
+
+    #[test]
+    fn test_first_sync_copies_both_ways_and_translates_ids() {
+        let (mut laptop, _, mut server, _) = replicas();
+        let area = laptop.new_area("Home".to_string(), None);
+        laptop.new_project("Garden".to_string(), Some(area.stake_id), None);
+        server.new_task("Before".to_string(), None, None);
+        let project = server.new_project("Taxes".to_string(), None, None);
+        server.new_task("File".to_string(), Some(project.stake_id), None);
+
+        let report = sync(&mut laptop, &mut server);
+        assert_eq!(report.merged, 5);
+        assert!(report.conflicts.is_empty());
+        assert!(report.unresolved.is_empty());
+        assert_eq!(laptop.records(), server.current(laptop.records()));
+        assert_eq!(laptop.tasks.len(), 2);
+        assert_eq!(server.projects.len(), 2);
+
+        // Local ids differ, parents follow by global id.
+        let file = task_named(&laptop, "File");
+        let taxes = laptop.get_project_by_id(file.parent_id.as_ref().unwrap());
+        assert_eq!(taxes.unwrap().stake_name, "Taxes");
+        let garden = server.get_project_by_id(&ProjectId::new(2)).unwrap();
+        assert_eq!(garden.stake_name, "Garden");
+        let home = server.get_area_by_id(garden.parent_id.as_ref().unwrap());
+        assert_eq!(home.unwrap().stake_name, "Home");
+        let server_id = server.replica_id();
+        assert!(laptop.last_sync_with(server_id).is_some());
+    }
+
+    #[test]
+    fn test_second_sync_without_edits_sends_nothing() {
+        let (mut laptop, _, mut server, _) = synced();
+        let server_id = server.replica_id();
+        assert!(laptop.changes_for(server_id).records.is_empty());
+        let report = sync(&mut laptop, &mut server);
+        assert_eq!(report, SyncReport::default());
+    }
+
+    #[test]
+    fn test_edits_to_different_fields_merge() {
+        let (mut laptop, laptop_clock, mut server, server_clock) = synced();
+        edit_task(&mut laptop, &laptop_clock, 1, |task| {
+            task.note = Some("By the fence".to_string())
+        });
+        edit_task(&mut server, &server_clock, 1, |task| {
+            task.stake_name = "Dig beds".to_string()
+        });
+
+        let report = sync(&mut laptop, &mut server);
+        assert_eq!(report.merged, 1);
+        assert!(report.conflicts.is_empty());
+        for mlw in [&laptop, &server] {
+            let task = task_named(mlw, "Dig beds");
+            assert_eq!(task.note.as_deref(), Some("By the fence"));
+        }
+        assert_eq!(laptop, server);
+    }
+
+    #[test]
+    fn test_same_field_goes_to_the_later_writer_and_is_reported() {
+        let (mut laptop, laptop_clock, mut server, server_clock) = synced();
+        edit_task(&mut server, &server_clock, 1, |task| {
+            task.stake_name = "Dig early".to_string()
+        });
+        laptop_clock.advance(Duration::minutes(5));
+        edit_task(&mut laptop, &laptop_clock, 1, |task| {
+            task.stake_name = "Dig late".to_string()
+        });
+
+        let report = sync(&mut laptop, &mut server);
+        assert_eq!(report.conflicts.len(), 1);
+        let conflict = &report.conflicts[0];
+        assert_eq!(conflict.field, "stake_name");
+        assert_eq!(conflict.kept, "Dig late");
+        assert_eq!(conflict.discarded, "Dig early");
+        assert_eq!(conflict.winner, laptop.replica_id());
+        assert!(report.unresolved.is_empty());
+        assert_eq!(
+            task_named(&server, "Dig late").date_modified,
+            laptop_clock.now()
+        );
+        assert_eq!(laptop, server);
+    }
+
+    #[test]
+    fn test_assignee_and_authors_travel_with_the_merge() {
+        let (mut laptop, laptop_clock, mut server, server_clock) = synced();
+        server.add_member("cal".to_string());
+        laptop.set_author(Some("ana".to_string()));
+        edit_task(&mut laptop, &laptop_clock, 1, |task| {
+            task.note = Some("By the fence".to_string())
+        });
+        server.set_author(Some("ben".to_string()));
+        server_clock.advance(Duration::minutes(5));
+        edit_task(&mut server, &server_clock, 1, |task| {
+            task.assignee = Some("cal".to_string())
+        });
+        laptop.set_author(None);
+        let area = laptop.new_area("Home".to_string(), None);
+
+        let report = sync(&mut laptop, &mut server);
+        assert!(report.conflicts.is_empty());
+        for mlw in [&laptop, &server] {
+            let task = task_named(mlw, "Dig");
+            assert_eq!(task.note.as_deref(), Some("By the fence"));
+            assert_eq!(task.assignee.as_deref(), Some("cal"));
+            // The later change was the server's, so it is the one remembered.
+            assert_eq!(task.modified_by.as_deref(), Some("ben"));
+        }
+        // Syncing records what happened elsewhere; it changes no authors itself.
+        let home = server.areas.local_id(&area.global_id.unwrap()).unwrap();
+        assert_eq!(server.get_area_by_id(&home).unwrap().created_by, None);
+        assert_eq!(laptop, server);
+
+        // The assignee came with the team, so the laptop can still edit the task.
+        assert_eq!(laptop.members(), ["cal"]);
+        edit_task(&mut laptop, &laptop_clock, 1, |task| {
+            task.stake_name = "Dig deep".to_string()
+        });
+        task_named(&laptop, "Dig deep");
+    }
+
+    #[test]
+    fn test_members_of_both_sides_are_joined() {
+        let (mut laptop, _, mut server, _) = synced();
+        laptop.add_member("ana".to_string());
+        laptop.add_member("ben".to_string());
+        server.add_member("ben".to_string());
+        server.add_member("cal".to_string());
+
+        sync(&mut laptop, &mut server);
+        // Each side keeps its own members first.
+        assert_eq!(laptop.members(), ["ana", "ben", "cal"]);
+        assert_eq!(server.members(), ["ben", "cal", "ana"]);
+        assert_eq!(laptop, server);
+    }
+
+    #[test]
+    fn test_an_assignee_from_a_peer_without_members_joins_the_team() {
+        let (mut laptop, laptop_clock, mut server, _) = synced();
+        laptop.add_member("cal".to_string());
+        edit_task(&mut laptop, &laptop_clock, 1, |task| {
+            task.assignee = Some("cal".to_string())
+        });
+        let mut changes = laptop.changes_for(server.replica_id());
+        changes.members.clear();
+
+        server.merge_changes(changes);
+        assert!(server.is_member("cal"));
+        assert_eq!(task_named(&server, "Dig").assignee.as_deref(), Some("cal"));
+    }
+
+    #[test]
+    fn test_ties_go_to_the_greater_replica_whoever_starts() {
+        for laptop_starts in [true, false] {
+            let (mut laptop, laptop_clock, mut server, server_clock) = synced();
+            edit_task(&mut laptop, &laptop_clock, 1, |task| {
+                task.stake_name = "Laptop".to_string()
+            });
+            edit_task(&mut server, &server_clock, 1, |task| {
+                task.stake_name = "Server".to_string()
+            });
+            let (laptop_id, server_id) = (laptop.replica_id(), server.replica_id());
+            let expected = if laptop_id > server_id {
+                "Laptop"
+            } else {
+                "Server"
+            };
+
+            let report = if laptop_starts {
+                sync(&mut laptop, &mut server)
+            } else {
+                sync(&mut server, &mut laptop)
+            };
+            assert_eq!(report.conflicts[0].kept, expected);
+            assert_eq!(report.conflicts[0].winner, laptop_id.max(server_id));
+            task_named(&laptop, expected);
+            assert_eq!(laptop, server);
+        }
+    }
+
+    #[test]
+    fn test_dropping_wins_over_later_edits() {
+        let (mut laptop, laptop_clock, mut server, server_clock) = synced();
+        edit_task(&mut laptop, &laptop_clock, 1, |task| task.dropped = true);
+        server_clock.advance(Duration::hours(1));
+        edit_task(&mut server, &server_clock, 1, |task| {
+            task.tags = vec!["outside".to_string()]
+        });
+
+        let report = sync(&mut laptop, &mut server);
+        assert!(report.conflicts.is_empty());
+        for mlw in [&laptop, &server] {
+            let task = task_named(mlw, "Dig");
+            assert!(task.dropped);
+            assert_eq!(task.tags, vec!["outside".to_string()]);
+        }
+        assert_eq!(laptop, server);
+    }
+
+    #[test]
+    fn test_unknown_parent_is_reported_and_the_stake_kept_in_place() {
+        let (mut laptop, _, mut server, _) = synced();
+        let laptop_id = laptop.replica_id();
+        let mut record = task_named(&laptop, "Dig").clone();
+        record.stake_name = "Dig elsewhere".to_string();
+        let mut record =
+            Record::from_stake(&record, record.global_id.unwrap(), Some(GlobalId::new()));
+        record.date_modified += Duration::minutes(1);
+
+        let (reply, report) = server.merge_changes(Changeset {
+            replica: laptop_id,
+            records: vec![record],
+            members: Vec::new(),
+        });
+        assert_eq!(report.unresolved.len(), 1);
+        assert_eq!(report.unresolved[0].stake_name, "Dig elsewhere");
+        assert!(report.unresolved[0].reason.contains("parent"));
+        let task = task_named(&server, "Dig elsewhere");
+        assert_eq!(task.parent_id, Some(ProjectId::new(1)));
+
+        // The reply carries the stake as the server placed it, so the laptop agrees.
+        assert!(laptop.accept_merge(reply).is_empty());
+        assert_eq!(laptop, server);
+    }
+
+    #[test]
+    fn test_sync_state_is_saved_with_the_mlw() {
+        let (laptop, _, mut server, _) = synced();
+        let server_id = server.replica_id();
+        let json = serde_json::to_string(&laptop).unwrap();
+        let mut loaded: MLW = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            loaded.last_sync_with(server_id),
+            laptop.last_sync_with(server_id)
+        );
+        assert!(loaded.changes_for(server_id).records.is_empty());
+
+        let fresh = serde_json::to_value(MLW::new()).unwrap();
+        assert!(fresh.get("sync").is_none());
+    }
+}
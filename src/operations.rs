@@ -0,0 +1,1354 @@
+// The commands a user can run against MLW, shared by every front end.
+//
+// `Command` doubles as the clap definition of the CLI subcommands, and `execute`
+// returns owned views so callers are free to mutate MLW again before rendering.
+// Both serialize, so a command can also be sent to `mlw serve` and run there.
+
+use std::fmt;
+
+use chrono::{Duration, Utc};
+use clap::Subcommand;
+use serde::{Deserialize, Serialize};
+
+use crate::capture::{self, CaptureError};
+use crate::dates::{DateContext, DateError};
+use crate::entities::{AreaId, ProjectId, Stake, StakeError, StakeKind, Task, TaskId};
+use crate::markdown::{self, ExportOptions};
+use crate::mlw::MLW;
+use crate::mlw::changes::Scope;
+use crate::presenter::{ScoredView, StakeView, Status};
+
+#[derive(Debug, Clone, PartialEq, Eq, Subcommand, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Command {
+    /// Capture a task: `Call dentist @phone #health +Project due:fri !high // note`
+    Capture {
+        #[arg(required = true)]
+        text: Vec<String>,
+    },
+    /// Manage areas of responsibility
+    Area {
+        #[command(subcommand)]
+        action: AreaAction,
+    },
+    /// Manage projects
+    Project {
+        #[command(subcommand)]
+        action: ProjectAction,
+    },
+    /// Manage tasks
+    Task {
+        #[command(subcommand)]
+        action: TaskAction,
+    },
+    /// List stakes of one kind, open ones only unless --all is given
+    List {
+        #[arg(default_value = "task")]
+        kind: StakeKind,
+        #[arg(long)]
+        all: bool,
+    },
+    /// Mark a stake as done
+    Done { kind: StakeKind, id: String },
+    /// Drop a stake without completing it
+    Drop { kind: StakeKind, id: String },
+    /// File a project under an area or a task under a project; omit --to to unfile it
+    Move {
+        kind: StakeKind,
+        id: String,
+        #[arg(long)]
+        to: Option<String>,
+    },
+    /// Search names and notes, best match first
+    Search {
+        #[arg(required = true)]
+        query: Vec<String>,
+        /// Match names approximately instead of by whole words
+        #[arg(long)]
+        fuzzy: bool,
+    },
+    /// Show the inbox, stakes due for review and projects without a next action
+    Review {
+        /// How many days a review stays fresh
+        #[arg(long, default_value_t = 7)]
+        days: i64,
+        /// Record the listed areas and projects as reviewed now
+        #[arg(long)]
+        mark: bool,
+    },
+    /// Show one stake and its children
+    Show { kind: StakeKind, id: String },
+    /// Show overdue tasks, tasks due today and tasks due later this week
+    Agenda,
+    /// Assign a project or task to a team member; omit the user to unassign it
+    Assign {
+        kind: StakeKind,
+        id: String,
+        user: Option<String>,
+    },
+    /// List the projects and tasks assigned to a team member, open ones only unless --all is given
+    Assigned {
+        user: String,
+        #[arg(long)]
+        all: bool,
+    },
+    /// Manage the people who share this workspace; lists them by default
+    Team {
+        #[command(subcommand)]
+        action: Option<TeamAction>,
+    },
+    /// Print areas, projects and tasks as Markdown, open ones only unless asked
+    Export {
+        /// Export only this area and what is filed under it
+        #[arg(long)]
+        area: Option<String>,
+        /// Export only this project and its tasks
+        #[arg(long, conflicts_with = "area")]
+        project: Option<String>,
+        /// Include completed stakes
+        #[arg(long)]
+        completed: bool,
+        /// Include dropped stakes
+        #[arg(long)]
+        dropped: bool,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Subcommand, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AreaAction {
+    /// Add a new area
+    Add {
+        #[arg(required = true)]
+        name: Vec<String>,
+        #[arg(long)]
+        note: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Subcommand, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectAction {
+    /// Add a new project, optionally filed under an area
+    Add {
+        #[arg(required = true)]
+        name: Vec<String>,
+        #[arg(long)]
+        area: Option<String>,
+        #[arg(long)]
+        note: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Subcommand, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskAction {
+    /// Add a new task, optionally filed under a project
+    Add {
+        #[arg(required = true)]
+        name: Vec<String>,
+        #[arg(long)]
+        project: Option<String>,
+        #[arg(long)]
+        note: Option<String>,
+        /// When the task is due: "fri", "in 3 days", "end of month", "2026-11-01 9am"...
+        #[arg(long)]
+        due: Option<String>,
+        /// Hide the task until this date; same forms as --due
+        #[arg(long)]
+        defer: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Subcommand, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TeamAction {
+    /// Add a team member, who can then be assigned stakes
+    Add { name: String },
+    /// Remove a team member and unassign everything assigned to them
+    Remove { name: String },
+    /// List the team members
+    List,
+}
+
+impl Command {
+    /// Whether running the command changes MLW, and so whether it needs saving afterwards.
+    pub fn is_mutating(&self) -> bool {
+        match self {
+            Command::List { .. }
+            | Command::Search { .. }
+            | Command::Show { .. }
+            | Command::Agenda
+            | Command::Assigned { .. }
+            | Command::Export { .. }
+            | Command::Team {
+                action: None | Some(TeamAction::List),
+            } => false,
+            Command::Review { mark, .. } => *mark,
+            _ => true,
+        }
+    }
+}
+
+/// What a command produced, ready to hand to the presenter.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum Outcome {
+    Created(StakeView),
+    Updated(StakeView),
+    Stakes(Vec<StakeView>),
+    Hits(Vec<ScoredView>),
+    Detail {
+        stake: StakeView,
+        children: Vec<StakeView>,
+    },
+    Review(ReviewReport),
+    Agenda(AgendaReport),
+    Members(Vec<String>),
+    Markdown(String),
+}
+
+/// The lists a weekly review walks through.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReviewReport {
+    /// Open tasks not yet filed under a project.
+    pub inbox: Vec<StakeView>,
+    /// Open areas and projects never reviewed, or not reviewed recently.
+    pub due: Vec<StakeView>,
+    /// Open projects with no open tasks.
+    pub stalled: Vec<StakeView>,
+}
+
+/// Open tasks with due dates, by day in the user's timezone.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgendaReport {
+    pub overdue: Vec<StakeView>,
+    pub today: Vec<StakeView>,
+    /// Due after today but before the week ends.
+    pub this_week: Vec<StakeView>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum OperationError {
+    Stake(StakeError),
+    Capture(CaptureError),
+    Date(DateError),
+    UnknownHandle { kind: StakeKind, handle: String },
+    Invalid(String),
+}
+
+impl fmt::Display for OperationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OperationError::Stake(error) => error.fmt(f),
+            OperationError::Capture(error) => error.fmt(f),
+            OperationError::Date(error) => error.fmt(f),
+            OperationError::UnknownHandle { kind, handle } => {
+                write!(f, "no {} matches '{}'", kind, handle)
+            }
+            OperationError::Invalid(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for OperationError {}
+
+impl From<StakeError> for OperationError {
+    fn from(error: StakeError) -> Self {
+        OperationError::Stake(error)
+    }
+}
+
+impl From<CaptureError> for OperationError {
+    fn from(error: CaptureError) -> Self {
+        OperationError::Capture(error)
+    }
+}
+
+impl From<DateError> for OperationError {
+    fn from(error: DateError) -> Self {
+        OperationError::Date(error)
+    }
+}
+
+fn area_id(mlw: &MLW, handle: &str) -> Result<AreaId, OperationError> {
+    mlw.resolve_area(handle)
+        .ok_or_else(|| unknown(StakeKind::Area, handle))
+}
+
+fn project_id(mlw: &MLW, handle: &str) -> Result<ProjectId, OperationError> {
+    mlw.resolve_project(handle)
+        .ok_or_else(|| unknown(StakeKind::Project, handle))
+}
+
+fn task_id(mlw: &MLW, handle: &str) -> Result<TaskId, OperationError> {
+    mlw.resolve_task(handle)
+        .ok_or_else(|| unknown(StakeKind::Task, handle))
+}
+
+fn unknown(kind: StakeKind, handle: &str) -> OperationError {
+    OperationError::UnknownHandle {
+        kind,
+        handle: handle.to_string(),
+    }
+}
+
+// Looks a stake up again after a change so the caller sees its new state.
+fn view(mlw: &MLW, kind: StakeKind, id: u32) -> StakeView {
+    match kind {
+        StakeKind::Area => mlw.areas().get_by_id(&AreaId::new(id)).map(StakeView::from),
+        StakeKind::Project => mlw
+            .projects()
+            .get_by_id(&ProjectId::new(id))
+            .map(StakeView::from),
+        StakeKind::Task => mlw.tasks().get_by_id(&TaskId::new(id)).map(StakeView::from),
+    }
+    .unwrap_or_else(|| panic!("{} {} vanished while being updated", kind, id))
+}
+
+/// Stakes of every kind matching `query`, best first. `fuzzy` matches names
+/// approximately instead of by whole words.
+pub fn search(mlw: &MLW, query: &str, fuzzy: bool) -> Vec<ScoredView> {
+    let mut hits: Vec<ScoredView> = if fuzzy {
+        mlw.fuzzy_search(query)
+            .into_iter()
+            .map(|hit| ScoredView {
+                score: hit.score,
+                stake: hit.stake.into(),
+            })
+            .collect()
+    } else {
+        let areas = mlw.areas().search(query).into_iter().map(|hit| ScoredView {
+            score: hit.score,
+            stake: hit.stake.into(),
+        });
+        let projects = mlw
+            .projects()
+            .search(query)
+            .into_iter()
+            .map(|hit| ScoredView {
+                score: hit.score,
+                stake: hit.stake.into(),
+            });
+        let tasks = mlw.tasks().search(query).into_iter().map(|hit| ScoredView {
+            score: hit.score,
+            stake: hit.stake.into(),
+        });
+        areas.chain(projects).chain(tasks).collect()
+    };
+    // Stable, so equal scores keep areas before projects before tasks.
+    hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+    hits
+}
+
+/// Runs one command against `mlw`. `dates` gives the current moment and the user's
+/// timezone, for reading typed dates and for date-based queries.
+pub fn execute(
+    mlw: &mut MLW,
+    command: &Command,
+    dates: &DateContext,
+) -> Result<Outcome, OperationError> {
+    match command {
+        Command::Capture { text } => {
+            // A single argument is the whole line, as typed inside quotes. Otherwise
+            // each argument is one word, so `"+Run a 10k"` keeps its grouping.
+            let line = match text.as_slice() {
+                [line] => line.clone(),
+                words => words
+                    .iter()
+                    .map(|word| {
+                        if word.contains(char::is_whitespace) {
+                            format!("\"{}\"", word)
+                        } else {
+                            word.clone()
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            };
+            let task = capture::capture(mlw, &line, dates)?;
+            Ok(Outcome::Created((&task).into()))
+        }
+        Command::Area {
+            action: AreaAction::Add { name, note },
+        } => {
+            let area = mlw.new_area(name.join(" "), note.clone());
+            Ok(Outcome::Created((&area).into()))
+        }
+        Command::Project {
+            action: ProjectAction::Add { name, area, note },
+        } => {
+            let parent = area.as_deref().map(|h| area_id(mlw, h)).transpose()?;
+            let project = mlw.new_project(name.join(" "), parent, note.clone());
+            Ok(Outcome::Created((&project).into()))
+        }
+        Command::Task {
+            action:
+                TaskAction::Add {
+                    name,
+                    project,
+                    note,
+                    due,
+                    defer,
+                },
+        } => {
+            let parent = project.as_deref().map(|h| project_id(mlw, h)).transpose()?;
+            let due = due.as_deref().map(|due| dates.parse(due)).transpose()?;
+            let defer = defer
+                .as_deref()
+                .map(|defer| dates.parse(defer))
+                .transpose()?;
+            let mut task = mlw.new_task(name.join(" "), parent, note.clone());
+            if due.is_some() || defer.is_some() {
+                task.due = due;
+                task.defer_until = defer;
+                mlw.update_task(task.clone())?;
+            }
+            Ok(Outcome::Created((&task).into()))
+        }
+        Command::Done { kind, id } => {
+            let number = match kind {
+                StakeKind::Area => {
+                    let id = area_id(mlw, id)?;
+                    mlw.mark_area_complete(&id)?;
+                    id.0
+                }
+                StakeKind::Project => {
+                    let id = project_id(mlw, id)?;
+                    mlw.mark_project_complete(&id)?;
+                    id.0
+                }
+                StakeKind::Task => {
+                    let id = task_id(mlw, id)?;
+                    mlw.mark_task_complete(&id)?;
+                    id.0
+                }
+            };
+            Ok(Outcome::Updated(view(mlw, *kind, number)))
+        }
+        Command::Drop { kind, id } => {
+            let number = match kind {
+                StakeKind::Area => {
+                    let id = area_id(mlw, id)?;
+                    mlw.mark_area_dropped(&id)?;
+                    id.0
+                }
+                StakeKind::Project => {
+                    let id = project_id(mlw, id)?;
+                    mlw.mark_project_dropped(&id)?;
+                    id.0
+                }
+                StakeKind::Task => {
+                    let id = task_id(mlw, id)?;
+                    mlw.mark_task_dropped(&id)?;
+                    id.0
+                }
+            };
+            Ok(Outcome::Updated(view(mlw, *kind, number)))
+        }
+        Command::Move { kind, id, to } => {
+            let number = match kind {
+                StakeKind::Area => {
+                    return Err(OperationError::Invalid(
+                        "areas sit at the top and cannot be moved".to_string(),
+                    ));
+                }
+                StakeKind::Project => {
+                    let id = project_id(mlw, id)?;
+                    let parent = to.as_deref().map(|h| area_id(mlw, h)).transpose()?;
+                    mlw.move_project(&id, parent)?;
+                    id.0
+                }
+                StakeKind::Task => {
+                    let id = task_id(mlw, id)?;
+                    let parent = to.as_deref().map(|h| project_id(mlw, h)).transpose()?;
+                    mlw.move_task(&id, parent)?;
+                    id.0
+                }
+            };
+            Ok(Outcome::Updated(view(mlw, *kind, number)))
+        }
+        Command::Review { days, mark: true } => {
+            let (due_areas, due_projects) = due_for_review(mlw, *days, dates);
+            for id in &due_areas {
+                mlw.mark_area_reviewed(id)?;
+            }
+            for id in &due_projects {
+                mlw.mark_project_reviewed(id)?;
+            }
+            Ok(Outcome::Review(review_report(
+                mlw,
+                &due_areas,
+                &due_projects,
+            )))
+        }
+        Command::Assign { kind, id, user } => {
+            let number = match kind {
+                StakeKind::Area => {
+                    return Err(OperationError::Invalid(
+                        "areas cannot be assigned; assign their projects instead".to_string(),
+                    ));
+                }
+                StakeKind::Project => {
+                    let id = project_id(mlw, id)?;
+                    mlw.assign_project(&id, user.clone())?;
+                    id.0
+                }
+                StakeKind::Task => {
+                    let id = task_id(mlw, id)?;
+                    mlw.assign_task(&id, user.clone())?;
+                    id.0
+                }
+            };
+            Ok(Outcome::Updated(view(mlw, *kind, number)))
+        }
+        Command::Team {
+            action: Some(TeamAction::Add { name }),
+        } => {
+            let name = name.trim();
+            if name.is_empty() {
+                return Err(OperationError::Invalid(
+                    "team members need a name".to_string(),
+                ));
+            }
+            if !mlw.add_member(name.to_string()) {
+                return Err(OperationError::Invalid(format!(
+                    "'{}' is already on the team",
+                    name
+                )));
+            }
+            Ok(Outcome::Members(mlw.members().to_vec()))
+        }
+        Command::Team {
+            action: Some(TeamAction::Remove { name }),
+        } => {
+            if !mlw.remove_member(name) {
+                return Err(StakeError::UnknownUser(name.clone()).into());
+            }
+            Ok(Outcome::Members(mlw.members().to_vec()))
+        }
+        _ => query(mlw, command, dates),
+    }
+}
+
+/// Runs a command that only reads, so it needs no mutable access. Commands that
+/// change MLW are refused; run those through `execute`.
+pub fn query(mlw: &MLW, command: &Command, dates: &DateContext) -> Result<Outcome, OperationError> {
+    match command {
+        Command::List { kind, all } => {
+            let keep = |view: &StakeView| *all || view.status == Status::Open;
+            let views: Vec<StakeView> = match kind {
+                StakeKind::Area => mlw.areas().iter().map(StakeView::from).collect(),
+                StakeKind::Project => mlw.projects().iter().map(StakeView::from).collect(),
+                StakeKind::Task => mlw.tasks().iter().map(StakeView::from).collect(),
+            };
+            Ok(Outcome::Stakes(views.into_iter().filter(keep).collect()))
+        }
+        Command::Search { query, fuzzy } => {
+            Ok(Outcome::Hits(search(mlw, &query.join(" "), *fuzzy)))
+        }
+        Command::Review { days, mark: false } => {
+            let (due_areas, due_projects) = due_for_review(mlw, *days, dates);
+            Ok(Outcome::Review(review_report(
+                mlw,
+                &due_areas,
+                &due_projects,
+            )))
+        }
+        Command::Show { kind, id } => {
+            let (stake, children) = match kind {
+                StakeKind::Area => {
+                    let id = area_id(mlw, id)?;
+                    let children = mlw.projects().get_all_children(&id);
+                    (
+                        view(mlw, StakeKind::Area, id.0),
+                        children.into_iter().map(StakeView::from).collect(),
+                    )
+                }
+                StakeKind::Project => {
+                    let id = project_id(mlw, id)?;
+                    let children = mlw.tasks().get_all_children(&id);
+                    (
+                        view(mlw, StakeKind::Project, id.0),
+                        children.into_iter().map(StakeView::from).collect(),
+                    )
+                }
+                StakeKind::Task => {
+                    let id = task_id(mlw, id)?;
+                    (view(mlw, StakeKind::Task, id.0), Vec::new())
+                }
+            };
+            Ok(Outcome::Detail { stake, children })
+        }
+        Command::Agenda => {
+            let views = |tasks: Vec<&Stake<Task>>| tasks.into_iter().map(StakeView::from).collect();
+            let tomorrow = dates.start_of_day(dates.today() + Duration::days(1));
+            let later_this_week = mlw
+                .tasks_due_this_week(dates)
+                .into_iter()
+                .filter(|task| task.due >= Some(tomorrow))
+                .collect();
+            Ok(Outcome::Agenda(AgendaReport {
+                overdue: views(mlw.overdue_tasks(dates)),
+                today: views(mlw.tasks_due_today(dates)),
+                this_week: views(later_this_week),
+            }))
+        }
+        Command::Assigned { user, all } => {
+            let projects = mlw
+                .projects_assigned_to(user)
+                .into_iter()
+                .map(StakeView::from);
+            let tasks = mlw.tasks_assigned_to(user).into_iter().map(StakeView::from);
+            Ok(Outcome::Stakes(
+                projects
+                    .chain(tasks)
+                    .filter(|view| *all || view.status == Status::Open)
+                    .collect(),
+            ))
+        }
+        Command::Team {
+            action: None | Some(TeamAction::List),
+        } => Ok(Outcome::Members(mlw.members().to_vec())),
+        Command::Export {
+            area,
+            project,
+            completed,
+            dropped,
+        } => {
+            let scope = match (area, project) {
+                (Some(area), _) => Scope::Area(area_id(mlw, area)?),
+                (None, Some(project)) => Scope::Project(project_id(mlw, project)?),
+                (None, None) => Scope::Everything,
+            };
+            let options = ExportOptions {
+                scope,
+                completed: *completed,
+                dropped: *dropped,
+            };
+            Ok(Outcome::Markdown(markdown::export(mlw, &options)?))
+        }
+        _ => Err(OperationError::Invalid(
+            "this command changes data and cannot run as a query".to_string(),
+        )),
+    }
+}
+
+// Open areas and projects never reviewed, or last reviewed more than `days` ago.
+fn due_for_review(mlw: &MLW, days: i64, dates: &DateContext) -> (Vec<AreaId>, Vec<ProjectId>) {
+    let cutoff = dates.now - Duration::days(days);
+    let is_due =
+        |reviewed: Option<chrono::DateTime<Utc>>| reviewed.is_none_or(|reviewed| reviewed < cutoff);
+    let due_areas = mlw
+        .active_areas()
+        .into_iter()
+        .filter(|area| is_due(area.date_reviewed))
+        .map(|area| area.stake_id.clone())
+        .collect();
+    let due_projects = mlw
+        .active_projects()
+        .into_iter()
+        .filter(|project| is_due(project.date_reviewed))
+        .map(|project| project.stake_id.clone())
+        .collect();
+    (due_areas, due_projects)
+}
+
+fn review_report(mlw: &MLW, due_areas: &[AreaId], due_projects: &[ProjectId]) -> ReviewReport {
+    let inbox = mlw
+        .active_tasks()
+        .into_iter()
+        .filter(|task| task.parent_id.is_none())
+        .map(StakeView::from)
+        .collect();
+    let stalled = mlw
+        .active_projects()
+        .into_iter()
+        .filter(|project| mlw.get_task_children(&project.stake_id).is_empty())
+        .map(StakeView::from)
+        .collect();
+    let due = due_areas
+        .iter()
+        .map(|id| view(mlw, StakeKind::Area, id.0))
+        .chain(
+            due_projects
+                .iter()
+                .map(|id| view(mlw, StakeKind::Project, id.0)),
+        )
+        .collect();
+    ReviewReport {
+        inbox,
+        due,
+        stalled,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::{Clock, FakeClock, Priority, SharedClock};
+    use chrono::TimeZone;
+
+    fn words(text: &str) -> Vec<String> {
+        text.split_whitespace().map(str::to_string).collect()
+    }
+
+    fn dates() -> DateContext {
+        DateContext::new(Utc::now(), chrono_tz::UTC)
+    }
+
+    // Runs at the MLW's own clock time, so tests on a fake clock control "now".
+    fn run(mlw: &mut MLW, command: Command) -> Outcome {
+        let dates = DateContext::new(mlw.clock().now(), chrono_tz::UTC);
+        execute(mlw, &command, &dates).expect("command should succeed")
+    }
+
+    fn created_id(outcome: Outcome) -> u32 {
+        match outcome {
+            Outcome::Created(view) => view.id,
+            other => panic!("expected a created stake, got {:?}", other),
+        }
+    }
+
+    fn stakes(outcome: Outcome) -> Vec<StakeView> {
+        match outcome {
+            Outcome::Stakes(views) => views,
+            other => panic!("expected a stake list, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_capture_creates_unfiled_task() {
+        let mut mlw = MLW::new();
+        let outcome = run(
+            &mut mlw,
+            Command::Capture {
+                text: words("Call dentist"),
+            },
+        );
+        let Outcome::Created(view) = outcome else {
+            panic!("expected Created");
+        };
+        assert_eq!(view.kind, StakeKind::Task);
+        assert_eq!(view.name, "Call dentist");
+        assert_eq!(view.parent_id, None);
+    }
+
+    #[test]
+    fn test_capture_parses_metadata_and_keeps_grouped_words() {
+        let mut mlw = MLW::new();
+        mlw.new_project("Run a 10k".to_string(), None, None);
+        let outcome = run(
+            &mut mlw,
+            Command::Capture {
+                text: vec![
+                    "Buy shoes".to_string(),
+                    "+Run a 10k".to_string(),
+                    "#gear".to_string(),
+                    "!high".to_string(),
+                ],
+            },
+        );
+        let Outcome::Created(view) = outcome else {
+            panic!("expected Created");
+        };
+        assert_eq!(view.name, "Buy shoes");
+        assert_eq!(view.parent_id, Some(1));
+        assert_eq!(view.tags, vec!["gear"]);
+        assert_eq!(view.priority, Some(Priority::High));
+    }
+
+    #[test]
+    fn test_capture_unknown_project_is_an_error() {
+        let mut mlw = MLW::new();
+        let result = execute(
+            &mut mlw,
+            &Command::Capture {
+                text: words("Buy seeds +Garden"),
+            },
+            &dates(),
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "no project matches 'Garden'"
+        );
+        assert!(mlw.active_tasks().is_empty());
+    }
+
+    #[test]
+    fn test_add_task_reads_due_and_defer_dates() {
+        let mut mlw = MLW::new();
+        let outcome = run(
+            &mut mlw,
+            Command::Task {
+                action: TaskAction::Add {
+                    name: words("File taxes"),
+                    project: None,
+                    note: None,
+                    due: Some("in 3 days".to_string()),
+                    defer: Some("tomorrow".to_string()),
+                },
+            },
+        );
+        let Outcome::Created(view) = outcome else {
+            panic!("expected Created");
+        };
+        let (due, defer) = (view.due.unwrap(), view.defer_until.unwrap());
+        assert_eq!(due - defer, Duration::days(2));
+        assert_eq!(mlw.get_task_by_id(&TaskId::new(1)).unwrap().due, Some(due));
+    }
+
+    #[test]
+    fn test_add_task_with_unreadable_date_creates_nothing() {
+        let mut mlw = MLW::new();
+        let result = execute(
+            &mut mlw,
+            &Command::Task {
+                action: TaskAction::Add {
+                    name: words("File taxes"),
+                    project: None,
+                    note: None,
+                    due: Some("someday".to_string()),
+                    defer: None,
+                },
+            },
+            &dates(),
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "cannot read 'someday' as a date"
+        );
+        assert!(mlw.tasks().is_empty());
+    }
+
+    #[test]
+    fn test_add_project_under_area_by_handle() {
+        let mut mlw = MLW::new();
+        let area = created_id(run(
+            &mut mlw,
+            Command::Area {
+                action: AreaAction::Add {
+                    name: words("Health"),
+                    note: None,
+                },
+            },
+        ));
+        let outcome = run(
+            &mut mlw,
+            Command::Project {
+                action: ProjectAction::Add {
+                    name: words("Run a 10k"),
+                    area: Some(area.to_string()),
+                    note: Some("spring race".to_string()),
+                },
+            },
+        );
+        let Outcome::Created(view) = outcome else {
+            panic!("expected Created");
+        };
+        assert_eq!(view.parent_id, Some(area));
+        assert_eq!(view.note.as_deref(), Some("spring race"));
+    }
+
+    #[test]
+    fn test_add_task_with_unknown_project_fails() {
+        let mut mlw = MLW::new();
+        let result = execute(
+            &mut mlw,
+            &Command::Task {
+                action: TaskAction::Add {
+                    name: words("Orphan"),
+                    project: Some("9".to_string()),
+                    note: None,
+                    due: None,
+                    defer: None,
+                },
+            },
+            &dates(),
+        );
+        assert_eq!(
+            result,
+            Err(OperationError::UnknownHandle {
+                kind: StakeKind::Project,
+                handle: "9".to_string()
+            })
+        );
+        assert!(mlw.tasks().is_empty());
+    }
+
+    #[test]
+    fn test_list_hides_finished_stakes_unless_all() {
+        let mut mlw = MLW::new();
+        mlw.new_task("Open".to_string(), None, None);
+        let done = mlw.new_task("Done".to_string(), None, None);
+        mlw.mark_task_complete(&done.stake_id).unwrap();
+
+        let open = stakes(run(
+            &mut mlw,
+            Command::List {
+                kind: StakeKind::Task,
+                all: false,
+            },
+        ));
+        assert_eq!(open.len(), 1);
+        let all = stakes(run(
+            &mut mlw,
+            Command::List {
+                kind: StakeKind::Task,
+                all: true,
+            },
+        ));
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[1].status, Status::Done);
+    }
+
+    #[test]
+    fn test_done_and_drop_update_status() {
+        let mut mlw = MLW::new();
+        mlw.new_project("Finish".to_string(), None, None);
+        mlw.new_project("Abandon".to_string(), None, None);
+
+        let done = run(
+            &mut mlw,
+            Command::Done {
+                kind: StakeKind::Project,
+                id: "1".to_string(),
+            },
+        );
+        let dropped = run(
+            &mut mlw,
+            Command::Drop {
+                kind: StakeKind::Project,
+                id: "2".to_string(),
+            },
+        );
+        assert!(matches!(done, Outcome::Updated(view) if view.status == Status::Done));
+        assert!(matches!(dropped, Outcome::Updated(view) if view.status == Status::Dropped));
+    }
+
+    #[test]
+    fn test_done_accepts_global_id() {
+        let mut mlw = MLW::new();
+        let task = mlw.new_task("Global".to_string(), None, None);
+        run(
+            &mut mlw,
+            Command::Done {
+                kind: StakeKind::Task,
+                id: task.global_id.unwrap().to_string(),
+            },
+        );
+        assert!(mlw.get_task_by_id(&task.stake_id).unwrap().complete);
+    }
+
+    #[test]
+    fn test_done_unknown_handle_fails() {
+        let mut mlw = MLW::new();
+        let result = execute(
+            &mut mlw,
+            &Command::Done {
+                kind: StakeKind::Area,
+                id: "1".to_string(),
+            },
+            &dates(),
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "no area matches '1'".to_string()
+        );
+    }
+
+    #[test]
+    fn test_move_files_and_unfiles_task() {
+        let mut mlw = MLW::new();
+        let project = mlw.new_project("Project".to_string(), None, None);
+        let task = mlw.new_task("Task".to_string(), None, None);
+
+        run(
+            &mut mlw,
+            Command::Move {
+                kind: StakeKind::Task,
+                id: "1".to_string(),
+                to: Some("1".to_string()),
+            },
+        );
+        assert_eq!(
+            mlw.get_task_by_id(&task.stake_id).unwrap().parent_id,
+            Some(project.stake_id)
+        );
+
+        run(
+            &mut mlw,
+            Command::Move {
+                kind: StakeKind::Task,
+                id: "1".to_string(),
+                to: None,
+            },
+        );
+        assert_eq!(mlw.get_task_by_id(&task.stake_id).unwrap().parent_id, None);
+    }
+
+    #[test]
+    fn test_move_area_is_invalid() {
+        let mut mlw = MLW::new();
+        mlw.new_area("Area".to_string(), None);
+        let result = execute(
+            &mut mlw,
+            &Command::Move {
+                kind: StakeKind::Area,
+                id: "1".to_string(),
+                to: None,
+            },
+            &dates(),
+        );
+        assert!(matches!(result, Err(OperationError::Invalid(_))));
+    }
+
+    #[test]
+    fn test_search_ranks_across_kinds() {
+        let mut mlw = MLW::new();
+        mlw.new_area("Finance".to_string(), None);
+        mlw.new_task(
+            "Pay rent".to_string(),
+            None,
+            Some("finance admin".to_string()),
+        );
+        mlw.new_task("Water plants".to_string(), None, None);
+
+        let Outcome::Hits(hits) = run(
+            &mut mlw,
+            Command::Search {
+                query: words("finance"),
+                fuzzy: false,
+            },
+        ) else {
+            panic!("expected hits");
+        };
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].stake.kind, StakeKind::Area);
+        assert_eq!(hits[1].stake.name, "Pay rent");
+    }
+
+    #[test]
+    fn test_search_fuzzy_tolerates_typos() {
+        let mut mlw = MLW::new();
+        mlw.new_area("Financial Management".to_string(), None);
+
+        let Outcome::Hits(hits) = run(
+            &mut mlw,
+            Command::Search {
+                query: words("finacial"),
+                fuzzy: true,
+            },
+        ) else {
+            panic!("expected hits");
+        };
+        assert_eq!(hits.len(), 1);
+    }
+
+    #[test]
+    fn test_review_lists_inbox_due_and_stalled() {
+        let mut mlw = MLW::new();
+        let area = mlw.new_area("Health".to_string(), None);
+        let busy = mlw.new_project("Busy".to_string(), Some(area.stake_id.clone()), None);
+        mlw.new_project("Stalled".to_string(), None, None);
+        mlw.new_task("Filed".to_string(), Some(busy.stake_id.clone()), None);
+        mlw.new_task("Inbox".to_string(), None, None);
+        mlw.mark_project_reviewed(&busy.stake_id).unwrap();
+
+        let Outcome::Review(report) = run(
+            &mut mlw,
+            Command::Review {
+                days: 7,
+                mark: false,
+            },
+        ) else {
+            panic!("expected a review");
+        };
+        let names = |views: &[StakeView]| -> Vec<String> {
+            views.iter().map(|view| view.name.clone()).collect()
+        };
+        assert_eq!(names(&report.inbox), vec!["Inbox"]);
+        assert_eq!(names(&report.due), vec!["Health", "Stalled"]);
+        assert_eq!(names(&report.stalled), vec!["Stalled"]);
+    }
+
+    #[test]
+    fn test_review_mark_stamps_due_stakes() {
+        let mut mlw = MLW::new();
+        let area = mlw.new_area("Health".to_string(), None);
+        let review = Command::Review {
+            days: 7,
+            mark: true,
+        };
+        assert!(review.is_mutating());
+
+        run(&mut mlw, review.clone());
+        assert!(
+            mlw.get_area_by_id(&area.stake_id)
+                .unwrap()
+                .date_reviewed
+                .is_some()
+        );
+        let Outcome::Review(report) = run(&mut mlw, review) else {
+            panic!("expected a review");
+        };
+        assert!(report.due.is_empty());
+    }
+
+    #[test]
+    fn test_review_comes_due_again_after_interval() {
+        let clock = FakeClock::new(Utc.with_ymd_and_hms(2026, 10, 14, 9, 0, 0).unwrap());
+        let mut mlw = MLW::with_clock(SharedClock::new(clock.clone()));
+        let area = mlw.new_area("Health".to_string(), None);
+        mlw.mark_area_reviewed(&area.stake_id).unwrap();
+        let review = Command::Review {
+            days: 7,
+            mark: false,
+        };
+        let due = |mlw: &mut MLW| {
+            let Outcome::Review(report) = run(mlw, review.clone()) else {
+                panic!("expected a review");
+            };
+            report.due.len()
+        };
+
+        clock.advance(Duration::days(7));
+        assert_eq!(due(&mut mlw), 0, "exactly seven days is not overdue yet");
+        clock.advance(Duration::seconds(1));
+        assert_eq!(due(&mut mlw), 1);
+    }
+
+    #[test]
+    fn test_show_lists_children_of_project() {
+        let mut mlw = MLW::new();
+        let project = mlw.new_project("Project".to_string(), None, None);
+        mlw.new_task("One".to_string(), Some(project.stake_id.clone()), None);
+        let two = mlw.new_task("Two".to_string(), Some(project.stake_id.clone()), None);
+        mlw.mark_task_complete(&two.stake_id).unwrap();
+
+        let Outcome::Detail { stake, children } = run(
+            &mut mlw,
+            Command::Show {
+                kind: StakeKind::Project,
+                id: "1".to_string(),
+            },
+        ) else {
+            panic!("expected detail");
+        };
+        assert_eq!(stake.name, "Project");
+        assert_eq!(children.len(), 2);
+    }
+
+    #[test]
+    fn test_read_only_commands_are_not_mutating() {
+        let show = Command::Show {
+            kind: StakeKind::Task,
+            id: "1".to_string(),
+        };
+        let list = Command::List {
+            kind: StakeKind::Task,
+            all: false,
+        };
+        let capture = Command::Capture {
+            text: words("Something"),
+        };
+        assert!(!show.is_mutating());
+        assert!(!list.is_mutating());
+        assert!(capture.is_mutating());
+    }
+
+    #[test]
+    fn test_query_runs_read_only_commands_and_refuses_others() {
+        let mut mlw = MLW::new();
+        run(
+            &mut mlw,
+            Command::Capture {
+                text: words("Call dentist"),
+            },
+        );
+        let list = Command::List {
+            kind: StakeKind::Task,
+            all: false,
+        };
+        let capture = Command::Capture {
+            text: words("Something"),
+        };
+
+        let listed = stakes(query(&mlw, &list, &dates()).unwrap());
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].name, "Call dentist");
+        assert!(matches!(
+            query(&mlw, &capture, &dates()),
+            Err(OperationError::Invalid(_))
+        ));
+        assert_eq!(mlw.active_tasks().len(), 1);
+    }
+
+    #[test]
+    fn test_agenda_splits_overdue_today_and_later_this_week() {
+        // Wednesday 14 October 2026.
+        let dates = DateContext::new(
+            Utc.with_ymd_and_hms(2026, 10, 14, 9, 0, 0).unwrap(),
+            chrono_tz::UTC,
+        );
+        let mut mlw = MLW::new();
+        for (name, due) in [
+            ("Late", "yesterday"),
+            ("Now", "today 5pm"),
+            ("Friday", "fri"),
+            ("Next week", "next week"),
+        ] {
+            execute(
+                &mut mlw,
+                &Command::Task {
+                    action: TaskAction::Add {
+                        name: words(name),
+                        project: None,
+                        note: None,
+                        due: Some(due.to_string()),
+                        defer: None,
+                    },
+                },
+                &dates,
+            )
+            .unwrap();
+        }
+
+        let Outcome::Agenda(report) = execute(&mut mlw, &Command::Agenda, &dates).unwrap() else {
+            panic!("expected Agenda");
+        };
+        let names = |views: &[StakeView]| views.iter().map(|v| v.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(&report.overdue), vec!["Late"]);
+        assert_eq!(names(&report.today), vec!["Now"]);
+        assert_eq!(names(&report.this_week), vec!["Friday"]);
+        assert!(!Command::Agenda.is_mutating());
+    }
+
+    #[test]
+    fn test_team_members_are_added_listed_and_removed() {
+        let mut mlw = MLW::new();
+        let team = |action| Command::Team { action };
+        let add = |name: &str| {
+            team(Some(TeamAction::Add {
+                name: name.to_string(),
+            }))
+        };
+        run(&mut mlw, add("ana"));
+        assert_eq!(
+            run(&mut mlw, add("ben")),
+            Outcome::Members(vec!["ana".to_string(), "ben".to_string()])
+        );
+        assert!(matches!(
+            execute(&mut mlw, &add("ana"), &dates()),
+            Err(OperationError::Invalid(_))
+        ));
+        assert!(matches!(
+            execute(&mut mlw, &add("  "), &dates()),
+            Err(OperationError::Invalid(_))
+        ));
+
+        let remove = team(Some(TeamAction::Remove {
+            name: "ana".to_string(),
+        }));
+        run(&mut mlw, remove.clone());
+        assert_eq!(
+            execute(&mut mlw, &remove, &dates()),
+            Err(OperationError::Stake(StakeError::UnknownUser(
+                "ana".to_string()
+            )))
+        );
+        assert!(!team(None).is_mutating());
+        assert!(remove.is_mutating());
+        assert_eq!(
+            query(&mlw, &team(Some(TeamAction::List)), &dates()).unwrap(),
+            Outcome::Members(vec!["ben".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_assign_and_list_what_a_member_is_assigned() {
+        let mut mlw = MLW::new();
+        mlw.add_member("ana".to_string());
+        let project = mlw.new_project("Launch".to_string(), None, None);
+        mlw.new_task("Write copy".to_string(), Some(project.stake_id), None);
+        let assign = |kind, id: &str, user: Option<&str>| Command::Assign {
+            kind,
+            id: id.to_string(),
+            user: user.map(str::to_string),
+        };
+
+        let Outcome::Updated(view) = run(&mut mlw, assign(StakeKind::Task, "1", Some("ana")))
+        else {
+            panic!("expected Updated");
+        };
+        assert_eq!(view.assignee.as_deref(), Some("ana"));
+        run(&mut mlw, assign(StakeKind::Project, "1", Some("ana")));
+        assert_eq!(
+            execute(
+                &mut mlw,
+                &assign(StakeKind::Task, "1", Some("cal")),
+                &dates()
+            ),
+            Err(OperationError::Stake(StakeError::UnknownUser(
+                "cal".to_string()
+            )))
+        );
+        assert!(matches!(
+            execute(
+                &mut mlw,
+                &assign(StakeKind::Area, "1", Some("ana")),
+                &dates()
+            ),
+            Err(OperationError::Invalid(_))
+        ));
+
+        let assigned = |all| Command::Assigned {
+            user: "ana".to_string(),
+            all,
+        };
+        let kinds: Vec<StakeKind> = stakes(run(&mut mlw, assigned(false)))
+            .iter()
+            .map(|view| view.kind)
+            .collect();
+        assert_eq!(kinds, vec![StakeKind::Project, StakeKind::Task]);
+        run(
+            &mut mlw,
+            Command::Done {
+                kind: StakeKind::Task,
+                id: "1".to_string(),
+            },
+        );
+        assert_eq!(stakes(run(&mut mlw, assigned(false))).len(), 1);
+        assert_eq!(stakes(run(&mut mlw, assigned(true))).len(), 2);
+        assert!(!assigned(false).is_mutating());
+
+        run(&mut mlw, assign(StakeKind::Task, "1", None));
+        assert_eq!(mlw.tasks_assigned_to("ana").len(), 0);
+    }
+
+    #[test]
+    fn test_export_prints_markdown_for_a_scope() {
+        let mut mlw = MLW::new();
+        let area = mlw.new_area("Home".to_string(), None);
+        let project = mlw.new_project("Garden".to_string(), Some(area.stake_id), None);
+        let task = mlw.new_task("Water".to_string(), Some(project.stake_id), None);
+        mlw.mark_task_complete(&task.stake_id).unwrap();
+        let export = |project: Option<&str>, completed| Command::Export {
+            area: None,
+            project: project.map(str::to_string),
+            completed,
+            dropped: false,
+        };
+
+        assert!(!export(None, false).is_mutating());
+        assert_eq!(
+            query(&mlw, &export(None, false), &dates()).unwrap(),
+            Outcome::Markdown("# Home\n\n## Garden\n".to_string())
+        );
+        assert_eq!(
+            query(&mlw, &export(Some("1"), true), &dates()).unwrap(),
+            Outcome::Markdown("## Garden\n\n- [x] Water\n".to_string())
+        );
+        assert!(query(&mlw, &export(Some("9"), false), &dates()).is_err());
+    }
+}
@@ -0,0 +1,844 @@
+// Turns domain data into what the user sees: plain-text tables by default, or JSON
+// for scripting. Nothing here touches MLW itself; `operations` hands over owned views.
+
+use chrono::{DateTime, NaiveTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::auth::Accounts;
+use crate::entities::{GlobalId, Kind, Priority, Stake, StakeKind, StakeRef};
+use crate::markdown::Imported;
+use crate::mlw::sync::SyncReport;
+use crate::operations::Outcome;
+use crate::workspaces::{WorkspaceHit, Workspaces};
+
+/// Lifecycle status of a stake, as shown to the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Open,
+    Done,
+    Dropped,
+}
+
+impl Status {
+    fn label(self) -> &'static str {
+        match self {
+            Status::Open => "open",
+            Status::Done => "done",
+            Status::Dropped => "dropped",
+        }
+    }
+}
+
+/// An owned, kind-erased snapshot of a stake for display.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StakeView {
+    pub kind: StakeKind,
+    pub id: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub global_id: Option<GlobalId>,
+    pub name: String,
+    pub parent_id: Option<u32>,
+    pub status: Status,
+    pub note: Option<String>,
+    pub date_created: DateTime<Utc>,
+    pub date_modified: DateTime<Utc>,
+    pub date_reviewed: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contexts: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub defer_until: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<Priority>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assignee: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_by: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modified_by: Option<String>,
+}
+
+impl<K: Kind> From<&Stake<K>> for StakeView {
+    fn from(stake: &Stake<K>) -> Self {
+        let status = if stake.dropped {
+            Status::Dropped
+        } else if stake.complete {
+            Status::Done
+        } else {
+            Status::Open
+        };
+        StakeView {
+            kind: stake.kind(),
+            id: stake.stake_id.0,
+            global_id: stake.global_id,
+            name: stake.stake_name.clone(),
+            parent_id: stake.parent_id.as_ref().map(|parent| parent.0),
+            status,
+            note: stake.note.clone(),
+            date_created: stake.date_created,
+            date_modified: stake.date_modified,
+            date_reviewed: stake.date_reviewed,
+            contexts: stake.contexts.clone(),
+            tags: stake.tags.clone(),
+            due: stake.due,
+            defer_until: stake.defer_until,
+            priority: stake.priority,
+            assignee: stake.assignee.clone(),
+            created_by: stake.created_by.clone(),
+            modified_by: stake.modified_by.clone(),
+        }
+    }
+}
+
+impl From<StakeRef<'_>> for StakeView {
+    fn from(stake: StakeRef<'_>) -> Self {
+        match stake {
+            StakeRef::Area(stake) => stake.into(),
+            StakeRef::Project(stake) => stake.into(),
+            StakeRef::Task(stake) => stake.into(),
+        }
+    }
+}
+
+/// A search result ready for display.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoredView {
+    pub score: f64,
+    pub stake: StakeView,
+}
+
+/// Renders an outcome as pretty JSON or as human-readable text. JSON keeps UTC
+/// timestamps; text shows them in `timezone`.
+pub fn render(outcome: &Outcome, json: bool, timezone: Tz) -> String {
+    if json {
+        return serde_json::to_string_pretty(outcome).expect("views always serialize");
+    }
+    match outcome {
+        Outcome::Created(stake) => format!("Created {} {}: {}", stake.kind, stake.id, stake.name),
+        Outcome::Updated(stake) => format!(
+            "Updated {} {}: {} ({})",
+            stake.kind,
+            stake.id,
+            stake.name,
+            stake.status.label()
+        ),
+        Outcome::Stakes(stakes) if stakes.is_empty() => "Nothing to show.".to_string(),
+        Outcome::Stakes(stakes) => stake_table(stakes),
+        Outcome::Hits(hits) if hits.is_empty() => "No matches.".to_string(),
+        Outcome::Hits(hits) => {
+            let rows: Vec<Vec<String>> = hits
+                .iter()
+                .map(|hit| {
+                    let mut row = vec![format!("{:.2}", hit.score)];
+                    row.extend(stake_row(&hit.stake));
+                    row
+                })
+                .collect();
+            let mut headers = vec!["SCORE"];
+            headers.extend(STAKE_HEADERS);
+            table(&headers, &rows)
+        }
+        Outcome::Detail { stake, children } => {
+            let mut lines = vec![
+                format!("{} {}: {}", stake.kind, stake.id, stake.name),
+                format!("status:   {}", stake.status.label()),
+            ];
+            if let Some(global_id) = stake.global_id {
+                lines.push(format!("id:       {}", global_id));
+            }
+            if let Some(parent_id) = stake.parent_id {
+                lines.push(format!("parent:   {}", parent_id));
+            }
+            if let Some(assignee) = &stake.assignee {
+                lines.push(format!("assignee: {}", assignee));
+            }
+            lines.push(format!(
+                "created:  {}{}",
+                format_time(stake.date_created, timezone),
+                by(&stake.created_by)
+            ));
+            lines.push(format!(
+                "modified: {}{}",
+                format_time(stake.date_modified, timezone),
+                by(&stake.modified_by)
+            ));
+            if let Some(reviewed) = stake.date_reviewed {
+                lines.push(format!("reviewed: {}", format_time(reviewed, timezone)));
+            }
+            if !stake.contexts.is_empty() {
+                lines.push(format!("contexts: @{}", stake.contexts.join(" @")));
+            }
+            if !stake.tags.is_empty() {
+                lines.push(format!("tags:     #{}", stake.tags.join(" #")));
+            }
+            if let Some(priority) = stake.priority {
+                lines.push(format!("priority: {}", priority));
+            }
+            if let Some(due) = stake.due {
+                lines.push(format!("due:      {}", format_time(due, timezone)));
+            }
+            if let Some(defer) = stake.defer_until {
+                lines.push(format!("deferred: {}", format_time(defer, timezone)));
+            }
+            if let Some(note) = &stake.note {
+                lines.push(format!("note:     {}", note));
+            }
+            if !children.is_empty() {
+                lines.push(String::new());
+                lines.push(stake_table(children));
+            }
+            lines.join("\n")
+        }
+        Outcome::Review(report) => {
+            let sections = [
+                ("Inbox (unfiled tasks)", &report.inbox),
+                ("Due for review", &report.due),
+                ("Projects without a next action", &report.stalled),
+            ];
+            sections
+                .iter()
+                .map(|(title, stakes)| {
+                    if stakes.is_empty() {
+                        format!("{}: none", title)
+                    } else {
+                        format!("{}:\n{}", title, stake_table(stakes))
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n\n")
+        }
+        Outcome::Agenda(report) => {
+            let sections = [
+                ("Overdue", &report.overdue),
+                ("Due today", &report.today),
+                ("Due later this week", &report.this_week),
+            ];
+            sections
+                .iter()
+                .map(|(title, stakes)| {
+                    if stakes.is_empty() {
+                        format!("{}: none", title)
+                    } else {
+                        format!("{}:\n{}", title, due_table(stakes, timezone))
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n\n")
+        }
+        Outcome::Members(members) if members.is_empty() => "No team members.".to_string(),
+        Outcome::Members(members) => members.join("\n"),
+        // Printed with a newline of its own.
+        Outcome::Markdown(text) => text.trim_end_matches('\n').to_string(),
+    }
+}
+
+fn by(author: &Option<String>) -> String {
+    author
+        .as_ref()
+        .map(|author| format!(" by {}", author))
+        .unwrap_or_default()
+}
+
+const STAKE_HEADERS: [&str; 5] = ["KIND", "ID", "NAME", "PARENT", "STATUS"];
+
+fn stake_row(stake: &StakeView) -> Vec<String> {
+    vec![
+        stake.kind.to_string(),
+        stake.id.to_string(),
+        stake.name.clone(),
+        stake
+            .parent_id
+            .map(|parent| parent.to_string())
+            .unwrap_or_else(|| "-".to_string()),
+        stake.status.label().to_string(),
+    ]
+}
+
+fn stake_table(stakes: &[StakeView]) -> String {
+    let rows: Vec<Vec<String>> = stakes.iter().map(stake_row).collect();
+    table(&STAKE_HEADERS, &rows)
+}
+
+fn format_time(time: DateTime<Utc>, timezone: Tz) -> String {
+    time.with_timezone(&timezone)
+        .format("%Y-%m-%d %H:%M %Z")
+        .to_string()
+}
+
+// A due date as a local day, with the time only when it is not the start of the day.
+fn format_due(due: DateTime<Utc>, timezone: Tz) -> String {
+    let local = due.with_timezone(&timezone);
+    if local.time() == NaiveTime::MIN {
+        local.format("%a %Y-%m-%d").to_string()
+    } else {
+        local.format("%a %Y-%m-%d %H:%M").to_string()
+    }
+}
+
+fn due_table(stakes: &[StakeView], timezone: Tz) -> String {
+    let rows: Vec<Vec<String>> = stakes
+        .iter()
+        .map(|stake| {
+            vec![
+                stake.id.to_string(),
+                stake.name.clone(),
+                stake
+                    .parent_id
+                    .map(|parent| parent.to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+                stake
+                    .due
+                    .map(|due| format_due(due, timezone))
+                    .unwrap_or_default(),
+            ]
+        })
+        .collect();
+    table(&["ID", "NAME", "PROJECT", "DUE"], &rows)
+}
+
+/// Renders the outcome of `mlw sync`: how many stakes changed, then a table of the
+/// fields both sides had changed and a list of changes that were not applied.
+pub fn render_sync(report: &SyncReport, json: bool) -> String {
+    if json {
+        return serde_json::to_string_pretty(report).expect("reports always serialize");
+    }
+    let mut sections = vec![match report.merged {
+        0 => "Already in sync.".to_string(),
+        1 => "Synced 1 stake.".to_string(),
+        n => format!("Synced {} stakes.", n),
+    }];
+    if !report.conflicts.is_empty() {
+        let rows: Vec<Vec<String>> = report
+            .conflicts
+            .iter()
+            .map(|conflict| {
+                vec![
+                    conflict.kind.to_string(),
+                    conflict.stake_name.clone(),
+                    conflict.field.clone(),
+                    conflict.kept.to_string(),
+                    conflict.discarded.to_string(),
+                ]
+            })
+            .collect();
+        sections.push(format!(
+            "Both sides changed (the later change was kept):\n{}",
+            table(&["KIND", "NAME", "FIELD", "KEPT", "DISCARDED"], &rows)
+        ));
+    }
+    if !report.unresolved.is_empty() {
+        let lines: Vec<String> = report
+            .unresolved
+            .iter()
+            .map(|unresolved| {
+                format!(
+                    "  {} {}: {}",
+                    unresolved.kind, unresolved.stake_name, unresolved.reason
+                )
+            })
+            .collect();
+        sections.push(format!("Not applied:\n{}", lines.join("\n")));
+    }
+    sections.join("\n\n")
+}
+
+/// What a Markdown import added, and how many lines it left out.
+pub fn render_import(imported: &Imported, json: bool) -> String {
+    if json {
+        return serde_json::to_string_pretty(imported).expect("counts always serialize");
+    }
+    let mut text = format!(
+        "Imported {}, {} and {}.",
+        count(imported.areas, "area"),
+        count(imported.projects, "project"),
+        count(imported.tasks, "task")
+    );
+    if imported.skipped > 0 {
+        text.push_str(&format!(
+            "\nSkipped {} not under a heading or list item.",
+            count(imported.skipped, "line")
+        ));
+    }
+    text
+}
+
+fn count(n: usize, noun: &str) -> String {
+    match n {
+        1 => format!("1 {}", noun),
+        n => format!("{} {}s", n, noun),
+    }
+}
+
+/// The users of a server and their tokens, never the secrets. Users without tokens
+/// get a row of their own.
+pub fn render_accounts(accounts: &Accounts, json: bool, timezone: Tz) -> String {
+    let tokens = accounts.tokens();
+    if json {
+        let users: Vec<&str> = accounts.users().collect();
+        return serde_json::to_string_pretty(&serde_json::json!({
+            "users": users,
+            "tokens": tokens,
+        }))
+        .expect("accounts always serialize");
+    }
+    if accounts.users().next().is_none() {
+        return "No users.".to_string();
+    }
+    let rows: Vec<Vec<String>> = accounts
+        .users()
+        .flat_map(|user| {
+            let theirs: Vec<Vec<String>> = tokens
+                .iter()
+                .filter(|token| token.user == user)
+                .map(|token| {
+                    vec![
+                        user.to_string(),
+                        token.id.clone(),
+                        token.access.to_string(),
+                        format_time(token.created, timezone),
+                    ]
+                })
+                .collect();
+            match theirs.is_empty() {
+                true => vec![vec![user.to_string(), "-".to_string()]],
+                false => theirs,
+            }
+        })
+        .collect();
+    table(&["USER", "TOKEN", "ACCESS", "CREATED"], &rows)
+}
+
+/// The workspaces and their data files, the current one marked with `*`.
+pub fn render_workspaces(workspaces: &Workspaces, json: bool) -> String {
+    if json {
+        let list: Vec<serde_json::Value> = workspaces
+            .iter()
+            .map(|(name, file)| {
+                serde_json::json!({
+                    "name": name,
+                    "file": file,
+                    "current": name == workspaces.current(),
+                })
+            })
+            .collect();
+        return serde_json::to_string_pretty(&list).expect("workspaces always serialize");
+    }
+    let rows: Vec<Vec<String>> = workspaces
+        .iter()
+        .map(|(name, file)| {
+            let marker = if name == workspaces.current() {
+                "*"
+            } else {
+                ""
+            };
+            vec![
+                marker.to_string(),
+                name.to_string(),
+                file.display().to_string(),
+            ]
+        })
+        .collect();
+    table(&["", "WORKSPACE", "FILE"], &rows)
+}
+
+/// Search hits from several workspaces, each row naming its workspace.
+pub fn render_workspace_hits(hits: &[WorkspaceHit], json: bool) -> String {
+    if json {
+        return serde_json::to_string_pretty(hits).expect("hits always serialize");
+    }
+    if hits.is_empty() {
+        return "No matches.".to_string();
+    }
+    let rows: Vec<Vec<String>> = hits
+        .iter()
+        .map(|hit| {
+            let mut row = vec![hit.workspace.clone(), format!("{:.2}", hit.hit.score)];
+            row.extend(stake_row(&hit.hit.stake));
+            row
+        })
+        .collect();
+    let mut headers = vec!["WORKSPACE", "SCORE"];
+    headers.extend(STAKE_HEADERS);
+    table(&headers, &rows)
+}
+
+/// Lays out rows in left-aligned columns separated by two spaces.
+pub fn table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let line = |cells: Vec<&str>| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    let mut lines = vec![line(headers.to_vec())];
+    lines.extend(
+        rows.iter()
+            .map(|row| line(row.iter().map(String::as_str).collect())),
+    );
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::{ProjectId, StakeId, Task};
+    use crate::operations::{AgendaReport, ReviewReport};
+    use chrono::TimeZone;
+
+    fn task(id: u32, name: &str, parent: Option<u32>) -> Stake<Task> {
+        Stake::new(
+            StakeId::new(id),
+            name.to_string(),
+            parent.map(ProjectId::new),
+            None,
+        )
+    }
+
+    #[test]
+    fn test_stake_view_status_prefers_dropped() {
+        let mut stake = task(1, "Both", None);
+        stake.complete = true;
+        assert_eq!(StakeView::from(&stake).status, Status::Done);
+        stake.dropped = true;
+        assert_eq!(StakeView::from(&stake).status, Status::Dropped);
+    }
+
+    #[test]
+    fn test_stake_view_from_ref_keeps_kind_and_parent() {
+        let stake = task(3, "Call dentist", Some(2));
+        let view = StakeView::from(StakeRef::Task(&stake));
+        assert_eq!(view.kind, StakeKind::Task);
+        assert_eq!(view.id, 3);
+        assert_eq!(view.parent_id, Some(2));
+    }
+
+    #[test]
+    fn test_table_aligns_columns() {
+        let rows = vec![
+            vec!["1".to_string(), "Short".to_string()],
+            vec!["22".to_string(), "A longer name".to_string()],
+        ];
+        assert_eq!(
+            table(&["ID", "NAME"], &rows),
+            "ID  NAME\n1   Short\n22  A longer name"
+        );
+    }
+
+    #[test]
+    fn test_render_stakes_as_table() {
+        let stakes = vec![
+            StakeView::from(&task(1, "Buy milk", None)),
+            StakeView::from(&task(2, "Call dentist", Some(4))),
+        ];
+        let text = render(&Outcome::Stakes(stakes), false, Tz::UTC);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "KIND  ID  NAME          PARENT  STATUS");
+        assert_eq!(lines[1], "task  1   Buy milk      -       open");
+        assert_eq!(lines[2], "task  2   Call dentist  4       open");
+    }
+
+    #[test]
+    fn test_render_empty_results() {
+        assert_eq!(
+            render(&Outcome::Stakes(vec![]), false, Tz::UTC),
+            "Nothing to show."
+        );
+        assert_eq!(
+            render(&Outcome::Hits(vec![]), false, Tz::UTC),
+            "No matches."
+        );
+    }
+
+    #[test]
+    fn test_render_created_message() {
+        let view = StakeView::from(&task(5, "Water plants", None));
+        assert_eq!(
+            render(&Outcome::Created(view), false, Tz::UTC),
+            "Created task 5: Water plants"
+        );
+    }
+
+    #[test]
+    fn test_render_sync_lists_conflicts_and_unresolved_changes() {
+        use crate::entities::GlobalId;
+        use crate::mlw::sync::{Conflict, ReplicaId, Unresolved};
+        use serde_json::json;
+
+        assert_eq!(
+            render_sync(&SyncReport::default(), false),
+            "Already in sync."
+        );
+        let report = SyncReport {
+            merged: 3,
+            conflicts: vec![Conflict {
+                kind: StakeKind::Task,
+                global_id: GlobalId::new(),
+                stake_name: "Dig".to_string(),
+                field: "note".to_string(),
+                kept: json!("By the fence"),
+                discarded: json!(null),
+                winner: ReplicaId::new(),
+            }],
+            unresolved: vec![Unresolved {
+                kind: StakeKind::Project,
+                global_id: GlobalId::new(),
+                stake_name: "Garden".to_string(),
+                reason: "it is a task here".to_string(),
+            }],
+        };
+        assert_eq!(
+            render_sync(&report, false),
+            "Synced 3 stakes.\n\n\
+             Both sides changed (the later change was kept):\n\
+             KIND  NAME  FIELD  KEPT            DISCARDED\n\
+             task  Dig   note   \"By the fence\"  null\n\n\
+             Not applied:\n  project Garden: it is a task here"
+        );
+        let json: serde_json::Value = serde_json::from_str(&render_sync(&report, true)).unwrap();
+        assert_eq!(json["conflicts"][0]["kept"], "By the fence");
+    }
+
+    #[test]
+    fn test_render_import_counts_what_was_added() {
+        let mut imported = Imported {
+            areas: 1,
+            projects: 0,
+            tasks: 3,
+            skipped: 0,
+        };
+        assert_eq!(
+            render_import(&imported, false),
+            "Imported 1 area, 0 projects and 3 tasks."
+        );
+        imported.skipped = 1;
+        assert!(
+            render_import(&imported, false)
+                .ends_with("\nSkipped 1 line not under a heading or list item.")
+        );
+        let json: serde_json::Value =
+            serde_json::from_str(&render_import(&imported, true)).unwrap();
+        assert_eq!(json["tasks"], 3);
+    }
+
+    #[test]
+    fn test_render_accounts_lists_tokens_by_user() {
+        use crate::auth::Access;
+        use chrono::TimeZone;
+
+        let mut accounts = Accounts::default();
+        assert_eq!(render_accounts(&accounts, false, Tz::UTC), "No users.");
+        accounts.add_user("alice").unwrap();
+        accounts.add_user("bob").unwrap();
+        let created = Utc.with_ymd_and_hms(2026, 10, 18, 9, 30, 0).unwrap();
+        let token = accounts
+            .issue_token("alice", Access::Read, created)
+            .unwrap();
+        let id = &accounts.tokens()[0].id;
+        assert_eq!(
+            render_accounts(&accounts, false, Tz::UTC),
+            format!(
+                "USER   TOKEN         ACCESS     CREATED\n\
+                 alice  {}  read-only  2026-10-18 09:30 UTC\n\
+                 bob    -",
+                id
+            )
+        );
+        let json = render_accounts(&accounts, true, Tz::UTC);
+        assert!(!json.contains(token.rsplit('_').next().unwrap()));
+        let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(json["users"], serde_json::json!(["alice", "bob"]));
+        assert_eq!(json["tokens"][0]["access"], "read");
+    }
+
+    #[test]
+    fn test_render_workspaces_marks_the_current_one() {
+        use std::path::PathBuf;
+
+        let mut workspaces = Workspaces::default().with_default(PathBuf::from("/h/.mlw.json"));
+        workspaces
+            .create("work", PathBuf::from("/h/.mlw/work.json"))
+            .unwrap();
+        workspaces.switch("work").unwrap();
+        assert_eq!(
+            render_workspaces(&workspaces, false),
+            "   WORKSPACE  FILE\n   default    /h/.mlw.json\n*  work       /h/.mlw/work.json"
+        );
+        let json: serde_json::Value =
+            serde_json::from_str(&render_workspaces(&workspaces, true)).unwrap();
+        assert_eq!(json[1]["name"], "work");
+        assert_eq!(json[1]["current"], true);
+
+        let hit = WorkspaceHit {
+            workspace: "work".to_string(),
+            hit: ScoredView {
+                score: 1.5,
+                stake: StakeView::from(&task(3, "File taxes", None)),
+            },
+        };
+        let text = render_workspace_hits(std::slice::from_ref(&hit), false);
+        assert!(text.starts_with("WORKSPACE  SCORE"), "{}", text);
+        assert!(text.contains("work       1.50"), "{}", text);
+        let json: serde_json::Value =
+            serde_json::from_str(&render_workspace_hits(&[hit], true)).unwrap();
+        assert_eq!(json[0]["workspace"], "work");
+        assert_eq!(json[0]["stake"]["name"], "File taxes");
+        assert_eq!(render_workspace_hits(&[], false), "No matches.");
+    }
+
+    #[test]
+    fn test_render_json_lists_views() {
+        let view = StakeView::from(&task(5, "Water plants", None));
+        let json: serde_json::Value =
+            serde_json::from_str(&render(&Outcome::Stakes(vec![view]), true, Tz::UTC)).unwrap();
+        assert_eq!(json[0]["kind"], "task");
+        assert_eq!(json[0]["id"], 5);
+        assert_eq!(json[0]["status"], "open");
+    }
+
+    #[test]
+    fn test_render_detail_shows_capture_metadata() {
+        let mut stake = task(7, "Call dentist", None);
+        stake.contexts = vec!["phone".to_string(), "town".to_string()];
+        stake.tags = vec!["health".to_string()];
+        stake.priority = Some(Priority::High);
+        stake.due = Some(stake.date_created);
+        let text = render(
+            &Outcome::Detail {
+                stake: StakeView::from(&stake),
+                children: vec![],
+            },
+            false,
+            Tz::UTC,
+        );
+        assert!(text.contains("contexts: @phone @town\n"));
+        assert!(text.contains("tags:     #health\n"));
+        assert!(text.contains("priority: high\n"));
+        assert!(text.contains("due:      "));
+        assert!(!text.contains("deferred:"));
+    }
+
+    #[test]
+    fn test_render_detail_shows_assignee_and_authors() {
+        let mut stake = task(7, "Write copy", None);
+        stake.assignee = Some("cal".to_string());
+        stake.created_by = Some("ana".to_string());
+        stake.modified_by = Some("ben".to_string());
+        let detail = |stake: &Stake<crate::entities::Task>| {
+            render(
+                &Outcome::Detail {
+                    stake: StakeView::from(stake),
+                    children: vec![],
+                },
+                false,
+                Tz::UTC,
+            )
+        };
+        let text = detail(&stake);
+        assert!(text.contains("assignee: cal\n"));
+        let line = |label: &str| {
+            text.lines()
+                .find(|line| line.starts_with(label))
+                .unwrap()
+                .to_string()
+        };
+        assert!(line("created:").ends_with(" by ana"));
+        assert!(line("modified:").ends_with(" by ben"));
+
+        let text = detail(&task(7, "Write copy", None));
+        assert!(!text.contains("assignee:"));
+        assert!(!text.contains(" by "));
+    }
+
+    #[test]
+    fn test_render_members() {
+        let members = vec!["ana".to_string(), "ben".to_string()];
+        assert_eq!(
+            render(&Outcome::Members(members), false, Tz::UTC),
+            "ana\nben"
+        );
+        assert_eq!(
+            render(&Outcome::Members(vec![]), false, Tz::UTC),
+            "No team members."
+        );
+    }
+
+    #[test]
+    fn test_markdown_is_printed_as_is() {
+        let markdown = Outcome::Markdown("# Home\n\n- [ ] Water\n".to_string());
+        assert_eq!(render(&markdown, false, Tz::UTC), "# Home\n\n- [ ] Water");
+        assert_eq!(
+            render(&markdown, true, Tz::UTC),
+            r##""# Home\n\n- [ ] Water\n""##
+        );
+    }
+
+    #[test]
+    fn test_render_review_sections() {
+        let report = ReviewReport {
+            inbox: vec![StakeView::from(&task(1, "Loose end", None))],
+            due: vec![],
+            stalled: vec![],
+        };
+        let text = render(&Outcome::Review(report), false, Tz::UTC);
+        assert!(text.starts_with("Inbox (unfiled tasks):\nKIND"));
+        assert!(text.contains("Due for review: none"));
+        assert!(text.contains("Projects without a next action: none"));
+    }
+
+    #[test]
+    fn test_render_detail_in_local_time() {
+        let mut stake = task(1, "Call dentist", None);
+        stake.date_created = Utc.with_ymd_and_hms(2026, 10, 14, 23, 30, 0).unwrap();
+        let detail = Outcome::Detail {
+            stake: StakeView::from(&stake),
+            children: vec![],
+        };
+        let text = render(&detail, false, chrono_tz::Europe::Berlin);
+        assert!(text.contains("created:  2026-10-15 01:30 CEST"));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&render(&detail, true, chrono_tz::Europe::Berlin)).unwrap();
+        assert_eq!(json["stake"]["date_created"], "2026-10-14T23:30:00Z");
+    }
+
+    #[test]
+    fn test_render_agenda_sections_with_local_due_dates() {
+        let mut late = task(3, "Pay rent", Some(2));
+        late.due = Some(Utc.with_ymd_and_hms(2026, 10, 12, 22, 0, 0).unwrap());
+        let mut meeting = task(4, "Standup", None);
+        meeting.due = Some(Utc.with_ymd_and_hms(2026, 10, 15, 7, 30, 0).unwrap());
+        let report = AgendaReport {
+            overdue: vec![StakeView::from(&late)],
+            today: vec![StakeView::from(&meeting)],
+            this_week: vec![],
+        };
+        assert_eq!(
+            render(&Outcome::Agenda(report), false, chrono_tz::Europe::Berlin),
+            "Overdue:\n\
+             ID  NAME      PROJECT  DUE\n\
+             3   Pay rent  2        Tue 2026-10-13\n\
+             \n\
+             Due today:\n\
+             ID  NAME     PROJECT  DUE\n\
+             4   Standup  -        Thu 2026-10-15 09:30\n\
+             \n\
+             Due later this week: none"
+        );
+    }
+}
@@ -0,0 +1,158 @@
+// Loading and saving the MLW data file, the user config and server accounts beside
+// it, and the list of workspaces.
+//
+// The file is the JSON form of `MLW`. Saves go to a sibling temporary file that is
+// then renamed over the original, so an interrupted save never leaves a half-written file.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::auth::Accounts;
+use crate::config::Config;
+use crate::mlw::MLW;
+use crate::workspaces::Workspaces;
+
+/// Why a data, config, accounts or workspaces file could not be read or written.
+#[derive(Debug)]
+pub enum StorageError {
+    Io(PathBuf, io::Error),
+    Format(PathBuf, serde_json::Error),
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::Io(path, error) => write!(f, "{}: {}", path.display(), error),
+            StorageError::Format(path, error) => {
+                write!(f, "{} is not valid JSON: {}", path.display(), error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+/// Reads the data file at `path`. A missing file is an empty MLW, so the first
+/// command against a new path just works.
+pub fn load(path: &Path) -> Result<MLW, StorageError> {
+    read_json(path)
+}
+
+/// Writes `mlw` to `path`, creating parent directories as needed.
+pub fn save(path: &Path, mlw: &MLW) -> Result<(), StorageError> {
+    write_json(path, mlw)
+}
+
+/// Reads the user config at `path`; a missing file gives the defaults.
+pub fn load_config(path: &Path) -> Result<Config, StorageError> {
+    read_json(path)
+}
+
+pub fn save_config(path: &Path, config: &Config) -> Result<(), StorageError> {
+    write_json(path, config)
+}
+
+pub fn load_accounts(path: &Path) -> Result<Accounts, StorageError> {
+    read_json(path)
+}
+
+pub fn save_accounts(path: &Path, accounts: &Accounts) -> Result<(), StorageError> {
+    write_json(path, accounts)
+}
+
+pub fn load_workspaces(path: &Path) -> Result<Workspaces, StorageError> {
+    read_json(path)
+}
+
+pub fn save_workspaces(path: &Path, workspaces: &Workspaces) -> Result<(), StorageError> {
+    write_json(path, workspaces)
+}
+
+fn read_json<T: DeserializeOwned + Default>(path: &Path) -> Result<T, StorageError> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(T::default()),
+        Err(error) => return Err(StorageError::Io(path.to_path_buf(), error)),
+    };
+    serde_json::from_str(&text).map_err(|error| StorageError::Format(path.to_path_buf(), error))
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), StorageError> {
+    let io_error = |error| StorageError::Io(path.to_path_buf(), error);
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent).map_err(io_error)?;
+    }
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|error| StorageError::Format(path.to_path_buf(), error))?;
+
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+    let temporary = PathBuf::from(temporary);
+    fs::write(&temporary, json).map_err(io_error)?;
+    fs::rename(&temporary, path).map_err(io_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_missing_file_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let mlw = load(&dir.path().join("absent.json")).unwrap();
+        assert_eq!(mlw, MLW::new());
+    }
+
+    #[test]
+    fn test_save_then_load_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mlw.json");
+        let mut mlw = MLW::new();
+        let area = mlw.new_area("Health".to_string(), None);
+        mlw.new_project("Run a 10k".to_string(), Some(area.stake_id), None);
+
+        save(&path, &mlw).unwrap();
+        assert_eq!(load(&path).unwrap(), mlw);
+    }
+
+    #[test]
+    fn test_save_creates_parent_directories_and_leaves_no_temp_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("mlw.json");
+
+        save(&path, &MLW::new()).unwrap();
+        assert!(path.exists());
+        assert!(!dir.path().join("nested").join("mlw.json.tmp").exists());
+    }
+
+    #[test]
+    fn test_load_reports_malformed_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mlw.json");
+        fs::write(&path, "{ not json").unwrap();
+
+        let error = load(&path).unwrap_err();
+        assert!(matches!(error, StorageError::Format(..)));
+        assert!(error.to_string().contains("is not valid JSON"));
+    }
+
+    #[test]
+    fn test_config_defaults_when_missing_and_roundtrips() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mlw.config.json");
+        assert_eq!(load_config(&path).unwrap(), Config::default());
+
+        let mut config = Config::default();
+        config.set("timezone", "Europe/Paris").unwrap();
+        save_config(&path, &config).unwrap();
+        assert_eq!(load_config(&path).unwrap(), config);
+    }
+}
@@ -0,0 +1,528 @@
+// The interactive `mlw shell`. MLW stays in memory for the whole session and is
+// written back on `save`, `exit` or end of input. Lines accept the same commands as
+// the one-shot CLI, parsed by the same clap definitions.
+
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::history::DefaultHistory;
+use rustyline::{Context, Editor, Helper, Highlighter, Hinter, Validator};
+
+use crate::config::Config;
+use crate::entities::{Clock, StakeKind};
+use crate::mlw::MLW;
+use crate::operations::{self, Command};
+use crate::presenter;
+use crate::serializer::{self, StorageError};
+
+const SHELL_COMMANDS: [&str; 4] = ["save", "exit", "quit", "help"];
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "mlw",
+    no_binary_name = true,
+    disable_version_flag = true,
+    after_help = "Shell commands:\n  save  Write changes to the data file\n  exit  Save and leave the shell (also quit, or Ctrl-D)"
+)]
+struct ShellLine {
+    #[command(subcommand)]
+    command: Command,
+}
+
+/// What the shell should do after a line has been handled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reply {
+    Continue(String),
+    Exit(String),
+}
+
+/// One shell session: the data file, MLW loaded from it and whether it has unsaved changes.
+#[derive(Debug)]
+pub struct Session {
+    mlw: MLW,
+    path: PathBuf,
+    config: Config,
+    json: bool,
+    unsaved: bool,
+}
+
+impl Session {
+    pub fn open(path: &Path, json: bool) -> Result<Self, StorageError> {
+        Ok(Session {
+            mlw: serializer::load(path)?,
+            path: path.to_path_buf(),
+            config: serializer::load_config(&Config::path_for(path))?,
+            json,
+            unsaved: false,
+        })
+    }
+
+    pub fn mlw(&self) -> &MLW {
+        &self.mlw
+    }
+
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.unsaved
+    }
+
+    pub fn save(&mut self) -> Result<(), StorageError> {
+        serializer::save(&self.path, &self.mlw)?;
+        self.unsaved = false;
+        Ok(())
+    }
+
+    // Saves only if needed and describes what happened.
+    fn save_message(&mut self) -> String {
+        if !self.unsaved {
+            return String::new();
+        }
+        match self.save() {
+            Ok(()) => format!("Saved to {}", self.path.display()),
+            Err(error) => format!("error: {}", error),
+        }
+    }
+
+    /// Handles one line of input.
+    pub fn handle(&mut self, line: &str) -> Reply {
+        let words = match shell_words::split(line) {
+            Ok(words) => words,
+            Err(error) => return Reply::Continue(format!("error: {}", error)),
+        };
+        match words.first().map(String::as_str) {
+            None => Reply::Continue(String::new()),
+            Some("exit" | "quit") => Reply::Exit(self.save_message()),
+            Some("save") => {
+                if self.unsaved {
+                    Reply::Continue(self.save_message())
+                } else {
+                    Reply::Continue("Nothing to save.".to_string())
+                }
+            }
+            Some(_) => Reply::Continue(self.run(&words)),
+        }
+    }
+
+    fn run(&mut self, words: &[String]) -> String {
+        let parsed = match ShellLine::try_parse_from(words) {
+            Ok(parsed) => parsed,
+            // Also covers `help` and `--help`, which clap reports as errors.
+            Err(error) => return error.render().to_string().trim_end().to_string(),
+        };
+        let dates = self.config.date_context(self.mlw.clock().now());
+        match operations::execute(&mut self.mlw, &parsed.command, &dates) {
+            Ok(outcome) => {
+                if parsed.command.is_mutating() {
+                    self.unsaved = true;
+                }
+                presenter::render(&outcome, self.json, self.config.timezone)
+            }
+            Err(error) => format!("error: {}", error),
+        }
+    }
+}
+
+// A stake the completer can offer, by id and name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Entry {
+    kind: StakeKind,
+    id: String,
+    name: String,
+}
+
+/// Tab completion for command names, kinds and stake ids, matched by id or by name.
+#[derive(Debug, Default, Helper, Hinter, Highlighter, Validator)]
+pub struct ShellHelper {
+    entries: Vec<Entry>,
+}
+
+impl ShellHelper {
+    /// Takes a fresh snapshot of the stakes to complete.
+    pub fn refresh(&mut self, mlw: &MLW) {
+        self.entries.clear();
+        let entries = mlw
+            .areas()
+            .iter()
+            .map(|stake| (stake.kind(), stake.stake_id.0, &stake.stake_name))
+            .chain(
+                mlw.projects()
+                    .iter()
+                    .map(|stake| (stake.kind(), stake.stake_id.0, &stake.stake_name)),
+            )
+            .chain(
+                mlw.tasks()
+                    .iter()
+                    .map(|stake| (stake.kind(), stake.stake_id.0, &stake.stake_name)),
+            );
+        for (kind, id, name) in entries {
+            self.entries.push(Entry {
+                kind,
+                id: id.to_string(),
+                name: name.clone(),
+            });
+        }
+    }
+
+    /// Returns where the word under the cursor starts and what could replace it.
+    pub fn completions(&self, line: &str, pos: usize) -> (usize, Vec<Pair>) {
+        let before = &line[..pos];
+        let start = before
+            .rfind(char::is_whitespace)
+            .map_or(0, |index| index + 1);
+        let partial = &before[start..];
+        let words: Vec<&str> = before[..start].split_whitespace().collect();
+
+        let candidates = match (words.first().copied(), words.len()) {
+            (None, _) => {
+                let mut names: Vec<String> = <ShellLine as clap::CommandFactory>::command()
+                    .get_subcommands()
+                    .map(|command| command.get_name().to_string())
+                    .filter(|name| name != "help")
+                    .collect();
+                names.extend(SHELL_COMMANDS.iter().map(|name| name.to_string()));
+                words_starting_with(names, partial)
+            }
+            _ if words.last() == Some(&"--area") => self.ids(StakeKind::Area, partial),
+            _ if words.last() == Some(&"--project") => self.ids(StakeKind::Project, partial),
+            (Some("move"), _) if words.last() == Some(&"--to") => {
+                match words.get(1).and_then(|kind| kind.parse().ok()) {
+                    Some(StakeKind::Task) => self.ids(StakeKind::Project, partial),
+                    Some(StakeKind::Project) => self.ids(StakeKind::Area, partial),
+                    _ => Vec::new(),
+                }
+            }
+            (Some("area" | "project" | "task"), 1) => {
+                words_starting_with(vec!["add".to_string()], partial)
+            }
+            (Some("done" | "drop" | "show" | "move"), 1) => kinds(partial, false),
+            (Some("list"), 1) => kinds(partial, true),
+            (Some("done" | "drop" | "show" | "move"), 2) => words[1]
+                .parse()
+                .map(|kind| self.ids(kind, partial))
+                .unwrap_or_default(),
+            (Some("search"), _) => self.names(partial),
+            _ => Vec::new(),
+        };
+        (start, candidates)
+    }
+
+    // Ids of one kind whose id starts with, or whose name contains, the partial word.
+    fn ids(&self, kind: StakeKind, partial: &str) -> Vec<Pair> {
+        let lower = partial.to_lowercase();
+        self.entries
+            .iter()
+            .filter(|entry| entry.kind == kind)
+            .filter(|entry| {
+                entry.id.starts_with(partial) || entry.name.to_lowercase().contains(&lower)
+            })
+            .map(|entry| Pair {
+                display: format!("{}  {}", entry.id, entry.name),
+                replacement: entry.id.clone(),
+            })
+            .collect()
+    }
+
+    fn names(&self, partial: &str) -> Vec<Pair> {
+        let lower = partial.to_lowercase();
+        let mut names: Vec<String> = self
+            .entries
+            .iter()
+            .filter(|entry| entry.name.to_lowercase().starts_with(&lower))
+            .map(|entry| entry.name.clone())
+            .collect();
+        names.dedup();
+        names
+            .into_iter()
+            .map(|name| Pair {
+                display: name.clone(),
+                replacement: name,
+            })
+            .collect()
+    }
+}
+
+fn words_starting_with(words: Vec<String>, partial: &str) -> Vec<Pair> {
+    words
+        .into_iter()
+        .filter(|word| word.starts_with(partial))
+        .map(|word| Pair {
+            display: word.clone(),
+            replacement: word,
+        })
+        .collect()
+}
+
+fn kinds(partial: &str, plural: bool) -> Vec<Pair> {
+    let names = ["area", "project", "task"]
+        .iter()
+        .map(|kind| {
+            if plural {
+                format!("{}s", kind)
+            } else {
+                kind.to_string()
+            }
+        })
+        .collect();
+    words_starting_with(names, partial)
+}
+
+impl Completer for ShellHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        Ok(self.completions(line, pos))
+    }
+}
+
+// History lives next to the data file, e.g. ~/.mlw.history for ~/.mlw.json.
+fn history_path(data_file: &Path) -> PathBuf {
+    data_file.with_extension("history")
+}
+
+/// Runs the shell until `exit`, Ctrl-D or end of input, saving pending changes on the way out.
+pub fn run(
+    path: &Path,
+    json: bool,
+    author: Option<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut session = Session::open(path, json)?;
+    session.mlw.set_author(author);
+    let mut editor: Editor<ShellHelper, DefaultHistory> = Editor::new()?;
+    let mut helper = ShellHelper::default();
+    helper.refresh(session.mlw());
+    editor.set_helper(Some(helper));
+    let history = history_path(path);
+    // A missing history file just means this is the first session.
+    let _ = editor.load_history(&history);
+
+    loop {
+        let reply = match editor.readline("mlw> ") {
+            Ok(line) => {
+                if !line.trim().is_empty() {
+                    editor.add_history_entry(line.as_str())?;
+                }
+                session.handle(&line)
+            }
+            Err(ReadlineError::Interrupted) => Reply::Continue(String::new()),
+            Err(ReadlineError::Eof) => session.handle("exit"),
+            Err(error) => return Err(error.into()),
+        };
+        match reply {
+            Reply::Continue(output) => {
+                if !output.is_empty() {
+                    println!("{}", output);
+                }
+                if let Some(helper) = editor.helper_mut() {
+                    helper.refresh(session.mlw());
+                }
+            }
+            Reply::Exit(output) => {
+                if !output.is_empty() {
+                    println!("{}", output);
+                }
+                break;
+            }
+        }
+    }
+    editor.save_history(&history)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session() -> (tempfile::TempDir, Session) {
+        let dir = tempfile::tempdir().unwrap();
+        let session = Session::open(&dir.path().join("mlw.json"), false).unwrap();
+        (dir, session)
+    }
+
+    fn output(reply: Reply) -> String {
+        match reply {
+            Reply::Continue(output) => output,
+            Reply::Exit(output) => panic!("unexpected exit: {}", output),
+        }
+    }
+
+    fn helper(session: &Session) -> ShellHelper {
+        let mut helper = ShellHelper::default();
+        helper.refresh(session.mlw());
+        helper
+    }
+
+    fn replacements(completions: (usize, Vec<Pair>)) -> Vec<String> {
+        completions
+            .1
+            .into_iter()
+            .map(|pair| pair.replacement)
+            .collect()
+    }
+
+    #[test]
+    fn test_handle_runs_cli_commands() {
+        let (_dir, mut session) = session();
+        assert_eq!(
+            output(session.handle("capture Call dentist")),
+            "Created task 1: Call dentist"
+        );
+        assert!(output(session.handle("list")).contains("Call dentist"));
+        assert!(session.has_unsaved_changes());
+    }
+
+    #[test]
+    fn test_handle_respects_quotes() {
+        let (_dir, mut session) = session();
+        session.handle(r#"task add "Buy shoes" --note "size 44""#);
+        let task = session.mlw().tasks().iter().next().unwrap();
+        assert_eq!(task.stake_name, "Buy shoes");
+        assert_eq!(task.note.as_deref(), Some("size 44"));
+    }
+
+    #[test]
+    fn test_handle_reports_errors_and_keeps_going() {
+        let (_dir, mut session) = session();
+        assert_eq!(
+            output(session.handle("done task 4")),
+            "error: no task matches '4'"
+        );
+        assert!(output(session.handle("frobnicate")).contains("unrecognized subcommand"));
+        assert!(output(session.handle("capture \"unterminated")).starts_with("error:"));
+        assert!(!session.has_unsaved_changes());
+    }
+
+    #[test]
+    fn test_handle_help_lists_shell_commands() {
+        let (_dir, mut session) = session();
+        let help = output(session.handle("help"));
+        assert!(help.contains("capture"));
+        assert!(help.contains("save"));
+    }
+
+    #[test]
+    fn test_read_only_commands_leave_session_clean() {
+        let (_dir, mut session) = session();
+        session.handle("list");
+        session.handle("review");
+        assert!(!session.has_unsaved_changes());
+        assert_eq!(output(session.handle("save")), "Nothing to save.");
+    }
+
+    #[test]
+    fn test_save_writes_file() {
+        let (dir, mut session) = session();
+        session.handle("capture Something");
+        assert!(output(session.handle("save")).starts_with("Saved to"));
+        assert!(!session.has_unsaved_changes());
+
+        let reloaded = serializer::load(&dir.path().join("mlw.json")).unwrap();
+        assert_eq!(reloaded.tasks().len(), 1);
+    }
+
+    #[test]
+    fn test_exit_saves_pending_changes() {
+        let (dir, mut session) = session();
+        session.handle("area add Health");
+        let reply = session.handle("quit");
+        assert!(matches!(reply, Reply::Exit(message) if message.starts_with("Saved to")));
+
+        let reloaded = serializer::load(&dir.path().join("mlw.json")).unwrap();
+        assert_eq!(reloaded.areas().len(), 1);
+    }
+
+    #[test]
+    fn test_exit_without_changes_is_silent() {
+        let (dir, mut session) = session();
+        assert_eq!(session.handle("exit"), Reply::Exit(String::new()));
+        assert!(!dir.path().join("mlw.json").exists());
+    }
+
+    #[test]
+    fn test_complete_command_names() {
+        let (_dir, session) = session();
+        let helper = helper(&session);
+        assert_eq!(
+            replacements(helper.completions("s", 1)),
+            vec!["search", "show", "save"]
+        );
+        assert_eq!(replacements(helper.completions("ca", 2)), vec!["capture"]);
+    }
+
+    #[test]
+    fn test_complete_kinds() {
+        let (_dir, session) = session();
+        let helper = helper(&session);
+        assert_eq!(replacements(helper.completions("done t", 6)), vec!["task"]);
+        assert_eq!(
+            replacements(helper.completions("list p", 6)),
+            vec!["projects"]
+        );
+    }
+
+    #[test]
+    fn test_complete_ids_by_number_or_name() {
+        let (_dir, mut session) = session();
+        session.handle("capture Call dentist");
+        session.handle("capture Buy milk");
+        session.handle("project add Dentistry");
+        let helper = helper(&session);
+
+        let (start, pairs) = helper.completions("done task dent", 14);
+        assert_eq!(start, 10);
+        assert_eq!(pairs.len(), 1);
+        assert_eq!(pairs[0].replacement, "1");
+        assert_eq!(pairs[0].display, "1  Call dentist");
+        assert_eq!(
+            replacements(helper.completions("show task ", 10)),
+            vec!["1", "2"]
+        );
+    }
+
+    #[test]
+    fn test_complete_parent_ids_for_options() {
+        let (_dir, mut session) = session();
+        session.handle("area add Health");
+        session.handle("project add Run a 10k");
+        session.handle("capture Stretch");
+        let helper = helper(&session);
+
+        let line = "task add Stretch --project ";
+        assert_eq!(
+            replacements(helper.completions(line, line.len())),
+            vec!["1"]
+        );
+        let line = "move task 1 --to r";
+        let pairs = helper.completions(line, line.len()).1;
+        assert_eq!(pairs[0].display, "1  Run a 10k");
+        let line = "move project 1 --to ";
+        assert_eq!(
+            helper.completions(line, line.len()).1[0].display,
+            "1  Health"
+        );
+    }
+
+    #[test]
+    fn test_complete_search_offers_names() {
+        let (_dir, mut session) = session();
+        session.handle("capture Call dentist");
+        let helper = helper(&session);
+        assert_eq!(
+            replacements(helper.completions("search ca", 9)),
+            vec!["Call dentist"]
+        );
+    }
+
+    #[test]
+    fn test_history_path_sits_next_to_data_file() {
+        assert_eq!(
+            history_path(Path::new("/home/me/.mlw.json")),
+            PathBuf::from("/home/me/.mlw.history")
+        );
+    }
+}
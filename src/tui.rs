@@ -0,0 +1,44 @@
+// The full-screen `mlw tui`. `app` holds the state and turns key presses into MLW
+// calls; `view` draws that state. Both run without a terminal, so the UI is tested
+// against ratatui's `TestBackend`.
+
+pub mod app;
+pub mod view;
+
+use std::path::Path;
+
+use ratatui::DefaultTerminal;
+use ratatui::crossterm::event::{self, Event, KeyEventKind};
+
+pub use app::App;
+
+use crate::config::Config;
+use crate::serializer;
+
+/// Opens the data file at `path` in the terminal UI until the user quits.
+pub fn run(path: &Path, author: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
+    let config = serializer::load_config(&Config::path_for(path))?;
+    let mut mlw = serializer::load(path)?;
+    mlw.set_author(author);
+    let mut app = App::new(mlw, path.to_path_buf(), config);
+    let mut terminal = ratatui::try_init()?;
+    let result = event_loop(&mut terminal, &mut app);
+    ratatui::restore();
+    result
+}
+
+fn event_loop(
+    terminal: &mut DefaultTerminal,
+    app: &mut App,
+) -> Result<(), Box<dyn std::error::Error>> {
+    while !app.should_quit() {
+        terminal.draw(|frame| view::draw(frame, app))?;
+        // Windows reports both press and release; only presses are actions.
+        if let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            app.handle_key(key);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,833 @@
+// State and key handling for the terminal UI. Everything here is plain data driven
+// through the MLW API, so it can be exercised without a terminal.
+
+use std::path::PathBuf;
+
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::capture;
+use crate::config::Config;
+use crate::entities::{AreaId, Clock, ProjectId, Stake, StakeRef, Task, TaskId};
+use crate::mlw::MLW;
+use crate::serializer;
+
+/// Which pane of the main screen receives navigation keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Focus {
+    Tree,
+    Tasks,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Screen {
+    Main,
+    Inbox,
+}
+
+/// A row of the left-hand tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TreeNode {
+    Inbox,
+    Area(AreaId),
+    Project(ProjectId),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeRow {
+    pub node: TreeNode,
+    pub depth: usize,
+    pub label: String,
+    pub open_tasks: usize,
+}
+
+/// The stake an action applies to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Selection {
+    Area(AreaId),
+    Project(ProjectId),
+    Task(TaskId),
+}
+
+/// What the text typed into the input line will be used for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Prompt {
+    AddTask(Option<ProjectId>),
+    AddProject(Option<AreaId>),
+    AddArea,
+    Move(Selection),
+    EditNote(Selection),
+}
+
+impl Prompt {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Prompt::AddTask(_) => "New task",
+            Prompt::AddProject(_) => "New project",
+            Prompt::AddArea => "New area",
+            Prompt::Move(Selection::Task(_)) => "Move to project (id or name, empty to unfile)",
+            Prompt::Move(_) => "Move to area (id or name, empty to unfile)",
+            Prompt::EditNote(_) => "Note",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Input {
+    pub prompt: Prompt,
+    pub buffer: String,
+}
+
+#[derive(Debug)]
+pub struct App {
+    mlw: MLW,
+    path: PathBuf,
+    config: Config,
+    focus: Focus,
+    screen: Screen,
+    tree_cursor: usize,
+    task_cursor: usize,
+    inbox_cursor: usize,
+    input: Option<Input>,
+    status: String,
+    unsaved: bool,
+    should_quit: bool,
+}
+
+impl App {
+    /// Creates the UI state for `mlw`, which is saved back to `path`.
+    pub fn new(mlw: MLW, path: PathBuf, config: Config) -> Self {
+        App {
+            mlw,
+            path,
+            config,
+            focus: Focus::Tree,
+            screen: Screen::Main,
+            tree_cursor: 0,
+            task_cursor: 0,
+            inbox_cursor: 0,
+            input: None,
+            status: String::new(),
+            unsaved: false,
+            should_quit: false,
+        }
+    }
+
+    pub fn mlw(&self) -> &MLW {
+        &self.mlw
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn focus(&self) -> Focus {
+        self.focus
+    }
+
+    pub fn screen(&self) -> Screen {
+        self.screen
+    }
+
+    pub fn tree_cursor(&self) -> usize {
+        self.tree_cursor
+    }
+
+    pub fn task_cursor(&self) -> usize {
+        self.task_cursor
+    }
+
+    pub fn inbox_cursor(&self) -> usize {
+        self.inbox_cursor
+    }
+
+    pub fn input(&self) -> Option<&Input> {
+        self.input.as_ref()
+    }
+
+    pub fn status(&self) -> &str {
+        &self.status
+    }
+
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.unsaved
+    }
+
+    pub fn should_quit(&self) -> bool {
+        self.should_quit
+    }
+
+    /// Inbox first, then each open area with its open projects, then open unfiled projects.
+    pub fn tree_rows(&self) -> Vec<TreeRow> {
+        let hierarchy = self.mlw.hierarchy();
+        let open_in = |tasks: &mut dyn Iterator<Item = &Stake<Task>>| {
+            tasks.filter(|task| task.is_active()).count()
+        };
+
+        let mut rows = vec![TreeRow {
+            node: TreeNode::Inbox,
+            depth: 0,
+            label: "Inbox".to_string(),
+            open_tasks: open_in(&mut hierarchy.unfiled_tasks.iter().copied()),
+        }];
+        for area in hierarchy.areas.iter().filter(|node| node.area.is_active()) {
+            rows.push(TreeRow {
+                node: TreeNode::Area(area.area.stake_id.clone()),
+                depth: 0,
+                label: area.area.stake_name.clone(),
+                open_tasks: area.task_counts.open,
+            });
+            for project in area.projects.iter().filter(|node| node.project.is_active()) {
+                rows.push(TreeRow {
+                    node: TreeNode::Project(project.project.stake_id.clone()),
+                    depth: 1,
+                    label: project.project.stake_name.clone(),
+                    open_tasks: project.task_counts.open,
+                });
+            }
+        }
+        for project in hierarchy
+            .unfiled_projects
+            .iter()
+            .filter(|node| node.project.is_active())
+        {
+            rows.push(TreeRow {
+                node: TreeNode::Project(project.project.stake_id.clone()),
+                depth: 0,
+                label: project.project.stake_name.clone(),
+                open_tasks: project.task_counts.open,
+            });
+        }
+        rows
+    }
+
+    pub fn selected_node(&self) -> TreeNode {
+        self.tree_rows()
+            .into_iter()
+            .nth(self.tree_cursor)
+            .map_or(TreeNode::Inbox, |row| row.node)
+    }
+
+    /// Open tasks under the selected tree node.
+    pub fn task_rows(&self) -> Vec<&Stake<Task>> {
+        match self.selected_node() {
+            TreeNode::Inbox => self.inbox(),
+            TreeNode::Area(id) => self
+                .mlw
+                .area_tree(&id)
+                .map(|node| node.tasks().filter(|task| task.is_active()).collect())
+                .unwrap_or_default(),
+            TreeNode::Project(id) => self.mlw.get_task_children(&id),
+        }
+    }
+
+    /// Open tasks not filed under any existing project.
+    pub fn inbox(&self) -> Vec<&Stake<Task>> {
+        self.mlw
+            .hierarchy()
+            .unfiled_tasks
+            .into_iter()
+            .filter(|task| task.is_active())
+            .collect()
+    }
+
+    pub fn selected_task(&self) -> Option<&Stake<Task>> {
+        match self.screen {
+            Screen::Main => self.task_rows().into_iter().nth(self.task_cursor),
+            Screen::Inbox => self.inbox().into_iter().nth(self.inbox_cursor),
+        }
+    }
+
+    // The stake that complete, drop, move and note act on.
+    fn selection(&self) -> Option<Selection> {
+        if self.screen == Screen::Inbox || self.focus == Focus::Tasks {
+            return self
+                .selected_task()
+                .map(|task| Selection::Task(task.stake_id.clone()));
+        }
+        match self.selected_node() {
+            TreeNode::Inbox => None,
+            TreeNode::Area(id) => Some(Selection::Area(id)),
+            TreeNode::Project(id) => Some(Selection::Project(id)),
+        }
+    }
+
+    /// Handles one key press.
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.quit();
+            return;
+        }
+        if self.input.is_some() {
+            self.handle_input_key(key.code);
+            return;
+        }
+        self.status.clear();
+        match (self.screen, key.code) {
+            (_, KeyCode::Char('q')) => self.quit(),
+            (_, KeyCode::Char('s')) => self.save(),
+            (_, KeyCode::Char('c') | KeyCode::Char(' ')) => self.complete(),
+            (_, KeyCode::Char('d')) => self.drop_selection(),
+            (_, KeyCode::Char('m')) => self.start_move(),
+            (_, KeyCode::Char('n')) => self.start_note(),
+            (_, KeyCode::Char('a')) => {
+                let project = match (self.screen, self.selected_node()) {
+                    (Screen::Main, TreeNode::Project(id)) => Some(id),
+                    _ => None,
+                };
+                self.prompt(Prompt::AddTask(project), String::new());
+            }
+            (Screen::Main, KeyCode::Char('p')) => {
+                let area = match self.selected_node() {
+                    TreeNode::Area(id) => Some(id),
+                    TreeNode::Project(id) => self
+                        .mlw
+                        .get_project_by_id(&id)
+                        .and_then(|project| project.parent_id.clone()),
+                    TreeNode::Inbox => None,
+                };
+                self.prompt(Prompt::AddProject(area), String::new());
+            }
+            (Screen::Main, KeyCode::Char('A')) => self.prompt(Prompt::AddArea, String::new()),
+            (Screen::Main, KeyCode::Char('i')) => {
+                self.screen = Screen::Inbox;
+                self.inbox_cursor = 0;
+            }
+            (Screen::Main, KeyCode::Esc) => self.quit(),
+            (Screen::Main, KeyCode::Tab | KeyCode::Left | KeyCode::Right)
+            | (Screen::Main, KeyCode::Char('h') | KeyCode::Char('l')) => {
+                self.focus = match self.focus {
+                    Focus::Tree => Focus::Tasks,
+                    Focus::Tasks => Focus::Tree,
+                };
+            }
+            (_, KeyCode::Down | KeyCode::Char('j')) => self.move_cursor(1),
+            (_, KeyCode::Up | KeyCode::Char('k')) => self.move_cursor(-1),
+            (Screen::Inbox, KeyCode::Esc | KeyCode::Char('i')) => self.screen = Screen::Main,
+            _ => {}
+        }
+    }
+
+    fn handle_input_key(&mut self, code: KeyCode) {
+        let Some(input) = self.input.as_mut() else {
+            return;
+        };
+        match code {
+            KeyCode::Char(c) => input.buffer.push(c),
+            KeyCode::Backspace => {
+                input.buffer.pop();
+            }
+            KeyCode::Esc => self.input = None,
+            KeyCode::Enter => {
+                if let Some(input) = self.input.take() {
+                    self.submit(input);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn prompt(&mut self, prompt: Prompt, buffer: String) {
+        self.input = Some(Input { prompt, buffer });
+    }
+
+    fn move_cursor(&mut self, delta: isize) {
+        let len = match (self.screen, self.focus) {
+            (Screen::Inbox, _) => self.inbox().len(),
+            (Screen::Main, Focus::Tree) => self.tree_rows().len(),
+            (Screen::Main, Focus::Tasks) => self.task_rows().len(),
+        };
+        let cursor = match (self.screen, self.focus) {
+            (Screen::Inbox, _) => &mut self.inbox_cursor,
+            (Screen::Main, Focus::Tree) => &mut self.tree_cursor,
+            (Screen::Main, Focus::Tasks) => &mut self.task_cursor,
+        };
+        let next = cursor.saturating_add_signed(delta);
+        *cursor = next.min(len.saturating_sub(1));
+        if self.screen == Screen::Main && self.focus == Focus::Tree {
+            self.task_cursor = 0;
+        }
+    }
+
+    // Keeps every cursor on a real row after stakes disappear from view.
+    fn clamp_cursors(&mut self) {
+        self.tree_cursor = self
+            .tree_cursor
+            .min(self.tree_rows().len().saturating_sub(1));
+        self.task_cursor = self
+            .task_cursor
+            .min(self.task_rows().len().saturating_sub(1));
+        self.inbox_cursor = self.inbox_cursor.min(self.inbox().len().saturating_sub(1));
+    }
+
+    fn changed(&mut self, message: String) {
+        self.unsaved = true;
+        self.status = message;
+        self.clamp_cursors();
+    }
+
+    fn complete(&mut self) {
+        let result = match self.selection() {
+            Some(Selection::Area(id)) => self.mlw.mark_area_complete(&id),
+            Some(Selection::Project(id)) => self.mlw.mark_project_complete(&id),
+            Some(Selection::Task(id)) => self.mlw.mark_task_complete(&id),
+            None => return,
+        };
+        match result {
+            Ok(()) => self.changed("Marked done".to_string()),
+            Err(error) => self.status = error.to_string(),
+        }
+    }
+
+    fn drop_selection(&mut self) {
+        let result = match self.selection() {
+            Some(Selection::Area(id)) => self.mlw.mark_area_dropped(&id),
+            Some(Selection::Project(id)) => self.mlw.mark_project_dropped(&id),
+            Some(Selection::Task(id)) => self.mlw.mark_task_dropped(&id),
+            None => return,
+        };
+        match result {
+            Ok(()) => self.changed("Dropped".to_string()),
+            Err(error) => self.status = error.to_string(),
+        }
+    }
+
+    fn start_move(&mut self) {
+        match self.selection() {
+            Some(Selection::Area(_)) => self.status = "Areas cannot be moved".to_string(),
+            Some(selection) => self.prompt(Prompt::Move(selection), String::new()),
+            None => {}
+        }
+    }
+
+    fn start_note(&mut self) {
+        let Some(selection) = self.selection() else {
+            return;
+        };
+        let note = match &selection {
+            Selection::Area(id) => self.mlw.get_area_by_id(id).and_then(|s| s.note.clone()),
+            Selection::Project(id) => self.mlw.get_project_by_id(id).and_then(|s| s.note.clone()),
+            Selection::Task(id) => self.mlw.get_task_by_id(id).and_then(|s| s.note.clone()),
+        };
+        self.prompt(Prompt::EditNote(selection), note.unwrap_or_default());
+    }
+
+    fn submit(&mut self, input: Input) {
+        let text = input.buffer.trim().to_string();
+        match input.prompt {
+            // Task names use the quick-capture syntax; without a +project the
+            // selected project is used.
+            Prompt::AddTask(project) if !text.is_empty() => {
+                let result =
+                    capture::parse(&text, &self.config.date_context(self.mlw.clock().now()))
+                        .and_then(|mut parsed| {
+                            if parsed.project.is_none() {
+                                parsed.project = project.map(|id| id.0.to_string());
+                            }
+                            parsed.into_task(&mut self.mlw)
+                        });
+                match result {
+                    Ok(task) => self.changed(format!(
+                        "Added task {}: {}",
+                        task.stake_id.0, task.stake_name
+                    )),
+                    Err(error) => self.status = error.to_string(),
+                }
+            }
+            Prompt::AddProject(area) if !text.is_empty() => {
+                let project = self.mlw.new_project(text, area, None);
+                self.changed(format!(
+                    "Added project {}: {}",
+                    project.stake_id.0, project.stake_name
+                ));
+            }
+            Prompt::AddArea if !text.is_empty() => {
+                let area = self.mlw.new_area(text, None);
+                self.changed(format!(
+                    "Added area {}: {}",
+                    area.stake_id.0, area.stake_name
+                ));
+            }
+            Prompt::Move(Selection::Task(id)) => {
+                let target = if text.is_empty() {
+                    None
+                } else {
+                    match self.find_project(&text) {
+                        Some(project) => Some(project),
+                        None => {
+                            self.status = format!("No project matches '{}'", text);
+                            return;
+                        }
+                    }
+                };
+                match self.mlw.move_task(&id, target) {
+                    Ok(()) => self.changed("Moved task".to_string()),
+                    Err(error) => self.status = error.to_string(),
+                }
+            }
+            Prompt::Move(Selection::Project(id)) => {
+                let target = if text.is_empty() {
+                    None
+                } else {
+                    match self.find_area(&text) {
+                        Some(area) => Some(area),
+                        None => {
+                            self.status = format!("No area matches '{}'", text);
+                            return;
+                        }
+                    }
+                };
+                match self.mlw.move_project(&id, target) {
+                    Ok(()) => self.changed("Moved project".to_string()),
+                    Err(error) => self.status = error.to_string(),
+                }
+            }
+            Prompt::EditNote(selection) => {
+                let note = (!text.is_empty()).then_some(text);
+                let now = self.mlw.clock().now();
+                let result = match selection {
+                    Selection::Area(id) => self.mlw.get_area_by_id(&id).cloned().map(|mut s| {
+                        s.note = note;
+                        s.date_modified = now;
+                        self.mlw.update_area(s)
+                    }),
+                    Selection::Project(id) => {
+                        self.mlw.get_project_by_id(&id).cloned().map(|mut s| {
+                            s.note = note;
+                            s.date_modified = now;
+                            self.mlw.update_project(s)
+                        })
+                    }
+                    Selection::Task(id) => self.mlw.get_task_by_id(&id).cloned().map(|mut s| {
+                        s.note = note;
+                        s.date_modified = now;
+                        self.mlw.update_task(s)
+                    }),
+                };
+                if let Some(Ok(())) = result {
+                    self.changed("Note saved".to_string());
+                }
+            }
+            // Empty names, and moving an area, do nothing.
+            _ => {}
+        }
+    }
+
+    // A typed handle first, then the best fuzzy name match.
+    fn find_project(&self, text: &str) -> Option<ProjectId> {
+        self.mlw.resolve_project(text).or_else(|| {
+            self.mlw
+                .fuzzy_search(text)
+                .into_iter()
+                .find_map(|hit| match hit.stake {
+                    StakeRef::Project(project) if project.is_active() => {
+                        Some(project.stake_id.clone())
+                    }
+                    _ => None,
+                })
+        })
+    }
+
+    fn find_area(&self, text: &str) -> Option<AreaId> {
+        self.mlw.resolve_area(text).or_else(|| {
+            self.mlw
+                .fuzzy_search(text)
+                .into_iter()
+                .find_map(|hit| match hit.stake {
+                    StakeRef::Area(area) if area.is_active() => Some(area.stake_id.clone()),
+                    _ => None,
+                })
+        })
+    }
+
+    fn save(&mut self) {
+        match serializer::save(&self.path, &self.mlw) {
+            Ok(()) => {
+                self.unsaved = false;
+                self.status = format!("Saved to {}", self.path.display());
+            }
+            Err(error) => self.status = error.to_string(),
+        }
+    }
+
+    // Saves pending changes first; a failed save keeps the UI open so nothing is lost.
+    fn quit(&mut self) {
+        if self.unsaved {
+            self.save();
+            if self.unsaved {
+                return;
+            }
+        }
+        self.should_quit = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app() -> (tempfile::TempDir, App) {
+        let dir = tempfile::tempdir().unwrap();
+        let mut mlw = MLW::new();
+        let health = mlw.new_area("Health".to_string(), None);
+        let run = mlw.new_project("Run a 10k".to_string(), Some(health.stake_id), None);
+        mlw.new_project("Side project".to_string(), None, None);
+        mlw.new_task("Buy shoes".to_string(), Some(run.stake_id.clone()), None);
+        mlw.new_task("Plan route".to_string(), Some(run.stake_id), None);
+        mlw.new_task("Call dentist".to_string(), None, None);
+        let app = App::new(mlw, dir.path().join("mlw.json"), Config::default());
+        (dir, app)
+    }
+
+    fn press(app: &mut App, code: KeyCode) {
+        app.handle_key(KeyEvent::from(code));
+    }
+
+    fn type_text(app: &mut App, text: &str) {
+        for c in text.chars() {
+            press(app, KeyCode::Char(c));
+        }
+        press(app, KeyCode::Enter);
+    }
+
+    fn task_names(app: &App) -> Vec<String> {
+        app.task_rows()
+            .iter()
+            .map(|task| task.stake_name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_tree_rows_list_inbox_areas_and_projects() {
+        let (_dir, app) = app();
+        let labels: Vec<(usize, String, usize)> = app
+            .tree_rows()
+            .into_iter()
+            .map(|row| (row.depth, row.label, row.open_tasks))
+            .collect();
+        assert_eq!(
+            labels,
+            vec![
+                (0, "Inbox".to_string(), 1),
+                (0, "Health".to_string(), 2),
+                (1, "Run a 10k".to_string(), 2),
+                (0, "Side project".to_string(), 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_task_pane_follows_tree_selection() {
+        let (_dir, mut app) = app();
+        assert_eq!(task_names(&app), vec!["Call dentist"]);
+        press(&mut app, KeyCode::Down);
+        assert_eq!(task_names(&app), vec!["Buy shoes", "Plan route"]);
+        press(&mut app, KeyCode::Char('j'));
+        press(&mut app, KeyCode::Char('j'));
+        assert_eq!(app.selected_node(), TreeNode::Project(ProjectId::new(2)));
+        assert!(task_names(&app).is_empty());
+    }
+
+    #[test]
+    fn test_cursor_stays_within_rows() {
+        let (_dir, mut app) = app();
+        press(&mut app, KeyCode::Up);
+        assert_eq!(app.tree_cursor(), 0);
+        for _ in 0..10 {
+            press(&mut app, KeyCode::Down);
+        }
+        assert_eq!(app.tree_cursor(), 3);
+    }
+
+    #[test]
+    fn test_tab_switches_focus() {
+        let (_dir, mut app) = app();
+        press(&mut app, KeyCode::Tab);
+        assert_eq!(app.focus(), Focus::Tasks);
+        press(&mut app, KeyCode::Tab);
+        assert_eq!(app.focus(), Focus::Tree);
+    }
+
+    #[test]
+    fn test_add_task_to_selected_project() {
+        let (_dir, mut app) = app();
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Char('a'));
+        assert_eq!(
+            app.input().unwrap().prompt,
+            Prompt::AddTask(Some(ProjectId::new(1)))
+        );
+        type_text(&mut app, "Stretch");
+
+        assert_eq!(task_names(&app), vec!["Buy shoes", "Plan route", "Stretch"]);
+        assert!(app.has_unsaved_changes());
+        assert_eq!(app.status(), "Added task 4: Stretch");
+    }
+
+    #[test]
+    fn test_add_task_uses_capture_syntax() {
+        let (_dir, mut app) = app();
+        press(&mut app, KeyCode::Char('a'));
+        type_text(&mut app, "Book physio +\"side project\" @phone");
+        let task = app.mlw().get_task_by_id(&TaskId::new(4)).unwrap();
+        assert_eq!(task.stake_name, "Book physio");
+        assert_eq!(task.parent_id, Some(ProjectId::new(2)));
+        assert_eq!(task.contexts, vec!["phone"]);
+
+        press(&mut app, KeyCode::Char('a'));
+        type_text(&mut app, "Nope +Garden");
+        assert_eq!(app.status(), "no project matches 'Garden'");
+    }
+
+    #[test]
+    fn test_add_project_under_selected_area_and_new_area() {
+        let (_dir, mut app) = app();
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Char('p'));
+        type_text(&mut app, "Sleep better");
+        press(&mut app, KeyCode::Char('A'));
+        type_text(&mut app, "Finance");
+
+        let labels: Vec<String> = app.tree_rows().into_iter().map(|row| row.label).collect();
+        assert_eq!(
+            labels,
+            vec![
+                "Inbox",
+                "Health",
+                "Run a 10k",
+                "Sleep better",
+                "Finance",
+                "Side project"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_escape_cancels_input() {
+        let (_dir, mut app) = app();
+        press(&mut app, KeyCode::Char('a'));
+        press(&mut app, KeyCode::Char('x'));
+        press(&mut app, KeyCode::Backspace);
+        assert_eq!(app.input().unwrap().buffer, "");
+        press(&mut app, KeyCode::Esc);
+        assert!(app.input().is_none());
+        assert!(!app.has_unsaved_changes());
+    }
+
+    #[test]
+    fn test_complete_task_in_task_pane() {
+        let (_dir, mut app) = app();
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Tab);
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Char('c'));
+
+        assert!(app.mlw().get_task_by_id(&TaskId::new(2)).unwrap().complete);
+        assert_eq!(task_names(&app), vec!["Buy shoes"]);
+        assert_eq!(app.task_cursor(), 0);
+    }
+
+    #[test]
+    fn test_drop_project_in_tree_pane() {
+        let (_dir, mut app) = app();
+        for _ in 0..3 {
+            press(&mut app, KeyCode::Down);
+        }
+        press(&mut app, KeyCode::Char('d'));
+
+        assert!(
+            app.mlw()
+                .get_project_by_id(&ProjectId::new(2))
+                .unwrap()
+                .dropped
+        );
+        assert_eq!(app.tree_rows().len(), 3);
+        assert_eq!(app.tree_cursor(), 2);
+    }
+
+    #[test]
+    fn test_move_task_by_project_name() {
+        let (_dir, mut app) = app();
+        press(&mut app, KeyCode::Tab);
+        press(&mut app, KeyCode::Char('m'));
+        type_text(&mut app, "side");
+
+        let task = app.mlw().get_task_by_id(&TaskId::new(3)).unwrap();
+        assert_eq!(task.parent_id, Some(ProjectId::new(2)));
+        assert!(app.inbox().is_empty());
+    }
+
+    #[test]
+    fn test_move_to_unknown_project_reports_and_keeps_task() {
+        let (_dir, mut app) = app();
+        press(&mut app, KeyCode::Tab);
+        press(&mut app, KeyCode::Char('m'));
+        type_text(&mut app, "zzzz");
+
+        assert_eq!(app.status(), "No project matches 'zzzz'");
+        assert_eq!(app.inbox().len(), 1);
+    }
+
+    #[test]
+    fn test_move_area_is_refused() {
+        let (_dir, mut app) = app();
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Char('m'));
+        assert!(app.input().is_none());
+        assert_eq!(app.status(), "Areas cannot be moved");
+    }
+
+    #[test]
+    fn test_edit_note_prefills_and_saves() {
+        let (_dir, mut app) = app();
+        press(&mut app, KeyCode::Tab);
+        press(&mut app, KeyCode::Char('n'));
+        type_text(&mut app, "after 3pm");
+        press(&mut app, KeyCode::Char('n'));
+        assert_eq!(app.input().unwrap().buffer, "after 3pm");
+        press(&mut app, KeyCode::Esc);
+
+        let task = app.mlw().get_task_by_id(&TaskId::new(3)).unwrap();
+        assert_eq!(task.note.as_deref(), Some("after 3pm"));
+    }
+
+    #[test]
+    fn test_inbox_screen_processes_items() {
+        let (_dir, mut app) = app();
+        press(&mut app, KeyCode::Char('a'));
+        type_text(&mut app, "Renew passport");
+        press(&mut app, KeyCode::Char('i'));
+        assert_eq!(app.screen(), Screen::Inbox);
+        assert_eq!(app.selected_task().unwrap().stake_name, "Call dentist");
+
+        press(&mut app, KeyCode::Char('m'));
+        type_text(&mut app, "1");
+        assert_eq!(app.selected_task().unwrap().stake_name, "Renew passport");
+        press(&mut app, KeyCode::Char('d'));
+        assert!(app.inbox().is_empty());
+        assert!(app.selected_task().is_none());
+
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.screen(), Screen::Main);
+    }
+
+    #[test]
+    fn test_save_and_quit_write_the_file() {
+        let (dir, mut app) = app();
+        press(&mut app, KeyCode::Char('A'));
+        type_text(&mut app, "Finance");
+        press(&mut app, KeyCode::Char('q'));
+
+        assert!(app.should_quit());
+        let saved = serializer::load(&dir.path().join("mlw.json")).unwrap();
+        assert_eq!(&saved, app.mlw());
+    }
+
+    #[test]
+    fn test_quit_without_changes_does_not_write() {
+        let (dir, mut app) = app();
+        press(&mut app, KeyCode::Char('q'));
+        assert!(app.should_quit());
+        assert!(!dir.path().join("mlw.json").exists());
+    }
+}
@@ -0,0 +1,264 @@
+// Drawing the terminal UI from `App` state. Nothing here changes the state.
+
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Wrap};
+
+use super::app::{App, Focus, Screen};
+
+const MAIN_HELP: &str =
+    "a task  p project  A area  c done  d drop  m move  n note  i inbox  s save  q quit";
+const INBOX_HELP: &str =
+    "c done  d drop  m move  n note  a capture  j/k next/previous  Esc back  q quit";
+
+/// Draws the current screen and the footer line.
+pub fn draw(frame: &mut Frame, app: &App) {
+    let [body, footer] =
+        Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+    match app.screen() {
+        Screen::Main => draw_main(frame, app, body),
+        Screen::Inbox => draw_inbox(frame, app, body),
+    }
+    draw_footer(frame, app, footer);
+}
+
+fn pane(title: String, focused: bool) -> Block<'static> {
+    let block = Block::bordered().title(title);
+    if focused {
+        block.border_style(Style::new().add_modifier(Modifier::BOLD))
+    } else {
+        block
+    }
+}
+
+fn draw_main(frame: &mut Frame, app: &App, area: Rect) {
+    let [left, right] =
+        Layout::horizontal([Constraint::Percentage(35), Constraint::Percentage(65)]).areas(area);
+    let [tasks_area, note_area] =
+        Layout::vertical([Constraint::Min(3), Constraint::Length(4)]).areas(right);
+
+    let rows = app.tree_rows();
+    let items: Vec<ListItem> = rows
+        .iter()
+        .map(|row| {
+            ListItem::new(format!(
+                "{}{} ({})",
+                "  ".repeat(row.depth),
+                row.label,
+                row.open_tasks
+            ))
+        })
+        .collect();
+    let tree = List::new(items)
+        .block(pane(
+            " Areas & Projects ".to_string(),
+            app.focus() == Focus::Tree,
+        ))
+        .highlight_symbol("> ")
+        .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+    let mut tree_state = ListState::default().with_selected(Some(app.tree_cursor()));
+    frame.render_stateful_widget(tree, left, &mut tree_state);
+
+    let title = rows
+        .get(app.tree_cursor())
+        .map_or("Tasks".to_string(), |row| row.label.clone());
+    let tasks = app.task_rows();
+    let items: Vec<ListItem> = tasks
+        .iter()
+        .map(|task| ListItem::new(format!("[ ] {}  #{}", task.stake_name, task.stake_id.0)))
+        .collect();
+    let mut task_state = ListState::default();
+    if app.focus() == Focus::Tasks && !tasks.is_empty() {
+        task_state.select(Some(app.task_cursor()));
+    }
+    let list = List::new(items)
+        .block(pane(format!(" {} ", title), app.focus() == Focus::Tasks))
+        .highlight_symbol("> ")
+        .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+    frame.render_stateful_widget(list, tasks_area, &mut task_state);
+
+    let note = tasks
+        .get(app.task_cursor())
+        .and_then(|task| task.note.clone())
+        .unwrap_or_default();
+    frame.render_widget(
+        Paragraph::new(note)
+            .wrap(Wrap { trim: true })
+            .block(Block::bordered().title(" Note ")),
+        note_area,
+    );
+}
+
+fn draw_inbox(frame: &mut Frame, app: &App, area: Rect) {
+    let inbox = app.inbox();
+    let title = if inbox.is_empty() {
+        " Inbox ".to_string()
+    } else {
+        format!(" Inbox: {} of {} ", app.inbox_cursor() + 1, inbox.len())
+    };
+    let lines = match app.selected_task() {
+        Some(task) => {
+            let mut lines = vec![
+                Line::styled(
+                    task.stake_name.clone(),
+                    Style::new().add_modifier(Modifier::BOLD),
+                ),
+                Line::from(format!(
+                    "Captured {}",
+                    task.date_created
+                        .with_timezone(&app.config().timezone)
+                        .format("%Y-%m-%d %H:%M")
+                )),
+            ];
+            if let Some(note) = &task.note {
+                lines.push(Line::from(""));
+                lines.push(Line::from(note.clone()));
+            }
+            lines
+        }
+        None => vec![Line::from("Inbox zero. Press Esc to go back.")],
+    };
+    frame.render_widget(
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: true })
+            .block(pane(title, true)),
+        area,
+    );
+}
+
+fn draw_footer(frame: &mut Frame, app: &App, area: Rect) {
+    let text = match app.input() {
+        Some(input) => format!("{}: {}_", input.prompt.label(), input.buffer),
+        None if !app.status().is_empty() => app.status().to_string(),
+        None => match app.screen() {
+            Screen::Main => MAIN_HELP.to_string(),
+            Screen::Inbox => INBOX_HELP.to_string(),
+        },
+    };
+    frame.render_widget(Paragraph::new(text), area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::mlw::MLW;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+    use ratatui::crossterm::event::{KeyCode, KeyEvent};
+    use std::path::PathBuf;
+
+    fn app() -> App {
+        let mut mlw = MLW::new();
+        let health = mlw.new_area("Health".to_string(), None);
+        let run = mlw.new_project("Run a 10k".to_string(), Some(health.stake_id), None);
+        mlw.new_task(
+            "Buy shoes".to_string(),
+            Some(run.stake_id),
+            Some("size 44".to_string()),
+        );
+        mlw.new_task("Call dentist".to_string(), None, None);
+        App::new(mlw, PathBuf::from("unused.json"), Config::default())
+    }
+
+    fn render(app: &App) -> Vec<String> {
+        let mut terminal = Terminal::new(TestBackend::new(60, 12)).unwrap();
+        terminal.draw(|frame| draw(frame, app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    fn press(app: &mut App, code: KeyCode) {
+        app.handle_key(KeyEvent::from(code));
+    }
+
+    #[test]
+    fn test_main_screen_shows_tree_and_inbox_tasks() {
+        assert_eq!(
+            render(&app()),
+            vec![
+                "┌ Areas & Projects ─┐┌ Inbox ──────────────────────────────┐",
+                "│> Inbox (1)        ││[ ] Call dentist  #2                 │",
+                "│  Health (1)       ││                                     │",
+                "│    Run a 10k (1)  ││                                     │",
+                "│                   ││                                     │",
+                "│                   ││                                     │",
+                "│                   │└─────────────────────────────────────┘",
+                "│                   │┌ Note ───────────────────────────────┐",
+                "│                   ││                                     │",
+                "│                   ││                                     │",
+                "└───────────────────┘└─────────────────────────────────────┘",
+                "a task  p project  A area  c done  d drop  m move  n note  i",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_selected_project_lists_its_tasks_and_note() {
+        let mut app = app();
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Tab);
+        let lines = render(&app);
+
+        assert_eq!(
+            lines[0],
+            "┌ Areas & Projects ─┐┌ Run a 10k ──────────────────────────┐"
+        );
+        assert_eq!(
+            lines[1],
+            "│  Inbox (1)        ││> [ ] Buy shoes  #1                  │"
+        );
+        assert_eq!(
+            lines[3],
+            "│>   Run a 10k (1)  ││                                     │"
+        );
+        assert_eq!(
+            lines[8],
+            "│                   ││size 44                              │"
+        );
+    }
+
+    #[test]
+    fn test_footer_shows_prompt_while_typing() {
+        let mut app = app();
+        press(&mut app, KeyCode::Char('A'));
+        press(&mut app, KeyCode::Char('F'));
+        let lines = render(&app);
+        assert_eq!(lines[11], "New area: F_");
+    }
+
+    #[test]
+    fn test_inbox_screen_shows_current_item() {
+        let mut app = app();
+        press(&mut app, KeyCode::Char('i'));
+        let lines = render(&app);
+
+        assert!(lines[0].starts_with("┌ Inbox: 1 of 1 ─"));
+        assert_eq!(lines[1], format!("│Call dentist{}│", " ".repeat(46)));
+        assert!(lines[2].starts_with("│Captured "));
+        assert!(lines[11].starts_with("c done  d drop"));
+    }
+
+    #[test]
+    fn test_empty_inbox_screen() {
+        let mut app = app();
+        press(&mut app, KeyCode::Char('i'));
+        press(&mut app, KeyCode::Char('c'));
+        let lines = render(&app);
+
+        assert!(lines[0].starts_with("┌ Inbox ─"));
+        assert!(lines[1].starts_with("│Inbox zero."));
+        assert_eq!(lines[11], "Marked done");
+    }
+}
@@ -0,0 +1,369 @@
+// Named MLWs, each with a data file of its own, so work and personal systems stay
+// apart in one installation. The list lives in `~/.mlw.workspaces.json` and names
+// the current workspace, which commands use unless --workspace or --file says
+// otherwise.
+//
+// Before any workspace is created there is one, "default", at `~/.mlw.json`. New
+// workspaces keep their data in `~/.mlw/<name>.json` unless given a file, and each
+// data file keeps its own config and server accounts beside it.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use crate::auth::Accounts;
+use crate::config::Config;
+use crate::operations;
+use crate::presenter::ScoredView;
+use crate::serializer::{self, StorageError};
+
+/// The name of the workspace every installation starts with.
+pub const DEFAULT: &str = "default";
+
+/// A change to the workspace list that could not be made.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorkspaceError {
+    InvalidName(String),
+    Exists(String),
+    Unknown(String),
+    /// The current workspace cannot be deleted; switch away first.
+    Current(String),
+    /// Another workspace already keeps its data in this file.
+    FileInUse {
+        file: PathBuf,
+        workspace: String,
+    },
+}
+
+impl fmt::Display for WorkspaceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorkspaceError::InvalidName(name) => write!(
+                f,
+                "'{}' is not a valid workspace name: use letters, digits, '-' and '_'",
+                name
+            ),
+            WorkspaceError::Exists(name) => write!(f, "workspace '{}' already exists", name),
+            WorkspaceError::Unknown(name) => write!(f, "no workspace named '{}'", name),
+            WorkspaceError::Current(name) => write!(
+                f,
+                "'{}' is the current workspace; switch to another before deleting it",
+                name
+            ),
+            WorkspaceError::FileInUse { file, workspace } => write!(
+                f,
+                "{} already holds workspace '{}'",
+                file.display(),
+                workspace
+            ),
+        }
+    }
+}
+
+impl std::error::Error for WorkspaceError {}
+
+/// The workspaces of one installation, in the order they were created.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Workspaces {
+    current: Option<String>,
+    workspaces: IndexMap<String, Workspace>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Workspace {
+    file: PathBuf,
+}
+
+/// A search hit and the workspace it was found in.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WorkspaceHit {
+    pub workspace: String,
+    #[serde(flatten)]
+    pub hit: ScoredView,
+}
+
+impl Workspaces {
+    /// Where the workspace list of the installation in `home` is kept.
+    pub fn path_for(home: &Path) -> PathBuf {
+        home.join(".mlw.workspaces.json")
+    }
+
+    /// Where a new workspace called `name` keeps its data, unless told otherwise.
+    pub fn file_for(home: &Path, name: &str) -> PathBuf {
+        home.join(".mlw").join(format!("{}.json", name))
+    }
+
+    /// Makes sure there is a workspace: with none, `default_file` becomes the
+    /// "default" one. Lists read from disk always have one; new lists start here.
+    pub fn with_default(mut self, default_file: PathBuf) -> Self {
+        if self.workspaces.is_empty() {
+            self.workspaces
+                .insert(DEFAULT.to_string(), Workspace { file: default_file });
+        }
+        if self
+            .current
+            .as_ref()
+            .is_none_or(|name| !self.workspaces.contains_key(name))
+        {
+            self.current = self.workspaces.keys().next().cloned();
+        }
+        self
+    }
+
+    /// The name of the current workspace.
+    pub fn current(&self) -> &str {
+        self.current.as_deref().unwrap_or(DEFAULT)
+    }
+
+    /// The data file of the workspace called `name`.
+    pub fn file(&self, name: &str) -> Result<&Path, WorkspaceError> {
+        self.workspaces
+            .get(name)
+            .map(|workspace| workspace.file.as_path())
+            .ok_or_else(|| WorkspaceError::Unknown(name.to_string()))
+    }
+
+    /// Every workspace with its data file.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Path)> {
+        self.workspaces
+            .iter()
+            .map(|(name, workspace)| (name.as_str(), workspace.file.as_path()))
+    }
+
+    /// Adds a workspace keeping its data in `file`, which may already hold some.
+    pub fn create(&mut self, name: &str, file: PathBuf) -> Result<(), WorkspaceError> {
+        check_name(name)?;
+        if self.workspaces.contains_key(name) {
+            return Err(WorkspaceError::Exists(name.to_string()));
+        }
+        if let Some((workspace, _)) = self.iter().find(|(_, used)| *used == file) {
+            return Err(WorkspaceError::FileInUse {
+                file,
+                workspace: workspace.to_string(),
+            });
+        }
+        self.workspaces.insert(name.to_string(), Workspace { file });
+        Ok(())
+    }
+
+    /// Makes `name` the current workspace.
+    pub fn switch(&mut self, name: &str) -> Result<(), WorkspaceError> {
+        self.file(name)?;
+        self.current = Some(name.to_string());
+        Ok(())
+    }
+
+    /// Renames a workspace in place. Its data file stays where it is.
+    pub fn rename(&mut self, name: &str, new_name: &str) -> Result<(), WorkspaceError> {
+        check_name(new_name)?;
+        if self.workspaces.contains_key(new_name) {
+            return Err(WorkspaceError::Exists(new_name.to_string()));
+        }
+        let at = self
+            .workspaces
+            .get_index_of(name)
+            .ok_or_else(|| WorkspaceError::Unknown(name.to_string()))?;
+        let (_, workspace) = self.workspaces.shift_remove_index(at).expect("index found");
+        self.workspaces
+            .shift_insert(at, new_name.to_string(), workspace);
+        if self.current.as_deref() == Some(name) {
+            self.current = Some(new_name.to_string());
+        }
+        Ok(())
+    }
+
+    /// Removes a workspace other than the current one and returns its data file,
+    /// which is left for the caller to delete or keep.
+    pub fn remove(&mut self, name: &str) -> Result<PathBuf, WorkspaceError> {
+        if self.current() == name {
+            return Err(WorkspaceError::Current(name.to_string()));
+        }
+        self.workspaces
+            .shift_remove(name)
+            .map(|workspace| workspace.file)
+            .ok_or_else(|| WorkspaceError::Unknown(name.to_string()))
+    }
+
+    /// Searches every workspace for `query`, best hits first whichever workspace
+    /// they are in.
+    pub fn search(&self, query: &str, fuzzy: bool) -> Result<Vec<WorkspaceHit>, StorageError> {
+        let mut hits = Vec::new();
+        for (name, file) in self.iter() {
+            let mlw = serializer::load(file)?;
+            hits.extend(
+                operations::search(&mlw, query, fuzzy)
+                    .into_iter()
+                    .map(|hit| WorkspaceHit {
+                        workspace: name.to_string(),
+                        hit,
+                    }),
+            );
+        }
+        // Stable, so equal scores keep the workspaces in order.
+        hits.sort_by(|a, b| b.hit.score.total_cmp(&a.hit.score));
+        Ok(hits)
+    }
+}
+
+/// Deletes a workspace's data file and the config and accounts kept beside it.
+/// Files that do not exist are skipped.
+pub fn delete_files(data_file: &Path) -> Result<(), StorageError> {
+    for path in [
+        data_file.to_path_buf(),
+        Config::path_for(data_file),
+        Accounts::path_for(data_file),
+    ] {
+        match fs::remove_file(&path) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => {
+                return Err(StorageError::Io(path, error));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+// Names become file names, so they keep to characters that are safe in one and
+// never contain the '.' that sets a data file apart from its config.
+fn check_name(name: &str) -> Result<(), WorkspaceError> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_'));
+    match valid {
+        true => Ok(()),
+        false => Err(WorkspaceError::InvalidName(name.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mlw::MLW;
+
+    fn workspaces(home: &Path) -> Workspaces {
+        let mut workspaces = Workspaces::default().with_default(home.join(".mlw.json"));
+        workspaces
+            .create("work", Workspaces::file_for(home, "work"))
+            .unwrap();
+        workspaces
+    }
+
+    #[test]
+    fn test_new_installations_have_a_default_workspace() {
+        let home = Path::new("/home/ana");
+        let workspaces = Workspaces::default().with_default(home.join(".mlw.json"));
+        assert_eq!(workspaces.current(), DEFAULT);
+        assert_eq!(
+            workspaces.file(DEFAULT).unwrap(),
+            Path::new("/home/ana/.mlw.json")
+        );
+        assert_eq!(
+            Workspaces::file_for(home, "work"),
+            Path::new("/home/ana/.mlw/work.json")
+        );
+        // A list that already has workspaces is left alone.
+        let mut workspaces = workspaces;
+        workspaces
+            .create("work", Workspaces::file_for(home, "work"))
+            .unwrap();
+        let again = workspaces.clone().with_default(PathBuf::from("/elsewhere"));
+        assert_eq!(again, workspaces);
+    }
+
+    #[test]
+    fn test_create_switch_and_rename() {
+        let home = Path::new("/home/ana");
+        let mut workspaces = workspaces(home);
+        workspaces.switch("work").unwrap();
+        assert_eq!(workspaces.current(), "work");
+
+        workspaces.rename("work", "office").unwrap();
+        assert_eq!(workspaces.current(), "office");
+        assert_eq!(
+            workspaces.file("office").unwrap(),
+            Path::new("/home/ana/.mlw/work.json")
+        );
+        let names: Vec<&str> = workspaces.iter().map(|(name, _)| name).collect();
+        assert_eq!(names, vec!["default", "office"]);
+        assert_eq!(
+            workspaces.switch("work"),
+            Err(WorkspaceError::Unknown("work".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_changes_are_checked() {
+        let home = Path::new("/home/ana");
+        let mut workspaces = workspaces(home);
+        assert_eq!(
+            workspaces.create("work", PathBuf::from("/tmp/w.json")),
+            Err(WorkspaceError::Exists("work".to_string()))
+        );
+        for name in ["", "a.b", "a/b", "my work"] {
+            assert_eq!(
+                workspaces.create(name, PathBuf::from("/tmp/x.json")),
+                Err(WorkspaceError::InvalidName(name.to_string()))
+            );
+        }
+        assert!(matches!(
+            workspaces.create("again", home.join(".mlw.json")),
+            Err(WorkspaceError::FileInUse { workspace, .. }) if workspace == DEFAULT
+        ));
+        assert_eq!(
+            workspaces.rename("work", DEFAULT),
+            Err(WorkspaceError::Exists(DEFAULT.to_string()))
+        );
+        assert_eq!(
+            workspaces.remove(DEFAULT),
+            Err(WorkspaceError::Current(DEFAULT.to_string()))
+        );
+        assert_eq!(
+            workspaces.remove("work").unwrap(),
+            Workspaces::file_for(home, "work")
+        );
+        assert!(workspaces.remove("work").is_err());
+    }
+
+    #[test]
+    fn test_search_spans_every_workspace() {
+        let dir = tempfile::tempdir().unwrap();
+        let workspaces = workspaces(dir.path());
+        let mut personal = MLW::new();
+        personal.new_task("Call the dentist".to_string(), None, None);
+        serializer::save(workspaces.file(DEFAULT).unwrap(), &personal).unwrap();
+        let mut work = MLW::new();
+        work.new_project("Dentist office move".to_string(), None, None);
+        work.new_task("File expenses".to_string(), None, None);
+        serializer::save(workspaces.file("work").unwrap(), &work).unwrap();
+
+        let hits = workspaces.search("dentist", false).unwrap();
+        let found: Vec<(&str, &str)> = hits
+            .iter()
+            .map(|hit| (hit.workspace.as_str(), hit.hit.stake.name.as_str()))
+            .collect();
+        assert_eq!(found.len(), 2);
+        assert!(found.contains(&(DEFAULT, "Call the dentist")));
+        assert!(found.contains(&("work", "Dentist office move")));
+        assert!(hits[0].hit.score >= hits[1].hit.score);
+        assert_eq!(workspaces.search("dentst", true).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_delete_files_removes_the_data_beside_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("work.json");
+        serializer::save(&file, &MLW::new()).unwrap();
+        serializer::save_config(&Config::path_for(&file), &Config::default()).unwrap();
+        delete_files(&file).unwrap();
+        assert!(!file.exists());
+        assert!(!Config::path_for(&file).exists());
+        // Deleting again finds nothing to delete, which is fine.
+        delete_files(&file).unwrap();
+    }
+}
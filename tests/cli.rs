@@ -0,0 +1,840 @@
+// Drives the `mlw` binary, one-shot, as a shell and as a server, against a data file in a
+// temporary directory.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Output, Stdio};
+
+use serde_json::Value;
+use tempfile::TempDir;
+
+struct Workspace {
+    _dir: TempDir,
+    file: PathBuf,
+}
+
+impl Workspace {
+    fn new() -> Self {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let file = dir.path().join("mlw.json");
+        Workspace { _dir: dir, file }
+    }
+
+    fn run(&self, args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_mlw"))
+            .arg("--file")
+            .arg(&self.file)
+            .args(args)
+            .env_remove("MLW_FILE")
+            .env_remove("MLW_REMOTE")
+            .env_remove("MLW_TOKEN")
+            .env_remove("MLW_WORKSPACE")
+            .env_remove("MLW_USER")
+            .output()
+            .expect("mlw should run")
+    }
+
+    // Runs a command that must succeed and returns its stdout.
+    fn ok(&self, args: &[&str]) -> String {
+        let output = self.run(args);
+        assert!(
+            output.status.success(),
+            "mlw {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8(output.stdout).expect("utf-8 output")
+    }
+
+    fn json(&self, args: &[&str]) -> Value {
+        let mut with_json = vec!["--json"];
+        with_json.extend_from_slice(args);
+        serde_json::from_str(&self.ok(&with_json)).expect("valid JSON output")
+    }
+
+    // Starts `mlw shell` and feeds it `input` as if typed line by line.
+    fn shell(&self, input: &str) -> Output {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_mlw"))
+            .arg("--file")
+            .arg(&self.file)
+            .arg("shell")
+            .env_remove("MLW_FILE")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("mlw shell should start");
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(input.as_bytes())
+            .unwrap();
+        child.wait_with_output().expect("mlw shell should finish")
+    }
+
+    fn data(&self) -> Value {
+        read_json(&self.file)
+    }
+
+    // Issues a token for `user`, adding the user first if needed, and returns it.
+    fn token(&self, user: &str, flags: &[&str]) -> String {
+        self.run(&["user", "add", user]);
+        let mut args = vec!["token", "issue", user];
+        args.extend_from_slice(flags);
+        self.ok(&args).trim().to_string()
+    }
+
+    // Starts `mlw serve` with `listen` (flag and address) and returns it with the
+    // address from its banner.
+    fn serve(&self, listen: [&str; 2]) -> (Child, String) {
+        let mut server = Command::new(env!("CARGO_BIN_EXE_mlw"))
+            .arg("--file")
+            .arg(&self.file)
+            .arg("serve")
+            .args(listen)
+            .env_remove("MLW_FILE")
+            .env_remove("MLW_REMOTE")
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("mlw serve should start");
+        // Warnings may come before the banner.
+        let mut stderr = BufReader::new(server.stderr.take().unwrap());
+        let mut banner = String::new();
+        while !banner.starts_with("Listening on ") {
+            banner.clear();
+            if stderr.read_line(&mut banner).unwrap() == 0 {
+                panic!("mlw serve exited without a banner");
+            }
+        }
+        let address = banner.trim()["Listening on ".len()..].to_string();
+        (server, address)
+    }
+}
+
+// Runs `mlw` without --file, as someone whose home directory is `home`, and returns
+// its stdout; it must succeed.
+fn at_home(home: &Path, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_mlw"))
+        .args(args)
+        .env("HOME", home)
+        .env_remove("MLW_FILE")
+        .env_remove("MLW_WORKSPACE")
+        .env_remove("MLW_REMOTE")
+        .output()
+        .expect("mlw should run");
+    assert!(
+        output.status.success(),
+        "mlw {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).expect("utf-8 output")
+}
+
+fn read_json(path: &Path) -> Value {
+    serde_json::from_str(&std::fs::read_to_string(path).expect("data file")).expect("valid JSON")
+}
+
+#[test]
+fn capture_persists_task_to_data_file() {
+    let workspace = Workspace::new();
+    let output = workspace.ok(&["capture", "Call", "dentist"]);
+
+    assert_eq!(output.trim(), "Created task 1: Call dentist");
+    let data = workspace.data();
+    assert_eq!(data["tasks"]["stakes"]["1"]["stake_name"], "Call dentist");
+    assert_eq!(data["tasks"]["nextId"], 2);
+}
+
+#[test]
+fn capture_reads_quick_capture_syntax() {
+    let workspace = Workspace::new();
+    workspace.ok(&["project", "add", "Personal"]);
+    let task = workspace.json(&[
+        "capture",
+        "Call dentist @phone #health +Personal !high // bring insurance card",
+    ]);
+
+    assert_eq!(task["name"], "Call dentist");
+    assert_eq!(task["parent_id"], 1);
+    assert_eq!(task["contexts"][0], "phone");
+    assert_eq!(task["tags"][0], "health");
+    assert_eq!(task["priority"], "high");
+    assert_eq!(task["note"], "bring insurance card");
+
+    let output = workspace.run(&["capture", "Buy seeds", "+Garden"]);
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr).trim(),
+        "error: no project matches 'Garden'"
+    );
+}
+
+#[test]
+fn add_area_project_and_task_builds_hierarchy() {
+    let workspace = Workspace::new();
+    workspace.ok(&["area", "add", "Health"]);
+    workspace.ok(&["project", "add", "Run a 10k", "--area", "1"]);
+    let task = workspace.json(&[
+        "task",
+        "add",
+        "Buy shoes",
+        "--project",
+        "1",
+        "--note",
+        "size 44",
+    ]);
+
+    assert_eq!(task["kind"], "task");
+    assert_eq!(task["parent_id"], 1);
+    assert_eq!(task["note"], "size 44");
+
+    let detail = workspace.json(&["show", "area", "1"]);
+    assert_eq!(detail["stake"]["name"], "Health");
+    assert_eq!(detail["children"][0]["name"], "Run a 10k");
+}
+
+#[test]
+fn list_prints_table_and_hides_done_tasks() {
+    let workspace = Workspace::new();
+    workspace.ok(&["capture", "Buy milk"]);
+    workspace.ok(&["capture", "Water plants"]);
+    workspace.ok(&["done", "task", "1"]);
+
+    let table = workspace.ok(&["list"]);
+    let lines: Vec<&str> = table.lines().collect();
+    assert_eq!(lines[0], "KIND  ID  NAME          PARENT  STATUS");
+    assert_eq!(lines[1], "task  2   Water plants  -       open");
+    assert_eq!(lines.len(), 2);
+
+    let all = workspace.json(&["list", "tasks", "--all"]);
+    assert_eq!(all.as_array().unwrap().len(), 2);
+    assert_eq!(all[0]["status"], "done");
+}
+
+#[test]
+fn drop_marks_stake_dropped() {
+    let workspace = Workspace::new();
+    workspace.ok(&["project", "add", "Learn the oboe"]);
+    let updated = workspace.json(&["drop", "project", "1"]);
+
+    assert_eq!(updated["status"], "dropped");
+    assert_eq!(workspace.data()["projects"]["stakes"]["1"]["dropped"], true);
+}
+
+#[test]
+fn move_files_task_under_project_and_back_out() {
+    let workspace = Workspace::new();
+    workspace.ok(&["project", "add", "Garden"]);
+    workspace.ok(&["capture", "Buy seeds"]);
+
+    let moved = workspace.json(&["move", "task", "1", "--to", "1"]);
+    assert_eq!(moved["parent_id"], 1);
+    let unfiled = workspace.json(&["move", "task", "1"]);
+    assert_eq!(unfiled["parent_id"], Value::Null);
+}
+
+#[test]
+fn team_members_are_assigned_stakes_and_changes_record_their_author() {
+    let workspace = Workspace::new();
+    workspace.ok(&["team", "add", "ana"]);
+    workspace.ok(&["team", "add", "ben"]);
+    assert_eq!(workspace.ok(&["team"]), "ana\nben\n");
+    workspace.ok(&["--user", "ana", "project", "add", "Launch"]);
+    workspace.ok(&[
+        "--user",
+        "ana",
+        "task",
+        "add",
+        "Write copy",
+        "--project",
+        "1",
+    ]);
+
+    let assigned = workspace.json(&["--user", "ben", "assign", "task", "1", "ana"]);
+    assert_eq!(assigned["assignee"], "ana");
+    workspace.ok(&["assign", "project", "1", "ben"]);
+    let listed = workspace.json(&["assigned", "ana"]);
+    assert_eq!(listed.as_array().unwrap().len(), 1);
+    assert_eq!(listed[0]["name"], "Write copy");
+    let shown = workspace.ok(&["show", "task", "1"]);
+    assert!(shown.contains("assignee: ana\n"), "{}", shown);
+    assert!(shown.contains(" by ben\n"), "{}", shown);
+
+    let stranger = workspace.run(&["assign", "task", "1", "cal"]);
+    assert!(!stranger.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&stranger.stderr).trim(),
+        "error: 'cal' is not a member of this workspace"
+    );
+    let data = workspace.data();
+    let task = &data["tasks"]["stakes"]["1"];
+    assert_eq!(task["created_by"], "ana");
+    assert_eq!(task["modified_by"], "ben");
+    assert_eq!(data["members"], serde_json::json!(["ana", "ben"]));
+    // A project was assigned without --user, so nobody is recorded as changing it.
+    assert!(data["projects"]["stakes"]["1"].get("modified_by").is_none());
+}
+
+#[test]
+fn export_prints_the_hierarchy_as_markdown() {
+    let workspace = Workspace::new();
+    workspace.ok(&["area", "add", "Home"]);
+    workspace.ok(&["project", "add", "Garden", "--area", "1"]);
+    workspace.ok(&["task", "add", "Dig beds", "--project", "1"]);
+    workspace.ok(&["task", "add", "Water", "--project", "1"]);
+    workspace.ok(&["done", "task", "2"]);
+
+    assert_eq!(
+        workspace.ok(&["export"]),
+        "# Home\n\n## Garden\n\n- [ ] Dig beds\n"
+    );
+    assert_eq!(
+        workspace.ok(&["export", "--project", "1", "--completed"]),
+        "## Garden\n\n- [ ] Dig beds\n- [x] Water\n"
+    );
+    assert!(!workspace.run(&["export", "--area", "9"]).status.success());
+}
+
+#[test]
+fn import_reads_back_what_export_wrote() {
+    let workspace = Workspace::new();
+    workspace.ok(&["area", "add", "Home"]);
+    workspace.ok(&["project", "add", "Garden", "--area", "1"]);
+    workspace.ok(&[
+        "task",
+        "add",
+        "Water",
+        "--project",
+        "1",
+        "--note",
+        "Evenings",
+    ]);
+    workspace.ok(&["done", "task", "1"]);
+    let exported = workspace.ok(&["export", "--completed"]);
+
+    let copy = Workspace::new();
+    let plan = copy.file.with_file_name("plan.md");
+    std::fs::write(&plan, &exported).unwrap();
+    assert_eq!(
+        copy.ok(&["import", plan.to_str().unwrap()]),
+        "Imported 1 area, 1 project and 1 task.\n"
+    );
+    assert_eq!(copy.ok(&["export", "--completed"]), exported);
+    let task = &copy.data()["tasks"]["stakes"]["1"];
+    assert_eq!(task["parent_id"], 1);
+    assert_eq!(task["complete"], true);
+    assert_eq!(task["note"], "Evenings");
+
+    let missing = copy.run(&["import", "nowhere.md"]);
+    assert!(!missing.status.success());
+}
+
+#[test]
+fn search_finds_notes_and_supports_fuzzy() {
+    let workspace = Workspace::new();
+    workspace.ok(&["area", "add", "Financial Management"]);
+    workspace.ok(&["task", "add", "Pay rent", "--note", "financial admin"]);
+
+    let hits = workspace.json(&["search", "financial"]);
+    assert_eq!(hits.as_array().unwrap().len(), 2);
+    assert_eq!(hits[0]["stake"]["kind"], "area");
+
+    let fuzzy = workspace.json(&["search", "--fuzzy", "finacial"]);
+    assert_eq!(fuzzy[0]["stake"]["name"], "Financial Management");
+}
+
+#[test]
+fn review_reports_and_marks() {
+    let workspace = Workspace::new();
+    workspace.ok(&["area", "add", "Health"]);
+    workspace.ok(&["capture", "Loose end"]);
+
+    let report = workspace.json(&["review"]);
+    assert_eq!(report["inbox"][0]["name"], "Loose end");
+    assert_eq!(report["due"][0]["name"], "Health");
+
+    workspace.ok(&["review", "--mark"]);
+    let after = workspace.json(&["review"]);
+    assert_eq!(after["due"], Value::Array(vec![]));
+}
+
+#[test]
+fn show_accepts_global_id() {
+    let workspace = Workspace::new();
+    let task = workspace.json(&["capture", "Global handle"]);
+    let global_id = task["global_id"].as_str().unwrap().to_string();
+
+    let detail = workspace.json(&["show", "task", &global_id]);
+    assert_eq!(detail["stake"]["id"], 1);
+}
+
+#[test]
+fn read_only_commands_do_not_create_data_file() {
+    let workspace = Workspace::new();
+    workspace.ok(&["list"]);
+    assert!(!workspace.file.exists());
+}
+
+#[test]
+fn unknown_id_fails_with_message() {
+    let workspace = Workspace::new();
+    let output = workspace.run(&["done", "task", "42"]);
+
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr).trim(),
+        "error: no task matches '42'"
+    );
+}
+
+#[test]
+fn malformed_data_file_is_reported_and_left_alone() {
+    let workspace = Workspace::new();
+    std::fs::write(&workspace.file, "not json").unwrap();
+    let output = workspace.run(&["capture", "Anything"]);
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("is not valid JSON"));
+    assert_eq!(
+        std::fs::read_to_string(&workspace.file).unwrap(),
+        "not json"
+    );
+}
+
+#[test]
+fn invalid_kind_is_a_usage_error() {
+    let workspace = Workspace::new();
+    let output = workspace.run(&["done", "goal", "1"]);
+
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn shell_runs_piped_commands_and_saves_on_exit() {
+    let workspace = Workspace::new();
+    let output =
+        workspace.shell("area add Health\nproject add \"Run a 10k\" --area 1\nshow area 1\nexit\n");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success());
+    assert!(stdout.contains("Created project 1: Run a 10k"));
+    assert!(stdout.contains("Saved to"));
+    assert_eq!(workspace.data()["projects"]["stakes"]["1"]["parent_id"], 1);
+    assert!(workspace.file.with_extension("history").exists());
+}
+
+#[test]
+fn shell_saves_when_input_ends() {
+    let workspace = Workspace::new();
+    let output = workspace.shell("capture Loose end\n");
+
+    assert!(output.status.success());
+    assert_eq!(
+        workspace.data()["tasks"]["stakes"]["1"]["stake_name"],
+        "Loose end"
+    );
+}
+
+#[test]
+fn config_sets_timezone_used_for_display_and_agenda() {
+    let workspace = Workspace::new();
+    let output = workspace.ok(&["config", "set", "timezone", "Asia/Tokyo"]);
+    assert_eq!(output.trim(), "timezone:   Asia/Tokyo\nweek-start: Mon");
+    assert!(workspace.file.with_extension("config.json").exists());
+
+    workspace.ok(&["task", "add", "Pay rent", "--due", "today 9am"]);
+    let detail = workspace.ok(&["show", "task", "1"]);
+    assert!(detail.contains(" JST"), "{}", detail);
+    let stored = workspace.data()["tasks"]["stakes"]["1"]["due"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    assert!(stored.ends_with("T00:00:00Z"), "{}", stored);
+
+    let agenda = workspace.ok(&["agenda"]);
+    assert!(agenda.contains("Due today:\nID  NAME"), "{}", agenda);
+
+    let bad = workspace.run(&["config", "set", "timezone", "Nowhere"]);
+    assert!(!bad.status.success());
+}
+
+#[test]
+fn serve_answers_json_rpc_and_saves() {
+    let workspace = Workspace::new();
+    let token = workspace.token("alice", &[]);
+    let (mut server, address) = workspace.serve(["--listen", "127.0.0.1:0"]);
+
+    let stream = TcpStream::connect(&address).expect("server accepts connections");
+    let mut replies = BufReader::new(&stream);
+    let mut send = |line: String| {
+        writeln!(&stream, "{}", line).unwrap();
+        let mut reply = String::new();
+        replies.read_line(&mut reply).unwrap();
+        serde_json::from_str::<Value>(&reply).unwrap()
+    };
+    let signed_in = send(format!(
+        r#"{{"jsonrpc": "2.0", "method": "authenticate", "params": {{"token": "{}"}}, "id": 1}}"#,
+        token
+    ));
+    let reply = send(
+        r#"{"jsonrpc": "2.0", "method": "new_task", "params": {"name": "From afar"}, "id": 2}"#
+            .to_string(),
+    );
+    server.kill().unwrap();
+    server.wait().unwrap();
+
+    assert_eq!(signed_in["result"]["user"], "alice");
+    assert_eq!(reply["id"], 2);
+    assert_eq!(reply["result"]["stake_name"], "From afar");
+    assert_eq!(
+        workspace.data()["tasks"]["stakes"]["1"]["stake_name"],
+        "From afar"
+    );
+}
+
+#[test]
+fn remote_flag_runs_commands_on_server() {
+    let served = Workspace::new();
+    let token = served.token("alice", &[]);
+    let (mut server, address) = served.serve(["--listen", "127.0.0.1:0"]);
+    // The local workspace has no data of its own; everything goes to the server.
+    let local = Workspace::new();
+    let remote = ["--remote", &address, "--token", &token];
+    fn on_server<'a>(remote: &[&'a str], args: &[&'a str]) -> Vec<&'a str> {
+        [remote, args].concat()
+    }
+    let on_server = |args| on_server(&remote, args);
+
+    let created = local.ok(&on_server(&["task", "add", "Call", "home"]));
+    assert_eq!(created.trim(), "Created task 1: Call home");
+    local.ok(&on_server(&["done", "task", "1"]));
+    let listed = local.json(&on_server(&["list", "--all"]));
+    let missing = local.run(&on_server(&["done", "task", "9"]));
+    let shell = local.run(&on_server(&["shell"]));
+    server.kill().unwrap();
+    server.wait().unwrap();
+
+    assert_eq!(listed[0]["name"], "Call home");
+    assert_eq!(listed[0]["status"], "done");
+    // The server records the token's user as the author.
+    assert_eq!(listed[0]["created_by"], "alice");
+    assert!(!missing.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&missing.stderr).trim(),
+        "error: no task matches '9'"
+    );
+    assert!(!shell.status.success());
+    assert!(!local.file.exists());
+    assert_eq!(served.data()["tasks"]["stakes"]["1"]["complete"], true);
+}
+
+#[test]
+fn sync_merges_data_file_with_server() {
+    let served = Workspace::new();
+    served.ok(&["task", "add", "From", "the", "server"]);
+    let token = served.token("alice", &[]);
+    let (mut server, address) = served.serve(["--listen", "127.0.0.1:0"]);
+    let local = Workspace::new();
+    local.ok(&["task", "add", "From", "the", "laptop"]);
+
+    let first = local.ok(&["sync", &address, "--token", &token]);
+    let second = local.ok(&["--remote", &address, "--token", &token, "sync"]);
+    let names = local.json(&["list"]);
+    let remote_names = local.json(&["--remote", &address, "--token", &token, "list"]);
+    server.kill().unwrap();
+    server.wait().unwrap();
+
+    assert_eq!(first.trim(), "Synced 2 stakes.");
+    assert_eq!(second.trim(), "Already in sync.");
+    assert_eq!(names.as_array().unwrap().len(), 2);
+    assert_eq!(remote_names.as_array().unwrap().len(), 2);
+    assert!(local.data()["sync"]["replica_id"].is_string());
+}
+
+#[test]
+fn serve_admits_only_valid_tokens() {
+    let served = Workspace::new();
+    served.ok(&["user", "add", "alice"]);
+    served.ok(&["user", "add", "bob"]);
+    let writer = served.token("alice", &[]);
+    let reader = served.token("bob", &["--read-only"]);
+    let users = served.json(&["user", "list"]);
+    assert_eq!(users["users"], serde_json::json!(["alice", "bob"]));
+    assert_eq!(users["tokens"][1]["access"], "read");
+    let bob_token = users["tokens"][1]["id"].as_str().unwrap().to_string();
+    let secrets = std::fs::read_to_string(served.file.with_extension("users.json")).unwrap();
+    assert!(!secrets.contains(writer.rsplit('_').next().unwrap()));
+
+    let (mut server, address) = served.serve(["--listen", "127.0.0.1:0"]);
+    let local = Workspace::new();
+    let error = |output: Output| {
+        assert!(!output.status.success());
+        String::from_utf8_lossy(&output.stderr).trim().to_string()
+    };
+    let anonymous = local.run(&["--remote", &address, "list"]);
+    let forged = local.run(&["--remote", &address, "--token", "mlw_1_2", "list"]);
+    local.ok(&[
+        "--remote", &address, "--token", &writer, "task", "add", "Plan",
+    ]);
+    let read = local.json(&["--remote", &address, "--token", &reader, "list"]);
+    let refused = local.run(&[
+        "--remote", &address, "--token", &reader, "done", "task", "1",
+    ]);
+    // Revoking takes effect without restarting the server.
+    served.ok(&["token", "revoke", &bob_token]);
+    let revoked = local.run(&["--remote", &address, "--token", &reader, "list"]);
+    server.kill().unwrap();
+    server.wait().unwrap();
+
+    assert_eq!(
+        error(anonymous),
+        "error: authentication required: send an access token"
+    );
+    assert_eq!(error(forged), "error: the access token is not valid");
+    assert_eq!(read[0]["name"], "Plan");
+    assert_eq!(error(refused), "error: the access token is read-only");
+    assert_eq!(error(revoked), "error: the access token is not valid");
+    assert_eq!(served.data()["tasks"]["stakes"]["1"]["complete"], false);
+}
+
+#[test]
+fn serve_warns_when_no_token_can_get_in() {
+    let workspace = Workspace::new();
+    let mut server = Command::new(env!("CARGO_BIN_EXE_mlw"))
+        .arg("--file")
+        .arg(&workspace.file)
+        .args(["serve", "--listen", "127.0.0.1:0"])
+        .env_remove("MLW_FILE")
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("mlw serve should start");
+    let mut warning = String::new();
+    BufReader::new(server.stderr.take().unwrap())
+        .read_line(&mut warning)
+        .unwrap();
+    server.kill().unwrap();
+    server.wait().unwrap();
+    assert!(warning.contains("no access tokens"), "{}", warning);
+    assert!(warning.contains("mlw token issue"), "{}", warning);
+}
+
+#[test]
+fn user_and_token_commands_check_their_arguments() {
+    let workspace = Workspace::new();
+    assert_eq!(workspace.ok(&["user"]).trim(), "No users.");
+    assert_eq!(
+        workspace.ok(&["user", "add", "alice"]).trim(),
+        "Added user alice."
+    );
+    let output = workspace.run(&["user", "add", "alice"]);
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr).trim(),
+        "error: user 'alice' already exists"
+    );
+    let output = workspace.run(&["token", "issue", "carol"]);
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr).trim(),
+        "error: no user named 'carol'"
+    );
+    let output = workspace.run(&["token", "issue", "alice"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("shown only once"));
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("mlw_"));
+    workspace.ok(&["user", "remove", "alice"]);
+    assert_eq!(workspace.ok(&["user", "list"]).trim(), "No users.");
+    assert!(!workspace.run(&["token", "revoke", "abc"]).status.success());
+}
+
+#[test]
+fn serve_http_answers_rest_requests() {
+    let workspace = Workspace::new();
+    let token = workspace.token("alice", &[]);
+    let (mut server, address) = workspace.serve(["--http", "127.0.0.1:0"]);
+    let address = address.strip_prefix("http://").expect("an HTTP address");
+
+    let body = r#"{"stake_name": "Over HTTP"}"#;
+    let mut stream = TcpStream::connect(address).expect("server accepts connections");
+    write!(
+        stream,
+        "POST /tasks HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\
+         Authorization: Bearer {}\r\nContent-Length: {}\r\n\r\n{}",
+        address,
+        token,
+        body.len(),
+        body
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    server.kill().unwrap();
+    server.wait().unwrap();
+
+    assert!(response.starts_with("HTTP/1.1 201"), "{}", response);
+    assert_eq!(
+        workspace.data()["tasks"]["stakes"]["1"]["stake_name"],
+        "Over HTTP"
+    );
+}
+
+#[test]
+fn workspaces_keep_separate_data_files() {
+    let home = tempfile::tempdir().expect("temp dir");
+    let home = home.path();
+    at_home(home, &["task", "add", "Call", "the", "dentist"]);
+    assert!(home.join(".mlw.json").exists());
+
+    let created = at_home(home, &["workspace", "create", "work", "--switch"]);
+    assert_eq!(
+        created.trim(),
+        format!(
+            "Created workspace work in {}.",
+            home.join(".mlw").join("work.json").display()
+        )
+    );
+    at_home(home, &["task", "add", "Dentist", "invoice"]);
+    at_home(
+        home,
+        &["--workspace", "default", "task", "add", "Water", "plants"],
+    );
+    let work = read_json(&home.join(".mlw").join("work.json"));
+    assert_eq!(
+        work["tasks"]["stakes"]["1"]["stake_name"],
+        "Dentist invoice"
+    );
+    let listed = at_home(home, &["--json", "list"]);
+    assert_eq!(
+        serde_json::from_str::<Value>(&listed).unwrap()[0]["name"],
+        "Dentist invoice"
+    );
+
+    // Plain search stays in the current workspace; cross-workspace search is asked for.
+    let here: Value =
+        serde_json::from_str(&at_home(home, &["--json", "search", "dentist"])).unwrap();
+    assert_eq!(here.as_array().unwrap().len(), 1);
+    let everywhere: Value = serde_json::from_str(&at_home(
+        home,
+        &["--json", "workspace", "search", "dentist"],
+    ))
+    .unwrap();
+    let mut found: Vec<&str> = everywhere
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|hit| hit["workspace"].as_str().unwrap())
+        .collect();
+    found.sort();
+    assert_eq!(found, vec!["default", "work"]);
+
+    at_home(home, &["workspace", "rename", "default", "personal"]);
+    at_home(home, &["workspace", "switch", "personal"]);
+    let listed = at_home(home, &["workspace"]);
+    assert!(listed.contains("*  personal"), "{}", listed);
+    assert!(listed.contains("   work"), "{}", listed);
+    let plants = at_home(home, &["--json", "list"]);
+    assert_eq!(
+        serde_json::from_str::<Value>(&plants)
+            .unwrap()
+            .as_array()
+            .unwrap()
+            .len(),
+        2
+    );
+
+    let deleted = at_home(home, &["workspace", "delete", "work"]);
+    assert_eq!(deleted.trim(), "Deleted workspace work and its data.");
+    assert!(!home.join(".mlw").join("work.json").exists());
+    let list: Value =
+        serde_json::from_str(&at_home(home, &["--json", "workspace", "list"])).unwrap();
+    assert_eq!(list.as_array().unwrap().len(), 1);
+    assert_eq!(list[0]["name"], "personal");
+}
+
+#[test]
+fn workspace_data_paths_are_stored_absolute() {
+    let home = tempfile::tempdir().expect("temp dir");
+    let elsewhere = tempfile::tempdir().expect("temp dir");
+    let run = |dir: &Path, args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_mlw"))
+            .args(args)
+            .current_dir(dir)
+            .env("HOME", home.path())
+            .env_remove("MLW_FILE")
+            .env_remove("MLW_WORKSPACE")
+            .output()
+            .expect("mlw should run")
+    };
+    let created = run(
+        home.path(),
+        &["workspace", "create", "work", "--data", "./work.json"],
+    );
+    assert!(created.status.success());
+    let file = home.path().join("work.json");
+    assert_eq!(
+        String::from_utf8_lossy(&created.stdout).trim(),
+        format!("Created workspace work in {}.", file.display())
+    );
+
+    // The same file by its absolute path, from another directory, is taken.
+    let again = run(
+        elsewhere.path(),
+        &[
+            "workspace",
+            "create",
+            "copy",
+            "--data",
+            file.to_str().unwrap(),
+        ],
+    );
+    assert!(!again.status.success());
+    assert!(String::from_utf8_lossy(&again.stderr).contains("'work'"));
+
+    // And it is the same file whichever directory the workspace is used from.
+    assert!(
+        run(
+            elsewhere.path(),
+            &["--workspace", "work", "capture", "Call"]
+        )
+        .status
+        .success()
+    );
+    assert!(file.exists());
+    assert!(!elsewhere.path().join("work.json").exists());
+}
+
+#[test]
+fn workspace_commands_check_their_arguments() {
+    let home = tempfile::tempdir().expect("temp dir");
+    let run = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_mlw"))
+            .args(args)
+            .env("HOME", home.path())
+            .env_remove("MLW_FILE")
+            .env_remove("MLW_WORKSPACE")
+            .output()
+            .expect("mlw should run")
+    };
+    let error = |args: &[&str]| {
+        let output = run(args);
+        assert!(!output.status.success(), "mlw {:?} succeeded", args);
+        String::from_utf8_lossy(&output.stderr).trim().to_string()
+    };
+    assert_eq!(
+        error(&["workspace", "delete", "default"]),
+        "error: 'default' is the current workspace; switch to another before deleting it"
+    );
+    assert_eq!(
+        error(&["workspace", "switch", "work"]),
+        "error: no workspace named 'work'"
+    );
+    assert_eq!(
+        error(&["--workspace", "work", "list"]),
+        "error: no workspace named 'work'"
+    );
+    assert!(error(&["workspace", "create", "a.b"]).contains("not a valid workspace name"));
+    assert!(
+        error(&["--file", "x.json", "--workspace", "w", "list"]).contains("cannot be used with")
+    );
+    // Nothing was written by the failed commands.
+    assert!(!home.path().join(".mlw.workspaces.json").exists());
+}
@@ -0,0 +1,116 @@
+// Golden tests for Markdown import and export: the files in tests/golden are read,
+// and the Markdown MLW writes back is compared with them byte for byte.
+
+use mlw_rust::entities::{Kind, Stake};
+use mlw_rust::markdown::{self, ExportOptions, Imported};
+use mlw_rust::mlw::MLW;
+
+fn golden(name: &str) -> String {
+    let path = format!("{}/tests/golden/{}", env!("CARGO_MANIFEST_DIR"), name);
+    std::fs::read_to_string(&path).unwrap_or_else(|error| panic!("{}: {}", path, error))
+}
+
+fn import(text: &str) -> (MLW, Imported) {
+    let mut mlw = MLW::new();
+    let imported = markdown::import(&mut mlw, text).expect("import should succeed");
+    (mlw, imported)
+}
+
+fn export(mlw: &MLW) -> String {
+    markdown::export(mlw, &ExportOptions::all()).expect("export should succeed")
+}
+
+// Everything Markdown carries about a stake, with its parent by name.
+fn describe<K: Kind>(stake: &Stake<K>, parent: Option<&str>) -> String {
+    format!(
+        "{:?} in {:?}: complete {}, dropped {}, note {:?}",
+        stake.stake_name, parent, stake.complete, stake.dropped, stake.note
+    )
+}
+
+fn outline(mlw: &MLW) -> Vec<String> {
+    let hierarchy = mlw.hierarchy();
+    let mut lines: Vec<String> = hierarchy
+        .unfiled_tasks
+        .iter()
+        .map(|task| describe(task, None))
+        .collect();
+    let mut projects: Vec<_> = hierarchy
+        .unfiled_projects
+        .iter()
+        .map(|project| (None, project))
+        .collect();
+    for area in &hierarchy.areas {
+        lines.push(describe(area.area, None));
+        let name = Some(area.area.stake_name.as_str());
+        projects.extend(area.projects.iter().map(|project| (name, project)));
+    }
+    for (area, project) in projects {
+        lines.push(describe(project.project, area));
+        let name = Some(project.project.stake_name.as_str());
+        lines.extend(project.tasks.iter().map(|task| describe(task, name)));
+    }
+    lines
+}
+
+#[test]
+fn exported_markdown_imports_and_exports_unchanged() {
+    let plan = golden("plan.md");
+    let (mlw, imported) = import(&plan);
+    assert_eq!(
+        imported,
+        Imported {
+            areas: 3,
+            projects: 5,
+            tasks: 10,
+            skipped: 0
+        }
+    );
+    assert_eq!(export(&mlw), plan);
+}
+
+#[test]
+fn a_round_trip_keeps_every_stake() {
+    let mut mlw = MLW::new();
+    let home = mlw.new_area("Home".to_string(), Some("Where\n\nwe live".to_string()));
+    let garden = mlw.new_project(
+        "Garden *beds*".to_string(),
+        Some(home.stake_id.clone()),
+        Some("  indented\n> not a quote".to_string()),
+    );
+    let dig = mlw.new_task(
+        "[x] Dig ~~deep~~".to_string(),
+        Some(garden.stake_id.clone()),
+        Some("- [ ] a list in a note\n\n# not a heading".to_string()),
+    );
+    mlw.mark_task_complete(&dig.stake_id).unwrap();
+    mlw.mark_task_dropped(&dig.stake_id).unwrap();
+    let shed = mlw.new_project("Shed".to_string(), Some(home.stake_id.clone()), None);
+    mlw.mark_project_dropped(&shed.stake_id).unwrap();
+    mlw.mark_area_complete(&home.stake_id).unwrap();
+    mlw.new_project(r"C:\Temp \\ #1".to_string(), None, None);
+    mlw.new_task(
+        "1. Numbered".to_string(),
+        None,
+        Some("\tTabbed".to_string()),
+    );
+
+    let (imported, _) = import(&export(&mlw));
+    assert_eq!(outline(&imported), outline(&mlw));
+    assert_eq!(export(&imported), export(&mlw));
+}
+
+#[test]
+fn a_loose_checklist_imports_as_the_golden_export() {
+    let (mlw, imported) = import(&golden("checklist.md"));
+    assert_eq!(
+        imported,
+        Imported {
+            areas: 2,
+            projects: 2,
+            tasks: 7,
+            skipped: 2
+        }
+    );
+    assert_eq!(export(&mlw), golden("checklist.expected.md"));
+}